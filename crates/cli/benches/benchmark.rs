@@ -1,6 +1,6 @@
 use std::{
     collections::BTreeMap,
-    env, fs,
+    env, fs, hint,
     path::{Path, PathBuf},
     str,
     sync::LazyLock,
@@ -9,7 +9,8 @@ use std::{
 
 use anyhow::Context;
 use log::info;
-use tree_sitter::{Language, Parser, Query};
+use streaming_iterator::StreamingIterator;
+use tree_sitter::{Language, Node, NodeIndex, Parser, Query, QueryCursor};
 use tree_sitter_loader::{CompileConfig, Loader};
 
 include!("../src/tests/helpers/dirs.rs");
@@ -127,6 +128,39 @@ fn main() {
             }));
         }
 
+        info!("  Looking Up Nodes (descendant search vs. node index):");
+        for example_path in example_paths {
+            if let Some(filter) = EXAMPLE_FILTER.as_ref()
+                && !example_path.to_str().unwrap().contains(filter.as_str())
+            {
+                continue;
+            }
+
+            lookup_nodes(example_path, max_path_length, &mut parser);
+        }
+
+        info!("  Filtering Nodes By Kind (kind names vs. kind ids):");
+        for example_path in example_paths {
+            if let Some(filter) = EXAMPLE_FILTER.as_ref()
+                && !example_path.to_str().unwrap().contains(filter.as_str())
+            {
+                continue;
+            }
+
+            filter_nodes(example_path, max_path_length, &mut parser);
+        }
+
+        info!("  Querying Named Nodes (all nodes vs. skipping anonymous nodes):");
+        for example_path in example_paths {
+            if let Some(filter) = EXAMPLE_FILTER.as_ref()
+                && !example_path.to_str().unwrap().contains(filter.as_str())
+            {
+                continue;
+            }
+
+            query_named_nodes(example_path, max_path_length, &mut parser, &language);
+        }
+
         info!("  Parsing Invalid Code (mismatched languages):");
         let mut error_speeds = Vec::new();
         for (other_language_path, (example_paths, _)) in
@@ -208,6 +242,135 @@ fn parse(path: &Path, max_path_length: usize, mut action: impl FnMut(&[u8])) ->
     speed as usize
 }
 
+fn lookup_nodes(path: &Path, max_path_length: usize, parser: &mut Parser) {
+    let source_code = fs::read(path)
+        .with_context(|| format!("Failed to read {}", path.display()))
+        .unwrap();
+    let tree = parser.parse(&source_code, None).expect("Failed to parse");
+    let root_node = tree.root_node();
+
+    let time = Instant::now();
+    for _ in 0..*REPETITION_COUNT {
+        for byte in 0..source_code.len() {
+            hint::black_box(root_node.named_descendant_for_byte_range(byte, byte));
+        }
+    }
+    let search_duration = time.elapsed() / (*REPETITION_COUNT as u32);
+
+    let time = Instant::now();
+    let index = NodeIndex::new(&tree);
+    let build_duration = time.elapsed();
+
+    let time = Instant::now();
+    for _ in 0..*REPETITION_COUNT {
+        for byte in 0..source_code.len() {
+            hint::black_box(index.named_node_for_byte(byte));
+        }
+    }
+    let index_duration = time.elapsed() / (*REPETITION_COUNT as u32);
+
+    info!(
+        "    {:max_path_length$}	search {:>7.2} ms	index build {:>7.2} ms	index {:>7.2} ms	({} lookups)",
+        path.file_name().unwrap().to_str().unwrap(),
+        search_duration.as_secs_f64() * 1e3,
+        build_duration.as_secs_f64() * 1e3,
+        index_duration.as_secs_f64() * 1e3,
+        source_code.len(),
+    );
+}
+
+fn filter_nodes(path: &Path, max_path_length: usize, parser: &mut Parser) {
+    const KINDS: [&str; 3] = ["identifier", "string", "comment"];
+
+    let source_code = fs::read(path)
+        .with_context(|| format!("Failed to read {}", path.display()))
+        .unwrap();
+    let tree = parser.parse(&source_code, None).expect("Failed to parse");
+
+    let count_nodes = |is_match: &dyn Fn(Node) -> bool| {
+        let mut count = 0;
+        let mut cursor = tree.walk();
+        let mut visited_children = false;
+        loop {
+            if visited_children {
+                if cursor.goto_next_sibling() {
+                    visited_children = false;
+                } else if !cursor.goto_parent() {
+                    break;
+                }
+            } else {
+                if is_match(cursor.node()) {
+                    count += 1;
+                }
+                visited_children = !cursor.goto_first_child();
+            }
+        }
+        count
+    };
+
+    let time = Instant::now();
+    for _ in 0..*REPETITION_COUNT {
+        hint::black_box(count_nodes(&|node| {
+            node.is_named() && KINDS.contains(&node.kind())
+        }));
+    }
+    let name_duration = time.elapsed() / (*REPETITION_COUNT as u32);
+
+    let kinds = tree.language().node_kind_set(&KINDS, true);
+    let time = Instant::now();
+    for _ in 0..*REPETITION_COUNT {
+        hint::black_box(count_nodes(&|node| kinds.contains(&node)));
+    }
+    let id_duration = time.elapsed() / (*REPETITION_COUNT as u32);
+
+    info!(
+        "    {:max_path_length$}\tnames {:>7.2} ms\tids {:>7.2} ms\t({} nodes)",
+        path.file_name().unwrap().to_str().unwrap(),
+        name_duration.as_secs_f64() * 1e3,
+        id_duration.as_secs_f64() * 1e3,
+        tree.root_node().descendant_count(),
+    );
+}
+
+fn query_named_nodes(
+    path: &Path,
+    max_path_length: usize,
+    parser: &mut Parser,
+    language: &Language,
+) {
+    let source_code = fs::read(path)
+        .with_context(|| format!("Failed to read {}", path.display()))
+        .unwrap();
+    let tree = parser.parse(&source_code, None).expect("Failed to parse");
+    let query = Query::new(language, "(_) @node").unwrap();
+    assert!(!query.matches_anonymous_nodes());
+
+    let mut cursor = QueryCursor::new();
+    let mut run_query = |skip_anonymous_nodes: bool| {
+        cursor.set_skip_anonymous_nodes(skip_anonymous_nodes);
+        let time = Instant::now();
+        let mut count = 0;
+        for _ in 0..*REPETITION_COUNT {
+            count = cursor
+                .matches(&query, tree.root_node(), source_code.as_slice())
+                .count();
+            hint::black_box(count);
+        }
+        (time.elapsed() / (*REPETITION_COUNT as u32), count)
+    };
+
+    let (all_duration, match_count) = run_query(false);
+    let (named_duration, _) = run_query(true);
+
+    info!(
+        "    {:max_path_length$}\tall {:>7.2} ms\tnamed {:>7.2} ms\t({match_count} matches, {} nodes)",
+        path.file_name().unwrap().to_str().unwrap(),
+        all_duration.as_secs_f64() * 1e3,
+        named_duration.as_secs_f64() * 1e3,
+        tree.root_node().descendant_count(),
+    );
+}
+
 fn get_language(path: &Path) -> Language {
     let src_path = GRAMMARS_DIR.join(path).join("src");
     TEST_LOADER
@@ -104,6 +104,18 @@ interface Grammar<
     previous: Rule[][],
   ) => RuleOrLiteral[][];
 
+  /**
+   * An array of arrays of token names. Each inner array declares a group of
+   * tokens that should never match the same string. Tree-sitter will warn
+   * about any pair of tokens in a group that can.
+   *
+   * @param $ grammar rules
+   */
+  token_groups?: (
+    $: GrammarSymbols<RuleName | BaseGrammarRuleName>,
+    previous: Rule[][],
+  ) => RuleOrLiteral[][];
+
   /**
    * An array of token names which can be returned by an _external scanner_.
    * External scanners allow you to write custom C code which runs during the
@@ -158,6 +170,14 @@ interface Grammar<
     previous: Rule[],
   ) => RuleOrLiteral[];
 
+  /**
+   * Human-readable descriptions of rules, keyed by rule name. These are
+   * included in the generated node types file, for tools that display them.
+   *
+   * @see https://tree-sitter.github.io/tree-sitter/using-parsers/6-static-node-types
+   */
+  descriptions?: { [K in RuleName | BaseGrammarRuleName]?: string };
+
   /**
    * The name of a token that will match keywords for the purpose of the
    * keyword extraction optimization.
@@ -335,10 +355,13 @@ declare function repeat1(rule: RuleOrLiteral): Repeat1Rule;
  * Overrides the global reserved word set for a given rule. The word set name
  * should be defined in the `reserved` field in the grammar.
  *
+ * Without a rule, this can be listed within another reserved word set to
+ * include all of the words from the given set.
+ *
  * @param wordset name of the reserved word set
  * @param rule rule that will use the reserved word set
  */
-declare function reserved(wordset: string, rule: RuleOrLiteral): ReservedRule;
+declare function reserved(wordset: string, rule?: RuleOrLiteral): ReservedRule;
 
 /**
  * Creates a rule that matches any number of other rules, one after another.
@@ -465,6 +465,7 @@ pub fn highlight(
                     stdout.write_all(&source[start..end])?;
                     write!(&mut stdout, "{style:#}").unwrap();
                 }
+                HighlightEvent::InjectionDepth(_) => {}
             }
         }
     }
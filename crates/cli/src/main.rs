@@ -1,6 +1,6 @@
 use std::{
     collections::HashSet,
-    env, fs,
+    env, fs, io,
     path::{Path, PathBuf},
 };
 
@@ -25,7 +25,7 @@ use tree_sitter_cli::{
     logger, paint,
     parse::{self, ParseDebugType, ParseFileOptions, ParseOutput, ParseTheme},
     playground,
-    query::{self, QueryFileOptions},
+    query::{self, QueryFileOptions, QueryFormat},
     tags::{self, TagsOptions},
     test::{self, TestOptions, TestStats, TestSummary},
     test_highlight, test_tags, util,
@@ -33,7 +33,7 @@ use tree_sitter_cli::{
     wasm,
 };
 use tree_sitter_config::Config;
-use tree_sitter_generate::{Diagnostic, GenerateError, OptLevel};
+use tree_sitter_generate::{Diagnostic, GenerateError, GenerateReport, OptLevel};
 use tree_sitter_highlight::Highlighter;
 use tree_sitter_loader::{self as loader, Bindings, TreeSitterJSON};
 use tree_sitter_tags::TagsContext;
@@ -71,6 +71,8 @@ enum Commands {
     Playground(Playground),
     /// Print info about all known language parsers
     DumpLanguages(DumpLanguages),
+    /// Inspect the CLI's configuration
+    Config(ConfigCommand),
     /// Generate shell completions
     Complete(Complete),
 }
@@ -129,6 +131,33 @@ struct Generate {
     /// Produce a report of the states for the given rule, use `-` to report every rule
     #[arg(long, conflicts_with = "json", conflicts_with = "json_summary")]
     pub report_states_for_rule: Option<String>,
+    /// Write a Graphviz DOT graph of the combined token DFA to the given path
+    #[arg(long = "dump-lex-dfa", value_name = "PATH")]
+    pub lex_dfa_path: Option<PathBuf>,
+    /// Write an SVG railroad diagram of the grammar's visible rules to the given path
+    #[arg(long, value_name = "PATH")]
+    pub railroad: Option<PathBuf>,
+    /// Print a summary of the generated parser, or write it to the given path
+    #[arg(
+        long,
+        value_name = "PATH",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "-",
+        conflicts_with = "no_parser"
+    )]
+    pub report: Option<PathBuf>,
+    /// Print the pairs of tokens that match overlapping strings as JSON, or write them to the
+    /// given path
+    #[arg(
+        long,
+        value_name = "PATH",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "-",
+        conflicts_with = "no_parser"
+    )]
+    pub report_token_conflicts: Option<PathBuf>,
     /// Deprecated: use --json-summary
     #[arg(
         long,
@@ -143,6 +172,19 @@ struct Generate {
         conflicts_with = "report_states_for_rule"
     )]
     pub json_summary: bool,
+    /// Fail if the grammar produces any warnings
+    #[arg(long)]
+    pub deny_warnings: bool,
+    /// The format in which to report warnings and errors
+    #[arg(
+        long,
+        value_enum,
+        value_name = "FORMAT",
+        conflicts_with = "json",
+        conflicts_with = "json_summary",
+        conflicts_with = "report_states_for_rule"
+    )]
+    pub diagnostics: Option<DiagnosticsFormat>,
     /// The name or path of the JavaScript runtime to use for generating parsers
     #[cfg(not(feature = "qjs-rt"))]
     #[arg(
@@ -164,10 +206,26 @@ struct Generate {
     /// to use the native `QuickJS` runtime
     pub js_runtime: Option<String>,
 
-    /// Disable optimizations when generating the parser. Currently, this only affects
-    /// the merging of compatible parse states.
+    /// Disable optimizations when generating the parser. Currently, this affects the
+    /// merging of compatible parse states and the sharing of identical small parse
+    /// table rows.
     #[arg(long)]
     pub disable_optimizations: bool,
+    /// Generate a parser that also compiles cleanly as C++
+    #[arg(long)]
+    pub cxx: bool,
+    /// Generate a parser that only tokenizes its input, producing a tree whose root node's
+    /// children are the tokens
+    #[arg(long)]
+    pub lexer_only: bool,
+    /// Split the lexer, parse table, and parse actions into separate C files that share a
+    /// generated header, so that they can be compiled in parallel
+    #[arg(long)]
+    pub split_tables: bool,
+    /// Also warn about single-element `seq` and `choice` rules that have no effect on the
+    /// parser, because they are replaced by their element
+    #[arg(long)]
+    pub strict: bool,
 }
 
 #[derive(Args)]
@@ -241,8 +299,32 @@ struct Parse {
     /// Output the parse data in a pretty-printed CST format
     #[arg(long = "cst", short = 'c')]
     pub output_cst: bool,
-    /// Show parsing statistics
-    #[arg(long, short, conflicts_with = "json", conflicts_with = "json_summary")]
+    /// Output the parse data as length-delimited protobuf messages, see `tree.proto`
+    #[arg(long = "proto", conflicts_with_all = ["output_dot", "output_xml", "output_cst"])]
+    pub output_proto: bool,
+    /// Output the parse data in the given format
+    #[arg(
+        long,
+        value_enum,
+        conflicts_with_all = [
+            "output_dot",
+            "output_xml",
+            "output_cst",
+            "output_proto",
+            "quiet",
+            "json",
+            "json_summary",
+        ]
+    )]
+    pub output_format: Option<ParseOutput>,
+    /// Show parsing statistics, and the size and number of errors of each tree
+    #[arg(
+        long,
+        short,
+        visible_alias = "stats",
+        conflicts_with = "json",
+        conflicts_with = "json_summary"
+    )]
     pub stat: bool,
     /// Interrupt the parsing process by timeout (µs)
     #[arg(long)]
@@ -289,6 +371,15 @@ struct Parse {
     /// Omit ranges in the output
     #[arg(long)]
     pub no_ranges: bool,
+    /// Only print nodes down to the given depth, eliding their descendants with `...`
+    #[arg(long, value_name = "DEPTH")]
+    pub max_depth: Option<usize>,
+}
+
+#[derive(ValueEnum, Clone, Copy, PartialEq, Eq)]
+pub enum DiagnosticsFormat {
+    Text,
+    Json,
 }
 
 #[derive(ValueEnum, Clone)]
@@ -359,6 +450,9 @@ struct Test {
     /// Output the test summary in a JSON format
     #[arg(long)]
     pub json_summary: bool,
+    /// Report the grammar rules that the corpus tests never exercise
+    #[arg(long)]
+    pub coverage: bool,
 }
 
 #[derive(Args)]
@@ -487,6 +581,10 @@ struct Query {
     /// Order by captures instead of matches
     #[arg(long, short)]
     pub captures: bool,
+    /// The output format. The csv and tsv formats print one row per capture, with zero-based
+    /// rows and columns
+    #[arg(long, value_enum, default_value_t = QueryFormat::Text, conflicts_with = "test")]
+    pub format: QueryFormat,
     /// Whether to run query tests or not
     #[arg(long)]
     pub test: bool,
@@ -607,6 +705,28 @@ struct DumpLanguages {
     pub config_path: Option<PathBuf>,
 }
 
+#[derive(Args)]
+struct ConfigCommand {
+    #[command(subcommand)]
+    pub command: ConfigSubcommand,
+}
+
+#[derive(Subcommand)]
+enum ConfigSubcommand {
+    /// Print the resolved configuration, the grammar search paths, and the grammars found in each
+    Dump(ConfigDump),
+}
+
+#[derive(Args)]
+struct ConfigDump {
+    /// The path to an alternative config.json file
+    #[arg(long)]
+    pub config_path: Option<PathBuf>,
+    /// Output the configuration as JSON
+    #[arg(long, short)]
+    pub json: bool,
+}
+
 #[derive(Args)]
 #[command(alias = "comp")]
 struct Complete {
@@ -934,16 +1054,13 @@ impl Generate {
         if self.log {
             logger::enable_debug();
         }
-        let abi_version =
-            self.abi_version
-                .as_ref()
-                .map_or(DEFAULT_GENERATE_ABI_VERSION, |version| {
-                    if version == "latest" {
-                        tree_sitter::LANGUAGE_VERSION
-                    } else {
-                        version.parse().expect("invalid abi version flag")
-                    }
-                });
+        let abi_version = match self.abi_version.as_deref() {
+            None => DEFAULT_GENERATE_ABI_VERSION,
+            Some("latest") => tree_sitter::LANGUAGE_VERSION,
+            Some(version) => version.parse().with_context(|| {
+                format!("Invalid ABI version `{version}`, expected a number or `latest`")
+            })?,
+        };
 
         let json_summary = if self.json {
             warn!("--json is deprecated, use --json-summary instead");
@@ -953,12 +1070,16 @@ impl Generate {
         };
 
         let mut diagnostics = Vec::new();
+        let mut report = (self.report.is_some() || self.report_token_conflicts.is_some())
+            .then(GenerateReport::default);
         let result = tree_sitter_generate::generate_parser_in_directory(
             current_dir,
             self.output.as_deref(),
             self.grammar_path.as_deref(),
             abi_version,
             self.report_states_for_rule.as_deref(),
+            self.lex_dfa_path.as_deref(),
+            self.railroad.as_deref(),
             self.js_runtime.as_deref(),
             !self.no_parser,
             if self.disable_optimizations {
@@ -966,9 +1087,45 @@ impl Generate {
             } else {
                 OptLevel::default()
             },
+            self.cxx,
+            self.lexer_only,
+            self.split_tables,
+            self.strict,
+            report.as_mut(),
             &mut diagnostics,
         );
-        if json_summary {
+        let result = if self.deny_warnings {
+            result.and_then(|()| tree_sitter_generate::deny_warnings(&diagnostics))
+        } else {
+            result
+        };
+        let grammar_path = current_dir.join(
+            self.grammar_path
+                .as_deref()
+                .unwrap_or_else(|| Path::new("grammar.js")),
+        );
+        let grammar_source = if grammar_path
+            .extension()
+            .is_some_and(|ext| ext == "js" || ext == "json")
+        {
+            fs::read_to_string(&grammar_path).ok()
+        } else {
+            None
+        };
+        if self.diagnostics == Some(DiagnosticsFormat::Json) {
+            let mut json = diagnostics
+                .iter()
+                .map(|d| d.to_json(grammar_source.as_deref()))
+                .collect::<Vec<_>>();
+            if let Err(err) = &result {
+                json.push(err.to_json(grammar_source.as_deref()));
+            }
+            eprintln!("{}", serde_json::to_string_pretty(&json)?);
+            if result.is_err() {
+                // Exit early to prevent errors from being printed a second time in the caller
+                std::process::exit(1);
+            }
+        } else if json_summary {
             #[derive(serde::Serialize)]
             struct Envelope<'a> {
                 diagnostics: &'a [Diagnostic],
@@ -988,8 +1145,41 @@ impl Generate {
                 warn!("{d}");
             }
             if let Err(err) = result {
+                let mut message = err.to_string();
+                if let Some((source, range)) = grammar_source
+                    .as_deref()
+                    .and_then(|source| Some((source, err.to_json(Some(source)).range?)))
+                {
+                    let path = grammar_path
+                        .strip_prefix(current_dir)
+                        .unwrap_or(&grammar_path);
+                    message.push('\n');
+                    message.push_str(&util::source_snippet(path, source, range.start..range.end));
+                }
                 // Removes extra context associated with the error
-                Err(anyhow!(err.to_string())).with_context(|| "Error when generating parser")?;
+                Err(anyhow!(message)).with_context(|| "Error when generating parser")?;
+            }
+        }
+
+        if let (Some(path), Some(report)) = (&self.report, &report) {
+            if path.as_os_str() == "-" {
+                println!("{report}");
+            } else {
+                let path = current_dir.join(path);
+                fs::write(&path, format!("{report}\n"))
+                    .with_context(|| format!("Failed to write report to {}", path.display()))?;
+            }
+        }
+
+        if let (Some(path), Some(report)) = (&self.report_token_conflicts, &report) {
+            let json = serde_json::to_string_pretty(&report.token_conflicts)?;
+            if path.as_os_str() == "-" {
+                println!("{json}");
+            } else {
+                let path = current_dir.join(path);
+                fs::write(&path, format!("{json}\n")).with_context(|| {
+                    format!("Failed to write token conflicts to {}", path.display())
+                })?;
             }
         }
 
@@ -1072,12 +1262,16 @@ impl Parse {
         } else {
             self.json_summary
         };
-        let output = if self.output_dot {
+        let output = if let Some(output_format) = self.output_format {
+            output_format
+        } else if self.output_dot {
             ParseOutput::Dot
         } else if self.output_xml {
             ParseOutput::Xml
         } else if self.output_cst {
             ParseOutput::Cst
+        } else if self.output_proto {
+            ParseOutput::Proto
         } else if self.quiet || json_summary {
             ParseOutput::Quiet
         } else {
@@ -1137,6 +1331,8 @@ impl Parse {
                 .collect::<Vec<&str>>(),
             output,
             print_time: time,
+            print_stats: should_track_stats,
+            collect_tree_stats: json_summary,
             timeout,
             stats: &mut stats,
             debug,
@@ -1145,6 +1341,7 @@ impl Parse {
             encoding,
             open_log: self.open_log,
             no_ranges: self.no_ranges,
+            max_depth: self.max_depth,
             parse_theme: &parse_theme,
         };
 
@@ -1159,6 +1356,9 @@ impl Parse {
                     stats.cumulative_stats.total_bytes += bytes;
                     stats.cumulative_stats.total_duration += duration;
                 }
+                if let Some(tree_stats) = &parse_result.tree_stats {
+                    stats.cumulative_tree_stats.add(tree_stats);
+                }
             }
 
             has_error |= !parse_result.successful;
@@ -1280,7 +1480,10 @@ impl Parse {
         }
 
         if should_track_stats {
-            println!("\n{}", stats.cumulative_stats);
+            println!(
+                "\n{}Total {}",
+                stats.cumulative_stats, stats.cumulative_tree_stats
+            );
         }
         if json_summary {
             println!("{}", serde_json::to_string_pretty(&stats)?);
@@ -1361,6 +1564,21 @@ impl Test {
             TestSummary::new(stat, self.update, self.overview_only, self.json_summary);
         test_summary.use_markers = self.show_diff_markers;
 
+        let coverage_rules = if self.coverage {
+            let grammar_json_path = current_dir.join("src").join("grammar.json");
+            let grammar_json = fs::read_to_string(&grammar_json_path).with_context(|| {
+                format!(
+                    "Failed to read {}, which is needed to report rule coverage",
+                    grammar_json_path.display()
+                )
+            })?;
+            Some(tree_sitter_generate::coverable_rules_for_grammar(
+                &grammar_json,
+            )?)
+        } else {
+            None
+        };
+
         // Run the corpus tests. Look for them in `test/corpus`.
         let test_corpus_dir = test_dir.join("corpus");
         if test_corpus_dir.is_dir() {
@@ -1376,6 +1594,7 @@ impl Test {
                 languages: languages.iter().map(|(l, n)| (n.as_str(), l)).collect(),
                 show_fields: self.show_fields,
                 overview_only: self.overview_only,
+                coverage: coverage_rules.as_deref(),
             };
 
             check_test(
@@ -1582,6 +1801,10 @@ impl Query {
             &cancellation_flag,
         )?;
 
+        if !self.quiet {
+            query::write_capture_row_header(&mut io::stdout(), self.format)?;
+        }
+
         match input {
             CliInput::Paths(paths) => {
                 let language = loader.select_language(
@@ -1600,6 +1823,7 @@ impl Query {
                     quiet: self.quiet,
                     print_time: self.time,
                     stdin: false,
+                    format: self.format,
                 };
                 for path in paths {
                     query::query_file_at_path(
@@ -1645,6 +1869,7 @@ impl Query {
                     quiet: self.quiet,
                     print_time: self.time,
                     stdin: true,
+                    format: self.format,
                 };
                 query::query_file_at_path(language, &path, &name, query_path, &opts, None)?;
                 fs::remove_file(path)?;
@@ -1665,6 +1890,7 @@ impl Query {
                     quiet: self.quiet,
                     print_time: self.time,
                     stdin: true,
+                    format: self.format,
                 };
                 query::query_file_at_path(&language, &path, "stdin", query_path, &opts, None)?;
                 fs::remove_file(path)?;
@@ -2013,6 +2239,90 @@ impl DumpLanguages {
     }
 }
 
+impl ConfigCommand {
+    fn run(self, loader: loader::Loader) -> Result<()> {
+        match self.command {
+            ConfigSubcommand::Dump(dump_options) => dump_options.run(loader),
+        }
+    }
+}
+
+impl ConfigDump {
+    fn run(self, mut loader: loader::Loader) -> Result<()> {
+        let config = Config::load(self.config_path)?;
+        let loader_config = config.get::<loader::Config>()?;
+        loader.find_all_languages(&loader_config)?;
+        let grammars = loader.discover();
+
+        // Paths are converted lossily, since serializing a path that isn't valid UTF-8 fails.
+        let parser_directories = loader_config
+            .parser_directories
+            .iter()
+            .map(|directory| {
+                let grammars = grammars
+                    .iter()
+                    .filter(|grammar| grammar.path.starts_with(directory))
+                    .map(|grammar| {
+                        serde_json::json!({
+                            "name": grammar.name,
+                            "path": grammar.path.to_string_lossy(),
+                            "scopes": grammar.scopes,
+                        })
+                    })
+                    .collect::<Vec<_>>();
+                serde_json::json!({
+                    "path": directory.to_string_lossy(),
+                    "exists": directory.is_dir(),
+                    "grammars": grammars,
+                })
+            })
+            .collect::<Vec<_>>();
+
+        if self.json {
+            let json = serde_json::json!({
+                "config_file": config.location.to_string_lossy(),
+                "config_file_exists": config.location.is_file(),
+                "config": config.config,
+                "parser_lib_path": loader.parser_lib_path.to_string_lossy(),
+                "parser_directories": parser_directories,
+            });
+            println!("{}", serde_json::to_string_pretty(&json)?);
+            return Ok(());
+        }
+
+        let location_note = if config.location.is_file() {
+            ""
+        } else {
+            " (not found, using defaults)"
+        };
+        println!("config file: {}{location_note}", config.location.display());
+        println!("config:\n{}", serde_json::to_string_pretty(&config.config)?);
+        println!("parser lib path: {}", loader.parser_lib_path.display());
+        if parser_directories.is_empty() {
+            println!("parser directories: none");
+        } else {
+            println!("parser directories:");
+        }
+        for (i, directory) in parser_directories.iter().enumerate() {
+            let path = directory["path"].as_str().unwrap_or_default();
+            let exists_note = if directory["exists"] == true {
+                ""
+            } else {
+                " (not found)"
+            };
+            println!("  {}. {path}{exists_note}", i + 1);
+            for grammar in directory["grammars"].as_array().into_iter().flatten() {
+                println!(
+                    "     {} ({})",
+                    grammar["name"].as_str().unwrap_or_default(),
+                    grammar["path"].as_str().unwrap_or_default(),
+                );
+            }
+        }
+        Ok(())
+    }
+}
+
 impl Complete {
     fn run(self, cli: &mut Command) {
         let name = cli.get_name().to_string();
@@ -2088,6 +2398,7 @@ fn run() -> Result<()> {
         | Commands::Generate(_)
         | Commands::InitConfig(_)
         | Commands::DumpLanguages(_)
+        | Commands::Config(_)
         | Commands::Complete(_) => &None,
     }
     .as_ref()
@@ -2109,6 +2420,7 @@ fn run() -> Result<()> {
         Commands::Tags(tags_options) => tags_options.run(loader, &current_dir)?,
         Commands::Playground(playground_options) => playground_options.run(&current_dir)?,
         Commands::DumpLanguages(dump_options) => dump_options.run(loader)?,
+        Commands::Config(config_options) => config_options.run(loader)?,
         Commands::Complete(complete_options) => complete_options.run(&mut cli),
     }
 
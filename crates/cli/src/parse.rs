@@ -1,8 +1,9 @@
 use std::{
     fmt, fs,
-    io::{self, Write},
+    io::{self, Read, Write},
     ops::ControlFlow,
     path::{Path, PathBuf},
+    rc::Rc,
     sync::atomic::{AtomicUsize, Ordering},
     time::{Duration, Instant},
 };
@@ -14,11 +15,11 @@ use log::info;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use tree_sitter::{
-    InputEdit, Language, LogType, ParseOptions, ParseState, Parser, Point, Range, Tree, TreeCursor,
-    ffi,
+    InputEdit, Language, LogType, Node, ParseOptions, ParseState, Parser, Point, Range, Tree,
+    TreeCursor, ffi,
 };
 
-use crate::{fuzz::edits::Edit, paint::paint, util};
+use crate::{fuzz::edits::Edit, paint::paint, proto, util};
 
 #[derive(Debug, Default, Serialize, JsonSchema)]
 pub struct Stats {
@@ -57,6 +58,70 @@ impl fmt::Display for Stats {
     }
 }
 
+/// The size and the number of errors of a parsed tree, shown by `tree-sitter parse --stat`.
+///
+/// Nodes are counted as a [`TreeCursor`] visits them, so hidden nodes aren't counted, but
+/// anonymous nodes, extras, and zero-width `MISSING` nodes are. The root node has a depth of 0.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct TreeStats {
+    pub node_count: usize,
+    pub max_depth: usize,
+    pub error_count: usize,
+    pub missing_count: usize,
+    pub bytes: usize,
+}
+
+impl TreeStats {
+    /// Count the nodes of a tree that was parsed from a document of the given length.
+    #[must_use]
+    pub fn new(tree: &Tree, bytes: usize) -> Self {
+        let mut stats = Self {
+            bytes,
+            ..Self::default()
+        };
+        let mut cursor = tree.walk();
+        loop {
+            let node = cursor.node();
+            stats.node_count += 1;
+            stats.max_depth = stats.max_depth.max(cursor.depth() as usize);
+            if node.is_error() {
+                stats.error_count += 1;
+            }
+            if node.is_missing() {
+                stats.missing_count += 1;
+            }
+
+            if cursor.goto_first_child() {
+                continue;
+            }
+            while !cursor.goto_next_sibling() {
+                if !cursor.goto_parent() {
+                    return stats;
+                }
+            }
+        }
+    }
+
+    /// Add another tree's statistics to these, keeping the greater of the two depths.
+    pub fn add(&mut self, other: &Self) {
+        self.node_count += other.node_count;
+        self.max_depth = self.max_depth.max(other.max_depth);
+        self.error_count += other.error_count;
+        self.missing_count += other.missing_count;
+        self.bytes += other.bytes;
+    }
+}
+
+impl fmt::Display for TreeStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "nodes: {}; max depth: {}; errors: {}; missing: {}; bytes: {}",
+            self.node_count, self.max_depth, self.error_count, self.missing_count, self.bytes,
+        )
+    }
+}
+
 /// Sets the color used in the output of `tree-sitter parse --cst`
 #[derive(Debug, Copy, Clone)]
 pub struct ParseTheme {
@@ -181,13 +246,25 @@ impl From<ParseThemeRaw> for ParseTheme {
     }
 }
 
-#[derive(Copy, Clone, PartialEq, Eq)]
+#[derive(Copy, Clone, PartialEq, Eq, ValueEnum)]
 pub enum ParseOutput {
+    #[value(name = "sexp")]
     Normal,
+    #[value(skip)]
     Quiet,
     Xml,
     Cst,
     Dot,
+    Proto,
+    Json,
+}
+
+impl ParseOutput {
+    /// Is this output meant to be consumed by other programs, such that nothing else may be
+    /// written to stdout alongside it?
+    const fn is_machine_readable(self) -> bool {
+        matches!(self, Self::Proto | Self::Json)
+    }
 }
 
 /// A position in a multi-line text document, in terms of rows and columns.
@@ -218,6 +295,7 @@ pub struct ParseSummary {
     pub end: Option<ParsePoint>,
     pub duration: Option<Duration>,
     pub bytes: Option<usize>,
+    pub tree_stats: Option<TreeStats>,
 }
 
 impl ParseSummary {
@@ -235,6 +313,7 @@ impl ParseSummary {
 pub struct ParseStats {
     pub parse_summaries: Vec<ParseSummary>,
     pub cumulative_stats: Stats,
+    pub cumulative_tree_stats: TreeStats,
     pub source_count: usize,
 }
 
@@ -243,6 +322,7 @@ impl Default for ParseStats {
         Self {
             parse_summaries: Vec::new(),
             cumulative_stats: Stats::default(),
+            cumulative_tree_stats: TreeStats::default(),
             source_count: 1,
         }
     }
@@ -261,6 +341,8 @@ pub struct ParseFileOptions<'a> {
     pub output: ParseOutput,
     pub stats: &'a mut ParseStats,
     pub print_time: bool,
+    pub print_stats: bool,
+    pub collect_tree_stats: bool,
     pub timeout: u64,
     pub debug: ParseDebugType,
     pub debug_graph: bool,
@@ -268,6 +350,7 @@ pub struct ParseFileOptions<'a> {
     pub encoding: Option<u32>,
     pub open_log: bool,
     pub no_ranges: bool,
+    pub max_depth: Option<usize>,
     pub parse_theme: &'a ParseTheme,
 }
 
@@ -457,58 +540,7 @@ pub fn parse_file_at_path(
         let mut cursor = tree.walk();
 
         if opts.output == ParseOutput::Normal {
-            let mut needs_newline = false;
-            let mut indent_level = 0;
-            let mut did_visit_children = false;
-            loop {
-                let node = cursor.node();
-                let is_named = node.is_named();
-                if did_visit_children {
-                    if is_named {
-                        stdout.write_all(b")")?;
-                        needs_newline = true;
-                    }
-                    if cursor.goto_next_sibling() {
-                        did_visit_children = false;
-                    } else if cursor.goto_parent() {
-                        did_visit_children = true;
-                        indent_level -= 1;
-                    } else {
-                        break;
-                    }
-                } else {
-                    if is_named {
-                        if needs_newline {
-                            stdout.write_all(b"\n")?;
-                        }
-                        for _ in 0..indent_level {
-                            stdout.write_all(b"  ")?;
-                        }
-                        let start = node.start_position();
-                        let end = node.end_position();
-                        if let Some(field_name) = cursor.field_name() {
-                            write!(&mut stdout, "{field_name}: ")?;
-                        }
-                        write!(&mut stdout, "({}", node.kind())?;
-                        if !opts.no_ranges {
-                            write!(
-                                &mut stdout,
-                                " [{}, {}] - [{}, {}]",
-                                start.row, start.column, end.row, end.column
-                            )?;
-                        }
-                        needs_newline = true;
-                    }
-                    if cursor.goto_first_child() {
-                        did_visit_children = false;
-                        indent_level += 1;
-                    } else {
-                        did_visit_children = true;
-                    }
-                }
-            }
-            cursor.reset(tree.root_node());
-            writeln!(&mut stdout)?;
+            render_sexp(&tree, &mut cursor, opts, &mut stdout)?;
         }
 
         if opts.output == ParseOutput::Cst {
@@ -621,6 +653,14 @@ pub fn parse_file_at_path(
             util::print_tree_graph(&tree, "log.html", opts.open_log).unwrap();
         }
 
+        if opts.output == ParseOutput::Proto {
+            proto::write_tree(&mut stdout, path, &tree)?;
+        }
+
+        if opts.output == ParseOutput::Json {
+            render_json(&tree, &mut cursor, &mut stdout)?;
+        }
+
         let mut first_error = None;
         let mut earliest_node_with_error = None;
         'outer: loop {
@@ -662,7 +702,8 @@ pub fn parse_file_at_path(
             }
         }
 
-        if first_error.is_some() || opts.print_time {
+        // Keep the protobuf and JSON streams on stdout free of any other output.
+        if (first_error.is_some() || opts.print_time) && !opts.output.is_machine_readable() {
             let path = path.to_string_lossy();
             write!(
                 &mut stdout,
@@ -711,6 +752,16 @@ pub fn parse_file_at_path(
             writeln!(&mut stdout)?;
         }
 
+        // Walking the tree is only worth it if the stats are printed or summarized.
+        let tree_stats = (opts.print_stats || opts.collect_tree_stats)
+            .then(|| TreeStats::new(&tree, source_code.len()));
+        if let Some(tree_stats) = &tree_stats
+            && opts.print_stats
+            && !opts.output.is_machine_readable()
+        {
+            writeln!(&mut stdout, "{name:max_path_length$}\t{tree_stats}")?;
+        }
+
         opts.stats.parse_summaries.push(ParseSummary {
             file: path.to_path_buf(),
             successful: first_error.is_none(),
@@ -718,13 +769,14 @@ pub fn parse_file_at_path(
             end: Some(tree.root_node().end_position().into()),
             duration: Some(parse_duration),
             bytes: Some(source_code.len()),
+            tree_stats,
         });
 
         return Ok(());
     }
     parser.stop_printing_dot_graphs();
 
-    if opts.print_time {
+    if opts.print_time && !opts.output.is_machine_readable() {
         let duration = parse_time.elapsed();
         let duration_ms = duration.as_micros() as f64 / 1e3;
         writeln!(
@@ -742,6 +794,7 @@ pub fn parse_file_at_path(
         end: None,
         duration: None,
         bytes: Some(source_code.len()),
+        tree_stats: None,
     });
 
     Ok(())
@@ -768,6 +821,129 @@ const fn escape_delimiter(c: char) -> Option<&'static str> {
     })
 }
 
+/// Print the tree as an S-expression, with a line for each named node.
+pub fn render_sexp<'a, 'b: 'a>(
+    tree: &'b Tree,
+    cursor: &mut TreeCursor<'a>,
+    opts: &ParseFileOptions,
+    out: &mut impl Write,
+) -> Result<()> {
+    let mut needs_newline = false;
+    let mut indent_level = 0;
+    let mut did_visit_children = false;
+    loop {
+        let node = cursor.node();
+        let is_named = node.is_named();
+        if did_visit_children {
+            if is_named {
+                out.write_all(b")")?;
+                needs_newline = true;
+            }
+            if cursor.goto_next_sibling() {
+                did_visit_children = false;
+            } else if cursor.goto_parent() {
+                did_visit_children = true;
+                indent_level -= 1;
+            } else {
+                break;
+            }
+        } else {
+            if is_named {
+                if needs_newline {
+                    out.write_all(b"\n")?;
+                }
+                for _ in 0..indent_level {
+                    out.write_all(b"  ")?;
+                }
+                let start = node.start_position();
+                let end = node.end_position();
+                if let Some(field_name) = cursor.field_name() {
+                    write!(out, "{field_name}: ")?;
+                }
+                write!(out, "({}", node.kind())?;
+                if !opts.no_ranges {
+                    write!(
+                        out,
+                        " [{}, {}] - [{}, {}]",
+                        start.row, start.column, end.row, end.column
+                    )?;
+                }
+                needs_newline = true;
+            }
+            if is_collapsed(opts, indent_level, node.named_child_count() > 0) {
+                out.write_all(b" ...")?;
+                did_visit_children = true;
+            } else if cursor.goto_first_child() {
+                did_visit_children = false;
+                indent_level += 1;
+            } else {
+                did_visit_children = true;
+            }
+        }
+    }
+    cursor.reset(tree.root_node());
+    writeln!(out)?;
+    Ok(())
+}
+
+/// Print the tree as a single line of JSON, with a nested object for every node, including
+/// anonymous nodes, extras, and error nodes.
+pub fn render_json<'a, 'b: 'a>(
+    tree: &'b Tree,
+    cursor: &mut TreeCursor<'a>,
+    out: &mut impl Write,
+) -> Result<()> {
+    let mut did_visit_children = false;
+    loop {
+        if did_visit_children {
+            out.write_all(b"]}")?;
+            if cursor.goto_next_sibling() {
+                out.write_all(b",")?;
+                did_visit_children = false;
+            } else if !cursor.goto_parent() {
+                break;
+            }
+        } else {
+            let node = cursor.node();
+            let start = node.start_position();
+            let end = node.end_position();
+            write!(
+                out,
+                "{{\"kind\":{},\"named\":{}",
+                serde_json::to_string(node.kind())?,
+                node.is_named()
+            )?;
+            if let Some(field_name) = cursor.field_name() {
+                write!(out, ",\"field\":{}", serde_json::to_string(field_name)?)?;
+            }
+            write!(
+                out,
+                ",\"start_byte\":{},\"end_byte\":{},\
+                 \"start_point\":{{\"row\":{},\"column\":{}}},\
+                 \"end_point\":{{\"row\":{},\"column\":{}}},\
+                 \"is_error\":{},\"is_missing\":{},\"children\":[",
+                node.start_byte(),
+                node.end_byte(),
+                start.row,
+                start.column,
+                end.row,
+                end.column,
+                node.is_error(),
+                node.is_missing(),
+            )?;
+            did_visit_children = !cursor.goto_first_child();
+        }
+    }
+    cursor.reset(tree.root_node());
+    writeln!(out)?;
+    Ok(())
+}
+
+/// Should the children of a node be elided, because of `--max-depth`?
+fn is_collapsed(opts: &ParseFileOptions, depth: usize, has_children: bool) -> bool {
+    has_children && opts.max_depth.is_some_and(|max_depth| depth >= max_depth)
+}
+
 pub fn render_cst<'a, 'b: 'a>(
     source_code: &[u8],
     tree: &'b Tree,
@@ -803,6 +979,7 @@ pub fn render_cst<'a, 'b: 'a>(
                 break;
             }
         } else {
+            let collapsed = is_cst_collapsed(opts, indent_level, cursor.node());
             cst_render_node(
                 opts,
                 cursor,
@@ -812,7 +989,9 @@ pub fn render_cst<'a, 'b: 'a>(
                 indent_level,
                 in_error,
             )?;
-            if cursor.goto_first_child() {
+            if collapsed {
+                did_visit_children = true;
+            } else if cursor.goto_first_child() {
                 did_visit_children = false;
                 indent_level += 1;
                 if cursor.node().has_error() {
@@ -827,6 +1006,12 @@ pub fn render_cst<'a, 'b: 'a>(
     Ok(())
 }
 
+/// The CST output is indented by an extra level when ranges are shown.
+fn is_cst_collapsed(opts: &ParseFileOptions, indent_level: usize, node: Node) -> bool {
+    let depth = indent_level - usize::from(!opts.no_ranges);
+    is_collapsed(opts, depth, node.child_count() > 0)
+}
+
 struct CstNodeText<'a>(&'a str);
 
 impl std::fmt::Display for CstNodeText<'_> {
@@ -1066,11 +1251,66 @@ fn cst_render_node(
             (total_width, indent_level),
         )?;
     }
+    if is_cst_collapsed(opts, indent_level, node) {
+        write!(out, " ...")?;
+    }
     writeln!(out)?;
 
     Ok(())
 }
 
+const READER_CHUNK_SIZE: usize = 64 * 1024;
+
+/// A chunk of input read by [`parse_reader`], starting at a given offset.
+struct ReaderChunk(Rc<[u8]>, usize);
+
+impl AsRef<[u8]> for ReaderChunk {
+    fn as_ref(&self) -> &[u8] {
+        &self.0[self.1..]
+    }
+}
+
+/// Parse the contents of a reader, such as a decompressor wrapping a compressed
+/// file, without first reading all of it into a string.
+///
+/// The input is read in chunks as the parser asks for it. The parser can go back
+/// to earlier offsets, so the chunks are kept until parsing finishes.
+pub fn parse_reader(parser: &mut Parser, mut reader: impl Read) -> Result<Option<Tree>> {
+    let mut chunks = Vec::<(usize, Rc<[u8]>)>::new();
+    let mut length = 0;
+    let mut error = None;
+    let tree = parser.parse_with_options(
+        &mut |offset, _| {
+            while offset >= length && error.is_none() {
+                let mut buffer = vec![0; READER_CHUNK_SIZE];
+                match reader.read(&mut buffer) {
+                    Ok(0) => break,
+                    Ok(count) => {
+                        buffer.truncate(count);
+                        chunks.push((length, buffer.into()));
+                        length += count;
+                    }
+                    Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                    Err(e) => error = Some(e),
+                }
+            }
+            let index = chunks.partition_point(|(start, _)| *start <= offset);
+            match chunks.get(index.wrapping_sub(1)) {
+                Some((start, bytes)) if offset < length => {
+                    ReaderChunk(bytes.clone(), offset - start)
+                }
+                _ => ReaderChunk(Rc::from([]), 0),
+            }
+        },
+        None,
+        None,
+    );
+    if let Some(error) = error {
+        return Err(error).context("Failed to read input");
+    }
+    Ok(tree)
+}
+
 pub fn perform_edit(tree: &mut Tree, input: &mut Vec<u8>, edit: &Edit) -> Result<InputEdit> {
     let start_byte = edit.position;
     let old_end_byte = edit.position + edit.deleted_length;
@@ -1180,3 +1420,240 @@ pub fn position_for_offset(input: &[u8], offset: usize) -> Result<Point> {
     };
     Ok(result)
 }
+
+#[cfg(test)]
+mod tests {
+    use tree_sitter::Parser;
+
+    use super::*;
+    use crate::tests::get_language;
+
+    fn render(source: &str, output: ParseOutput, max_depth: Option<usize>) -> String {
+        let language = get_language("javascript");
+        let mut parser = Parser::new();
+        parser.set_language(&language).unwrap();
+        let tree = parser.parse(source, None).unwrap();
+        let opts = ParseFileOptions {
+            edits: &[],
+            output,
+            stats: &mut ParseStats::default(),
+            print_time: false,
+            print_stats: false,
+            collect_tree_stats: false,
+            timeout: 0,
+            debug: ParseDebugType::Quiet,
+            debug_graph: false,
+            cancellation_flag: None,
+            encoding: None,
+            open_log: false,
+            no_ranges: true,
+            max_depth,
+            parse_theme: &ParseTheme::empty(),
+        };
+
+        let mut result = Vec::new();
+        let mut cursor = tree.walk();
+        if output == ParseOutput::Cst {
+            render_cst(source.as_bytes(), &tree, &mut cursor, &opts, &mut result).unwrap();
+        } else if output == ParseOutput::Json {
+            render_json(&tree, &mut cursor, &mut result).unwrap();
+        } else {
+            render_sexp(&tree, &mut cursor, &opts, &mut result).unwrap();
+        }
+        String::from_utf8(result).unwrap()
+    }
+
+    #[test]
+    fn test_rendering_with_max_depth() {
+        let source = "function a() { return b; }";
+        assert_eq!(
+            render(source, ParseOutput::Normal, None),
+            "\
+(program
+  (function_declaration
+    name: (identifier)
+    parameters: (formal_parameters)
+    body: (statement_block
+      (return_statement
+        (identifier)))))
+"
+        );
+
+        // Nodes without named children aren't collapsed in the S-expression output,
+        // because their anonymous children are never printed.
+        assert_eq!(
+            render(source, ParseOutput::Normal, Some(2)),
+            "\
+(program
+  (function_declaration
+    name: (identifier)
+    parameters: (formal_parameters)
+    body: (statement_block ...)))
+"
+        );
+        assert_eq!(
+            render(source, ParseOutput::Normal, Some(0)),
+            "(program ...)\n"
+        );
+
+        assert_eq!(
+            render(source, ParseOutput::Cst, Some(2)),
+            "\
+program
+  function_declaration
+    \"function\"
+    name: identifier `a`
+    parameters: formal_parameters ...
+    body: statement_block ...
+"
+        );
+    }
+
+    #[test]
+    fn test_rendering_json() {
+        let output = render("x = (1 /* c */;\n", ParseOutput::Json, None);
+        assert_eq!(output.lines().count(), 1);
+        let root = serde_json::from_str::<serde_json::Value>(&output).unwrap();
+        assert_eq!(root["kind"], "program");
+        assert_eq!(root["named"], true);
+        assert_eq!(root["start_byte"], 0);
+        assert_eq!(root["end_byte"], 16);
+        assert_eq!(
+            root["end_point"],
+            serde_json::json!({ "row": 1, "column": 0 })
+        );
+        assert!(root.get("field").is_none());
+
+        let assignment = &root["children"][0]["children"][0];
+        assert_eq!(assignment["kind"], "assignment_expression");
+        let children = assignment["children"].as_array().unwrap();
+        let fields = children.iter().map(|c| c.get("field")).collect::<Vec<_>>();
+        assert_eq!(fields, [Some(&"left".into()), None, Some(&"right".into())]);
+
+        // Anonymous nodes, extras, and missing nodes are all included.
+        let parenthesized = &children[2];
+        let kinds = parenthesized["children"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|c| (c["kind"].as_str().unwrap(), c["named"].as_bool().unwrap()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            kinds,
+            [
+                ("(", false),
+                ("number", true),
+                ("comment", true),
+                (")", false)
+            ]
+        );
+        let comment = &parenthesized["children"][2];
+        assert_eq!(comment["start_byte"], 7);
+        assert_eq!(
+            comment["end_point"],
+            serde_json::json!({ "row": 0, "column": 14 })
+        );
+        assert_eq!(comment["children"], serde_json::json!([]));
+        let missing = &parenthesized["children"][3];
+        assert_eq!(missing["is_missing"], true);
+        assert_eq!(missing["is_error"], false);
+        assert_eq!(missing["start_byte"], missing["end_byte"]);
+
+        let output = render("x = (1 +;\n", ParseOutput::Json, None);
+        let root = serde_json::from_str::<serde_json::Value>(&output).unwrap();
+        let error = &root["children"][0]["children"][1];
+        assert_eq!(error["kind"], "ERROR");
+        assert_eq!(error["is_error"], true);
+        assert_eq!(error["is_missing"], false);
+        assert_eq!(error["children"].as_array().unwrap().len(), 4);
+    }
+
+    #[test]
+    fn test_parsing_with_tree_stats() {
+        let language = get_language("javascript");
+        let mut parser = Parser::new();
+        let dir = tempfile::tempdir().unwrap();
+        let files = [("a.js", "a; }\n"), ("b.js", "x = (1 /* c */;\n")];
+
+        let mut stats = ParseStats::default();
+        let mut opts = ParseFileOptions {
+            edits: &[],
+            output: ParseOutput::Quiet,
+            stats: &mut stats,
+            print_time: false,
+            print_stats: false,
+            collect_tree_stats: true,
+            timeout: 0,
+            debug: ParseDebugType::Quiet,
+            debug_graph: false,
+            cancellation_flag: None,
+            encoding: None,
+            open_log: false,
+            no_ranges: true,
+            max_depth: None,
+            parse_theme: &ParseTheme::empty(),
+        };
+        for (name, source) in files {
+            let path = dir.path().join(name);
+            fs::write(&path, source).unwrap();
+            parse_file_at_path(&mut parser, &language, &path, name, 4, &mut opts).unwrap();
+        }
+
+        // The comment is an extra and the missing `)` is zero-width, but both are counted as
+        // nodes, along with the anonymous `=` and `;` tokens.
+        let tree_stats = opts
+            .stats
+            .parse_summaries
+            .iter()
+            .map(|summary| summary.tree_stats.unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            tree_stats,
+            [
+                TreeStats {
+                    node_count: 6,
+                    max_depth: 2,
+                    error_count: 1,
+                    missing_count: 0,
+                    bytes: 5,
+                },
+                TreeStats {
+                    node_count: 11,
+                    max_depth: 4,
+                    error_count: 0,
+                    missing_count: 1,
+                    bytes: 16,
+                },
+            ]
+        );
+
+        let mut total = TreeStats::default();
+        for stats in &tree_stats {
+            total.add(stats);
+        }
+        assert_eq!(
+            total.to_string(),
+            "nodes: 17; max depth: 4; errors: 1; missing: 1; bytes: 21"
+        );
+
+        // The trees aren't walked if their stats aren't needed.
+        opts.collect_tree_stats = false;
+        parse_file_at_path(
+            &mut parser,
+            &language,
+            &dir.path().join("a.js"),
+            "a.js",
+            4,
+            &mut opts,
+        )
+        .unwrap();
+        assert!(
+            opts.stats
+                .parse_summaries
+                .last()
+                .unwrap()
+                .tree_stats
+                .is_none()
+        );
+    }
+}
@@ -0,0 +1,271 @@
+//! Encoding of syntax trees as protobuf messages, following the schema in `tree.proto`.
+
+use std::{
+    collections::HashMap,
+    io::{self, Write},
+    path::Path,
+};
+
+use tree_sitter::Tree;
+
+pub const NODE_FLAG_NAMED: u32 = 1;
+pub const NODE_FLAG_EXTRA: u32 = 2;
+pub const NODE_FLAG_ERROR: u32 = 4;
+pub const NODE_FLAG_MISSING: u32 = 8;
+pub const NODE_FLAG_HAS_ERROR: u32 = 16;
+
+const WIRE_TYPE_VARINT: u32 = 0;
+const WIRE_TYPE_LEN: u32 = 2;
+
+/// Write a tree as a length-delimited `Tree` message.
+pub fn write_tree(writer: &mut impl Write, path: &Path, tree: &Tree) -> io::Result<()> {
+    let message = encode_tree(path, tree);
+    let mut prefix = Vec::with_capacity(10);
+    write_varint(&mut prefix, message.len() as u64);
+    writer.write_all(&prefix)?;
+    writer.write_all(&message)
+}
+
+/// Encode a tree as a `Tree` message, without a length prefix.
+#[must_use]
+pub fn encode_tree(path: &Path, tree: &Tree) -> Vec<u8> {
+    let mut kinds = Interner::default();
+    let mut fields = Interner::default();
+    let mut nodes = Vec::new();
+
+    let mut node_message = Vec::new();
+    let mut cursor = tree.walk();
+    let mut visited_children = false;
+    loop {
+        if visited_children {
+            if cursor.goto_next_sibling() {
+                visited_children = false;
+            } else if !cursor.goto_parent() {
+                break;
+            }
+            continue;
+        }
+
+        let node = cursor.node();
+        let mut flags = 0;
+        for (flag, value) in [
+            (NODE_FLAG_NAMED, node.is_named()),
+            (NODE_FLAG_EXTRA, node.is_extra()),
+            (NODE_FLAG_ERROR, node.is_error()),
+            (NODE_FLAG_MISSING, node.is_missing()),
+            (NODE_FLAG_HAS_ERROR, node.has_error()),
+        ] {
+            if value {
+                flags |= flag;
+            }
+        }
+        let field = cursor
+            .field_name()
+            .map_or(0, |name| fields.intern(name) + 1);
+        let start = node.start_position();
+        let end = node.end_position();
+
+        node_message.clear();
+        for (number, value) in [
+            (1, kinds.intern(node.kind())),
+            (2, field),
+            (3, node.child_count()),
+            (4, node.start_byte() as u32),
+            (5, node.end_byte() as u32),
+            (6, start.row as u32),
+            (7, start.column as u32),
+            (8, end.row as u32),
+            (9, end.column as u32),
+            (10, flags),
+        ] {
+            write_uint32_field(&mut node_message, number, value);
+        }
+        write_bytes_field(&mut nodes, 4, &node_message);
+
+        visited_children = !cursor.goto_first_child();
+    }
+
+    let mut message = Vec::with_capacity(nodes.len() + 256);
+    write_bytes_field(&mut message, 1, path.to_string_lossy().as_bytes());
+    for kind in &kinds.values {
+        write_bytes_field(&mut message, 2, kind.as_bytes());
+    }
+    for field in &fields.values {
+        write_bytes_field(&mut message, 3, field.as_bytes());
+    }
+    message.extend_from_slice(&nodes);
+    message
+}
+
+#[derive(Default)]
+struct Interner<'a> {
+    ids: HashMap<&'a str, u32>,
+    values: Vec<&'a str>,
+}
+
+impl<'a> Interner<'a> {
+    fn intern(&mut self, value: &'a str) -> u32 {
+        *self.ids.entry(value).or_insert_with(|| {
+            self.values.push(value);
+            self.values.len() as u32 - 1
+        })
+    }
+}
+
+fn write_varint(output: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        output.push((value as u8) | 0x80);
+        value >>= 7;
+    }
+    output.push(value as u8);
+}
+
+fn write_uint32_field(output: &mut Vec<u8>, number: u32, value: u32) {
+    // Scalar fields with default values are omitted in proto3.
+    if value != 0 {
+        write_varint(output, u64::from(number << 3 | WIRE_TYPE_VARINT));
+        write_varint(output, u64::from(value));
+    }
+}
+
+fn write_bytes_field(output: &mut Vec<u8>, number: u32, value: &[u8]) {
+    write_varint(output, u64::from(number << 3 | WIRE_TYPE_LEN));
+    write_varint(output, value.len() as u64);
+    output.extend_from_slice(value);
+}
+
+#[cfg(test)]
+mod tests {
+    use tree_sitter::Parser;
+
+    use super::*;
+    use crate::tests::get_language;
+
+    #[derive(Debug, Default, PartialEq, Eq)]
+    struct DecodedTree {
+        path: String,
+        kinds: Vec<String>,
+        fields: Vec<String>,
+        nodes: Vec<[u32; 10]>,
+    }
+
+    fn read_varint(input: &mut &[u8]) -> u64 {
+        let mut result = 0;
+        let mut shift = 0;
+        loop {
+            let byte = input[0];
+            *input = &input[1..];
+            result |= u64::from(byte & 0x7f) << shift;
+            if byte & 0x80 == 0 {
+                return result;
+            }
+            shift += 7;
+        }
+    }
+
+    fn read_fields(mut input: &[u8], mut f: impl FnMut(u32, Result<u64, &[u8]>)) {
+        while !input.is_empty() {
+            let key = read_varint(&mut input) as u32;
+            match key & 7 {
+                WIRE_TYPE_VARINT => f(key >> 3, Ok(read_varint(&mut input))),
+                WIRE_TYPE_LEN => {
+                    let len = read_varint(&mut input) as usize;
+                    let (value, rest) = input.split_at(len);
+                    input = rest;
+                    f(key >> 3, Err(value));
+                }
+                wire_type => panic!("unexpected wire type {wire_type}"),
+            }
+        }
+    }
+
+    fn decode_tree(mut input: &[u8]) -> DecodedTree {
+        let len = read_varint(&mut input) as usize;
+        assert_eq!(input.len(), len);
+
+        let mut tree = DecodedTree::default();
+        let string = |bytes: &[u8]| String::from_utf8(bytes.to_vec()).unwrap();
+        read_fields(input, |number, value| match (number, value) {
+            (1, Err(bytes)) => tree.path = string(bytes),
+            (2, Err(bytes)) => tree.kinds.push(string(bytes)),
+            (3, Err(bytes)) => tree.fields.push(string(bytes)),
+            (4, Err(bytes)) => {
+                let mut node = [0; 10];
+                read_fields(bytes, |number, value| {
+                    node[number as usize - 1] = value.unwrap() as u32;
+                });
+                tree.nodes.push(node);
+            }
+            _ => panic!("unexpected field {number}"),
+        });
+        tree
+    }
+
+    #[test]
+    fn test_tree_round_trip() {
+        let source = "[1, {\"a\": true}, nul]";
+        let mut parser = Parser::new();
+        parser.set_language(&get_language("json")).unwrap();
+        let tree = parser.parse(source, None).unwrap();
+
+        let mut output = Vec::new();
+        write_tree(&mut output, Path::new("example.json"), &tree).unwrap();
+        let decoded = decode_tree(&output);
+        assert_eq!(decoded.path, "example.json");
+
+        let mut nodes = decoded.nodes.iter();
+        let mut cursor = tree.walk();
+        let mut visited_children = false;
+        loop {
+            if visited_children {
+                if cursor.goto_next_sibling() {
+                    visited_children = false;
+                } else if !cursor.goto_parent() {
+                    break;
+                }
+                continue;
+            }
+
+            let node = cursor.node();
+            let [
+                kind,
+                field,
+                child_count,
+                start_byte,
+                end_byte,
+                start_row,
+                start_column,
+                end_row,
+                end_column,
+                flags,
+            ] = *nodes.next().unwrap();
+            assert_eq!(decoded.kinds[kind as usize], node.kind());
+            assert_eq!(
+                field
+                    .checked_sub(1)
+                    .map(|i| decoded.fields[i as usize].as_str()),
+                cursor.field_name()
+            );
+            assert_eq!(child_count, node.child_count());
+            assert_eq!(start_byte as usize, node.start_byte());
+            assert_eq!(end_byte as usize, node.end_byte());
+            assert_eq!(start_row as usize, node.start_position().row);
+            assert_eq!(start_column as usize, node.start_position().column);
+            assert_eq!(end_row as usize, node.end_position().row);
+            assert_eq!(end_column as usize, node.end_position().column);
+            assert_eq!(flags & NODE_FLAG_NAMED != 0, node.is_named());
+            assert_eq!(flags & NODE_FLAG_ERROR != 0, node.is_error());
+            assert_eq!(flags & NODE_FLAG_HAS_ERROR != 0, node.has_error());
+
+            visited_children = !cursor.goto_first_child();
+        }
+        assert!(nodes.next().is_none());
+        assert!(decoded.fields.contains(&"key".to_string()));
+        assert!(
+            decoded
+                .nodes
+                .iter()
+                .any(|node| node[9] & NODE_FLAG_ERROR != 0)
+        );
+    }
+}
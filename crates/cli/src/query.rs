@@ -7,15 +7,109 @@ use std::{
 };
 
 use anyhow::{Context, Result};
+use clap::ValueEnum;
 use log::warn;
 use streaming_iterator::StreamingIterator;
-use tree_sitter::{Language, Parser, Point, Query, QueryCursor};
+use tree_sitter::{Language, Node, Parser, Point, Query, QueryCursor};
 
 use crate::{
     query_testing::{self, to_utf8_point},
     test::{TestInfo, TestOutcome, TestResult, TestSummary},
 };
 
+#[derive(ValueEnum, Debug, Copy, Clone, Default, Eq, PartialEq)]
+pub enum QueryFormat {
+    /// Human-readable output, grouped by match
+    #[default]
+    Text,
+    /// One comma-separated row per capture
+    Csv,
+    /// One tab-separated row per capture
+    Tsv,
+}
+
+impl QueryFormat {
+    const fn delimiter(self) -> Option<char> {
+        match self {
+            Self::Text => None,
+            Self::Csv => Some(','),
+            Self::Tsv => Some('\t'),
+        }
+    }
+}
+
+const CAPTURE_ROW_COLUMNS: [&str; 7] = [
+    "capture",
+    "text",
+    "start_row",
+    "start_column",
+    "end_row",
+    "end_column",
+    "file",
+];
+
+/// Write the header row for the given delimited format. Nothing is written for
+/// [`QueryFormat::Text`].
+pub fn write_capture_row_header(out: &mut impl Write, format: QueryFormat) -> io::Result<()> {
+    if let Some(delimiter) = format.delimiter() {
+        write_delimited_row(out, delimiter, &CAPTURE_ROW_COLUMNS)?;
+    }
+    Ok(())
+}
+
+fn write_capture_row(
+    out: &mut impl Write,
+    delimiter: char,
+    capture_name: &str,
+    node: Node,
+    source_code: &[u8],
+    file: &str,
+) -> io::Result<()> {
+    let start = node.start_position();
+    let end = node.end_position();
+    write_delimited_row(
+        out,
+        delimiter,
+        &[
+            capture_name,
+            &String::from_utf8_lossy(&source_code[node.byte_range()]),
+            &start.row.to_string(),
+            &start.column.to_string(),
+            &end.row.to_string(),
+            &end.column.to_string(),
+            file,
+        ],
+    )
+}
+
+/// Write a single row, escaping fields as needed. CSV fields that contain the
+/// delimiter, a quote, or a line break are quoted, with any quotes doubled. TSV
+/// fields can't be quoted, so tabs, line breaks, and backslashes are escaped
+/// with a backslash instead.
+fn write_delimited_row(out: &mut impl Write, delimiter: char, fields: &[&str]) -> io::Result<()> {
+    for (i, field) in fields.iter().enumerate() {
+        if i > 0 {
+            write!(out, "{delimiter}")?;
+        }
+        if delimiter == '\t' {
+            for c in field.chars() {
+                match c {
+                    '\t' => write!(out, "\\t")?,
+                    '\n' => write!(out, "\\n")?,
+                    '\r' => write!(out, "\\r")?,
+                    '\\' => write!(out, "\\\\")?,
+                    c => write!(out, "{c}")?,
+                }
+            }
+        } else if field.contains([delimiter, '"', '\n', '\r']) {
+            write!(out, "\"{}\"", field.replace('"', "\"\""))?;
+        } else {
+            write!(out, "{field}")?;
+        }
+    }
+    writeln!(out)
+}
+
 #[derive(Default)]
 pub struct QueryFileOptions {
     pub ordered_captures: bool,
@@ -26,6 +120,7 @@ pub struct QueryFileOptions {
     pub quiet: bool,
     pub print_time: bool,
     pub stdin: bool,
+    pub format: QueryFormat,
 }
 
 pub fn query_file_at_path(
@@ -38,7 +133,26 @@ pub fn query_file_at_path(
 ) -> Result<()> {
     let stdout = io::stdout();
     let mut stdout = io::BufWriter::with_capacity(64 * 1024, stdout.lock());
+    query_file(
+        &mut stdout,
+        language,
+        path,
+        name,
+        query_path,
+        opts,
+        test_summary,
+    )
+}
 
+fn query_file(
+    stdout: &mut impl Write,
+    language: &Language,
+    path: &Path,
+    name: &str,
+    query_path: &Path,
+    opts: &QueryFileOptions,
+    test_summary: Option<&mut TestSummary>,
+) -> Result<()> {
     let query_source = fs::read_to_string(query_path)
         .with_context(|| format!("Error reading query file {}", query_path.display()))?;
     let query = Query::new(language, &query_source).with_context(|| "Query compilation failed")?;
@@ -62,9 +176,11 @@ pub fn query_file_at_path(
 
     let mut results = Vec::new();
     let should_test = test_summary.is_some();
+    let delimiter = opts.format.delimiter().filter(|_| !should_test);
+    let file_name = if opts.stdin { "stdin" } else { name };
 
-    if !should_test && !opts.stdin {
-        writeln!(&mut stdout, "{name}")?;
+    if !should_test && !opts.stdin && delimiter.is_none() {
+        writeln!(stdout, "{name}")?;
     }
 
     let source_code =
@@ -77,9 +193,20 @@ pub fn query_file_at_path(
         while let Some((mat, capture_index)) = captures.next() {
             let capture = mat.captures[*capture_index];
             let capture_name = &query.capture_names()[capture.index as usize];
-            if !opts.quiet && !should_test {
+            if let Some(delimiter) = delimiter {
+                if !opts.quiet {
+                    write_capture_row(
+                        stdout,
+                        delimiter,
+                        capture_name,
+                        capture.node,
+                        &source_code,
+                        file_name,
+                    )?;
+                }
+            } else if !opts.quiet && !should_test {
                 writeln!(
-                    &mut stdout,
+                    stdout,
                     "    pattern: {:>2}, capture: {} - {capture_name}, start: {}, end: {}, text: `{}`",
                     mat.pattern_index,
                     capture.index,
@@ -99,24 +226,35 @@ pub fn query_file_at_path(
     } else {
         let mut matches = query_cursor.matches(&query, tree.root_node(), source_code.as_slice());
         while let Some(m) = matches.next() {
-            if !opts.quiet && !should_test {
-                writeln!(&mut stdout, "  pattern: {}", m.pattern_index)?;
+            if !opts.quiet && !should_test && delimiter.is_none() {
+                writeln!(stdout, "  pattern: {}", m.pattern_index)?;
             }
             for capture in m.captures {
                 let start = capture.node.start_position();
                 let end = capture.node.end_position();
                 let capture_name = &query.capture_names()[capture.index as usize];
-                if !opts.quiet && !should_test {
+                if let Some(delimiter) = delimiter {
+                    if !opts.quiet {
+                        write_capture_row(
+                            stdout,
+                            delimiter,
+                            capture_name,
+                            capture.node,
+                            &source_code,
+                            file_name,
+                        )?;
+                    }
+                } else if !opts.quiet && !should_test {
                     if end.row == start.row {
                         writeln!(
-                            &mut stdout,
+                            stdout,
                             "    capture: {} - {capture_name}, start: {start}, end: {end}, text: `{}`",
                             capture.index,
                             capture.node.utf8_text(&source_code).unwrap_or("")
                         )?;
                     } else {
                         writeln!(
-                            &mut stdout,
+                            stdout,
                             "    capture: {capture_name}, start: {start}, end: {end}",
                         )?;
                     }
@@ -167,8 +305,179 @@ pub fn query_file_at_path(
         }
     }
     if opts.print_time {
-        writeln!(&mut stdout, "{:?}", start.elapsed())?;
+        // Keep the timing out of delimited output, so that it stays parseable.
+        if delimiter.is_some() {
+            eprintln!("{:?}", start.elapsed());
+        } else {
+            writeln!(stdout, "{:?}", start.elapsed())?;
+        }
     }
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::get_language;
+
+    fn render(source: &str, query: &str, format: QueryFormat) -> String {
+        render_with_options(
+            source,
+            query,
+            &QueryFileOptions {
+                format,
+                ..Default::default()
+            },
+        )
+    }
+
+    fn render_with_options(source: &str, query: &str, opts: &QueryFileOptions) -> String {
+        let dir = tempfile::tempdir().unwrap();
+        let source_path = dir.path().join("input.js");
+        let query_path = dir.path().join("query.scm");
+        fs::write(&source_path, source).unwrap();
+        fs::write(&query_path, query).unwrap();
+
+        let mut output = Vec::new();
+        write_capture_row_header(&mut output, opts.format).unwrap();
+        query_file(
+            &mut output,
+            &get_language("javascript"),
+            &source_path,
+            "input.js",
+            &query_path,
+            opts,
+            None,
+        )
+        .unwrap();
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn test_query_csv_output() {
+        let source = "function f(a, b) {}\nf(\"x, \\\"y\\\"\", `one\ntwo`);\n";
+        let query = "(function_declaration name: (identifier) @name)\n(arguments (_) @arg)\n";
+        assert_eq!(
+            render(source, query, QueryFormat::Csv),
+            [
+                "capture,text,start_row,start_column,end_row,end_column,file",
+                "name,f,0,9,0,10,input.js",
+                r#"arg,"""x, \""y\""""",1,2,1,12,input.js"#,
+                "arg,\"`one\ntwo`\",1,14,2,4,input.js",
+                "",
+            ]
+            .join("\n")
+        );
+    }
+
+    #[test]
+    fn test_query_tsv_output() {
+        let source = "f(\"a\\tb\", `one\ntwo`);\n";
+        let query = "(arguments (_) @arg)\n";
+        assert_eq!(
+            render(source, query, QueryFormat::Tsv),
+            [
+                "capture\ttext\tstart_row\tstart_column\tend_row\tend_column\tfile",
+                "arg\t\"a\\\\tb\"\t0\t2\t0\t8\tinput.js",
+                "arg\t`one\\ntwo`\t0\t10\t1\t4\tinput.js",
+                "",
+            ]
+            .join("\n")
+        );
+    }
+
+    #[test]
+    fn test_query_csv_output_with_time() {
+        let output = render_with_options(
+            "a;\n",
+            "(identifier) @id\n",
+            &QueryFileOptions {
+                format: QueryFormat::Csv,
+                print_time: true,
+                ..Default::default()
+            },
+        );
+        assert_eq!(
+            output,
+            [
+                "capture,text,start_row,start_column,end_row,end_column,file",
+                "id,a,0,0,0,1,input.js",
+                "",
+            ]
+            .join("\n")
+        );
+    }
+
+    #[test]
+    fn test_query_with_byte_range() {
+        let source = "a;\nbb;\nccc;\ndddd;\n";
+        let query = "(identifier) @id\n";
+        let render_range = |byte_range| {
+            render_with_options(
+                source,
+                query,
+                &QueryFileOptions {
+                    format: QueryFormat::Csv,
+                    byte_range: Some(byte_range),
+                    ..Default::default()
+                },
+            )
+        };
+
+        // Matches that end before the range or start after it are excluded, while matches
+        // that straddle either of its boundaries are included.
+        assert_eq!(
+            render_range(4..8),
+            [
+                "capture,text,start_row,start_column,end_row,end_column,file",
+                "id,bb,1,0,1,2,input.js",
+                "id,ccc,2,0,2,3,input.js",
+                "",
+            ]
+            .join("\n")
+        );
+
+        // A match that ends where the range starts, or starts where it ends, doesn't intersect it.
+        assert_eq!(
+            render_range(5..7),
+            "capture,text,start_row,start_column,end_row,end_column,file\n"
+        );
+        assert_eq!(
+            render_range(3..7),
+            [
+                "capture,text,start_row,start_column,end_row,end_column,file",
+                "id,bb,1,0,1,2,input.js",
+                "",
+            ]
+            .join("\n")
+        );
+    }
+
+    #[test]
+    fn test_query_with_row_range() {
+        let source = "a;\n`b\nc`;\nd;\n";
+        let query = "(expression_statement) @stmt\n";
+        let output = render_with_options(
+            source,
+            query,
+            &QueryFileOptions {
+                format: QueryFormat::Csv,
+                point_range: Some(Point::new(2, 0)..Point::new(3, 0)),
+                ..Default::default()
+            },
+        );
+
+        // The template string's statement spans rows 1 and 2, so it straddles the start of the
+        // range, while the statement on row 3 starts at the range's exclusive end.
+        assert_eq!(
+            output,
+            [
+                "capture,text,start_row,start_column,end_row,end_column,file",
+                "stmt,\"`b\nc`;\",1,0,2,3,input.js",
+                "",
+            ]
+            .join("\n")
+        );
+    }
+}
@@ -1,10 +1,13 @@
 use std::{
-    collections::BTreeMap,
+    cell::RefCell,
+    collections::{BTreeMap, HashSet},
     ffi::OsStr,
     fmt::{Display as _, Write as _},
     fs,
     io::{self, Write},
+    iter,
     path::{Path, PathBuf},
+    rc::Rc,
     str,
     time::Duration,
 };
@@ -19,6 +22,7 @@ use schemars::{JsonSchema, Schema, SchemaGenerator};
 use serde::Serialize;
 use similar::{ChangeTag, TextDiff};
 use tree_sitter::{Language, LogType, Parser, Query, Tree, format_sexp};
+use tree_sitter_generate::{CoverableRule, CoverableSymbol};
 use walkdir::WalkDir;
 
 use super::util;
@@ -171,6 +175,8 @@ pub struct TestOptions<'a> {
     pub languages: BTreeMap<&'a str, &'a Language>,
     pub show_fields: bool,
     pub overview_only: bool,
+    /// The grammar's rules, if the rules that the corpus exercises should be recorded
+    pub coverage: Option<&'a [CoverableRule]>,
 }
 
 /// A stateful object used to collect results from running a grammar's test suite
@@ -182,6 +188,8 @@ pub struct TestSummary {
     pub parse_results: TestResultHierarchy,
     pub parse_failures: Vec<TestFailure>,
     pub parse_stats: Stats,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rule_coverage: Option<RuleCoverage>,
     #[schemars(skip)]
     #[serde(skip)]
     pub has_parse_errors: bool,
@@ -238,6 +246,89 @@ impl TestSummary {
     }
 }
 
+/// The grammar rules that were exercised while parsing the corpus.
+#[derive(Debug, Default, PartialEq, Serialize, JsonSchema)]
+pub struct RuleCoverage {
+    pub covered_rules: usize,
+    pub total_rules: usize,
+    pub percentage: f64,
+    pub uncovered_rules: Vec<String>,
+}
+
+impl RuleCoverage {
+    fn new(rules: &[CoverableRule], log: &CoverageLog) -> Self {
+        let uncovered_rules = rules
+            .iter()
+            .filter(|rule| {
+                !iter::once(&rule.symbol)
+                    .chain(&rule.unit_symbols)
+                    .any(|symbol| log.contains(symbol))
+            })
+            .map(|rule| rule.name.clone())
+            .collect::<Vec<_>>();
+        let covered_rules = rules.len() - uncovered_rules.len();
+        let percentage = if rules.is_empty() {
+            100.0
+        } else {
+            covered_rules as f64 / rules.len() as f64 * 100.0
+        };
+        Self {
+            covered_rules,
+            total_rules: rules.len(),
+            percentage,
+            uncovered_rules,
+        }
+    }
+}
+
+impl std::fmt::Display for RuleCoverage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "Rule coverage: {:.2}% ({} of {} rules)",
+            self.percentage, self.covered_rules, self.total_rules
+        )?;
+        if !self.uncovered_rules.is_empty() {
+            writeln!(f, "  Uncovered rules:")?;
+            for rule in &self.uncovered_rules {
+                writeln!(f, "    {rule}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The symbols that the parser has reduced and lexed, as recorded from its log.
+#[derive(Default)]
+struct CoverageLog {
+    reduced_symbols: HashSet<String>,
+    lexed_symbols: HashSet<String>,
+}
+
+impl CoverageLog {
+    fn contains(&self, symbol: &CoverableSymbol) -> bool {
+        if symbol.is_token {
+            self.lexed_symbols.contains(&symbol.name)
+        } else {
+            self.reduced_symbols.contains(&symbol.name)
+        }
+    }
+
+    fn record(&mut self, message: &str) {
+        if let Some((symbol, _)) = message
+            .strip_prefix("reduce sym:")
+            .and_then(|rest| rest.rsplit_once(", child_count:"))
+        {
+            self.reduced_symbols.insert(symbol.to_string());
+        } else if let Some((symbol, _)) = message
+            .strip_prefix("lexed_lookahead sym:")
+            .and_then(|rest| rest.rsplit_once(", size:"))
+        {
+            self.lexed_symbols.insert(symbol.to_string());
+        }
+    }
+}
+
 #[derive(Debug, Default, JsonSchema)]
 pub struct TestResultHierarchy {
     root_group: Vec<TestResult>,
@@ -560,7 +651,10 @@ impl TestSummary {
             {
                 if expected == "NO ERROR" {
                     writeln!(f, "\n  {}. {name}:\n", i + 1)?;
-                    writeln!(f, "  Expected an ERROR node, but got:")?;
+                    writeln!(
+                        f,
+                        "  Expected an ERROR or MISSING node, but the input parsed without errors:"
+                    )?;
                     let actual = if *is_cst {
                         actual
                     } else {
@@ -583,6 +677,12 @@ impl TestSummary {
                                 .with_markers(self.use_markers)
                         )?;
                     }
+                    if !*is_cst && sexp_has_error(actual) && !sexp_has_error(expected) {
+                        writeln!(
+                            f,
+                            "  The input contains a parse error. If this is expected, mark the test with `:error`."
+                        )?;
+                    }
                 }
             }
         } else {
@@ -593,6 +693,11 @@ impl TestSummary {
     }
 }
 
+/// Whether a rendered tree contains an `ERROR` or `MISSING` node.
+fn sexp_has_error(sexp: &str) -> bool {
+    sexp.contains("(ERROR") || sexp.contains("(MISSING")
+}
+
 impl std::fmt::Display for TestSummary {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         self.fmt_parse_results(f)?;
@@ -642,6 +747,10 @@ impl std::fmt::Display for TestSummary {
 
         write!(f, "{}", self.parse_stats)?;
 
+        if let Some(rule_coverage) = &self.rule_coverage {
+            write!(f, "\n{rule_coverage}")?;
+        }
+
         Ok(())
     }
 }
@@ -658,12 +767,20 @@ pub fn run_tests_at_path(
     } else {
         None
     };
-    if opts.debug {
-        parser.set_logger(Some(Box::new(|log_type, message| {
-            if log_type == LogType::Lex {
-                io::stderr().write_all(b"  ").unwrap();
+    let coverage_log = Rc::new(RefCell::new(CoverageLog::default()));
+    if opts.debug || opts.coverage.is_some() {
+        let debug = opts.debug;
+        let coverage_log = opts.coverage.is_some().then(|| coverage_log.clone());
+        parser.set_logger(Some(Box::new(move |log_type, message| {
+            if let Some(coverage_log) = &coverage_log {
+                coverage_log.borrow_mut().record(message);
+            }
+            if debug {
+                if log_type == LogType::Lex {
+                    io::stderr().write_all(b"  ").unwrap();
+                }
+                writeln!(&mut io::stderr(), "{message}").unwrap();
             }
-            writeln!(&mut io::stderr(), "{message}").unwrap();
         })));
     }
 
@@ -679,6 +796,13 @@ pub fn run_tests_at_path(
 
     parser.stop_printing_dot_graphs();
 
+    if let Some(rules) = opts.coverage {
+        test_summary.rule_coverage = Some(RuleCoverage::new(rules, &coverage_log.borrow()));
+        if !opts.debug {
+            parser.set_logger(None);
+        }
+    }
+
     if test_summary.parse_failures.is_empty() || (opts.update && !test_summary.has_parse_errors) {
         Ok(())
     } else if opts.update && test_summary.has_parse_errors {
@@ -1179,6 +1303,8 @@ fn render_test_cst(input: &[u8], tree: &Tree) -> Result<String> {
         output: ParseOutput::Cst,
         stats: &mut ParseStats::default(),
         print_time: false,
+        print_stats: false,
+        collect_tree_stats: false,
         timeout: 0,
         debug: ParseDebugType::Quiet,
         debug_graph: false,
@@ -1186,6 +1312,7 @@ fn render_test_cst(input: &[u8], tree: &Tree) -> Result<String> {
         encoding: None,
         open_log: false,
         no_ranges: false,
+        max_depth: None,
         parse_theme: &ParseTheme::empty(),
     };
     render_cst(input, tree, &mut cursor, &opts, &mut rendered_cst)?;
@@ -1663,7 +1790,7 @@ fn build_test_entry(
 mod tests {
     use serde_json::json;
 
-    use crate::tests::get_language;
+    use crate::tests::{generate_parser, get_language, get_test_language};
 
     use super::*;
 
@@ -2456,6 +2583,7 @@ Test with cst marker
             languages,
             show_fields: false,
             overview_only: false,
+            coverage: None,
         }
     }
 
@@ -2704,4 +2832,146 @@ Test with cst marker
             .to_string()
         );
     }
+
+    #[test]
+    fn run_tests_with_rule_coverage() {
+        let grammar_json = r#"{
+            "name": "test_grammar_with_coverage",
+            "extras": [{"type": "PATTERN", "value": "\\s"}],
+            "rules": {
+                "program": {"type": "REPEAT", "content": {"type": "SYMBOL", "name": "_item"}},
+                "_item": {"type": "CHOICE", "members": [
+                    {"type": "SYMBOL", "name": "number"},
+                    {"type": "SYMBOL", "name": "list"}
+                ]},
+                "list": {"type": "SEQ", "members": [
+                    {"type": "STRING", "value": "("},
+                    {"type": "REPEAT", "content": {"type": "SYMBOL", "name": "word"}},
+                    {"type": "STRING", "value": ")"}
+                ]},
+                "number": {"type": "PATTERN", "value": "\\d+"},
+                "word": {"type": "PATTERN", "value": "[a-z]+"}
+            }
+        }"#;
+        let (parser_name, parser_code) = generate_parser(grammar_json).unwrap();
+        let language = get_test_language(&parser_name, &parser_code, None);
+        let rules = tree_sitter_generate::coverable_rules_for_grammar(grammar_json).unwrap();
+
+        // None of the corpus's tests contain a list of words.
+        let dir = tempfile::tempdir().unwrap();
+        let corpus_path = dir.path().join("numbers.txt");
+        fs::write(
+            &corpus_path,
+            "=======\nNumbers\n=======\n\n1 23\n\n---\n\n(program (number) (number))\n",
+        )
+        .unwrap();
+
+        let mut parser = Parser::new();
+        parser.set_language(&language).unwrap();
+        let mut opts = c_test_options(&language);
+        opts.path = corpus_path;
+        opts.debug = false;
+        opts.coverage = Some(&rules);
+        let mut test_summary = TestSummary::new(TestStats::All, false, false, false);
+        run_tests_at_path(&mut parser, &opts, &mut test_summary).unwrap();
+
+        // The hidden `_item` rule counts as exercised, even though the parser doesn't reduce its
+        // single-symbol alternatives.
+        assert_eq!(
+            test_summary.rule_coverage,
+            Some(RuleCoverage {
+                covered_rules: 3,
+                total_rules: 5,
+                percentage: 60.0,
+                uncovered_rules: vec!["list".to_string(), "word".to_string()],
+            })
+        );
+        assert_eq!(
+            test_summary.rule_coverage.unwrap().to_string(),
+            "Rule coverage: 60.00% (3 of 5 rules)\n  Uncovered rules:\n    list\n    word\n"
+        );
+    }
+
+    #[test]
+    fn run_tests_with_error_expectations() {
+        let (mut parser, language) = c_parser_and_language();
+
+        let dir = tempfile::tempdir().unwrap();
+        let corpus_path = dir.path().join("errors.txt");
+        fs::write(
+            &corpus_path,
+            r"
+==================
+Invalid expression
+:error
+==================
+
+1 +;
+
+---
+
+==================
+Unclosed argument list
+:error
+==================
+
+f(1;
+
+---
+
+==================
+Valid expression
+:error
+==================
+
+1;
+
+---
+
+==================
+Unexpected error
+==================
+
+1 +;
+
+---
+
+(translation_unit (expression_statement (number_literal)))
+",
+        )
+        .unwrap();
+
+        let mut opts = c_test_options(&language);
+        opts.path = corpus_path;
+        opts.debug = false;
+        let mut test_summary = TestSummary::new(TestStats::All, false, false, false);
+        // Any failure makes the whole run fail.
+        assert!(run_tests_at_path(&mut parser, &opts, &mut test_summary).is_err());
+
+        // Tests marked with `:error` pass when the tree contains an `ERROR` or `MISSING` node,
+        // and fail when it doesn't.
+        assert_eq!(
+            test_summary
+                .parse_failures
+                .iter()
+                .map(|failure| (failure.name.as_str(), failure.expected.as_str()))
+                .collect::<Vec<_>>(),
+            [
+                ("Valid expression", "NO ERROR"),
+                (
+                    "Unexpected error",
+                    "(translation_unit (expression_statement (number_literal)))"
+                ),
+            ]
+        );
+        assert_eq!(test_summary.parse_stats.successful_parses, 2);
+
+        let output = test_summary.to_string();
+        assert!(
+            output.contains(
+                "Expected an ERROR or MISSING node, but the input parsed without errors:"
+            )
+        );
+        assert!(output.contains("If this is expected, mark the test with `:error`."));
+    }
 }
@@ -255,6 +255,7 @@ pub fn get_highlight_positions(
                     result.push((utf8_start_position, utf8_end_position, *highlight));
                 }
             }
+            HighlightEvent::InjectionDepth(_) => {}
         }
     }
     Ok(result)
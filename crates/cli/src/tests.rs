@@ -25,11 +25,11 @@ pub use crate::fuzz::{
     random::Rand,
 };
 
-pub use helpers::fixtures::get_language;
+pub use helpers::fixtures::{get_language, get_test_language};
 
 /// This is a simple wrapper around [`tree_sitter_generate::generate_parser_for_grammar`], because
 /// our tests do not need to pass in a version number, only the grammar JSON.
-fn generate_parser(grammar_json: &str) -> GenerateResult<(String, String)> {
+pub fn generate_parser(grammar_json: &str) -> GenerateResult<(String, String)> {
     tree_sitter_generate::generate_parser_for_grammar(
         grammar_json,
         Some((0, 0, 0)),
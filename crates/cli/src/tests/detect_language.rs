@@ -1,6 +1,6 @@
 use std::{fs, path::Path};
 
-use tree_sitter_loader::Loader;
+use tree_sitter_loader::{Config, Loader};
 
 use crate::tests::helpers::fixtures::scratch_dir;
 
@@ -249,6 +249,81 @@ fn detect_language_without_file_extension() {
     );
 }
 
+#[test]
+fn discover_grammars_without_loading_them() {
+    let parsers_dir = tempfile::tempdir().unwrap();
+    for name in ["alpha", "beta", "gamma"] {
+        let src_dir = parsers_dir.path().join(format!("tree-sitter-{name}/src"));
+        fs::create_dir_all(&src_dir).unwrap();
+        fs::write(
+            src_dir.join("grammar.json"),
+            format!(r#"{{"name":"{name}"}}"#),
+        )
+        .unwrap();
+        fs::write(src_dir.join("parser.c"), "").unwrap();
+    }
+    fs::write(
+        parsers_dir
+            .path()
+            .join("tree-sitter-alpha/tree-sitter.json"),
+        r#"{
+  "grammars": [
+    {
+      "name": "alpha",
+      "scope": "source.alpha",
+      "file-types": ["alpha", "al"]
+    }
+  ],
+  "metadata": {
+    "version": "0.0.1"
+  }
+}
+"#,
+    )
+    .unwrap();
+    // Directories without the `tree-sitter-` prefix are not searched.
+    fs::rename(
+        parsers_dir.path().join("tree-sitter-gamma"),
+        parsers_dir.path().join("gamma"),
+    )
+    .unwrap();
+
+    let lib_dir = tempfile::tempdir().unwrap();
+    let mut loader = Loader::with_parser_lib_path(lib_dir.path().to_path_buf());
+    let config = Config {
+        parser_directories: vec![parsers_dir.path().to_path_buf()],
+    };
+    loader.find_all_languages(&config).unwrap();
+
+    let mut grammars = loader.discover();
+    grammars.sort_unstable_by(|a, b| a.name.cmp(&b.name));
+    assert_eq!(
+        grammars.iter().map(|g| g.name.as_str()).collect::<Vec<_>>(),
+        ["alpha", "beta"]
+    );
+    assert_eq!(
+        grammars[0].path,
+        parsers_dir.path().join("tree-sitter-alpha")
+    );
+    assert_eq!(grammars[0].scopes, ["source.alpha"]);
+    assert_eq!(grammars[0].file_types, ["alpha", "al"]);
+    assert!(grammars[1].scopes.is_empty());
+    assert!(grammars.iter().all(|g| !g.is_compiled));
+    assert!(fs::read_dir(lib_dir.path()).unwrap().next().is_none());
+
+    // A library that is newer than the grammar's sources counts as compiled.
+    fs::write(&grammars[1].library_path, "").unwrap();
+    let mut grammars = loader.discover();
+    grammars.sort_unstable_by(|a, b| a.name.cmp(&b.name));
+    assert_eq!(
+        grammars
+            .iter()
+            .map(|g| (g.name.as_str(), g.is_compiled))
+            .collect::<Vec<_>>(),
+        [("alpha", false), ("beta", true)]
+    );
+}
+
 fn tree_sitter_dir(tree_sitter_json: &str, name: &str) -> tempfile::TempDir {
     let temp_dir = tempfile::tempdir().unwrap();
     fs::write(temp_dir.path().join("tree-sitter.json"), tree_sitter_json).unwrap();
@@ -10,7 +10,8 @@ use std::{
 };
 
 use tree_sitter_highlight::{
-    Error, Highlight, HighlightConfiguration, HighlightEvent, Highlighter, HtmlRenderer, c,
+    Error, Highlight, HighlightConfiguration, HighlightEvent, Highlighter, HtmlRenderer,
+    SemanticTokens, c,
 };
 
 use super::helpers::fixtures::{get_highlight_config, get_language, get_language_queries_path};
@@ -176,6 +177,49 @@ fn test_highlighting_injected_javascript_in_html_mini() {
     );
 }
 
+#[test]
+fn test_highlighting_with_injection_depth() {
+    let source = "<script>const s = html `<b>${a}</b>`;</script>";
+    let src = source.as_bytes();
+    let mut highlighter = Highlighter::new();
+    highlighter.set_report_injection_depth(true);
+    let events = highlighter
+        .highlight(
+            &HTML_HIGHLIGHT,
+            src,
+            None,
+            None,
+            &test_language_for_injection_string,
+        )
+        .unwrap();
+
+    let mut depth = None;
+    let mut depths_by_text = Vec::new();
+    let mut starts = Vec::new();
+    for event in events {
+        match event.unwrap() {
+            HighlightEvent::InjectionDepth(d) => depth = Some(d),
+            HighlightEvent::HighlightStart(_) => {
+                starts.push(depth.take().expect("depth should precede every start"));
+            }
+            HighlightEvent::HighlightEnd => {
+                starts.pop();
+            }
+            HighlightEvent::Source { start, end } => {
+                if let Some(depth) = starts.last() {
+                    depths_by_text.push((str::from_utf8(&src[start..end]).unwrap(), *depth));
+                }
+            }
+        }
+    }
+
+    assert!(depths_by_text.contains(&("script", 0)));
+    assert!(depths_by_text.contains(&("const", 1)));
+    assert!(depths_by_text.contains(&("b", 2)));
+    // The template substitution is highlighted by the JavaScript layer.
+    assert!(depths_by_text.contains(&("a", 1)));
+}
+
 #[test]
 fn test_highlighting_injected_javascript_in_html() {
     let source = [
@@ -394,6 +438,59 @@ fn test_highlighting_ejs_with_html_and_javascript() {
     );
 }
 
+#[test]
+fn test_highlighting_with_unknown_nested_injection() {
+    // EJS injects HTML, which injects JavaScript into `<script>` tags, and EJS also injects
+    // JavaScript directly. Only HTML is available, so the JavaScript is left unhighlighted
+    // while the rest of the document is highlighted as usual.
+    let source = "<div><% foo() %></div><script> bar() </script>";
+    let html_only = |name: &str| (name == "html").then_some(&*HTML_HIGHLIGHT);
+
+    assert_eq!(
+        &to_token_vector_with_injections(source, &EJS_HIGHLIGHT, html_only).unwrap(),
+        &[[
+            ("<", vec!["punctuation.bracket"]),
+            ("div", vec!["tag"]),
+            (">", vec!["punctuation.bracket"]),
+            ("<%", vec!["keyword"]),
+            (" foo() ", vec![]),
+            ("%>", vec!["keyword"]),
+            ("</", vec!["punctuation.bracket"]),
+            ("div", vec!["tag"]),
+            (">", vec!["punctuation.bracket"]),
+            ("<", vec!["punctuation.bracket"]),
+            ("script", vec!["tag"]),
+            (">", vec!["punctuation.bracket"]),
+            (" bar() ", vec![]),
+            ("</", vec!["punctuation.bracket"]),
+            ("script", vec!["tag"]),
+            (">", vec!["punctuation.bracket"]),
+        ]],
+    );
+
+    // The source events cover the whole input, in order, even without the outer language.
+    let no_languages = |_: &str| None;
+    for callback in [
+        &html_only as &dyn Fn(&str) -> Option<&'static HighlightConfiguration>,
+        &no_languages,
+    ] {
+        let mut highlighter = Highlighter::new();
+        let mut end_of_previous_event = 0;
+        for event in highlighter
+            .highlight(&EJS_HIGHLIGHT, source.as_bytes(), None, None, |name| {
+                callback(name)
+            })
+            .unwrap()
+        {
+            if let HighlightEvent::Source { start, end } = event.unwrap() {
+                assert_eq!(start, end_of_previous_event);
+                end_of_previous_event = end;
+            }
+        }
+        assert_eq!(end_of_previous_event, source.len());
+    }
+}
+
 #[test]
 fn test_highlighting_javascript_with_jsdoc() {
     // Regression test: the middle comment has no highlights. This should not prevent
@@ -686,6 +783,61 @@ fn test_highlighting_with_all_captures_applied() {
     );
 }
 
+#[test]
+fn test_highlighting_to_semantic_tokens() {
+    let source = "let s = '𝒳';\n/* a\r\n b */ s";
+    let highlight = |name: &str| HIGHLIGHT_NAMES.iter().position(|n| n == name).unwrap() as u32;
+    let keyword = highlight("keyword");
+    let variable = highlight("variable");
+    let operator = highlight("operator");
+    let string = highlight("string");
+    let delimiter = highlight("punctuation.delimiter");
+    let comment = highlight("comment");
+
+    assert_eq!(
+        to_semantic_tokens(source).data,
+        [
+            [0, 0, 3, keyword, 0],
+            [0, 4, 1, variable, 0],
+            [0, 2, 1, operator, 0],
+            // The string's astral character takes two UTF-16 code units.
+            [0, 2, 4, string, 0],
+            [0, 4, 1, delimiter, 0],
+            // The multi-line comment is split into one token per line, without the CRLF.
+            [1, 0, 4, comment, 0],
+            [1, 0, 5, comment, 0],
+            [0, 6, 1, variable, 0],
+        ]
+        .concat()
+    );
+}
+
+#[test]
+fn test_semantic_token_deltas() {
+    let old_source = "const a = 1;\nfunction f(b) {\n  return b + a;\n}\nf(a);\n";
+    let new_source = "const a = 1;\nfunction f(b, c) {\n  return b + a;\n}\nf(a);\n";
+    let old_tokens = to_semantic_tokens(old_source);
+    let new_tokens = to_semantic_tokens(new_source);
+
+    // Applying the delta to the old tokens yields the same tokens as a full recompute.
+    let edits = new_tokens.delta_from(&old_tokens);
+    assert_eq!(edits.len(), 1);
+    let mut data = old_tokens.data;
+    for edit in &edits {
+        let start = edit.start as usize;
+        data.splice(
+            start..start + edit.delete_count as usize,
+            edit.data.iter().copied(),
+        );
+    }
+    assert_eq!(data, new_tokens.data);
+
+    // Only the tokens around the added parameter are sent.
+    assert!(edits[0].data.len() < new_tokens.data.len() / 2);
+
+    assert!(new_tokens.delta_from(&new_tokens).is_empty());
+}
+
 #[test]
 fn test_decode_utf8_lossy() {
     use tree_sitter::LossyUtf8;
@@ -717,6 +869,20 @@ fn test_language_for_injection_string<'a>(string: &str) -> Option<&'a HighlightC
     }
 }
 
+fn to_semantic_tokens(src: &str) -> SemanticTokens {
+    let mut highlighter = Highlighter::new();
+    let events = highlighter
+        .highlight(
+            &JS_HIGHLIGHT,
+            src.as_bytes(),
+            None,
+            None,
+            &test_language_for_injection_string,
+        )
+        .unwrap();
+    SemanticTokens::new(events, src.as_bytes()).unwrap()
+}
+
 fn to_html<'a>(
     src: &'a str,
     language_config: &'a HighlightConfiguration,
@@ -756,19 +922,27 @@ fn to_html<'a>(
 fn to_token_vector<'a>(
     src: &'a str,
     language_config: &'a HighlightConfiguration,
+) -> Result<Vec<Vec<(&'a str, Vec<&'static str>)>>, Error> {
+    to_token_vector_with_injections(src, language_config, test_language_for_injection_string)
+}
+
+#[expect(
+    clippy::type_complexity,
+    reason = "return type represents structured highlight tokens"
+)]
+fn to_token_vector_with_injections<'a>(
+    src: &'a str,
+    language_config: &'a HighlightConfiguration,
+    injection_callback: impl Fn(&str) -> Option<&'static HighlightConfiguration>,
 ) -> Result<Vec<Vec<(&'a str, Vec<&'static str>)>>, Error> {
     let src = src.as_bytes();
     let mut highlighter = Highlighter::new();
     let mut lines = Vec::new();
     let mut highlights = Vec::new();
     let mut line = Vec::new();
-    let events = highlighter.highlight(
-        language_config,
-        src,
-        None,
-        None,
-        &test_language_for_injection_string,
-    )?;
+    let events = highlighter.highlight(language_config, src, None, None, move |name| {
+        injection_callback(name)
+    })?;
     for event in events {
         match event? {
             HighlightEvent::HighlightStart(s) => highlights.push(HIGHLIGHT_NAMES[s.0].as_str()),
@@ -787,6 +961,7 @@ fn to_token_vector<'a>(
                     }
                 }
             }
+            HighlightEvent::InjectionDepth(_) => {}
         }
     }
     if !line.is_empty() {
@@ -1,6 +1,10 @@
+use std::{fs, num::NonZeroU16};
+
 use tree_sitter::{self, Parser};
+use tree_sitter_generate::NodeTypes;
 
-use super::helpers::fixtures::get_language;
+use super::helpers::fixtures::{fixtures_dir, get_language, get_test_language};
+use crate::tests::generate_parser;
 
 #[test]
 fn test_lookahead_iterator() {
@@ -43,6 +47,161 @@ fn test_lookahead_iterator() {
     );
 }
 
+#[test]
+fn test_language_next_state() {
+    let mut parser = Parser::new();
+    let language = get_language("json");
+    parser.set_language(&language).unwrap();
+
+    let tree = parser.parse("[[1], 2]", None).unwrap();
+    let outer_array = tree.root_node().child(0).unwrap();
+    let open_bracket = outer_array.child(0).unwrap();
+    let inner_array = outer_array.child(1).unwrap();
+    let comma = outer_array.child(2).unwrap();
+    assert_eq!(inner_array.kind(), "array");
+    assert_eq!(comma.kind(), ",");
+
+    // Stepping over the opening bracket leads to the state in which the inner array was parsed.
+    let next_state = language.next_state(open_bracket.parse_state(), open_bracket.grammar_id());
+    assert_ne!(next_state, 0);
+    assert_eq!(next_state, inner_array.parse_state());
+
+    // Stepping over the inner array, which is a non-terminal, leads to a state in which the
+    // outer array can be continued or closed.
+    let next_state = language.next_state(inner_array.parse_state(), inner_array.grammar_id());
+    assert_ne!(next_state, 0);
+    assert_eq!(next_state, inner_array.next_parse_state());
+    let mut lookahead = language.lookahead_iterator(next_state).unwrap();
+    let names = lookahead.iter_names().collect::<Vec<_>>();
+    assert!(names.contains(&",") && names.contains(&"]"));
+
+    // A symbol that can't follow the state, or that isn't valid at all, has no transition.
+    let state = inner_array.parse_state();
+    assert_eq!(language.next_state(state, comma.grammar_id()), 0);
+    assert_eq!(
+        language.next_state(state, language.node_kind_count() as u16),
+        0
+    );
+    assert_eq!(language.next_state(state, u16::MAX), 0);
+    assert_eq!(
+        language.next_state(
+            language.parse_state_count() as u16,
+            inner_array.grammar_id()
+        ),
+        0
+    );
+}
+
+#[test]
+fn test_lookahead_iterator_symbols_at_error() {
+    let mut parser = Parser::new();
+    let language = get_language("javascript");
+    parser.set_language(&language).unwrap();
+
+    let tree = parser.parse("let x = ;", None).unwrap();
+    let declaration = tree.root_node().child(0).unwrap();
+    let error = declaration
+        .children(&mut tree.walk())
+        .find(tree_sitter::Node::is_error)
+        .unwrap();
+
+    // The error contains the `=`, which is missing the value that follows it.
+    let token = error.child(error.child_count() - 1).unwrap();
+    assert_eq!(token.kind(), "=");
+    let mut lookahead = language
+        .lookahead_iterator(token.next_parse_state())
+        .unwrap();
+    let symbols = lookahead.iter_symbols().collect::<Vec<_>>();
+    for (id, name) in &symbols {
+        assert_eq!(language.node_kind_for_id(*id), Some(*name));
+    }
+    let names = symbols.iter().map(|(_, name)| *name).collect::<Vec<_>>();
+    for expected in ["identifier", "number", "string", "(", "[", "!"] {
+        assert!(
+            names.contains(&expected),
+            "missing {expected:?} in {names:?}"
+        );
+    }
+    assert!(!names.contains(&";"));
+}
+
+#[test]
+fn test_lexing_tokens() {
+    let mut parser = Parser::new();
+    let language = get_language("rust");
+    parser.set_language(&language).unwrap();
+
+    let source = "let x = 1 + y;";
+    let tree = parser.parse(source, None).unwrap();
+
+    // Lex each token in the state that the parser lexed it in, and check that the
+    // lexer on its own finds the same tokens as the parser. None of these tokens
+    // come from the external scanner.
+    let mut tokens = Vec::new();
+    let mut cursor = tree.walk();
+    let mut position = 0;
+    loop {
+        let node = cursor.node();
+        if node.child_count() == 0 {
+            let (id, range) = language
+                .lex(node.parse_state(), &source.as_bytes()[position..])
+                .unwrap();
+            assert_eq!(id, node.grammar_id());
+            assert_eq!(
+                position + range.start..position + range.end,
+                node.byte_range()
+            );
+            tokens.push(language.node_kind_for_id(id).unwrap());
+            position = node.end_byte();
+        } else if cursor.goto_first_child() {
+            continue;
+        }
+        while !cursor.goto_next_sibling() {
+            if !cursor.goto_parent() {
+                break;
+            }
+        }
+        if cursor.node() == tree.root_node() {
+            break;
+        }
+    }
+    assert_eq!(
+        tokens,
+        [
+            "let",
+            "identifier",
+            "=",
+            "integer_literal",
+            "+",
+            "identifier",
+            ";"
+        ]
+    );
+
+    // Keywords are distinguished from identifiers.
+    let let_state = tree
+        .root_node()
+        .child(0)
+        .unwrap()
+        .child(0)
+        .unwrap()
+        .parse_state();
+    let (id, range) = language.lex(let_state, b"  let").unwrap();
+    assert_eq!(language.node_kind_for_id(id), Some("let"));
+    assert_eq!(range, 2..5);
+    let (id, range) = language.lex(let_state, b"letter").unwrap();
+    assert_eq!(language.node_kind_for_id(id), Some("identifier"));
+    assert_eq!(range, 0..6);
+
+    // At the end of the text, the lexer finds the end-of-file token.
+    assert_eq!(language.lex(let_state, b"  "), Some((0, 2..2)));
+    assert_eq!(language.lex(let_state, b"`"), None);
+    assert_eq!(
+        language.lex(language.parse_state_count() as u16, b"let"),
+        None
+    );
+}
+
 #[test]
 fn test_lookahead_iterator_modifiable_only_by_mut() {
     let mut parser = Parser::new();
@@ -98,6 +257,90 @@ fn test_symbol_metadata_checks() {
     }
 }
 
+#[test]
+fn test_field_ids() {
+    let language = get_language("rust");
+    assert!(language.field_count() > 0);
+
+    // Every field id in the language round-trips through its name.
+    for id in 1..=language.field_count() as u16 {
+        let name = language.field_name_for_id(id).unwrap();
+        assert_eq!(
+            language.field_id_for_name(name).map(NonZeroU16::get),
+            Some(id)
+        );
+    }
+
+    // The zero id means "no field", and ids past the end don't exist.
+    assert_eq!(language.field_name_for_id(0), None);
+    assert_eq!(
+        language.field_name_for_id(language.field_count() as u16 + 1),
+        None
+    );
+    assert_eq!(language.field_id_for_name(""), None);
+    assert_eq!(language.field_id_for_name("nonexistent_field"), None);
+
+    // A cached id can be used in place of the field name.
+    let mut parser = Parser::new();
+    parser.set_language(&language).unwrap();
+    let tree = parser.parse("fn main() {}", None).unwrap();
+    let function = tree.root_node().child(0).unwrap();
+    let name_id = language.field_id_for_name("name").unwrap();
+    assert_eq!(
+        function.child_by_field_id(name_id.get()),
+        function.child_by_field_name("name")
+    );
+    assert_eq!(
+        function.child_by_field_id(name_id.get()).unwrap().kind(),
+        "identifier"
+    );
+
+    let mut cursor = function.walk();
+    cursor.goto_first_child();
+    let mut field_ids = Vec::new();
+    loop {
+        field_ids.push(cursor.field_id());
+        if !cursor.goto_next_sibling() {
+            break;
+        }
+    }
+    assert_eq!(field_ids[0], None);
+    assert_eq!(field_ids[1], Some(name_id));
+}
+
+#[test]
+fn test_external_token_names() {
+    let language = get_language("python");
+    assert_eq!(
+        language.external_token_names(),
+        &[
+            "_newline",
+            "_indent",
+            "_dedent",
+            "string_start",
+            "_string_content",
+            "escape_interpolation",
+            "string_end",
+            "comment",
+            "]",
+            ")",
+            "}",
+            "except",
+        ]
+    );
+
+    // External tokens that are also used elsewhere in the grammar share the
+    // symbol of the corresponding internal token.
+    let external_tokens = language.external_tokens();
+    assert_eq!(
+        language.id_for_node_kind("comment", true),
+        external_tokens[7]
+    );
+    assert_eq!(language.id_for_node_kind("]", false), external_tokens[8]);
+
+    assert!(get_language("json").external_token_names().is_empty());
+}
+
 #[test]
 fn test_supertypes() {
     let language = get_language("rust");
@@ -199,3 +442,131 @@ fn test_supertypes() {
         }
     }
 }
+
+#[test]
+fn test_supertypes_of_generated_grammar() {
+    let (parser_name, parser_code) = generate_parser(
+        r#"
+        {
+            "name": "test_grammar_with_supertypes",
+            "extras": [{"type": "PATTERN", "value": "\\s+"}],
+            "supertypes": ["_statement", "_expression"],
+            "rules": {
+                "program": {
+                    "type": "REPEAT",
+                    "content": {"type": "SYMBOL", "name": "_statement"}
+                },
+                "_statement": {
+                    "type": "CHOICE",
+                    "members": [
+                        {"type": "SYMBOL", "name": "expression_statement"},
+                        {"type": "SYMBOL", "name": "return_statement"}
+                    ]
+                },
+                "expression_statement": {
+                    "type": "SEQ",
+                    "members": [
+                        {"type": "SYMBOL", "name": "_expression"},
+                        {"type": "STRING", "value": ";"}
+                    ]
+                },
+                "return_statement": {
+                    "type": "SEQ",
+                    "members": [
+                        {"type": "STRING", "value": "return"},
+                        {"type": "SYMBOL", "name": "_expression"},
+                        {"type": "STRING", "value": ";"}
+                    ]
+                },
+                "_expression": {
+                    "type": "CHOICE",
+                    "members": [
+                        {"type": "SYMBOL", "name": "identifier"},
+                        {"type": "SYMBOL", "name": "number"}
+                    ]
+                },
+                "identifier": {"type": "PATTERN", "value": "[a-z]+"},
+                "number": {"type": "PATTERN", "value": "\\d+"}
+            }
+        }
+        "#,
+    )
+    .unwrap();
+    let language = get_test_language(&parser_name, &parser_code, None);
+
+    let mut supertypes = language
+        .supertypes()
+        .iter()
+        .map(|&s| language.node_kind_for_id(s).unwrap())
+        .collect::<Vec<_>>();
+    supertypes.sort_unstable();
+    assert_eq!(supertypes, ["_expression", "_statement"]);
+
+    let subtypes = |supertype: &str| {
+        let id = language.id_for_node_kind(supertype, true);
+        assert!(language.node_kind_is_supertype(id));
+        let mut subtypes = language
+            .subtypes_for_supertype(id)
+            .iter()
+            .map(|&s| language.node_kind_for_id(s).unwrap())
+            .collect::<Vec<_>>();
+        subtypes.sort_unstable();
+        subtypes
+    };
+    assert_eq!(
+        subtypes("_statement"),
+        ["expression_statement", "return_statement"]
+    );
+    assert_eq!(subtypes("_expression"), ["identifier", "number"]);
+
+    // Symbols that aren't supertypes have no subtypes.
+    let program = language.id_for_node_kind("program", true);
+    assert!(!language.node_kind_is_supertype(program));
+    assert!(language.subtypes_for_supertype(program).is_empty());
+}
+
+#[test]
+fn test_node_types_json() {
+    let json = fs::read_to_string(
+        fixtures_dir()
+            .join("grammars")
+            .join("rust")
+            .join("src")
+            .join("node-types.json"),
+    )
+    .unwrap();
+    let node_types = NodeTypes::from_json(&json).unwrap();
+
+    assert!(node_types.is_named("function_item"));
+    assert!(!node_types.is_named("fn"));
+    assert_eq!(
+        node_types.fields_of("function_item"),
+        [
+            "body",
+            "name",
+            "parameters",
+            "return_type",
+            "type_parameters"
+        ]
+    );
+    let name = node_types.field("function_item", "name").unwrap();
+    assert!(name.required);
+    assert!(name.types.iter().any(|t| t.kind == "identifier" && t.named));
+
+    let mut supertypes = node_types.supertypes_of("identifier", true);
+    supertypes.sort_unstable();
+    assert_eq!(supertypes, ["_expression", "_pattern"]);
+    // `boolean_literal` is only a `_pattern` by way of `_literal_pattern`.
+    let mut supertypes = node_types.supertypes_of("boolean_literal", true);
+    supertypes.sort_unstable();
+    assert_eq!(
+        supertypes,
+        ["_expression", "_literal", "_literal_pattern", "_pattern"]
+    );
+    assert!(
+        node_types
+            .subtypes_of("_literal")
+            .iter()
+            .any(|t| t.kind == "string_literal")
+    );
+}
@@ -1,4 +1,8 @@
-use tree_sitter::{InputEdit, Node, Parser, Point, Tree};
+use std::ops::ControlFlow;
+
+use tree_sitter::{
+    Descend, InputEdit, Node, NodeIndex, Parser, Point, Tree, format_sexp, parse_sexp,
+};
 use tree_sitter_generate::load_grammar_file;
 
 use super::{
@@ -270,6 +274,137 @@ fn test_node_parent_of_child_by_field_name() {
     );
 }
 
+#[test]
+fn test_node_ancestor_in_field() {
+    let mut parser = Parser::new();
+    parser.set_language(&get_language("javascript")).unwrap();
+    let source = "function f(a) { if (a) { return b; } }";
+    let tree = parser.parse(source, None).unwrap();
+    let root = tree.root_node();
+    let function = root.named_child(0).unwrap();
+    let b = root
+        .named_descendant_for_byte_range(source.find('b').unwrap(), source.find('b').unwrap() + 1)
+        .unwrap();
+    assert_eq!(b.kind(), "identifier");
+
+    let body = b.ancestor_in_field("body").unwrap();
+    assert_eq!(body.kind(), "statement_block");
+    assert_eq!(Some(body), function.child_by_field_name("body"));
+
+    let consequence = b.ancestor_in_field("consequence").unwrap();
+    assert_eq!(
+        consequence.utf8_text(source.as_bytes()).unwrap(),
+        "{ return b; }"
+    );
+
+    // A node that is itself in the field is returned.
+    let name = function.child_by_field_name("name").unwrap();
+    assert_eq!(name.ancestor_in_field("name"), Some(name));
+
+    assert_eq!(b.ancestor_in_field("name"), None);
+    assert_eq!(b.ancestor_in_field("not_a_field"), None);
+}
+
+#[test]
+fn test_node_field_name() {
+    let mut parser = Parser::new();
+    parser.set_language(&get_language("javascript")).unwrap();
+    let source = "function f(a) { return a + b; }";
+    let tree = parser.parse(source, None).unwrap();
+    let root = tree.root_node();
+    let function = root.named_child(0).unwrap();
+    assert_eq!(function.kind(), "function_declaration");
+
+    let name = function.child_by_field_name("name").unwrap();
+    assert_eq!(name.field_name(), Some("name"));
+    let body = function.child_by_field_name("body").unwrap();
+    assert_eq!(body.field_name(), Some("body"));
+
+    let binary = body.named_child(0).unwrap().named_child(0).unwrap();
+    assert_eq!(binary.kind(), "binary_expression");
+    assert_eq!(binary.child(0).unwrap().field_name(), Some("left"));
+    assert_eq!(binary.child(1).unwrap().field_name(), Some("operator"));
+    assert_eq!(binary.child(2).unwrap().field_name(), Some("right"));
+
+    // Nodes that aren't stored in a field, and the root, have no field name.
+    assert_eq!(body.child(0).unwrap().field_name(), None);
+    assert_eq!(function.field_name(), None);
+    assert_eq!(root.field_name(), None);
+}
+
+#[test]
+fn test_node_for_each_descendant() {
+    let mut parser = Parser::new();
+    parser.set_language(&get_language("javascript")).unwrap();
+    let source = "function f(a) { return a + b; }\nx;";
+    let tree = parser.parse(source, None).unwrap();
+    let root = tree.root_node();
+
+    let mut visited = Vec::new();
+    let result = root.for_each_descendant(|node, depth| {
+        if node.is_named() {
+            visited.push((node.kind(), depth));
+        }
+        ControlFlow::<(), _>::Continue(Descend::Children)
+    });
+    assert_eq!(result, ControlFlow::Continue(()));
+    assert_eq!(
+        visited,
+        [
+            ("program", 0),
+            ("function_declaration", 1),
+            ("identifier", 2),
+            ("formal_parameters", 2),
+            ("identifier", 3),
+            ("statement_block", 2),
+            ("return_statement", 3),
+            ("binary_expression", 4),
+            ("identifier", 5),
+            ("identifier", 5),
+            ("expression_statement", 1),
+            ("identifier", 2),
+        ]
+    );
+
+    // Skipping a node prunes its subtree but continues with its siblings.
+    let mut visited = Vec::new();
+    let _ = root.for_each_descendant(|node, _| {
+        visited.push(node.kind());
+        if node.kind() == "statement_block" {
+            ControlFlow::<(), _>::Continue(Descend::Skip)
+        } else {
+            ControlFlow::Continue(Descend::Children)
+        }
+    });
+    assert!(visited.contains(&"statement_block"));
+    assert!(visited.contains(&"expression_statement"));
+    assert!(!visited.contains(&"return_statement"));
+    assert!(!visited.contains(&"binary_expression"));
+
+    // Traversal is confined to the starting node's subtree.
+    let function = root.named_child(0).unwrap();
+    let mut count = 0;
+    let _ = function.for_each_descendant(|node, _| {
+        assert_ne!(node.kind(), "expression_statement");
+        count += 1;
+        ControlFlow::<(), _>::Continue(Descend::Children)
+    });
+    assert_eq!(count, function.descendant_count());
+
+    // Breaking out stops the traversal and returns the given value.
+    let mut count = 0;
+    let result = root.for_each_descendant(|node, _| {
+        count += 1;
+        if node.kind() == "formal_parameters" {
+            ControlFlow::Break(node.start_byte())
+        } else {
+            ControlFlow::Continue(Descend::Children)
+        }
+    });
+    assert_eq!(result, ControlFlow::Break(10));
+    assert_eq!(count, 5);
+}
+
 #[test]
 fn test_parent_of_zero_width_node() {
     let code = "def dupa(foo):";
@@ -756,6 +891,231 @@ fn test_node_descendant_for_range() {
     );
 }
 
+#[test]
+fn test_node_descendant_for_point_range_edge_cases() {
+    let tree = parse_json_example();
+    let root = tree.root_node();
+    let number_index = JSON_EXAMPLE.find("123").unwrap();
+    let number_node = root
+        .named_descendant_for_byte_range(number_index + 1, number_index + 1)
+        .unwrap();
+    assert_eq!(number_node.kind(), "number");
+    assert_eq!(number_node.start_position(), Point::new(3, 2));
+
+    // A point inside of a leaf node
+    for node in [
+        root.descendant_for_point_range(Point::new(3, 3), Point::new(3, 3)),
+        root.named_descendant_for_point_range(Point::new(3, 3), Point::new(3, 3)),
+        root.descendant_for_point_range(Point::new(3, 3), Point::new(3, 4)),
+    ] {
+        assert_eq!(node, Some(number_node));
+    }
+
+    // A range that crosses from one node into another returns their lowest common ancestor
+    let array_node = root.child(0).unwrap();
+    assert_eq!(array_node.kind(), "array");
+    assert_eq!(
+        root.descendant_for_point_range(Point::new(3, 3), Point::new(4, 4)),
+        Some(array_node)
+    );
+    let object_node = array_node.named_child(2).unwrap();
+    assert_eq!(object_node.kind(), "object");
+    assert_eq!(
+        root.descendant_for_point_range(Point::new(6, 10), Point::new(7, 3)),
+        Some(object_node)
+    );
+    assert_eq!(
+        root.named_descendant_for_point_range(Point::new(6, 5), Point::new(6, 12)),
+        Some(object_node.named_child(0).unwrap())
+    );
+
+    // An empty range at the end of the document returns the node that ends there.
+    assert_eq!(
+        root.descendant_for_point_range(root.end_position(), root.end_position())
+            .map(|node| node.kind()),
+        Some("document")
+    );
+}
+
+#[test]
+fn test_node_nodes_in_range() {
+    let mut parser = Parser::new();
+    parser.set_language(&get_language("javascript")).unwrap();
+    let source = "let a = 1;\nfoo(a, b);\nif (c) { d(); }\nreturn e;\n";
+    let d = source.find('d').unwrap();
+    let tree = parser.parse(source, None).unwrap();
+    let root = tree.root_node();
+    let all_nodes = get_all_nodes(&tree);
+
+    let call_start = source.find("foo").unwrap();
+    let call_end = source.find("b);").unwrap() + 3;
+    let ranges = [
+        0..source.len(),
+        call_start..call_end,
+        source.find('b').unwrap()..source.find("if").unwrap(),
+        d..d + 1,
+        source.len()..source.len() + 1,
+        5..5,
+    ];
+    for range in ranges {
+        let expected = all_nodes
+            .iter()
+            .copied()
+            .filter(|node| node.start_byte() < range.end && node.end_byte() > range.start)
+            .collect::<Vec<_>>();
+        let actual = root.nodes_in_range(range.clone()).collect::<Vec<_>>();
+        assert_eq!(actual, expected, "range {range:?}");
+    }
+
+    // Only the `if` statement and the nodes on the path to `d` are visited.
+    let kinds = root
+        .nodes_in_range(d..d + 1)
+        .map(|node| node.kind())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        kinds,
+        [
+            "program",
+            "if_statement",
+            "statement_block",
+            "expression_statement",
+            "call_expression",
+            "identifier",
+        ]
+    );
+    assert!(
+        root.nodes_in_range(call_start..call_end)
+            .all(|node| node.kind() != "lexical_declaration" && node.kind() != "if_statement")
+    );
+}
+
+#[test]
+fn test_node_nodes_in_range_with_missing_nodes() {
+    let mut parser = Parser::new();
+    parser.set_language(&get_language("javascript")).unwrap();
+    let source = "x = (1 ;\ny = [2;\n";
+    let tree = parser.parse(source, None).unwrap();
+    let root = tree.root_node();
+    let all_nodes = get_all_nodes(&tree);
+
+    // The missing `)` and `]` are empty, and each is followed by a `;` token.
+    let missing = all_nodes
+        .iter()
+        .filter(|node| node.is_missing())
+        .map(|node| (node.kind(), node.byte_range()))
+        .collect::<Vec<_>>();
+    assert_eq!(missing, [(")", 6..6), ("]", 15..15)]);
+
+    for start in 0..source.len() {
+        for end in start..=source.len() {
+            let range = start..end;
+            let expected = all_nodes
+                .iter()
+                .copied()
+                .filter(|node| node.start_byte() < range.end && node.end_byte() > range.start)
+                .collect::<Vec<_>>();
+            let actual = root.nodes_in_range(range.clone()).collect::<Vec<_>>();
+            assert_eq!(actual, expected, "range {range:?}");
+        }
+    }
+}
+
+#[test]
+fn test_node_index() {
+    let tree = parse_json_example();
+    let index = NodeIndex::new(&tree);
+
+    let mut named_nodes = Vec::new();
+    let mut cursor = tree.walk();
+    let mut visited_children = false;
+    loop {
+        if visited_children {
+            if cursor.goto_next_sibling() {
+                visited_children = false;
+            } else if !cursor.goto_parent() {
+                break;
+            }
+        } else {
+            if cursor.node().is_named() {
+                named_nodes.push(cursor.node());
+            }
+            if !cursor.goto_first_child() {
+                visited_children = true;
+            }
+        }
+    }
+
+    // Named nodes are properly nested, so the last one in pre-order that contains
+    // a byte is the smallest one.
+    for byte in 0..=JSON_EXAMPLE.len() {
+        let expected = named_nodes
+            .iter()
+            .rev()
+            .find(|node| node.start_byte() <= byte && byte < node.end_byte())
+            .copied();
+        assert_eq!(index.named_node_for_byte(byte), expected, "byte {byte}");
+    }
+
+    let null_index = JSON_EXAMPLE.find("null").unwrap();
+    assert_eq!(
+        index.named_node_for_byte(null_index).unwrap().kind(),
+        "null"
+    );
+    let colon_index = JSON_EXAMPLE.find(':').unwrap();
+    assert_eq!(
+        index.named_node_for_byte(colon_index).unwrap().kind(),
+        "pair"
+    );
+    assert_eq!(index.named_node_for_byte(0), None);
+    assert_eq!(index.named_node_for_byte(JSON_EXAMPLE.len()), None);
+}
+
+#[test]
+fn test_node_utf16_range() {
+    // `é` is two bytes in UTF-8 but one code unit in UTF-16, and `😀` is four bytes in UTF-8
+    // and a surrogate pair in UTF-16.
+    let source = "[\"café\", \"😀\",\n \"😀é\", 1]";
+    let source_utf16 = source.encode_utf16().collect::<Vec<_>>();
+
+    let mut parser = Parser::new();
+    parser.set_language(&get_language("json")).unwrap();
+    let tree = parser.parse(source, None).unwrap();
+    let array = tree.root_node().child(0).unwrap();
+    let elements = array.named_children(&mut array.walk()).collect::<Vec<_>>();
+
+    assert_eq!(
+        elements
+            .iter()
+            .map(|node| node.utf16_range(&source_utf16))
+            .collect::<Vec<_>>(),
+        [1..7, 9..13, 16..21, 23..24]
+    );
+    for node in &elements {
+        assert_eq!(
+            String::from_utf16(&source_utf16[node.utf16_range(&source_utf16)]).unwrap(),
+            node.utf8_text(source.as_bytes()).unwrap()
+        );
+    }
+
+    // Columns are counted from the start of the node's line.
+    let [.., emoji, number] = elements.as_slice() else {
+        unreachable!()
+    };
+    assert_eq!(emoji.start_position(), Point::new(1, 1));
+    assert_eq!(emoji.end_position(), Point::new(1, 9));
+    assert_eq!(emoji.utf16_start_position(&source_utf16), Point::new(1, 1));
+    assert_eq!(emoji.utf16_end_position(&source_utf16), Point::new(1, 6));
+    assert_eq!(number.start_position(), Point::new(1, 11));
+    assert_eq!(number.utf16_start_position(&source_utf16), Point::new(1, 8));
+    assert_eq!(number.utf16_end_position(&source_utf16), Point::new(1, 9));
+
+    // The range of the whole document ends after the last code unit.
+    assert_eq!(
+        tree.root_node().utf16_range(&source_utf16),
+        0..source_utf16.len()
+    );
+}
+
 #[test]
 fn test_node_edit() {
     let mut code = JSON_EXAMPLE.as_bytes().to_vec();
@@ -788,6 +1148,40 @@ fn test_node_edit() {
     }
 }
 
+#[test]
+fn test_node_edit_shifts_detached_node() {
+    let mut parser = Parser::new();
+    parser.set_language(&get_language("javascript")).unwrap();
+    let source = "a;\nfoo(bar);";
+    let tree = parser.parse(source, None).unwrap();
+
+    let mut node = tree.root_node().child(1).unwrap();
+    let original = node;
+    assert_eq!(node.kind(), "expression_statement");
+    assert_eq!(node.byte_range(), 3..12);
+    assert_eq!(node.start_position(), Point::new(1, 0));
+    assert_eq!(node.end_position(), Point::new(1, 9));
+
+    // Replace `a` with `abc\n`, which adds three bytes and a line before the node.
+    node.edit(&InputEdit {
+        start_byte: 0,
+        old_end_byte: 1,
+        new_end_byte: 4,
+        start_position: Point::new(0, 0),
+        old_end_position: Point::new(0, 1),
+        new_end_position: Point::new(1, 0),
+    });
+    assert_eq!(node.byte_range(), 6..15);
+    assert_eq!(node.start_position(), Point::new(2, 0));
+    assert_eq!(node.end_position(), Point::new(2, 9));
+    assert_eq!(node.child(0).unwrap().byte_range(), 6..14);
+
+    // Neither the tree nor other copies of the node are affected.
+    assert_eq!(original.byte_range(), 3..12);
+    assert_eq!(tree.root_node().child(1).unwrap().byte_range(), 3..12);
+    assert_eq!(tree.root_node().end_byte(), source.len());
+}
+
 #[test]
 fn test_root_node_with_offset() {
     let mut parser = Parser::new();
@@ -831,6 +1225,136 @@ fn test_node_is_extra() {
     assert!(comment_node.is_extra());
 }
 
+#[test]
+fn test_node_prev_non_extra_sibling() {
+    let mut parser = Parser::new();
+    parser.set_language(&get_language("javascript")).unwrap();
+    let source = "foo(a, /* hi */ /* there */ b);";
+    let tree = parser.parse(source, None).unwrap();
+
+    let b = tree.root_node().descendant_for_byte_range(28, 29).unwrap();
+    assert_eq!(b.utf8_text(source.as_bytes()).unwrap(), "b");
+    assert_eq!(b.prev_sibling().unwrap().kind(), "comment");
+    assert_eq!(b.prev_named_sibling().unwrap().kind(), "comment");
+
+    let comma = b.prev_non_extra_sibling().unwrap();
+    assert_eq!(comma.kind(), ",");
+    let a = comma.prev_non_extra_sibling().unwrap();
+    assert_eq!(a.kind(), "identifier");
+    let open_paren = a.prev_non_extra_sibling().unwrap();
+    assert_eq!(open_paren.kind(), "(");
+    assert_eq!(open_paren.prev_non_extra_sibling(), None);
+}
+
+#[test]
+fn test_node_is_kind() {
+    let mut parser = Parser::new();
+    parser.set_language(&get_language("javascript")).unwrap();
+    let tree = parser
+        .parse("const a = {b: c(d)}; /* e */ if (f) g.h;", None)
+        .unwrap();
+
+    let mut cursor = tree.walk();
+    let mut nodes = Vec::new();
+    loop {
+        nodes.push(cursor.node());
+        if !cursor.goto_first_child() {
+            while !cursor.goto_next_sibling() {
+                if !cursor.goto_parent() {
+                    break;
+                }
+            }
+            if cursor.node() == tree.root_node() {
+                break;
+            }
+        }
+    }
+    let kinds = nodes.iter().map(Node::kind).collect::<Vec<_>>();
+    assert!(kinds.contains(&"property_identifier"));
+    assert!(kinds.contains(&"comment"));
+    assert!(kinds.contains(&"("));
+
+    for node in &nodes {
+        for kind in kinds.iter().copied().chain(["ERROR", "end", "nonexistent"]) {
+            assert_eq!(node.is_kind(kind), node.kind() == kind, "{node} {kind}");
+            assert_eq!(
+                node.is_named_kind(kind),
+                node.is_named() && node.kind() == kind,
+                "{node} {kind}"
+            );
+        }
+    }
+}
+
+#[test]
+fn test_node_kind_set() {
+    let language = get_language("javascript");
+    let mut parser = Parser::new();
+    parser.set_language(&language).unwrap();
+    let source = "const a = {b: c(d)}; /* e */ if (f) g.h;";
+    let tree = parser.parse(source, None).unwrap();
+
+    let named_set = language.node_kind_set(&["identifier", "comment", "nonexistent"], true);
+    let anonymous_set = language.node_kind_set(&["(", "if", "identifier"], false);
+    assert!(!named_set.contains_id(0));
+    assert!(named_set.contains_id(language.id_for_node_kind("comment", true)));
+    assert!(!named_set.contains_id(u16::MAX));
+    assert!(
+        !language
+            .node_kind_set(&[], true)
+            .contains(&tree.root_node())
+    );
+
+    let mut cursor = tree.walk();
+    let mut named_texts = Vec::new();
+    let mut anonymous_texts = Vec::new();
+    loop {
+        let node = cursor.node();
+        if named_set.contains(&node) {
+            named_texts.push(&source[node.byte_range()]);
+        }
+        if anonymous_set.contains(&node) {
+            anonymous_texts.push(&source[node.byte_range()]);
+        }
+        assert_eq!(
+            named_set.contains(&node),
+            node.is_named() && ["identifier", "comment"].contains(&node.kind())
+        );
+        if !cursor.goto_first_child() {
+            while !cursor.goto_next_sibling() {
+                if !cursor.goto_parent() {
+                    break;
+                }
+            }
+            if cursor.node() == tree.root_node() {
+                break;
+            }
+        }
+    }
+    assert_eq!(named_texts, ["a", "c", "d", "/* e */", "f", "g"]);
+    assert_eq!(anonymous_texts, ["(", "if", "("]);
+}
+
+#[test]
+fn test_node_kind_set_with_errors() {
+    let language = get_language("javascript");
+    let mut parser = Parser::new();
+    parser.set_language(&language).unwrap();
+    let tree = parser.parse("foo(", None).unwrap();
+    let error_node = tree.root_node().child(0).unwrap();
+    assert!(error_node.is_error());
+
+    let set = language.node_kind_set(&["ERROR", "identifier"], true);
+    assert!(set.contains(&error_node));
+    assert!(set.contains(&error_node.child(0).unwrap()));
+    assert!(!set.contains(&tree.root_node()));
+    assert!(
+        !language
+            .node_kind_set(&["identifier"], true)
+            .contains(&error_node)
+    );
+}
+
 #[test]
 fn test_node_is_error() {
     let mut parser = Parser::new();
@@ -845,6 +1369,54 @@ fn test_node_is_error() {
     assert!(child.is_error());
 }
 
+#[test]
+fn test_node_error_cost() {
+    let mut parser = Parser::new();
+    parser.set_language(&get_language("javascript")).unwrap();
+    let source = "f(a, %);\ng(b, % % % % % %\n% % % %);\nh(c);\n";
+    let tree = parser.parse(source, None).unwrap();
+    let root_node = tree.root_node();
+    assert!(root_node.has_error());
+    assert_eq!(root_node.error_cost(), None);
+
+    let mut errors = Vec::new();
+    let mut cursor = root_node.walk();
+    loop {
+        let node = cursor.node();
+        if let Some(cost) = node.error_cost() {
+            errors.push((node.utf8_text(source.as_bytes()).unwrap(), cost));
+        } else if cursor.goto_first_child() {
+            continue;
+        }
+        while !cursor.goto_next_sibling() {
+            if !cursor.goto_parent() {
+                break;
+            }
+        }
+        if cursor.node() == root_node {
+            break;
+        }
+    }
+
+    // The error that skips more of the input is more costly.
+    assert_eq!(errors.len(), 2);
+    assert_eq!(errors[0].0, "%");
+    assert_eq!(errors[1].0, ", % % % % % %\n% % % %");
+    assert!(errors[0].1 > 0);
+    assert!(errors[1].1 > errors[0].1);
+
+    // Missing nodes also have a cost.
+    let tree = parser.parse("f(a;", None).unwrap();
+    let arguments = tree
+        .root_node()
+        .named_descendant_for_byte_range(1, 3)
+        .unwrap();
+    assert_eq!(arguments.kind(), "arguments");
+    let missing = arguments.child(arguments.child_count() - 1).unwrap();
+    assert!(missing.is_missing());
+    assert!(missing.error_cost().unwrap() > 0);
+}
+
 #[test]
 fn test_edit_point() {
     let edit = InputEdit {
@@ -948,6 +1520,60 @@ fn test_node_sexp() {
     assert_eq!(identifier_node.to_sexp(), "(identifier)");
 }
 
+#[test]
+fn test_parse_sexp() {
+    let mut parser = Parser::new();
+    parser.set_language(&get_language("javascript")).unwrap();
+
+    for source in [
+        "if (a) { b.c(d, e) }",
+        "var x = { a: }",
+        "if (a) { b",
+        "f(a, \\);",
+        "a = 1 #",
+    ] {
+        let tree = parser.parse(source, None).unwrap();
+        let sexp = tree.root_node().to_sexp();
+        let sexp_tree = parse_sexp(&sexp).unwrap();
+        assert_eq!(sexp_tree.to_string(), sexp);
+        assert_eq!(parse_sexp(&format_sexp(&sexp, 0)).unwrap(), sexp_tree);
+    }
+
+    let tree = parser.parse("if (a) { b", None).unwrap();
+    let sexp_tree = parse_sexp(&tree.root_node().to_sexp()).unwrap();
+    let if_statement = &sexp_tree.children[0];
+    assert_eq!(if_statement.kind, "if_statement");
+    assert_eq!(
+        if_statement
+            .children
+            .iter()
+            .map(|child| child.field_name.as_deref())
+            .collect::<Vec<_>>(),
+        &[Some("condition"), Some("consequence")]
+    );
+    let missing_brace = if_statement.children[1].children.last().unwrap();
+    assert_eq!(missing_brace.kind, "}");
+    assert!(missing_brace.is_missing);
+    assert!(!missing_brace.is_named);
+
+    let sexp = "(a b: (\")\") (MISSING \"\"\") (MISSING c) (UNEXPECTED ')') (UNEXPECTED 200))";
+    let sexp_tree = parse_sexp(sexp).unwrap();
+    assert_eq!(sexp_tree.to_string(), sexp);
+    assert_eq!(sexp_tree.children[0].kind, ")");
+    assert_eq!(sexp_tree.children[0].field_name.as_deref(), Some("b"));
+    assert!(!sexp_tree.children[0].is_named);
+    assert_eq!(sexp_tree.children[1].kind, "\"");
+    assert!(sexp_tree.children[1].is_missing);
+    assert!(sexp_tree.children[2].is_named);
+    assert_eq!(sexp_tree.children[3].kind, "ERROR");
+    assert_eq!(sexp_tree.children[3].unexpected.as_deref(), Some("')'"));
+    assert_eq!(sexp_tree.children[4].unexpected.as_deref(), Some("200"));
+
+    for (sexp, offset) in [("(a (b)", 6), ("(a b)", 4), ("(a) (b)", 4), ("a", 0)] {
+        assert_eq!(parse_sexp(sexp).unwrap_err().offset, offset, "{sexp}");
+    }
+}
+
 #[test]
 fn test_node_field_names() {
     // - "x":
@@ -1110,6 +1736,76 @@ fn test_node_field_calls_in_language_without_fields() {
     assert_eq!(cursor.field_name(), None);
 }
 
+#[test]
+fn test_node_named_children_with_fields() {
+    let (parser_name, parser_code) = generate_parser(
+        r#"
+        {
+            "name": "test_grammar_with_repeated_fields",
+            "extras": [
+                {"type": "PATTERN", "value": "\\s+"}
+            ],
+            "rules": {
+                "list": {
+                    "type": "SEQ",
+                    "members": [
+                        {"type": "STRING", "value": "("},
+                        {
+                            "type": "FIELD",
+                            "name": "head",
+                            "content": {"type": "SYMBOL", "name": "identifier"}
+                        },
+                        {
+                            "type": "REPEAT",
+                            "content": {
+                                "type": "SEQ",
+                                "members": [
+                                    {"type": "STRING", "value": ","},
+                                    {
+                                        "type": "FIELD",
+                                        "name": "item",
+                                        "content": {"type": "SYMBOL", "name": "identifier"}
+                                    }
+                                ]
+                            }
+                        },
+                        {"type": "SYMBOL", "name": "number"},
+                        {"type": "STRING", "value": ")"}
+                    ]
+                },
+                "identifier": {"type": "PATTERN", "value": "[a-z]+"},
+                "number": {"type": "PATTERN", "value": "\\d+"}
+            }
+        }
+    "#,
+    )
+    .unwrap();
+
+    let mut parser = Parser::new();
+    let language = get_test_language(&parser_name, &parser_code, None);
+    parser.set_language(&language).unwrap();
+
+    let source = "(a, b, c 1)";
+    let tree = parser.parse(source, None).unwrap();
+    let root_node = tree.root_node();
+    assert_eq!(root_node.kind(), "list");
+
+    let mut cursor = root_node.walk();
+    let children = root_node
+        .named_children_with_fields(&mut cursor)
+        .map(|(field_name, node)| (field_name, node.utf8_text(source.as_bytes()).unwrap()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        children,
+        [
+            (Some("head"), "a"),
+            (Some("item"), "b"),
+            (Some("item"), "c"),
+            (None, "1"),
+        ]
+    );
+}
+
 #[test]
 fn test_node_is_named_but_aliased_as_anonymous() {
     let grammar_json = load_grammar_file(
@@ -1,6 +1,10 @@
 use std::{
+    fs,
+    io::{self, Write},
     ops::ControlFlow,
+    path::Path,
     sync::{
+        Arc,
         atomic::{AtomicUsize, Ordering},
         mpsc,
     },
@@ -9,19 +13,23 @@ use std::{
 };
 
 use tree_sitter::{
-    Decode, IncludedRangesError, InputEdit, LogType, ParseOptions, ParseState, Parser, Point, Range,
+    Decode, ErrorRecoveryMode, IncludedRangesError, IncludedRangesErrorKind, InputEdit, LogType,
+    Node, ParseError, ParseLogEvent, ParseOptions, ParseState, Parser, ParserPool, Point, Range,
+    Tree,
+    test_utils::{SourceEdit, assert_scanner_roundtrip},
 };
-use tree_sitter_generate::load_grammar_file;
+use tree_sitter_generate::{OptLevel, SPLIT_FILE_NAMES, load_grammar_file};
+use tree_sitter_loader::CompileConfig;
 use tree_sitter_proc_macro::retry;
 
 use super::helpers::{
     allocations,
     edits::ReadRecorder,
-    fixtures::{get_language, get_test_language},
+    fixtures::{get_language, get_test_language, test_loader},
 };
 use crate::{
     fuzz::edits::Edit,
-    parse::perform_edit,
+    parse::{parse_reader, perform_edit},
     tests::{
         generate_parser,
         helpers::fixtures::{fixtures_dir, get_test_fixture_language},
@@ -96,6 +104,58 @@ fn test_parsing_with_logging() {
     assert!(row_starts_from_0);
 }
 
+#[test]
+fn test_parsing_with_structured_logging() {
+    let mut parser = Parser::new();
+    parser.set_language(&get_language("rust")).unwrap();
+
+    let mut events = Vec::new();
+    parser.set_structured_logger(|event| events.push(event));
+    parser.parse("struct Stuff {}", None).unwrap();
+
+    assert!(
+        events
+            .iter()
+            .any(|event| matches!(event, ParseLogEvent::Shift { state } if *state != 0))
+    );
+    assert!(events.contains(&ParseLogEvent::Reduce {
+        symbol: "struct_item".to_string(),
+        child_count: 3,
+    }));
+    assert!(events.contains(&ParseLogEvent::Lookahead {
+        symbol: "identifier".to_string(),
+        size: 6,
+    }));
+    assert!(events.contains(&ParseLogEvent::Advance {
+        character: i32::from(b' '),
+        skip: true,
+    }));
+    assert!(events.iter().any(|event| matches!(
+        event,
+        ParseLogEvent::Lex { external: false, position, .. } if *position == Point::new(0, 0)
+    )));
+    assert_eq!(events.last(), Some(&ParseLogEvent::Raw("done".to_string())));
+
+    assert_eq!(
+        ParseLogEvent::parse(LogType::Parse, r"lexed_lookahead sym:\\\t, size:1"),
+        ParseLogEvent::Lookahead {
+            symbol: "\\\t".to_string(),
+            size: 1,
+        }
+    );
+    assert_eq!(
+        ParseLogEvent::parse(LogType::Lex, "consume character:10"),
+        ParseLogEvent::Advance {
+            character: 10,
+            skip: false,
+        }
+    );
+    assert_eq!(
+        ParseLogEvent::parse(LogType::Parse, "shift state:abc"),
+        ParseLogEvent::Raw("shift state:abc".to_string())
+    );
+}
+
 #[test]
 fn test_parsing_with_debug_graph_enabled() {
     use std::io::{BufRead, BufReader, Seek};
@@ -165,6 +225,155 @@ fn test_parsing_with_custom_utf8_input() {
     assert_eq!(root.child(0).unwrap().kind(), "function_item");
 }
 
+#[test]
+fn test_parsing_from_a_compressed_reader() {
+    // A minimal run-length decoder, standing in for a real decompressor. It
+    // decodes at most one run per read, so the input arrives in many chunks.
+    struct RunLengthDecoder<'a> {
+        runs: &'a [u8],
+    }
+
+    impl io::Read for RunLengthDecoder<'_> {
+        fn read(&mut self, buffer: &mut [u8]) -> io::Result<usize> {
+            let [count, byte, rest @ ..] = self.runs else {
+                return Ok(0);
+            };
+            let count = usize::from(*count).min(buffer.len());
+            buffer[..count].fill(*byte);
+            self.runs = rest;
+            Ok(count)
+        }
+    }
+
+    let source = format!("{{\n{}x = [1, 2];\n}}\n", " ".repeat(100)).repeat(2000);
+    let mut runs = Vec::new();
+    for byte in source.bytes() {
+        match runs.as_mut_slice() {
+            [.., count, last] if *last == byte && *count < u8::MAX => *count += 1,
+            _ => runs.extend([1, byte]),
+        }
+    }
+    assert!(runs.len() < source.len());
+
+    let mut parser = Parser::new();
+    parser.set_language(&get_language("javascript")).unwrap();
+    let expected = parser.parse(&source, None).unwrap();
+    let tree = parse_reader(&mut parser, RunLengthDecoder { runs: &runs })
+        .unwrap()
+        .unwrap();
+    assert_eq!(tree.root_node().byte_range(), 0..source.len());
+    assert_eq!(tree.root_node().to_sexp(), expected.root_node().to_sexp());
+
+    // Read errors are reported instead of being treated as the end of the input.
+    struct FailingReader;
+
+    impl io::Read for FailingReader {
+        fn read(&mut self, _: &mut [u8]) -> io::Result<usize> {
+            Err(io::Error::other("corrupt input"))
+        }
+    }
+
+    let error = parse_reader(&mut parser, FailingReader).unwrap_err();
+    assert_eq!(error.to_string(), "Failed to read input");
+    assert_eq!(error.root_cause().to_string(), "corrupt input");
+}
+
+#[test]
+fn test_parsing_with_seekable_reader() {
+    let source = "{\n  x = [1, 2];\n}\n".repeat(10000);
+    let mut file = tempfile::tempfile().unwrap();
+    file.write_all(source.as_bytes()).unwrap();
+
+    let mut parser = Parser::new();
+    parser.set_language(&get_language("javascript")).unwrap();
+    let expected = parser.parse(&source, None).unwrap();
+    let tree = parser.parse_reader(&file, None).unwrap().unwrap();
+    assert_eq!(tree.root_node().byte_range(), 0..source.len());
+    assert_eq!(tree.root_node().to_sexp(), expected.root_node().to_sexp());
+
+    // When reparsing, the parser skips over the reused parts of the old tree, so the reader
+    // has to seek. Replace the `1` in the middle block with an identifier.
+    let block = 5000;
+    let position = source.len() / 2 + "{\n  x = [".len();
+    let point = Point::new(block * 3 + 1, "  x = [".len());
+    let mut new_source = source.clone();
+    new_source.replace_range(position..=position, "y");
+    let mut old_tree = tree;
+    old_tree.edit(&InputEdit {
+        start_byte: position,
+        old_end_byte: position + 1,
+        new_end_byte: position + 1,
+        start_position: point,
+        old_end_position: Point::new(point.row, point.column + 1),
+        new_end_position: Point::new(point.row, point.column + 1),
+    });
+    let expected = parser.parse(&new_source, Some(&old_tree)).unwrap();
+    let tree = parser
+        .parse_reader(io::Cursor::new(&new_source), Some(&old_tree))
+        .unwrap()
+        .unwrap();
+    assert_eq!(tree.root_node().to_sexp(), expected.root_node().to_sexp());
+    assert_ne!(tree.root_node().to_sexp(), old_tree.root_node().to_sexp());
+}
+
+#[test]
+fn test_scanner_roundtrip() {
+    // Python's external scanner tracks the indentation of each enclosing block, so these
+    // edits change which `_indent` and `_dedent` tokens are produced after the reused part
+    // of the tree.
+    let source = indoc::indoc! {"
+        def a():
+            if b:
+                c()
+            d()
+        e()
+    "};
+    let position = |text: &str| source.find(text).unwrap();
+    assert_scanner_roundtrip(
+        &get_language("python"),
+        source,
+        // The edits are applied in order, so they go from the end of the text to the start to
+        // keep the positions in the original text valid.
+        &[
+            SourceEdit {
+                range: position("e()")..position("e()"),
+                text: "    ",
+            },
+            SourceEdit {
+                range: position("d()")..position("d()"),
+                text: "    ",
+            },
+            SourceEdit {
+                range: position("    c()")..position("    c()") + 4,
+                text: "",
+            },
+        ],
+    );
+}
+
+#[test]
+fn test_scanner_roundtrip_with_unserialized_state() {
+    // This grammar's scanner keeps the delimiter of the current string in the scanner object,
+    // without serializing it, so the reparsed string after the edit doesn't end where it should.
+    let language = get_test_fixture_language("external_tokens");
+    let source = "%{sup {} #{x + y} {} scanner?}";
+    let edit = SourceEdit {
+        range: source.len()..source.len(),
+        text: " + %(b)",
+    };
+    let error = std::panic::catch_unwind(|| assert_scanner_roundtrip(&language, source, &[edit]))
+        .unwrap_err();
+    let message = error.downcast_ref::<String>().unwrap();
+    assert!(
+        message.starts_with("After edit 0, the reparsed tree differs from a fresh parse"),
+        "{message}"
+    );
+    assert!(
+        message.contains("\nExpected: (expression (string "),
+        "{message}"
+    );
+}
+
 #[test]
 fn test_parsing_with_custom_utf16le_input() {
     let mut parser = Parser::new();
@@ -436,6 +645,104 @@ fn test_parsing_ends_when_input_callback_returns_empty() {
     assert_eq!(tree.root_node().end_byte(), 6);
 }
 
+#[test]
+fn test_parsing_with_conservative_error_recovery() {
+    let mut parser = Parser::new();
+    parser.set_language(&get_language("javascript")).unwrap();
+    assert_eq!(parser.error_recovery(), ErrorRecoveryMode::Default);
+
+    let source = "let x = [1, 2;\nfoo(a;\nbar();";
+    let default_sexp = concat!(
+        "(program ",
+        "(lexical_declaration (variable_declarator name: (identifier) ",
+        "value: (array (number) (number) (MISSING \"]\")))) ",
+        "(expression_statement (call_expression function: (identifier) ",
+        "arguments: (arguments (identifier) (MISSING \")\")))) ",
+        "(expression_statement (call_expression function: (identifier) arguments: (arguments))))",
+    );
+    let tree = parser.parse(source, None).unwrap();
+    assert_eq!(tree.root_node().to_sexp(), default_sexp);
+
+    // Without inserting the missing tokens, the invalid text is wrapped in an ERROR.
+    parser.set_error_recovery(ErrorRecoveryMode::Conservative);
+    assert_eq!(parser.error_recovery(), ErrorRecoveryMode::Conservative);
+    let tree = parser.parse(source, None).unwrap();
+    assert_eq!(
+        tree.root_node().to_sexp(),
+        concat!(
+            "(program (lexical_declaration (variable_declarator name: (identifier)) ",
+            "(ERROR (number) (ERROR (number)) (identifier) (ERROR (identifier)) ",
+            "(call_expression function: (identifier) arguments: (arguments)))))",
+        )
+    );
+
+    // Valid input is unaffected by the mode.
+    let tree = parser.parse("let x = [1, 2];", None).unwrap();
+    assert!(!tree.root_node().has_error());
+
+    parser.set_error_recovery(ErrorRecoveryMode::Default);
+    let tree = parser.parse(source, None).unwrap();
+    assert_eq!(tree.root_node().to_sexp(), default_sexp);
+}
+
+#[test]
+fn test_parsing_with_extra_override() {
+    let language = get_language("javascript");
+    let comment = language.id_for_node_kind("comment", true);
+    let mut parser = Parser::new();
+    parser.set_language(&language).unwrap();
+    assert!(parser.extra_override().is_empty());
+
+    let source = "// a\nb /* c */ + d;";
+    let tree = parser.parse(source, None).unwrap();
+    fn comments(tree: &Tree) -> [Node<'_>; 2] {
+        let root = tree.root_node();
+        let binary = root.named_child(1).unwrap().named_child(0).unwrap();
+        [root.child(0).unwrap(), binary.child(1).unwrap()]
+    }
+    for node in comments(&tree) {
+        assert_eq!(node.kind(), "comment");
+        assert!(node.is_extra());
+    }
+
+    // The kinds are sorted and deduplicated. Kinds that aren't extras are unaffected.
+    let identifier = language.id_for_node_kind("identifier", true);
+    parser.set_extra_override(&[comment, identifier, comment]);
+    let mut expected = [comment, identifier];
+    expected.sort_unstable();
+    assert_eq!(parser.extra_override(), expected);
+    let overridden_tree = parser.parse(source, Some(&tree)).unwrap();
+    assert_eq!(
+        overridden_tree.root_node().to_sexp(),
+        tree.root_node().to_sexp()
+    );
+    for node in comments(&overridden_tree)
+        .into_iter()
+        .chain(comments(&overridden_tree.clone()))
+    {
+        assert!(!node.is_extra());
+    }
+
+    // The comments are still not stored in fields, and the fields after them are unchanged.
+    let binary = comments(&overridden_tree)[1].parent().unwrap();
+    assert_eq!(binary.field_name_for_child(1), None);
+    assert_eq!(binary.field_name_for_child(3), Some("right"));
+    assert_eq!(
+        binary
+            .child_by_field_name("right")
+            .unwrap()
+            .utf8_text(source.as_bytes()),
+        Ok("d")
+    );
+
+    // The trees that were already parsed keep the override they were parsed with.
+    parser.set_extra_override(&[]);
+    assert!(parser.extra_override().is_empty());
+    let tree = parser.parse(source, Some(&overridden_tree)).unwrap();
+    assert!(comments(&tree).iter().all(Node::is_extra));
+    assert!(!comments(&overridden_tree).iter().any(Node::is_extra));
+}
+
 // Incremental parsing
 
 #[test]
@@ -486,6 +793,110 @@ fn test_parsing_after_editing_beginning_of_code() {
     assert_eq!(recorder.strings_read(), vec!["123 || 5 "]);
 }
 
+#[test]
+fn test_parsing_with_stats() {
+    let mut parser = Parser::new();
+    parser.set_language(&get_language("javascript")).unwrap();
+
+    let mut code = Vec::new();
+    for i in 0..100 {
+        writeln!(&mut code, "let x{i} = [{i}, \"{i}\", f({i})];").unwrap();
+    }
+    let (tree, initial_stats) = parser.parse_with_stats(&code, None);
+    let mut tree = tree.unwrap();
+    assert_eq!(initial_stats.reused_node_count, 0);
+    assert_eq!(initial_stats.error_recovery_count, 0);
+    assert!(initial_stats.lexed_token_count >= 100 * 14);
+
+    let position = code.len() / 2;
+    perform_edit(
+        &mut tree,
+        &mut code,
+        &Edit {
+            position,
+            deleted_length: 0,
+            inserted_text: b" ".to_vec(),
+        },
+    )
+    .unwrap();
+    let (tree, stats) = parser.parse_with_stats(&code, Some(&tree));
+    let tree = tree.unwrap();
+    assert!(!tree.root_node().has_error());
+    let node_count = tree.root_node().descendant_count();
+    assert!(
+        stats.reused_node_count * 10 > node_count * 9,
+        "reused {} of {node_count} nodes",
+        stats.reused_node_count,
+    );
+    assert!(stats.lexed_token_count * 10 < initial_stats.lexed_token_count);
+    assert_eq!(stats.error_recovery_count, 0);
+
+    let (tree, stats) = parser.parse_with_stats("let x = [1, 2;", None);
+    assert!(tree.unwrap().root_node().has_error());
+    assert!(stats.error_recovery_count > 0);
+}
+
+#[test]
+fn test_parsing_after_batch_of_edits() {
+    let mut parser = Parser::new();
+    parser.set_language(&get_language("javascript")).unwrap();
+
+    let mut code = b"const a = f(1, 2);\nlet b = a + c;\nreturn b;\n".to_vec();
+    let tree = parser.parse(&code, None).unwrap();
+    let old_sexp = tree.root_node().to_sexp();
+
+    // Each edit's position refers to the text produced by the edits before it.
+    let mut edited_tree = tree.clone();
+    let edits = [
+        Edit {
+            position: 0,
+            deleted_length: 0,
+            inserted_text: b"// header\n".to_vec(),
+        },
+        Edit {
+            position: "// header\nconst a = f(1".len(),
+            deleted_length: 3,
+            inserted_text: b", [2, 3], 4".to_vec(),
+        },
+        Edit {
+            position: "// header\nconst a = f(1, [2, 3], 4);\nlet b = a + ".len(),
+            deleted_length: 1,
+            inserted_text: b"g(c)".to_vec(),
+        },
+        Edit {
+            position: "// header\nconst a = f(1, [2, 3], 4);\nlet b = a + g(c);\n".len(),
+            deleted_length: "return b;\n".len(),
+            inserted_text: Vec::new(),
+        },
+    ]
+    .iter()
+    .map(|edit| perform_edit(&mut edited_tree, &mut code, edit).unwrap())
+    .collect::<Vec<_>>();
+    assert_eq!(
+        code,
+        b"// header\nconst a = f(1, [2, 3], 4);\nlet b = a + g(c);\n"
+    );
+
+    let new_tree = parser.reparse_batch(&tree, &edits, &code).unwrap();
+    assert_eq!(
+        new_tree.root_node().to_sexp(),
+        parser.parse(&code, None).unwrap().root_node().to_sexp()
+    );
+    assert_eq!(
+        new_tree.root_node().to_sexp(),
+        parser
+            .parse(&code, Some(&edited_tree))
+            .unwrap()
+            .root_node()
+            .to_sexp()
+    );
+
+    // The original tree is left as it was.
+    assert!(!tree.root_node().has_changes());
+    assert_eq!(tree.root_node().to_sexp(), old_sexp);
+    assert_eq!(tree.root_node().end_byte(), 44);
+}
+
 #[test]
 fn test_parsing_after_editing_end_of_code() {
     let mut parser = Parser::new();
@@ -1003,6 +1414,97 @@ fn test_parsing_with_a_timeout_and_implicit_reset() {
     });
 }
 
+#[test]
+fn test_parsing_with_a_deadline() {
+    allocations::record(|| {
+        let mut parser = Parser::new();
+        assert_eq!(
+            parser
+                .parse_with_deadline("[]", None, time::Instant::now())
+                .unwrap_err(),
+            ParseError::NoLanguage
+        );
+
+        parser.set_language(&get_language("json")).unwrap();
+        let code = format!("[{}0]", "0, ".repeat(50000));
+
+        // A deadline that has already passed stops the parse at the first check.
+        assert_eq!(
+            parser
+                .parse_with_deadline(&code, None, time::Instant::now())
+                .unwrap_err(),
+            ParseError::Timeout
+        );
+
+        // Parsing again resumes the partial parse and runs it to completion.
+        let deadline = time::Instant::now() + Duration::from_secs(60);
+        let tree = parser.parse_with_deadline(&code, None, deadline).unwrap();
+        let array = tree.root_node().child(0).unwrap();
+        assert_eq!(array.kind(), "array");
+        assert_eq!(array.named_child_count(), 50001);
+        assert!(!tree.root_node().has_error());
+    });
+}
+
+#[test]
+fn test_parser_pool() {
+    let language = get_language("json");
+    let log_count = Arc::new(AtomicUsize::new(0));
+    let pool = ParserPool::new(&language).unwrap().capacity(1).logger({
+        let log_count = log_count.clone();
+        move |_, _| {
+            log_count.fetch_add(1, Ordering::Relaxed);
+        }
+    });
+
+    // A parse that stopped partway through is not resumed by the next document that the same
+    // parser is used for.
+    let code = format!("[{}0]", "0, ".repeat(50000));
+    assert_eq!(
+        pool.get()
+            .parse_with_deadline(&code, None, time::Instant::now())
+            .unwrap_err(),
+        ParseError::Timeout
+    );
+    let tree = pool.parse("{}", None).unwrap();
+    assert_eq!(tree.root_node().to_sexp(), "(document (object))");
+    assert!(log_count.load(Ordering::Relaxed) > 0);
+
+    let timeout_pool = ParserPool::new(&language).unwrap().timeout(Duration::ZERO);
+    assert!(timeout_pool.parse(&code, None).is_none());
+    assert!(timeout_pool.parse("{}", None).is_some());
+}
+
+#[test]
+fn test_parser_pool_across_threads() {
+    let language = get_language("javascript");
+    let pool = ParserPool::new(&language).unwrap().capacity(2);
+    let sources = (0..12)
+        .map(|i| format!("function f{i}() {{ return [{}]; }}", "x, ".repeat(i)))
+        .collect::<Vec<_>>();
+    let expected = sources
+        .iter()
+        .map(|source| {
+            let mut parser = Parser::new();
+            parser.set_language(&language).unwrap();
+            parser.parse(source, None).unwrap().root_node().to_sexp()
+        })
+        .collect::<Vec<_>>();
+
+    thread::scope(|scope| {
+        for thread_index in 0..4 {
+            let (pool, sources, expected) = (&pool, &sources, &expected);
+            scope.spawn(move || {
+                for i in 0..sources.len() {
+                    let i = (i + thread_index * 3) % sources.len();
+                    let tree = pool.parse(&sources[i], None).unwrap();
+                    assert_eq!(tree.root_node().to_sexp(), expected[i]);
+                }
+            });
+        }
+    });
+}
+
 #[test]
 #[retry(10)]
 fn test_parsing_with_timeout_and_no_completion() {
@@ -1361,7 +1863,17 @@ fn test_parsing_error_in_invalid_included_ranges() {
             },
         ])
         .unwrap_err();
-    assert_eq!(error, IncludedRangesError(1));
+    assert_eq!(
+        error,
+        IncludedRangesError {
+            index: 1,
+            kind: IncludedRangesErrorKind::OutOfOrder
+        }
+    );
+    assert_eq!(
+        error.to_string(),
+        "Included range 1 starts before the previous range"
+    );
 
     // Range ends before it starts
     let error = parser
@@ -1372,7 +1884,60 @@ fn test_parsing_error_in_invalid_included_ranges() {
             end_point: Point::new(0, 5),
         }])
         .unwrap_err();
-    assert_eq!(error, IncludedRangesError(0));
+    assert_eq!(
+        error,
+        IncludedRangesError {
+            index: 0,
+            kind: IncludedRangesErrorKind::EndBeforeStart
+        }
+    );
+}
+
+#[test]
+fn test_parsing_with_included_ranges_validation() {
+    let range = |start_byte: usize, end_byte: usize| Range {
+        start_byte,
+        end_byte,
+        start_point: Point::new(0, start_byte),
+        end_point: Point::new(0, end_byte),
+    };
+    let mut parser = Parser::new();
+
+    // Ordered ranges are accepted, including empty and adjacent ones.
+    let ranges = [range(0, 5), range(5, 5), range(5, 10), range(20, 30)];
+    parser.set_included_ranges(&ranges).unwrap();
+    assert_eq!(parser.included_ranges(), ranges);
+
+    // A swapped pair of ranges
+    let error = parser
+        .set_included_ranges(&[range(0, 5), range(20, 30), range(10, 15)])
+        .unwrap_err();
+    assert_eq!(
+        error,
+        IncludedRangesError {
+            index: 2,
+            kind: IncludedRangesErrorKind::OutOfOrder
+        }
+    );
+
+    // A pair of overlapping ranges
+    let error = parser
+        .set_included_ranges(&[range(0, 5), range(10, 20), range(15, 25)])
+        .unwrap_err();
+    assert_eq!(
+        error,
+        IncludedRangesError {
+            index: 2,
+            kind: IncludedRangesErrorKind::Overlap
+        }
+    );
+    assert_eq!(
+        error.to_string(),
+        "Included range 2 overlaps the previous range"
+    );
+
+    // A failed call leaves the previous ranges in place.
+    assert_eq!(parser.included_ranges(), ranges);
 }
 
 #[test]
@@ -2235,3 +2800,416 @@ fn test_grammar_that_should_hang_and_not_segfault() {
         }
     }
 }
+
+#[test]
+fn test_parsing_with_deduplicated_small_parse_states() {
+    let grammar_json =
+        fs::read_to_string(fixtures_dir().join("grammars/c/src/grammar.json")).unwrap();
+    let mut grammar = serde_json::from_str::<serde_json::Value>(&grammar_json).unwrap();
+
+    let mut generate = |name: &str, optimizations: OptLevel| {
+        grammar["name"] = name.into();
+        let dir = tempfile::tempdir().unwrap();
+        let grammar_path = dir.path().join("src").join("grammar.json");
+        fs::create_dir_all(grammar_path.parent().unwrap()).unwrap();
+        fs::write(&grammar_path, serde_json::to_string(&grammar).unwrap()).unwrap();
+        tree_sitter_generate::generate_parser_in_directory(
+            dir.path(),
+            None::<&Path>,
+            Some(&grammar_path),
+            tree_sitter::LANGUAGE_VERSION,
+            None,
+            None,
+            None,
+            None,
+            true,
+            optimizations,
+            false,
+            false,
+            false,
+            false,
+            None,
+            &mut Vec::new(),
+        )
+        .unwrap();
+        fs::read_to_string(dir.path().join("src").join("parser.c")).unwrap()
+    };
+    let deduplicated_code = generate("c_deduplicated", OptLevel::default());
+    let duplicated_code = generate(
+        "c_duplicated",
+        OptLevel::default() - OptLevel::DeduplicateSmallStates,
+    );
+    assert!(deduplicated_code.len() < duplicated_code.len());
+
+    let mut deduplicated_parser = Parser::new();
+    deduplicated_parser
+        .set_language(&get_test_language(
+            "c_deduplicated",
+            &deduplicated_code,
+            None,
+        ))
+        .unwrap();
+    let mut duplicated_parser = Parser::new();
+    duplicated_parser
+        .set_language(&get_test_language("c_duplicated", &duplicated_code, None))
+        .unwrap();
+
+    for source in [
+        "#include <stdio.h>\n\nint main(int argc, char **argv) {\n  printf(\"%d\\n\", argc);\n  return 0;\n}\n",
+        "typedef struct { int x, y; } point;\nstatic point *p = &(point){.x = 1, [0] = 2};\n",
+        "void f() { for (int i = 0; i < n; i++) { if (a[i] > b) break; else continue; } }\n",
+        "int x = (int) y ? z : *w; int f(int (*g)(void), ...) { g(); }\n",
+        "int main() { int x = ; foo(1, 2 return; } ++ }\n",
+    ] {
+        let deduplicated_tree = deduplicated_parser.parse(source, None).unwrap();
+        let duplicated_tree = duplicated_parser.parse(source, None).unwrap();
+        assert_eq!(
+            deduplicated_tree.root_node().to_sexp(),
+            duplicated_tree.root_node().to_sexp(),
+        );
+        assert_eq!(
+            deduplicated_tree.root_node().descendant_count(),
+            duplicated_tree.root_node().descendant_count(),
+        );
+    }
+}
+
+#[test]
+fn test_parsing_with_split_parser_tables() {
+    let c_src_dir = fixtures_dir().join("grammars/c/src");
+    let grammar_json = fs::read_to_string(c_src_dir.join("grammar.json")).unwrap();
+    let mut grammar = serde_json::from_str::<serde_json::Value>(&grammar_json).unwrap();
+
+    let dir = tempfile::tempdir().unwrap();
+    let mut generate = |name: &str, split_tables: bool| {
+        grammar["name"] = name.into();
+        let grammar_path = dir.path().join(name).join("src").join("grammar.json");
+        fs::create_dir_all(grammar_path.parent().unwrap()).unwrap();
+        fs::write(&grammar_path, serde_json::to_string(&grammar).unwrap()).unwrap();
+        tree_sitter_generate::generate_parser_in_directory(
+            dir.path().join(name),
+            None::<&Path>,
+            Some(&grammar_path),
+            tree_sitter::LANGUAGE_VERSION,
+            None,
+            None,
+            None,
+            None,
+            true,
+            OptLevel::default(),
+            false,
+            false,
+            split_tables,
+            false,
+            None,
+            &mut Vec::new(),
+        )
+        .unwrap();
+        dir.path().join(name).join("src")
+    };
+
+    let single_src_dir = generate("c_single_file", false);
+    let split_src_dir = generate("c_split_tables", true);
+    for file_name in SPLIT_FILE_NAMES {
+        assert!(!single_src_dir.join(file_name).exists());
+        assert!(split_src_dir.join(file_name).exists());
+    }
+    let split_parser_code = fs::read_to_string(split_src_dir.join("parser.c")).unwrap();
+    assert!(!split_parser_code.contains("ts_parse_actions[] = {"));
+
+    let single_language = get_test_language(
+        "c_single_file",
+        &fs::read_to_string(single_src_dir.join("parser.c")).unwrap(),
+        None,
+    );
+    let mut config = CompileConfig::new(&split_src_dir, None, None);
+    config.name = "c_split_tables".to_string();
+    let split_language = test_loader()
+        .load_language_at_path_with_name(config)
+        .unwrap();
+
+    let mut single_parser = Parser::new();
+    single_parser.set_language(&single_language).unwrap();
+    let mut split_parser = Parser::new();
+    split_parser.set_language(&split_language).unwrap();
+
+    let mut sources = ["parser.h", "array.h", "alloc.h"]
+        .iter()
+        .map(|file_name| fs::read_to_string(c_src_dir.join("tree_sitter").join(file_name)).unwrap())
+        .collect::<Vec<_>>();
+    sources.push("int main() { int x = ; foo(1, 2 return; } ++ }\n".to_string());
+    for source in &sources {
+        let single_tree = single_parser.parse(source, None).unwrap();
+        let split_tree = split_parser.parse(source, None).unwrap();
+        assert_eq!(
+            single_tree.root_node().to_sexp(),
+            split_tree.root_node().to_sexp(),
+        );
+        assert_eq!(
+            single_tree.root_node().descendant_count(),
+            split_tree.root_node().descendant_count(),
+        );
+    }
+
+    // Generating without splitting removes the files from the previous generation.
+    generate("c_split_tables", false);
+    for file_name in SPLIT_FILE_NAMES {
+        assert!(!split_src_dir.join(file_name).exists());
+    }
+}
+
+#[test]
+fn test_generating_a_parser_that_compiles_as_cxx() {
+    let dir = tempfile::tempdir().unwrap();
+    let src_path = dir.path().join("src");
+    let grammar_path = src_path.join("grammar.json");
+    fs::create_dir_all(&src_path).unwrap();
+    fs::copy(
+        fixtures_dir().join("grammars/javascript/src/grammar.json"),
+        &grammar_path,
+    )
+    .unwrap();
+    tree_sitter_generate::generate_parser_in_directory(
+        dir.path(),
+        None::<&Path>,
+        Some(&grammar_path),
+        tree_sitter::LANGUAGE_VERSION,
+        None,
+        None,
+        None,
+        None,
+        true,
+        OptLevel::default(),
+        true,
+        false,
+        false,
+        false,
+        None,
+        &mut Vec::new(),
+    )
+    .unwrap();
+    let parser_path = src_path.join("parser.c");
+    let code = fs::read_to_string(&parser_path).unwrap();
+
+    // Identifiers containing a double underscore are reserved in C++.
+    assert!(!code.contains("sym__"));
+    assert!(!code.contains("ts_external_token__"));
+
+    // The output compiles cleanly both as C++ and as C.
+    for (compiler_var, default_compiler, args) in [
+        ("CXX", "c++", ["-std=c++17", "-x", "c++"]),
+        ("CC", "cc", ["-std=c11", "-x", "c"]),
+    ] {
+        let compiler = std::env::var(compiler_var).unwrap_or_else(|_| default_compiler.to_string());
+        let output = std::process::Command::new(&compiler)
+            .args(args)
+            .args(["-fsyntax-only", "-Wall", "-Werror"])
+            .arg(&parser_path)
+            .arg("-I")
+            .arg(&src_path)
+            .output()
+            .unwrap_or_else(|e| panic!("Failed to run {compiler}: {e}"));
+        assert!(
+            output.status.success(),
+            "{compiler} failed:\n{}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+    }
+}
+
+#[test]
+fn test_generating_a_lexer_only_parser() {
+    let dir = tempfile::tempdir().unwrap();
+    let src_path = dir.path().join("src");
+    let grammar_path = src_path.join("grammar.json");
+    fs::create_dir_all(&src_path).unwrap();
+    fs::write(
+        &grammar_path,
+        r##"{
+            "name": "test_lexer_only",
+            "word": "identifier",
+            "extras": [
+                {"type": "PATTERN", "value": "\\s"},
+                {"type": "SYMBOL", "name": "comment"}
+            ],
+            "rules": {
+                "program": {"type": "REPEAT", "content": {"type": "SYMBOL", "name": "statement"}},
+                "statement": {
+                    "type": "SEQ",
+                    "members": [
+                        {"type": "STRING", "value": "let"},
+                        {"type": "SYMBOL", "name": "identifier"},
+                        {"type": "STRING", "value": "="},
+                        {"type": "SYMBOL", "name": "_value"},
+                        {"type": "STRING", "value": ";"}
+                    ]
+                },
+                "_value": {
+                    "type": "CHOICE",
+                    "members": [
+                        {"type": "SYMBOL", "name": "identifier"},
+                        {"type": "SYMBOL", "name": "_number"}
+                    ]
+                },
+                "identifier": {"type": "PATTERN", "value": "[a-z]+"},
+                "_number": {"type": "PATTERN", "value": "\\d+"},
+                "comment": {"type": "PATTERN", "value": "#.*"}
+            }
+        }"##,
+    )
+    .unwrap();
+    tree_sitter_generate::generate_parser_in_directory(
+        dir.path(),
+        None::<&Path>,
+        Some(&grammar_path),
+        tree_sitter::LANGUAGE_VERSION,
+        None,
+        None,
+        None,
+        None,
+        true,
+        OptLevel::default(),
+        false,
+        true,
+        false,
+        false,
+        None,
+        &mut Vec::new(),
+    )
+    .unwrap();
+    let parser_code = fs::read_to_string(src_path.join("parser.c")).unwrap();
+
+    let mut parser = Parser::new();
+    parser
+        .set_language(&get_test_language("test_lexer_only", &parser_code, None))
+        .unwrap();
+
+    // The tokens don't need to form valid statements, and hidden tokens are
+    // included in the token stream.
+    let source = "let x = 12; # comment\nlet = = y";
+    let tree = parser.parse(source, None).unwrap();
+    let root = tree.root_node();
+    assert_eq!(root.kind(), "program");
+    assert!(!root.has_error());
+    let tokens = root
+        .children(&mut root.walk())
+        .map(|token| (token.kind(), token.utf8_text(source.as_bytes()).unwrap()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        tokens,
+        [
+            ("let", "let"),
+            ("identifier", "x"),
+            ("=", "="),
+            ("_number", "12"),
+            (";", ";"),
+            ("comment", "# comment"),
+            ("let", "let"),
+            ("=", "="),
+            ("=", "="),
+            ("identifier", "y"),
+        ]
+    );
+}
+
+#[test]
+fn test_parsing_with_a_custom_allocator() {
+    use std::{cell::Cell, env, os::raw::c_void, process::Command};
+
+    use crate::fuzz::allocations::{
+        ts_record_calloc, ts_record_free, ts_record_malloc, ts_record_realloc,
+    };
+
+    // The allocator is global, so replacing it while other tests are allocating would race
+    // with them, and could free their memory with a different allocator than the one that
+    // allocated it. Instead, this test runs itself in a separate process, on its own.
+    const SUBPROCESS_VAR: &str = "TREE_SITTER_TEST_CUSTOM_ALLOCATOR";
+    if env::var_os(SUBPROCESS_VAR).is_none() {
+        let (_, module) = module_path!().split_once("::").unwrap();
+        let output = Command::new(env::current_exe().unwrap())
+            .args([
+                "--exact",
+                &format!("{module}::test_parsing_with_a_custom_allocator"),
+                "--test-threads=1",
+            ])
+            .env(SUBPROCESS_VAR, "1")
+            .output()
+            .unwrap();
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert!(
+            output.status.success(),
+            "{stdout}{}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        assert!(stdout.contains("1 passed"), "{stdout}");
+        return;
+    }
+
+    thread_local! {
+        static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+        static FREES: Cell<usize> = const { Cell::new(0) };
+    }
+
+    unsafe extern "C" fn counting_malloc(size: usize) -> *mut c_void {
+        ALLOCATIONS.with(|count| count.set(count.get() + 1));
+        unsafe { ts_record_malloc(size) }
+    }
+
+    unsafe extern "C" fn counting_calloc(count: usize, size: usize) -> *mut c_void {
+        ALLOCATIONS.with(|count| count.set(count.get() + 1));
+        unsafe { ts_record_calloc(count, size) }
+    }
+
+    unsafe extern "C" fn counting_realloc(ptr: *mut c_void, size: usize) -> *mut c_void {
+        if ptr.is_null() {
+            ALLOCATIONS.with(|count| count.set(count.get() + 1));
+        }
+        unsafe { ts_record_realloc(ptr, size) }
+    }
+
+    unsafe extern "C" fn counting_free(ptr: *mut c_void) {
+        if !ptr.is_null() {
+            FREES.with(|count| count.set(count.get() + 1));
+        }
+        unsafe { ts_record_free(ptr) }
+    }
+
+    struct RestoreAllocator;
+
+    impl Drop for RestoreAllocator {
+        fn drop(&mut self) {
+            unsafe {
+                tree_sitter::set_allocator(Some(tree_sitter::Allocator {
+                    malloc: ts_record_malloc,
+                    calloc: ts_record_calloc,
+                    realloc: ts_record_realloc,
+                    free: ts_record_free,
+                }));
+            }
+        }
+    }
+
+    let language = get_language("javascript");
+    let _restore = RestoreAllocator;
+    unsafe {
+        tree_sitter::set_allocator(Some(tree_sitter::Allocator {
+            malloc: counting_malloc,
+            calloc: counting_calloc,
+            realloc: counting_realloc,
+            free: counting_free,
+        }));
+    }
+
+    {
+        let mut parser = Parser::new();
+        parser.set_language(&language).unwrap();
+        let tree = parser
+            .parse("const a = [1, 2, 3].map(x => x * 2);", None)
+            .unwrap();
+        assert!(!tree.root_node().has_error());
+    }
+
+    let allocations = ALLOCATIONS.with(Cell::get);
+    assert!(allocations > 0);
+    assert_eq!(FREES.with(Cell::get), allocations);
+}
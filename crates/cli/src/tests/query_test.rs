@@ -4,8 +4,9 @@ use indoc::indoc;
 use rand::{SeedableRng, prelude::StdRng};
 use streaming_iterator::StreamingIterator;
 use tree_sitter::{
-    CaptureQuantifier, InputEdit, Language, Node, Parser, Point, Query, QueryCursor,
-    QueryCursorOptions, QueryError, QueryErrorKind, QueryPredicate, QueryPredicateArg,
+    CachedQuery, CachedQueryCapture, CachedQueryMatch, CaptureQuantifier, InputEdit, Language,
+    LayeredQueryCursor, Node, Parser, PatternNode, PatternShape, Point, Query, QueryCursor,
+    QueryCursorOptions, QueryError, QueryErrorKind, QueryLayer, QueryPredicate, QueryPredicateArg,
     QueryProperty, Range,
 };
 use tree_sitter_generate::load_grammar_file;
@@ -462,6 +463,43 @@ fn test_query_errors_on_invalid_predicates() {
     });
 }
 
+#[test]
+fn test_query_errors_on_unknown_predicates_in_strict_mode() {
+    allocations::record(|| {
+        let language = get_language("javascript");
+        let source = "
+            ((identifier) @id (#eq? @id \"a\"))
+            ((identifier) @id
+              (#match? @id \"^b\")
+              (#frobnicate? @id))
+        ";
+
+        let query = Query::new(&language, source).unwrap();
+        assert_eq!(
+            query.general_predicates(1)[0].operator.as_ref(),
+            "frobnicate?"
+        );
+
+        let offset = source.find("#frobnicate?").unwrap();
+        assert_eq!(
+            Query::new_strict(&language, source).unwrap_err(),
+            QueryError {
+                kind: QueryErrorKind::Predicate,
+                row: 4,
+                column: 15,
+                offset,
+                message: "Unknown predicate #frobnicate?".to_string(),
+            }
+        );
+
+        Query::new_strict(
+            &language,
+            "((identifier) @id (#eq? @id \"a\") (#set! foo) (#is-not? local))",
+        )
+        .unwrap();
+    });
+}
+
 #[test]
 fn test_query_errors_on_impossible_patterns() {
     let js_lang = get_language("javascript");
@@ -2328,6 +2366,44 @@ fn test_query_matches_with_too_many_permutations_to_track() {
     });
 }
 
+#[test]
+fn test_query_matches_after_raising_an_exceeded_match_limit() {
+    allocations::record(|| {
+        let language = get_language("javascript");
+        let query = Query::new(&language, "(array (identifier) @pre (identifier) @post)").unwrap();
+
+        let mut source = "hello, ".repeat(50);
+        source.insert(0, '[');
+        source.push_str("];");
+
+        let mut parser = Parser::new();
+        parser.set_language(&language).unwrap();
+        let tree = parser.parse(&source, None).unwrap();
+
+        // Every ordered pair of the array's identifiers is a match.
+        let expected_match_count = 50 * 49 / 2;
+        let mut cursor = QueryCursor::new();
+        cursor.set_match_limit(8);
+        let match_count = cursor
+            .matches(&query, tree.root_node(), source.as_bytes())
+            .count();
+        assert!(cursor.did_exceed_match_limit());
+        assert!(match_count < expected_match_count);
+
+        // Grow the limit and retry until no matches are dropped.
+        while cursor.did_exceed_match_limit() {
+            cursor.set_match_limit(cursor.match_limit() * 2);
+            let match_count = cursor
+                .matches(&query, tree.root_node(), source.as_bytes())
+                .count();
+            if !cursor.did_exceed_match_limit() {
+                assert_eq!(match_count, expected_match_count);
+            }
+        }
+        assert!(cursor.match_limit() <= 128);
+    });
+}
+
 #[test]
 fn test_query_sibling_patterns_dont_match_children_of_an_error() {
     allocations::record(|| {
@@ -2502,6 +2578,48 @@ fn test_query_matches_with_anonymous_tokens() {
     });
 }
 
+#[test]
+fn test_query_matches_with_anonymous_tokens_used_in_multiple_rules() {
+    allocations::record(|| {
+        let language = get_language("javascript");
+
+        // `(` is used by `arguments`, `parenthesized_expression` and `formal_parameters`.
+        let query = Query::new(&language, r#""(" @paren"#).unwrap();
+        assert_query_matches(
+            &language,
+            &query,
+            "f((a), function (b) {});",
+            &[
+                (0, vec![("paren", "(")]),
+                (0, vec![("paren", "(")]),
+                (0, vec![("paren", "(")]),
+            ],
+        );
+
+        // `-` is used by both `binary_expression` and `unary_expression`.
+        let query = Query::new(
+            &language,
+            r#"
+            (binary_expression operator: "-" @minus)
+            (unary_expression "-" @negation)
+            "-" @any
+            "#,
+        )
+        .unwrap();
+        assert_query_matches(
+            &language,
+            &query,
+            "a - -b;",
+            &[
+                (0, vec![("minus", "-")]),
+                (2, vec![("any", "-")]),
+                (1, vec![("negation", "-")]),
+                (2, vec![("any", "-")]),
+            ],
+        );
+    });
+}
+
 #[test]
 fn test_query_matches_with_supertypes() {
     allocations::record(|| {
@@ -2601,6 +2719,164 @@ fn test_query_matches_within_byte_range() {
     });
 }
 
+#[test]
+fn test_cached_query_matches() {
+    allocations::record(|| {
+        let language = get_language("javascript");
+        let query = Query::new(&language, "(identifier) @element").unwrap();
+
+        let mut source = "[a, b, c, d, e, f, g]".to_string();
+
+        let mut parser = Parser::new();
+        parser.set_language(&language).unwrap();
+        let mut tree = parser.parse(&source, None).unwrap();
+
+        let capture = |start: usize| CachedQueryCapture {
+            index: 0,
+            range: Range {
+                start_byte: start,
+                end_byte: start + 1,
+                start_point: Point::new(0, start),
+                end_point: Point::new(0, start + 1),
+            },
+        };
+        let element = |start: usize| CachedQueryMatch {
+            pattern_index: 0,
+            captures: vec![capture(start)],
+        };
+
+        let mut cache = CachedQuery::new(query);
+        assert!(!cache.is_cached(&(0..5)));
+        let matches = cache
+            .matches(tree.root_node(), 0..5, source.as_bytes())
+            .to_vec();
+        assert_eq!(matches, [element(1), element(4)]);
+        cache.matches(tree.root_node(), 6..9, source.as_bytes());
+        cache.matches(tree.root_node(), 15..21, source.as_bytes());
+
+        // While the tree is unchanged, the cached matches are reused.
+        assert!(cache.is_cached(&(0..5)));
+        assert_eq!(
+            cache.matches(tree.root_node(), 0..5, source.as_bytes()),
+            matches
+        );
+
+        // Rename `c` to `cc`. Only the results that depend on it are discarded,
+        // and the results after it are shifted.
+        let edit = InputEdit {
+            start_byte: 7,
+            old_end_byte: 8,
+            new_end_byte: 9,
+            start_position: Point::new(0, 7),
+            old_end_position: Point::new(0, 8),
+            new_end_position: Point::new(0, 9),
+        };
+        source.replace_range(7..8, "cc");
+        tree.edit(&edit);
+        cache.edit(&edit);
+        let new_tree = parser.parse(&source, Some(&tree)).unwrap();
+        cache.invalidate_changed_ranges(&tree, &new_tree);
+
+        assert!(cache.is_cached(&(0..5)));
+        assert!(!cache.is_cached(&(6..9)));
+        assert!(!cache.is_cached(&(15..21)));
+        assert!(cache.is_cached(&(16..22)));
+        assert_eq!(
+            cache.matches(new_tree.root_node(), 16..22, source.as_bytes()),
+            [element(17), element(20)]
+        );
+
+        cache.clear();
+        assert!(!cache.is_cached(&(0..5)));
+    });
+}
+
+#[test]
+fn test_query_matches_with_a_custom_filter() {
+    allocations::record(|| {
+        let language = get_language("javascript");
+        let query = Query::new(
+            &language,
+            "(call_expression function: (identifier) @function arguments: (arguments) @arguments)",
+        )
+        .unwrap();
+
+        let source = "a(); b(1, 2); cc(3, 4, 5); d(6); ee(7, 8);";
+
+        let mut parser = Parser::new();
+        parser.set_language(&language).unwrap();
+        let tree = parser.parse(source, None).unwrap();
+
+        // Only keep calls with more than one argument, whose function name is a
+        // single character.
+        let mut cursor = QueryCursor::new();
+        let matches = cursor
+            .matches(&query, tree.root_node(), source.as_bytes())
+            .filter_matches(|m, source| {
+                let function = m.captures[0].node;
+                let arguments = m.captures[1].node;
+                arguments.named_child_count() > 1 && source[function.byte_range()].len() == 1
+            });
+        assert_eq!(
+            collect_matches(matches, &query, source),
+            &[(0, vec![("function", "b"), ("arguments", "(1, 2)")])]
+        );
+
+        let matches = cursor
+            .matches(&query, tree.root_node(), source.as_bytes())
+            .filter_matches(|_, _| false);
+        assert!(collect_matches(matches, &query, source).is_empty());
+    });
+}
+
+#[test]
+fn test_query_matches_within_pushed_byte_ranges() {
+    allocations::record(|| {
+        let language = get_language("javascript");
+        let query = Query::new(&language, "(identifier) @element").unwrap();
+
+        let source = "[a, b, c, d, e, f, g]";
+
+        let mut parser = Parser::new();
+        parser.set_language(&language).unwrap();
+        let tree = parser.parse(source, None).unwrap();
+
+        let mut cursor = QueryCursor::new();
+
+        let matches =
+            cursor
+                .push_byte_range(0..5)
+                .matches(&query, tree.root_node(), source.as_bytes());
+        assert_eq!(
+            collect_matches(matches, &query, source),
+            &[(0, vec![("element", "a")]), (0, vec![("element", "b")])]
+        );
+
+        let matches =
+            cursor
+                .push_byte_range(15..21)
+                .matches(&query, tree.root_node(), source.as_bytes());
+        assert_eq!(
+            collect_matches(matches, &query, source),
+            &[(0, vec![("element", "f")]), (0, vec![("element", "g")])]
+        );
+
+        // Popping a range restores the one that was pushed before it.
+        assert_eq!(cursor.pop_byte_range(), Some(15..21));
+        let matches = cursor.matches(&query, tree.root_node(), source.as_bytes());
+        assert_eq!(
+            collect_matches(matches, &query, source),
+            &[(0, vec![("element", "a")]), (0, vec![("element", "b")])]
+        );
+
+        // Popping the last range removes the restriction.
+        assert_eq!(cursor.pop_byte_range(), Some(0..5));
+        assert_eq!(cursor.pop_byte_range(), None);
+        let matches = cursor.matches(&query, tree.root_node(), source.as_bytes());
+        assert_eq!(collect_matches(matches, &query, source).len(), 7);
+    });
+}
+
 #[test]
 fn test_query_matches_within_point_range() {
     allocations::record(|| {
@@ -3773,6 +4049,231 @@ fn test_query_captures_with_text_conditions() {
     });
 }
 
+#[test]
+fn test_query_captures_with_case_insensitive_text_conditions() {
+    allocations::record(|| {
+        let language = get_language("javascript");
+        let query = Query::new(
+            &language,
+            r#"
+            ((identifier) @keyword
+             (#match-ci? @keyword "^select$"))
+
+            ((identifier) @variable
+             (#not-match-ci? @variable "^(select|from)$"))
+            "#,
+        )
+        .unwrap();
+
+        let source = "SELECT; select; Select; FROM; selected;";
+
+        let mut parser = Parser::new();
+        parser.set_language(&language).unwrap();
+        let tree = parser.parse(source, None).unwrap();
+        let mut cursor = QueryCursor::new();
+
+        let captures = cursor.captures(&query, tree.root_node(), source.as_bytes());
+        assert_eq!(
+            collect_captures(captures, &query, source),
+            &[
+                ("keyword", "SELECT"),
+                ("keyword", "select"),
+                ("keyword", "Select"),
+                ("variable", "selected"),
+            ],
+        );
+    });
+}
+
+#[test]
+fn test_query_captures_with_case_insensitive_eq_predicates() {
+    allocations::record(|| {
+        let language = get_language("javascript");
+        let query = Query::new(
+            &language,
+            r#"
+            ((identifier) @keyword
+             (#eq-ci? @keyword "SELECT"))
+
+            ((identifier) @variable
+             (#not-eq-ci? @variable "select"))
+
+            ((pair
+               key: (property_identifier) @key
+               value: (identifier) @value)
+             (#eq-ci? @key @value))
+
+            ((array (identifier)+ @from-list)
+             (#any-eq-ci? @from-list "from"))
+            "#,
+        )
+        .unwrap();
+
+        let source = "SELECT; select; Select; selected; ({Ä: ä}); [a, FROM];";
+
+        let mut parser = Parser::new();
+        parser.set_language(&language).unwrap();
+        let tree = parser.parse(source, None).unwrap();
+        let mut cursor = QueryCursor::new();
+
+        let captures = cursor.captures(&query, tree.root_node(), source.as_bytes());
+        assert_eq!(
+            collect_captures(captures, &query, source),
+            &[
+                ("keyword", "SELECT"),
+                ("keyword", "select"),
+                ("keyword", "Select"),
+                ("variable", "selected"),
+                ("key", "Ä"),
+                ("variable", "ä"),
+                ("value", "ä"),
+                ("variable", "a"),
+                ("from-list", "a"),
+                ("variable", "FROM"),
+                ("from-list", "FROM"),
+            ],
+        );
+
+        assert_eq!(
+            Query::new(&language, r#"((identifier) @id (#eq-ci? @id "a" "b"))"#).unwrap_err(),
+            QueryError {
+                kind: QueryErrorKind::Predicate,
+                row: 0,
+                column: 0,
+                offset: 0,
+                message: "Wrong number of arguments to #eq? predicate. Expected 2, got 3."
+                    .to_string()
+            }
+        );
+    });
+}
+
+#[test]
+fn test_query_captures_with_nth_child_predicates() {
+    allocations::record(|| {
+        let language = get_language("javascript");
+        let query = Query::new(
+            &language,
+            "
+            ((arguments (_) @first) (#nth-child? @first 0))
+            ((arguments (_) @second) (#nth-child? @second 1))
+            ((arguments (_) @last) (#nth-child? @last -1))
+            ",
+        )
+        .unwrap();
+
+        let source = "f(a, b, c); g(d);";
+
+        let mut parser = Parser::new();
+        parser.set_language(&language).unwrap();
+        let tree = parser.parse(source, None).unwrap();
+        let mut cursor = QueryCursor::new();
+
+        let captures = cursor.captures(&query, tree.root_node(), source.as_bytes());
+        assert_eq!(
+            collect_captures(captures, &query, source),
+            &[
+                ("first", "a"),
+                ("second", "b"),
+                ("last", "c"),
+                ("first", "d"),
+                ("last", "d"),
+            ],
+        );
+
+        for (predicate, message) in [
+            (
+                "(#nth-child? @a)",
+                "Wrong number of arguments to #nth-child? predicate. Expected 2, got 1.",
+            ),
+            (
+                "(#nth-child? @a first)",
+                "Second argument to #nth-child? predicate must be an integer. Got \"first\".",
+            ),
+        ] {
+            let error =
+                Query::new(&language, &format!("((identifier) @a {predicate})")).unwrap_err();
+            assert_eq!(error.kind, QueryErrorKind::Predicate);
+            assert_eq!(error.message, message);
+        }
+    });
+}
+
+#[test]
+fn test_query_captures_across_layers() {
+    allocations::record(|| {
+        let language = get_language("javascript");
+        let host_query = Query::new(
+            &language,
+            "
+            (identifier) @variable
+            (string) @string
+            (string_fragment) @injection.content
+            ",
+        )
+        .unwrap();
+        let injection_query = Query::new(
+            &language,
+            "
+            (program) @injection.root
+            (identifier) @variable
+            (number) @number
+            ",
+        )
+        .unwrap();
+
+        let source = "let a = \"b + 1\"; c;";
+        let injection_offset = source.find('b').unwrap();
+        let injection_source = &source[injection_offset..source.find("\";").unwrap()];
+        assert_eq!(injection_source, "b + 1");
+
+        let mut parser = Parser::new();
+        parser.set_language(&language).unwrap();
+        let host_tree = parser.parse(source, None).unwrap();
+        let injection_tree = parser.parse(injection_source, None).unwrap();
+
+        let layers = [
+            QueryLayer {
+                query: &host_query,
+                node: host_tree.root_node(),
+                byte_offset: 0,
+            },
+            QueryLayer {
+                query: &injection_query,
+                node: injection_tree.root_node(),
+                byte_offset: injection_offset,
+            },
+        ];
+        let mut cursor = LayeredQueryCursor::new();
+        let captures = cursor.captures(&layers, source.as_bytes());
+
+        assert_eq!(
+            captures
+                .iter()
+                .map(|capture| {
+                    let query = layers[capture.layer_index].query;
+                    (
+                        capture.layer_index,
+                        query.capture_names()[capture.capture.index as usize],
+                        &source[capture.byte_range.clone()],
+                    )
+                })
+                .collect::<Vec<_>>(),
+            &[
+                (0, "variable", "a"),
+                (0, "string", "\"b + 1\""),
+                // When captures from different layers cover the same range, the
+                // earlier layer's capture comes first.
+                (0, "injection.content", "b + 1"),
+                (1, "injection.root", "b + 1"),
+                (1, "variable", "b"),
+                (1, "number", "1"),
+                (0, "variable", "c"),
+            ],
+        );
+    });
+}
+
 #[test]
 fn test_query_captures_with_predicates() {
     allocations::record(|| {
@@ -5521,6 +6022,50 @@ fn test_capture_quantifiers() {
     });
 }
 
+#[test]
+fn test_query_capture_quantifier() {
+    let language = get_language("javascript");
+    let query = Query::new(
+        &language,
+        "
+        (array (identifier)? @a (number)+ @b)
+        (array (number) @b)
+        (array [(identifier) @a (string)]* @c)
+        ",
+    )
+    .unwrap();
+    let a = query.capture_index_for_name("a").unwrap();
+    let b = query.capture_index_for_name("b").unwrap();
+    let c = query.capture_index_for_name("c").unwrap();
+
+    assert_eq!(query.capture_quantifier(0, a), CaptureQuantifier::ZeroOrOne);
+    assert_eq!(query.capture_quantifier(0, b), CaptureQuantifier::OneOrMore);
+    assert_eq!(query.capture_quantifier(0, c), CaptureQuantifier::Zero);
+
+    assert_eq!(query.capture_quantifier(1, a), CaptureQuantifier::Zero);
+    assert_eq!(query.capture_quantifier(1, b), CaptureQuantifier::One);
+    assert_eq!(query.capture_quantifier(1, c), CaptureQuantifier::Zero);
+
+    assert_eq!(
+        query.capture_quantifier(2, a),
+        CaptureQuantifier::ZeroOrMore
+    );
+    assert_eq!(query.capture_quantifier(2, b), CaptureQuantifier::Zero);
+    assert_eq!(
+        query.capture_quantifier(2, c),
+        CaptureQuantifier::ZeroOrMore
+    );
+
+    for pattern_index in 0..query.pattern_count() {
+        for capture_index in 0..query.capture_names().len() as u32 {
+            assert_eq!(
+                query.capture_quantifier(pattern_index, capture_index),
+                query.capture_quantifiers(pattern_index)[capture_index as usize]
+            );
+        }
+    }
+}
+
 #[test]
 fn test_query_quantified_captures() {
     struct Row {
@@ -5866,6 +6411,218 @@ fn test_query_max_start_depth_more() {
     });
 }
 
+#[test]
+fn test_query_max_start_depth_with_deep_captures() {
+    let source = indoc! {"
+        if (a && b) {
+            if (c && d) {
+                if (e && f) { }
+            }
+        }
+    "};
+
+    allocations::record(|| {
+        let language = get_language("c");
+        let mut parser = Parser::new();
+        parser.set_language(&language).unwrap();
+        let tree = parser.parse(source, None).unwrap();
+        let query = Query::new(
+            &language,
+            "
+            (if_statement
+                condition: (parenthesized_expression
+                    (binary_expression
+                        left: (identifier) @left
+                        right: (identifier) @right)))
+            ",
+        )
+        .unwrap();
+        let mut cursor = QueryCursor::new();
+
+        // Only the start of a match is limited, so the outermost `if` statement still matches
+        // fully, even though its captures are deeper than the maximum start depth.
+        cursor.set_max_start_depth(Some(1));
+        let matches = cursor.matches(&query, tree.root_node(), source.as_bytes());
+        assert_eq!(
+            collect_matches(matches, &query, source),
+            &[(0, vec![("left", "a"), ("right", "b")])],
+        );
+
+        cursor.set_max_start_depth(None);
+        let matches = cursor.matches(&query, tree.root_node(), source.as_bytes());
+        assert_eq!(
+            collect_matches(matches, &query, source),
+            &[
+                (0, vec![("left", "a"), ("right", "b")]),
+                (0, vec![("left", "c"), ("right", "d")]),
+                (0, vec![("left", "e"), ("right", "f")]),
+            ],
+        );
+    });
+}
+
+#[test]
+fn test_query_cursor_skipping_anonymous_nodes() {
+    allocations::record(|| {
+        let language = get_language("javascript");
+        let mut parser = Parser::new();
+        parser.set_language(&language).unwrap();
+
+        let source = indoc! {r#"
+            const a = [1, 2, 3].map((x) => x * 2 + (y - 1));
+            if (a && !b) { f(a, b, c); } else { g({ k: "v", [m]: n }); }
+            class C extends D { static #p = 1; get q() { return this.#p; } }
+        "#};
+        let tree = parser.parse(source, None).unwrap();
+
+        for (pattern, matches_anonymous_nodes) in [
+            ("(identifier) @id", false),
+            ("(_) @node", false),
+            (
+                "(binary_expression left: (_) @left right: (_) @right)",
+                false,
+            ),
+            (
+                "(call_expression function: (identifier) @f (arguments (_) @arg))",
+                false,
+            ),
+            ("(arguments . (_) @first)", false),
+            ("(arguments (_) @last .)", false),
+            ("(expression) @expression", false),
+            ("((identifier) @id (#eq? @id \"a\"))", false),
+            ("[(number) (string)] @literal", false),
+            ("(binary_expression \"*\" @op)", true),
+            ("(binary_expression operator: _ @op)", true),
+            ("(array (number) . (number))", false),
+            ("(array (number)+ @numbers)", true),
+            ("(array (number)? . (number) @n)", false),
+            ("(array . (number)? @n (number))", true),
+            ("(MISSING) @missing", true),
+        ] {
+            let query = Query::new(&language, pattern).unwrap();
+            assert_eq!(
+                query.matches_anonymous_nodes(),
+                matches_anonymous_nodes,
+                "pattern: {pattern}"
+            );
+
+            let mut cursor = QueryCursor::new();
+            let mut skipping_cursor = QueryCursor::new();
+            skipping_cursor.set_skip_anonymous_nodes(true);
+
+            let matches = cursor.matches(&query, tree.root_node(), source.as_bytes());
+            let skipped_matches =
+                skipping_cursor.matches(&query, tree.root_node(), source.as_bytes());
+            assert_eq!(
+                collect_matches(skipped_matches, &query, source),
+                collect_matches(matches, &query, source),
+                "pattern: {pattern}"
+            );
+
+            let captures = cursor.captures(&query, tree.root_node(), source.as_bytes());
+            let skipped_captures =
+                skipping_cursor.captures(&query, tree.root_node(), source.as_bytes());
+            assert_eq!(
+                collect_captures(skipped_captures, &query, source),
+                collect_captures(captures, &query, source),
+                "pattern: {pattern}"
+            );
+        }
+    });
+}
+
+#[test]
+fn test_query_describe_pattern() {
+    allocations::record(|| {
+        let language = get_language("javascript");
+        let query = Query::new(
+            &language,
+            r#"
+            (call_expression
+              function: (member_expression
+                object: (identifier) @object
+                property: (_) @method)
+              arguments: (arguments (string)? @arg)) @call
+
+            [(number) (string) @string] @literal
+
+            ((identifier) @id (#eq? @id "a"))
+            "#,
+        )
+        .unwrap();
+
+        let node = |kind, field, captures: &[&'static str], is_optional, children| PatternNode {
+            kind,
+            is_named: true,
+            field,
+            captures: captures.to_vec(),
+            is_optional,
+            children,
+        };
+
+        assert_eq!(
+            query.describe_pattern(0),
+            PatternShape {
+                roots: vec![node(
+                    Some("call_expression"),
+                    None,
+                    &["call"],
+                    false,
+                    vec![
+                        node(
+                            Some("member_expression"),
+                            Some("function"),
+                            &[],
+                            false,
+                            vec![
+                                node(
+                                    Some("identifier"),
+                                    Some("object"),
+                                    &["object"],
+                                    false,
+                                    vec![]
+                                ),
+                                node(None, Some("property"), &["method"], false, vec![]),
+                            ],
+                        ),
+                        node(
+                            Some("arguments"),
+                            Some("arguments"),
+                            &[],
+                            false,
+                            vec![node(Some("string"), None, &["arg"], true, vec![])],
+                        ),
+                    ],
+                )],
+            }
+        );
+
+        assert_eq!(
+            query.describe_pattern(1),
+            PatternShape {
+                roots: vec![
+                    node(Some("number"), None, &["literal"], true, vec![]),
+                    node(Some("string"), None, &["string", "literal"], true, vec![]),
+                ],
+            }
+        );
+
+        assert_eq!(
+            query.describe_pattern(2),
+            PatternShape {
+                roots: vec![node(Some("identifier"), None, &["id"], false, vec![])],
+            }
+        );
+    });
+}
+
+#[test]
+#[should_panic(expected = "Pattern index is 1 but the pattern count is 1")]
+fn test_query_describe_pattern_with_invalid_index() {
+    let query = Query::new(&get_language("javascript"), "(identifier) @id").unwrap();
+    let _ = query.describe_pattern(1);
+}
+
 #[test]
 fn test_grammar_with_aliased_literal_query() {
     // module.exports = grammar({
@@ -6458,3 +7215,48 @@ export default grammar({
 
     assert_query_matches(&language, &query, source, &[(0, vec![("tuple", "()")])]);
 }
+
+#[test]
+fn test_query_match_nodes_for_capture_index() {
+    allocations::record(|| {
+        let language = get_language("javascript");
+        let mut parser = Parser::new();
+        parser.set_language(&language).unwrap();
+
+        let source = indoc! {"
+            // one
+            // two
+            // three
+            function a() {}
+        "};
+        let tree = parser.parse(source, None).unwrap();
+        let query = Query::new(
+            &language,
+            "
+            (
+                (comment)+ @doc
+                .
+                (function_declaration
+                    name: (identifier) @name)
+            )
+            (class_declaration) @class
+            ",
+        )
+        .unwrap();
+        let capture_index = |name| query.capture_index_for_name(name).unwrap();
+
+        let mut cursor = QueryCursor::new();
+        let mut matches = cursor.matches(&query, tree.root_node(), source.as_bytes());
+        let m = matches.next().unwrap();
+        let texts = |name| {
+            m.nodes_for_capture_index(capture_index(name))
+                .map(|node| node.utf8_text(source.as_bytes()).unwrap())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(texts("doc"), ["// one", "// two", "// three"]);
+        assert_eq!(texts("name"), ["a"]);
+        // The capture belongs to another pattern, so it isn't in this match.
+        assert!(texts("class").is_empty());
+        assert!(matches.next().is_none());
+    });
+}
@@ -1,4 +1,4 @@
-use std::{iter, sync::Arc};
+use std::{iter, ops::Range, sync::Arc};
 
 use streaming_iterator::StreamingIterator;
 use tree_sitter::{Language, Node, Parser, Point, Query, QueryCursor, TextProvider, Tree};
@@ -54,6 +54,25 @@ fn check_parsing_callback<T, F, I: AsRef<[u8]>>(
     tree_query(&tree, text_provider, &language);
 }
 
+fn capture_sequence<'a, I: AsRef<[u8]>>(
+    query: &'a Query,
+    tree: &Tree,
+    text_provider: impl TextProvider<I>,
+) -> Vec<(usize, &'a str, Range<usize>)> {
+    let mut cursor = QueryCursor::new();
+    let mut captures = cursor.captures(query, tree.root_node(), text_provider);
+    let mut result = Vec::new();
+    while let Some((m, i)) = captures.next() {
+        let capture = m.captures[*i];
+        result.push((
+            m.pattern_index,
+            query.capture_names()[capture.index as usize],
+            capture.node.byte_range(),
+        ));
+    }
+    result
+}
+
 #[test]
 fn test_text_provider_for_str_slice() {
     let text: &str = "// comment";
@@ -192,3 +211,75 @@ fn test_text_provider_callback_with_owned_arc_of_bytes_slice() {
         },
     );
 }
+
+#[test]
+fn test_text_provider_callback_with_chunked_text_preserves_capture_order() {
+    // The first declaration is missing its `;`, which yields a zero-width capture, and the
+    // patterns overlap so that several captures start at the same byte.
+    let text = "int a = 1\nint abc = abc;\nint xyz = abc;\n";
+    let (tree, language) = parse_text(text);
+    assert!(tree.root_node().to_sexp().contains("(MISSING \";\")"));
+
+    let query = Query::new(
+        &language,
+        r#"
+        (declaration) @declaration
+        (init_declarator declarator: (identifier) @name value: (_) @value)
+        ((identifier) @same (#eq? @same "abc"))
+        ((init_declarator declarator: (_) @lhs value: (_) @rhs) (#eq? @lhs @rhs))
+        ";" @semicolon
+        "#,
+    )
+    .unwrap();
+
+    // Split the text into small fixed chunks, like a rope would, so that the nodes' text
+    // straddles chunk boundaries.
+    let rope = text.as_bytes().chunks(4).collect::<Vec<_>>();
+    let chunked = |node: Node<'_>| {
+        let range = node.byte_range();
+        let mut chunk_start = 0;
+        rope.iter().filter_map(move |chunk| {
+            let chunk_end = chunk_start + chunk.len();
+            let start = range.start.max(chunk_start);
+            let end = range.end.min(chunk_end);
+            let offset = chunk_start;
+            chunk_start = chunk_end;
+            (start < end).then(|| &chunk[start - offset..end - offset])
+        })
+    };
+
+    let expected = capture_sequence(&query, &tree, text.as_bytes());
+    let actual = capture_sequence(&query, &tree, chunked);
+
+    assert_eq!(actual, expected);
+    assert!(actual.windows(2).all(|w| w[0].2.start <= w[1].2.start));
+    assert!(
+        actual
+            .iter()
+            .any(|(_, name, range)| *name == "semicolon" && range.is_empty())
+    );
+    assert_eq!(
+        actual
+            .iter()
+            .filter(|(_, name, _)| *name == "same")
+            .map(|(_, _, range)| &text[range.clone()])
+            .collect::<Vec<_>>(),
+        ["abc", "abc", "abc"],
+    );
+    assert_eq!(
+        actual
+            .iter()
+            .filter(|(_, name, _)| *name == "lhs")
+            .map(|(_, _, range)| &text[range.clone()])
+            .collect::<Vec<_>>(),
+        ["abc"],
+    );
+
+    // Captures are produced lazily, so stopping early yields a prefix of the full sequence.
+    let mut cursor = QueryCursor::new();
+    let mut captures = cursor.captures(&query, tree.root_node(), chunked);
+    for (_, _, expected_range) in expected.iter().take(3) {
+        let (m, i) = captures.next().unwrap();
+        assert_eq!(m.captures[*i].node.byte_range(), *expected_range);
+    }
+}
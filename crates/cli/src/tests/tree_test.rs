@@ -9,6 +9,40 @@ use crate::{
     tests::{helpers::fixtures::get_test_fixture_language, invert_edit},
 };
 
+#[test]
+fn test_tree_source() {
+    let mut parser = Parser::new();
+    parser.set_language(&get_language("javascript")).unwrap();
+
+    let source = "let a = b;".to_string();
+    assert_eq!(parser.parse(&source, None).unwrap().source(), None);
+
+    let tree = parser.parse_owned(source.clone(), None).unwrap();
+    assert_eq!(tree.source(), Some(source.as_bytes()));
+    assert_eq!(
+        tree.root_node().to_sexp(),
+        parser.parse(&source, None).unwrap().root_node().to_sexp()
+    );
+
+    let mut tree_copy = tree.clone();
+    assert_eq!(tree_copy.source(), Some(source.as_bytes()));
+
+    // Once the tree is edited, it no longer corresponds to its source.
+    tree_copy.edit(&InputEdit {
+        start_byte: 4,
+        old_end_byte: 5,
+        new_end_byte: 5,
+        start_position: Point::new(0, 4),
+        old_end_position: Point::new(0, 5),
+        new_end_position: Point::new(0, 5),
+    });
+    assert_eq!(tree_copy.source(), None);
+    assert_eq!(tree.source(), Some(source.as_bytes()));
+
+    let new_tree = parser.parse_owned("let c = b;", Some(&tree_copy)).unwrap();
+    assert_eq!(new_tree.source(), Some(b"let c = b;".as_slice()));
+}
+
 #[test]
 fn test_tree_edit() {
     let mut parser = Parser::new();
@@ -381,6 +415,50 @@ fn test_tree_cursor() {
     assert_eq!(copy.node().kind(), "struct_item");
 }
 
+#[test]
+fn test_tree_cursor_for_node_stays_within_node() {
+    let mut parser = Parser::new();
+    parser.set_language(&get_language("rust")).unwrap();
+
+    let tree = parser
+        .parse(
+            "struct A { a: A } struct B { b: B } struct C { c: C }",
+            None,
+        )
+        .unwrap();
+    let node = tree.root_node().child(1).unwrap();
+    let body = node.child_by_field_name("body").unwrap();
+
+    let mut cursor = body.walk();
+    assert_eq!(cursor.node(), body);
+    assert_eq!(cursor.depth(), 0);
+    assert!(!cursor.goto_parent());
+    assert!(!cursor.goto_next_sibling());
+    assert!(!cursor.goto_previous_sibling());
+    assert_eq!(cursor.node(), body);
+
+    assert!(cursor.goto_first_child());
+    assert_eq!(cursor.node().kind(), "{");
+    assert!(cursor.goto_next_sibling());
+    assert_eq!(cursor.node().kind(), "field_declaration");
+    assert!(cursor.goto_first_child());
+    assert_eq!(cursor.depth(), 2);
+
+    // Ascending stops at the node that the cursor was created from.
+    assert!(cursor.goto_parent());
+    assert!(cursor.goto_parent());
+    assert_eq!(cursor.node(), body);
+    assert!(!cursor.goto_parent());
+    assert_eq!(cursor.node(), body);
+
+    // Resetting the cursor to another node makes that node the new root.
+    cursor.reset(node);
+    assert!(!cursor.goto_parent());
+    assert!(!cursor.goto_next_sibling());
+    assert_eq!(cursor.node().kind(), "struct_item");
+    assert_eq!(cursor.node().start_byte(), "struct A { a: A } ".len());
+}
+
 #[test]
 fn test_tree_cursor_previous_sibling_with_aliases() {
     let mut parser = Parser::new();
@@ -491,6 +569,61 @@ fn test_tree_cursor_fields() {
     assert_eq!(cursor.field_name(), Some("parameters"));
 }
 
+#[test]
+fn test_tree_cursor_child_for_point_matches_byte() {
+    let mut parser = Parser::new();
+    parser.set_language(&get_language("javascript")).unwrap();
+    let source = "f(\"é\", ü);\nlet ø = [1];\n";
+    let tree = parser.parse(source, None).unwrap();
+
+    let mut points = Vec::new();
+    let mut point = Point::new(0, 0);
+    for byte in source.bytes() {
+        points.push(point);
+        if byte == b'\n' {
+            point = Point::new(point.row + 1, 0);
+        } else {
+            point.column += 1;
+        }
+    }
+    points.push(point);
+
+    // Descend to each node starting at the root, and compare both lookups
+    // from each of the node's children at every offset.
+    let mut nodes = vec![tree.root_node()];
+    while let Some(node) = nodes.pop() {
+        for (byte, point) in points.iter().enumerate() {
+            let mut by_byte = node.walk();
+            let mut by_point = node.walk();
+            let byte_result = by_byte.goto_first_child_for_byte(byte);
+            let point_result = by_point.goto_first_child_for_point(*point);
+            assert_eq!(byte_result, point_result, "{node:?} at {point:?}");
+            assert_eq!(by_byte.node(), by_point.node(), "{node:?} at {point:?}");
+        }
+        let mut cursor = node.walk();
+        nodes.extend(node.children(&mut cursor));
+    }
+
+    let call = tree.root_node().child(0).unwrap().child(0).unwrap();
+    let arguments = call.child_by_field_name("arguments").unwrap();
+    let mut c = arguments.walk();
+
+    // A point within a child: the `ü` identifier takes up two bytes.
+    assert_eq!(c.goto_first_child_for_point(Point::new(0, 8)), Some(3));
+    assert_eq!(c.node().kind(), "identifier");
+    assert_eq!(c.node().end_position(), Point::new(0, 10));
+    assert!(c.goto_parent());
+
+    // A point on the end boundary of a child selects the following child.
+    assert_eq!(c.goto_first_child_for_point(Point::new(0, 10)), Some(4));
+    assert_eq!(c.node().kind(), ")");
+    assert!(c.goto_parent());
+
+    // A point past the last child leaves the cursor where it was.
+    assert_eq!(c.goto_first_child_for_point(Point::new(0, 11)), None);
+    assert_eq!(c.node(), arguments);
+}
+
 #[test]
 fn test_tree_cursor_child_for_point() {
     let mut parser = Parser::new();
@@ -611,6 +744,162 @@ fn test_tree_node_equality() {
     assert_ne!(node1.child(0).unwrap(), node2);
 }
 
+#[test]
+fn test_tree_to_edges() {
+    let mut parser = Parser::new();
+    parser.set_language(&get_language("javascript")).unwrap();
+    let tree = parser.parse("let a = f(1);", None).unwrap();
+    let root_node = tree.root_node();
+
+    let edges = tree.to_edges();
+    assert_eq!(edges.len(), root_node.descendant_count() - 1);
+
+    let first = edges[0];
+    assert_eq!(first.parent_id, root_node.id());
+    assert_eq!(first.parent_kind, "program");
+    assert_eq!(first.parent_range, root_node.range());
+    assert_eq!(first.child_kind, "lexical_declaration");
+    assert_eq!(first.child_range.end_byte, 13);
+    assert_eq!(first.field_name, None);
+
+    // Every child is reached by exactly one edge, and every parent is a node in the tree.
+    let mut child_ids = edges.iter().map(|edge| edge.child_id).collect::<Vec<_>>();
+    child_ids.sort_unstable();
+    child_ids.dedup();
+    assert_eq!(child_ids.len(), edges.len());
+    assert!(!child_ids.contains(&root_node.id()));
+    assert!(
+        edges.iter().all(|edge| edge.parent_id == root_node.id()
+            || child_ids.binary_search(&edge.parent_id).is_ok())
+    );
+
+    assert_eq!(
+        edges
+            .iter()
+            .filter_map(|edge| Some((edge.parent_kind, edge.field_name?, edge.child_kind)))
+            .collect::<Vec<_>>(),
+        [
+            ("lexical_declaration", "kind", "let"),
+            ("variable_declarator", "name", "identifier"),
+            ("variable_declarator", "value", "call_expression"),
+            ("call_expression", "function", "identifier"),
+            ("call_expression", "arguments", "arguments"),
+        ]
+    );
+}
+
+#[test]
+fn test_tree_token_before_byte() {
+    let mut parser = Parser::new();
+    parser.set_language(&get_language("javascript")).unwrap();
+    let source = "let a = f(1);\nfoo";
+    let tree = parser.parse(source, None).unwrap();
+    let token_before = |offset| {
+        tree.token_before_byte(offset)
+            .map(|node| (node.kind(), node.byte_range()))
+    };
+
+    // At the boundary between two tokens, the token that ends there is returned.
+    assert_eq!(token_before(9), Some(("identifier", 8..9)));
+    assert_eq!(token_before(10), Some(("(", 9..10)));
+
+    // Inside a token or in the whitespace after one, the preceding token is returned.
+    assert_eq!(token_before(4), Some(("let", 0..3)));
+    assert_eq!(token_before(8), Some(("=", 6..7)));
+    assert_eq!(token_before(16), Some((";", 12..13)));
+
+    // The preceding token can be in a different subtree.
+    assert_eq!(token_before(source.len()), Some(("identifier", 14..17)));
+    assert_eq!(token_before(14), Some((";", 12..13)));
+
+    assert_eq!(token_before(0), None);
+    assert_eq!(token_before(2), None);
+
+    // Missing tokens are skipped.
+    let tree = parser.parse("f(1;", None).unwrap();
+    assert_eq!(
+        tree.root_node().to_sexp(),
+        "(program (expression_statement (call_expression function: (identifier) arguments: (arguments (number) (MISSING \")\")))))"
+    );
+    let token = tree.token_before_byte(3).unwrap();
+    assert_eq!((token.kind(), token.byte_range()), ("number", 2..3));
+}
+
+#[test]
+fn test_tree_to_dot_string() {
+    let mut parser = Parser::new();
+    parser.set_language(&get_language("javascript")).unwrap();
+    let tree = parser.parse("a = f(\"\\n\";", None).unwrap();
+    let dot = tree.to_dot_string();
+
+    let mut lines = dot.lines();
+    assert_eq!(lines.next(), Some("digraph tree {"));
+    assert_eq!(lines.next(), Some("edge [arrowhead=none]"));
+    assert_eq!(
+        lines.next(),
+        Some("node_0 [label=\"program\", tooltip=\"range: 0 - 11\"]")
+    );
+    assert_eq!(dot.lines().last(), Some("}"));
+
+    assert!(dot.contains("node_0 -> node_1\n"));
+    assert!(dot.contains("node_2 -> node_3 [label=\"left\"]\n"));
+    assert!(dot.contains("node_5 -> node_7 [label=\"arguments\"]\n"));
+    assert!(dot.contains("[label=\"\\\"=\\\"\", shape=plaintext, tooltip=\"range: 2 - 3\"]"));
+    assert!(dot.contains("[label=\"escape_sequence\", shape=plaintext, tooltip=\"range: 7 - 9\"]"));
+    assert!(dot.contains(
+        "[label=\"MISSING \\\")\\\"\", shape=plaintext, color=red, fontcolor=red, style=dashed, tooltip=\"range: 10 - 10\"]"
+    ));
+
+    let mut buffer = Vec::new();
+    tree.write_dot_graph(&mut buffer).unwrap();
+    assert_eq!(String::from_utf8(buffer).unwrap(), dot);
+
+    let tree = parser.parse("a = f(\"\\n\"", None).unwrap();
+    assert!(tree.to_dot_string().contains(
+        "node_1 [label=\"ERROR\", color=red, fontcolor=red, tooltip=\"range: 0 - 10\"]\nnode_0 -> node_1\n"
+    ));
+}
+
+#[test]
+fn test_tree_node_stable_ids() {
+    let mut source_code = b"let a = foo(1);\nlet b = bar(2);\n".to_vec();
+
+    let mut parser = Parser::new();
+    parser.set_language(&get_language("javascript")).unwrap();
+    let tree = parser.parse(&source_code, None).unwrap();
+
+    // Edit the second statement.
+    let mut edited_tree = tree.clone();
+    let edit = Edit {
+        position: index_of(&source_code, "2"),
+        deleted_length: 1,
+        inserted_text: b"3 + 4".to_vec(),
+    };
+    perform_edit(&mut edited_tree, &mut source_code, &edit).unwrap();
+    let new_tree = parser.parse(&source_code, Some(&edited_tree)).unwrap();
+
+    let old_root = tree.root_node();
+    let new_root = new_tree.root_node();
+    assert_eq!(new_root.child_count(), 2);
+
+    // The unedited statement is reused, so it keeps its id.
+    let old_first = old_root.child(0).unwrap();
+    let new_first = new_root.child(0).unwrap();
+    assert!(old_first.stable_id().is_some());
+    assert_eq!(old_first.stable_id(), new_first.stable_id());
+
+    // The edited statement is a new node.
+    let old_second = old_root.child(1).unwrap();
+    let new_second = new_root.child(1).unwrap();
+    assert!(new_second.stable_id().is_some());
+    assert_ne!(old_second.stable_id(), new_second.stable_id());
+
+    // Identifiers are small enough to be stored inline, so they have no stable id.
+    let identifier = new_first.named_descendant_for_byte_range(4, 5).unwrap();
+    assert_eq!(identifier.kind(), "identifier");
+    assert_eq!(identifier.stable_id(), None);
+}
+
 #[test]
 fn test_get_changed_ranges() {
     let source_code = b"{a: null};\n".to_vec();
@@ -718,6 +1007,126 @@ fn test_get_changed_ranges() {
     }
 }
 
+#[test]
+fn test_get_changed_ranges_at_end_of_file() {
+    let mut parser = Parser::new();
+    parser.set_language(&get_language("javascript")).unwrap();
+
+    // Appending a statement
+    {
+        let mut source_code = b"a;".to_vec();
+        let mut tree = parser.parse(&source_code, None).unwrap();
+        let edit = Edit {
+            position: source_code.len(),
+            deleted_length: 0,
+            inserted_text: b"\nb;".to_vec(),
+        };
+        let ranges = get_changed_ranges(&mut parser, &mut tree, &mut source_code, &edit);
+        assert_eq!(byte_ranges(&ranges), [(2, source_code.len())]);
+        assert_eq!(ranges[0].end_point, Point::new(1, 2));
+    }
+
+    // Appending to the last token, which changes the structure of its statement
+    {
+        let mut source_code = b"a;\nb".to_vec();
+        let mut tree = parser.parse(&source_code, None).unwrap();
+        let edit = Edit {
+            position: source_code.len(),
+            deleted_length: 0,
+            inserted_text: b"()".to_vec(),
+        };
+        let inverse_edit = invert_edit(&source_code, &edit);
+        let ranges = get_changed_ranges(&mut parser, &mut tree, &mut source_code, &edit);
+        assert_eq!(byte_ranges(&ranges), [(3, source_code.len())]);
+        assert_eq!(ranges[0].end_point, Point::new(1, 3));
+
+        // Deleting the trailing bytes again
+        let ranges = get_changed_ranges(&mut parser, &mut tree, &mut source_code, &inverse_edit);
+        assert_eq!(byte_ranges(&ranges), [(3, source_code.len())]);
+        assert_eq!(ranges[0].end_point, Point::new(1, 1));
+    }
+
+    // Deleting the closing bracket at the end of the file
+    {
+        let mut source_code = b"x = [1, 2]".to_vec();
+        let mut tree = parser.parse(&source_code, None).unwrap();
+        let edit = Edit {
+            position: source_code.len() - 1,
+            deleted_length: 1,
+            inserted_text: Vec::new(),
+        };
+        let ranges = get_changed_ranges(&mut parser, &mut tree, &mut source_code, &edit);
+        assert_eq!(ranges, vec![range_of(&source_code, "x = [1, 2")]);
+    }
+
+    // Deleting an entire trailing statement leaves no node in the new tree whose structure
+    // changed, so there are no ranges to report.
+    {
+        let mut source_code = b"a;\nb;".to_vec();
+        let mut tree = parser.parse(&source_code, None).unwrap();
+        let edit = Edit {
+            position: index_of(&source_code, "\nb;"),
+            deleted_length: 3,
+            inserted_text: Vec::new(),
+        };
+        let ranges = get_changed_ranges(&mut parser, &mut tree, &mut source_code, &edit);
+        assert_eq!(ranges, vec![]);
+        assert_eq!(
+            tree.root_node().to_sexp(),
+            "(program (expression_statement (identifier)))"
+        );
+    }
+
+    // Changing a token in the middle of the file
+    {
+        let mut source_code = b"f(x);\ng(y);\n".to_vec();
+        let mut tree = parser.parse(&source_code, None).unwrap();
+        let edit = Edit {
+            position: index_of(&source_code, "x"),
+            deleted_length: 1,
+            inserted_text: b"null".to_vec(),
+        };
+        let ranges = get_changed_ranges(&mut parser, &mut tree, &mut source_code, &edit);
+        assert_eq!(byte_ranges(&ranges), [(2, 6)]);
+    }
+}
+
+#[test]
+fn test_estimate_reparse_region() {
+    let mut source_code = b"function a() { if (b) { c(); } d(); }".to_vec();
+
+    let mut parser = Parser::new();
+    parser.set_language(&get_language("javascript")).unwrap();
+    let tree = parser.parse(&source_code, None).unwrap();
+
+    // Add a statement to the end of the inner block.
+    let mut edited_tree = tree.clone();
+    let edit = Edit {
+        position: index_of(&source_code, " }"),
+        deleted_length: 0,
+        inserted_text: b" e();".to_vec(),
+    };
+    let input_edit = perform_edit(&mut edited_tree, &mut source_code, &edit).unwrap();
+
+    // The estimate doesn't modify the tree. It contains the inserted text, and
+    // stays within the inner block.
+    let region = tree.estimate_reparse_region(&input_edit);
+    assert!(!tree.root_node().has_changes());
+    let inner_block = range_of(&source_code, "{ c(); e(); }");
+    assert!(region.start_byte <= input_edit.start_byte);
+    assert!(region.end_byte >= input_edit.new_end_byte);
+    assert!(region.start_byte >= inner_block.start_byte);
+    assert!(region.end_byte <= inner_block.end_byte);
+
+    // Everything that actually changed is within the estimated region.
+    let new_tree = parser.parse(&source_code, Some(&edited_tree)).unwrap();
+    let changed_ranges = edited_tree.changed_ranges(&new_tree).collect::<Vec<_>>();
+    assert!(!changed_ranges.is_empty());
+    for range in changed_ranges {
+        assert!(range.start_byte >= region.start_byte && range.end_byte <= region.end_byte);
+    }
+}
+
 #[test]
 fn test_consistency_with_mid_codepoint_edit() {
     let mut parser = Parser::new();
@@ -783,6 +1192,13 @@ fn range_of(text: &[u8], substring: &str) -> Range {
     }
 }
 
+fn byte_ranges(ranges: &[Range]) -> Vec<(usize, usize)> {
+    ranges
+        .iter()
+        .map(|range| (range.start_byte, range.end_byte))
+        .collect()
+}
+
 fn get_changed_ranges(
     parser: &mut Parser,
     tree: &mut Tree,
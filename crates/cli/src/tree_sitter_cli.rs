@@ -8,6 +8,7 @@ pub mod logger;
 pub mod paint;
 pub mod parse;
 pub mod playground;
+pub mod proto;
 pub mod query;
 pub mod query_testing;
 pub mod tags;
@@ -1,4 +1,5 @@
 use std::{
+    ops::Range,
     path::{Path, PathBuf},
     process::{Child, ChildStdin, Command, Stdio},
     sync::{
@@ -23,6 +24,32 @@ svg { width: 100%; }
 
 ";
 
+/// Render the line of `source` that contains the given byte range, with the range underlined,
+/// for pointing to the location of an error in a file.
+#[must_use]
+pub fn source_snippet(path: &Path, source: &str, range: Range<usize>) -> String {
+    let line_start = source[..range.start].rfind('\n').map_or(0, |i| i + 1);
+    let line_end = source[range.start..]
+        .find('\n')
+        .map_or(source.len(), |i| range.start + i);
+    let line = &source[line_start..line_end];
+    let row = source[..line_start].matches('\n').count() + 1;
+    let column = source[line_start..range.start].chars().count();
+    let width = source[range.start..range.end.min(line_end)]
+        .chars()
+        .count()
+        .max(1);
+
+    let gutter = " ".repeat(row.to_string().len());
+    format!(
+        "{gutter}--> {}:{row}:{}\n{gutter} |\n{row} | {line}\n{gutter} | {}{}",
+        path.display(),
+        column + 1,
+        " ".repeat(column),
+        "^".repeat(width),
+    )
+}
+
 #[must_use]
 pub fn lang_not_found_for_path(path: &Path, loader_config: &LoaderConfig) -> String {
     let path = path.display();
@@ -129,3 +156,35 @@ impl Drop for LogSession {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::source_snippet;
+
+    #[test]
+    fn test_source_snippet() {
+        let source = "rules: {\n  statement: $ => seq('let', $.expresion),\n}\n";
+        let start = source.find("expresion").unwrap();
+        assert_eq!(
+            source_snippet(Path::new("grammar.js"), source, start..start + 9),
+            [
+                " --> grammar.js:2:32",
+                "  |",
+                "2 |   statement: $ => seq('let', $.expresion),",
+                "  |                                ^^^^^^^^^",
+            ]
+            .join("\n")
+        );
+
+        // A range that spans several lines is underlined up to the end of its first line.
+        let start = source.find("$ =>").unwrap();
+        assert_eq!(
+            source_snippet(Path::new("grammar.js"), source, start..source.len())
+                .lines()
+                .last(),
+            Some(format!("  | {}{}", " ".repeat(13), "^".repeat(29)).as_str())
+        );
+    }
+}
@@ -0,0 +1,68 @@
+//! Compares how long it takes to generate a parser for a large grammar using a single thread
+//! and using every available thread, and checks that both produce the same parser.
+
+use std::{
+    env, fs, iter,
+    num::NonZeroUsize,
+    path::Path,
+    sync::LazyLock,
+    thread,
+    time::{Duration, Instant},
+};
+
+use tree_sitter_generate::generate_parser_for_grammar;
+
+static GRAMMAR_NAME: LazyLock<String> = LazyLock::new(|| {
+    env::var("TREE_SITTER_BENCHMARK_GRAMMAR").unwrap_or_else(|_| "cpp".to_string())
+});
+static REPETITION_COUNT: LazyLock<usize> = LazyLock::new(|| {
+    env::var("TREE_SITTER_BENCHMARK_REPETITION_COUNT").map_or(3, |s| s.parse::<usize>().unwrap())
+});
+
+fn main() {
+    let grammar_path = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("../../test/fixtures/grammars")
+        .join(GRAMMAR_NAME.as_str())
+        .join("src/grammar.json");
+    let grammar_json = fs::read_to_string(&grammar_path).unwrap_or_else(|e| {
+        panic!(
+            "Failed to read {}. Run `cargo xtask fetch-fixtures` first: {e}",
+            grammar_path.display()
+        )
+    });
+
+    let parallelism = thread::available_parallelism().map_or(1, NonZeroUsize::get);
+    let mut serial_parser = None;
+    let mut serial_duration = Duration::ZERO;
+    for thread_count in iter::once(1).chain((parallelism > 1).then_some(parallelism)) {
+        unsafe { env::set_var("TREE_SITTER_GENERATE_THREADS", thread_count.to_string()) };
+
+        let mut durations = Vec::with_capacity(*REPETITION_COUNT);
+        let mut parser = String::new();
+        for _ in 0..*REPETITION_COUNT {
+            let start = Instant::now();
+            (_, parser) = generate_parser_for_grammar(&grammar_json, None, &mut Vec::new())
+                .expect("Failed to generate parser");
+            durations.push(start.elapsed());
+        }
+        let duration = durations.into_iter().min().unwrap_or_default();
+
+        match &serial_parser {
+            None => {
+                serial_duration = duration;
+                serial_parser = Some(parser);
+            }
+            Some(serial_parser) => assert!(
+                *serial_parser == parser,
+                "Generating with {thread_count} threads produced a different parser"
+            ),
+        }
+
+        println!(
+            "  {} with {thread_count:>2} thread(s): {:>8.2?} ({:.2}x)",
+            GRAMMAR_NAME.as_str(),
+            duration,
+            serial_duration.as_secs_f64() / duration.as_secs_f64(),
+        );
+    }
+}
@@ -92,6 +92,41 @@ pub struct BitVec {
     capacity: u32,
 }
 
+// SAFETY: A shared reference to a `BitVec` only allows its words to be read. Allocating or
+// freeing words requires ownership or a mutable reference, and those can never move to
+// another thread because `BitVec` is not `Send`, so words are only ever returned to the arena
+// of the thread that allocated them. Cloning through a shared reference allocates from the
+// arena of the cloning thread.
+unsafe impl Sync for BitVec {}
+
+/// A copy of a [`BitVec`] whose words are owned by an ordinary `Vec` instead of a thread's
+/// arena, so that it can be sent between threads.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct DetachedBitVec {
+    words: Vec<u64>,
+    num_bits: u32,
+}
+
+impl DetachedBitVec {
+    /// Copy the words back into the current thread's arena.
+    #[must_use]
+    pub fn attach(&self) -> BitVec {
+        let words = self.words.len();
+        if words == 0 {
+            return BitVec::new();
+        }
+        let data = arena_alloc(words);
+        // SAFETY: data points to `words` valid zeroed words.
+        let dst = unsafe { std::slice::from_raw_parts_mut(data, words) };
+        dst.copy_from_slice(&self.words);
+        BitVec {
+            data,
+            num_bits: self.num_bits,
+            capacity: words as u32,
+        }
+    }
+}
+
 impl BitVec {
     #[must_use]
     pub const fn new() -> Self {
@@ -145,6 +180,15 @@ impl BitVec {
         unsafe { std::slice::from_raw_parts_mut(self.data, n) }
     }
 
+    /// Copy the in-use words out of the arena.
+    #[must_use]
+    pub fn detach(&self) -> DetachedBitVec {
+        DetachedBitVec {
+            words: self.as_slice().to_vec(),
+            num_bits: self.num_bits,
+        }
+    }
+
     #[must_use]
     #[allow(clippy::len_without_is_empty)]
     pub const fn len(&self) -> usize {
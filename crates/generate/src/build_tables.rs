@@ -8,7 +8,7 @@ mod token_conflicts;
 
 use std::collections::BTreeSet;
 
-pub use build_lex_table::LARGE_CHARACTER_RANGE_COUNT;
+pub use build_lex_table::{LARGE_CHARACTER_RANGE_COUNT, render_lex_dfa_dot};
 use build_parse_table::BuildTableResult;
 pub use build_parse_table::ParseTableBuilderError;
 use log::{debug, info};
@@ -23,7 +23,7 @@ use self::{
     token_conflicts::TokenConflictMap,
 };
 use crate::{
-    Diagnostic, OptLevel,
+    Diagnostic, OptLevel, TokenConflictResolution,
     grammars::{InlinedProductionMap, LexicalGrammar, SyntaxGrammar},
     nfa::{CharacterSet, NfaCursor},
     node_types::VariableInfo,
@@ -36,6 +36,8 @@ pub struct Tables {
     pub main_lex_table: LexTable,
     pub keyword_lex_table: LexTable,
     pub large_character_sets: Vec<(Option<Symbol>, CharacterSet)>,
+    /// The pairs of lexical tokens that match overlapping strings, as token indices.
+    pub token_conflicts: Vec<(usize, usize, TokenConflictResolution)>,
 }
 
 #[expect(
@@ -119,6 +121,7 @@ pub fn build_tables(
         main_lex_table: lex_tables.main_lex_table,
         keyword_lex_table: lex_tables.keyword_lex_table,
         large_character_sets: lex_tables.large_character_sets,
+        token_conflicts: token_conflict_map.conflicts(),
     })
 }
 
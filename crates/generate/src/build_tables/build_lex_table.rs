@@ -1,5 +1,6 @@
 use std::{
     collections::{VecDeque, hash_map::Entry},
+    fmt::Write,
     mem,
 };
 
@@ -130,6 +131,86 @@ pub fn build_lex_table(
     }
 }
 
+/// Build a single lex table that recognizes every token in the grammar, and render it
+/// as a Graphviz DOT graph. Accepting states are labeled with the name of the token
+/// they recognize.
+pub fn render_lex_dfa_dot(lexical_grammar: &LexicalGrammar) -> String {
+    let mut builder = LexTableBuilder::new(lexical_grammar);
+    let all_tokens = (0..lexical_grammar.variables.len())
+        .map(Symbol::terminal)
+        .collect::<TokenSet>();
+    builder.add_state_for_tokens(&all_tokens);
+
+    let mut dot = String::new();
+    writeln!(dot, "digraph lex_dfa {{").unwrap();
+    writeln!(dot, "  rankdir=LR;").unwrap();
+    writeln!(dot, "  node [shape=circle];").unwrap();
+    for (state_id, state) in builder.table.states.iter().enumerate() {
+        match state.accept_action {
+            Some(symbol) if symbol.is_terminal() => writeln!(
+                dot,
+                "  {state_id} [shape=doublecircle, label=\"{state_id}\\n{}\"];",
+                escape_dot(&lexical_grammar.variables[symbol.index].name)
+            ),
+            Some(_) => writeln!(
+                dot,
+                "  {state_id} [shape=doublecircle, label=\"{state_id}\\nEOF\"];"
+            ),
+            None => writeln!(dot, "  {state_id};"),
+        }
+        .unwrap();
+        if let Some(action) = &state.eof_action {
+            writeln!(dot, "  {state_id} -> {} [label=\"EOF\"];", action.state).unwrap();
+        }
+        for (characters, action) in &state.advance_actions {
+            let style = if action.in_main_token {
+                ""
+            } else {
+                ", style=dashed"
+            };
+            writeln!(
+                dot,
+                "  {state_id} -> {} [label=\"{}\"{style}];",
+                action.state,
+                escape_dot(&format_character_set(characters))
+            )
+            .unwrap();
+        }
+    }
+    writeln!(dot, "}}").unwrap();
+    dot
+}
+
+fn format_character_set(characters: &CharacterSet) -> String {
+    let mut result = String::new();
+    let mut set = characters.clone();
+    if characters.contains(char::MAX) {
+        result.push('^');
+        set = set.negate();
+    }
+    for range in set.ranges() {
+        let (start, end) = (*range.start(), *range.end());
+        push_character(&mut result, start);
+        if start != end {
+            result.push('-');
+            push_character(&mut result, end);
+        }
+    }
+    result
+}
+
+fn push_character(result: &mut String, c: char) {
+    if c.is_control() || c.is_whitespace() {
+        write!(result, "{}", c.escape_debug()).unwrap();
+    } else {
+        result.push(c);
+    }
+}
+
+fn escape_dot(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
 struct QueueEntry {
     state_id: usize,
     nfa_states: Vec<u32>,
@@ -459,3 +540,41 @@ fn sort_states(table: &mut LexTable, parse_table: &mut ParseTable) {
         state.lex_state_id = new_ids_by_old_id[state.lex_state_id];
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        grammars::{Variable, VariableType},
+        prepare_grammar::{ExtractedLexicalGrammar, expand_tokens},
+        rules::Rule,
+    };
+
+    #[test]
+    fn test_render_lex_dfa_dot() {
+        let grammar = expand_tokens(ExtractedLexicalGrammar {
+            separators: Vec::new(),
+            variables: vec![
+                Variable {
+                    name: "if".to_string(),
+                    kind: VariableType::Anonymous,
+                    rule: Rule::string("if"),
+                },
+                Variable {
+                    name: "number".to_string(),
+                    kind: VariableType::Named,
+                    rule: Rule::pattern("\\d+", ""),
+                },
+            ],
+        })
+        .unwrap();
+
+        let dot = render_lex_dfa_dot(&grammar);
+        assert!(dot.starts_with("digraph lex_dfa {\n"));
+        assert!(dot.ends_with("}\n"));
+        assert!(dot.contains("label=\"0-9\""));
+        assert!(dot.contains("label=\"i\""));
+        assert!(dot.contains("\\nif\"]"));
+        assert!(dot.contains("\\nnumber\"]"));
+    }
+}
@@ -1,7 +1,10 @@
 use std::{
     cmp::Ordering,
     collections::{BTreeMap, BTreeSet, VecDeque},
+    env,
     hash::BuildHasherDefault,
+    num::NonZeroUsize,
+    thread,
 };
 
 use indexmap::{IndexMap, map::Entry};
@@ -24,6 +27,15 @@ use crate::{
     },
 };
 
+/// The environment variable that overrides the number of threads used to compute the
+/// item set closures of parse states. By default, all available cores are used.
+const THREAD_COUNT_VAR: &str = "TREE_SITTER_GENERATE_THREADS";
+
+/// The number of queued parse states per thread whose closures are computed together.
+/// Larger batches spread the work more evenly, at the cost of holding more closures in
+/// memory at once.
+const CLOSURE_BATCH_SIZE_PER_THREAD: usize = 64;
+
 // For conflict reporting, each parse state is associated with an example
 // sequence of symbols that could lead to that parse state.
 type SymbolSequence = Vec<Symbol>;
@@ -63,6 +75,7 @@ struct ParseTableBuilder<'a> {
     non_terminal_extra_states: Vec<(Symbol, usize)>,
     actual_conflicts: FxHashSet<Vec<Symbol>>,
     parse_table: ParseTable,
+    thread_count: NonZeroUsize,
 }
 
 pub type BuildTableResult<T> = Result<T, ParseTableBuilderError>;
@@ -244,6 +257,7 @@ impl<'a> ParseTableBuilder<'a> {
         lexical_grammar: &'a LexicalGrammar,
         item_set_builder: ParseItemSetBuilder<'a>,
         variable_info: &'a [VariableInfo],
+        thread_count: NonZeroUsize,
     ) -> Self {
         Self {
             syntax_grammar,
@@ -262,7 +276,9 @@ impl<'a> ParseTableBuilder<'a> {
                 external_lex_states: Vec::new(),
                 production_infos: Vec::new(),
                 max_aliased_production_length: 1,
+                resolved_conflict_count: 0,
             },
+            thread_count,
         }
     }
 
@@ -335,17 +351,26 @@ impl<'a> ParseTableBuilder<'a> {
             self.non_terminal_extra_states.push((terminal, state_id));
         }
 
-        while let Some(entry) = self.parse_state_queue.pop_front() {
-            let item_set = self
-                .item_set_builder
-                .transitive_closure(&self.parse_state_info_by_id[entry.state_id].1);
-
-            self.add_actions(
-                self.parse_state_info_by_id[entry.state_id].0.clone(),
-                entry.preceding_auxiliary_symbols,
-                entry.state_id,
-                &item_set,
-            )?;
+        // The closures of queued item sets don't depend on each other, so they are computed
+        // a batch at a time, in parallel. The actions for each state are still added in queue
+        // order, and any new states are queued behind the rest of the batch, so the states
+        // are numbered exactly as if they were processed one at a time.
+        let batch_size = self.thread_count.get() * CLOSURE_BATCH_SIZE_PER_THREAD;
+        while !self.parse_state_queue.is_empty() {
+            let batch_len = self.parse_state_queue.len().min(batch_size);
+            let entries = self
+                .parse_state_queue
+                .drain(..batch_len)
+                .collect::<Vec<_>>();
+            let item_sets = self.transitive_closures(&entries);
+            for (entry, item_set) in entries.into_iter().zip(item_sets) {
+                self.add_actions(
+                    self.parse_state_info_by_id[entry.state_id].0.clone(),
+                    entry.preceding_auxiliary_symbols,
+                    entry.state_id,
+                    &item_set,
+                )?;
+            }
         }
 
         if !self.actual_conflicts.is_empty() {
@@ -361,6 +386,49 @@ impl<'a> ParseTableBuilder<'a> {
         Ok((self.parse_table, self.parse_state_info_by_id))
     }
 
+    fn transitive_closures(&self, entries: &[ParseStateQueueEntry]) -> Vec<ParseItemSet<'a>> {
+        let item_sets = entries
+            .iter()
+            .map(|entry| &self.parse_state_info_by_id[entry.state_id].1)
+            .collect::<Vec<_>>();
+        let item_set_builder = &self.item_set_builder;
+        if self.thread_count.get() == 1 || item_sets.len() < 2 {
+            return item_sets
+                .into_iter()
+                .map(|item_set| item_set_builder.transitive_closure(item_set))
+                .collect();
+        }
+
+        // Each batch is split into one chunk per thread and joined before any of its states
+        // are numbered, so a single fork and join is all that is needed. Scoped threads can
+        // borrow the builder and the item sets directly, without a thread pool dependency.
+        let chunk_size = item_sets.len().div_ceil(self.thread_count.get());
+        thread::scope(|scope| {
+            #[expect(
+                clippy::needless_collect,
+                reason = "all of the threads must be spawned before any of them are joined"
+            )]
+            let handles = item_sets
+                .chunks(chunk_size)
+                .map(|chunk| {
+                    scope.spawn(move || {
+                        // Token sets can't leave the thread whose arena they were allocated
+                        // from, so each closure is detached before it is sent back.
+                        chunk
+                            .iter()
+                            .map(|item_set| item_set_builder.transitive_closure(item_set).detach())
+                            .collect::<Vec<_>>()
+                    })
+                })
+                .collect::<Vec<_>>();
+            handles
+                .into_iter()
+                .flat_map(|handle| handle.join().unwrap())
+                .map(|item_set| item_set.attach())
+                .collect()
+        })
+    }
+
     fn add_parse_state(
         &mut self,
         preceding_symbols: &SymbolSequence,
@@ -787,11 +855,13 @@ impl<'a> ParseTableBuilder<'a> {
 
             if shift_is_more && !shift_is_less {
                 entry.actions.drain(0..entry.actions.len() - 1);
+                self.parse_table.resolved_conflict_count += 1;
             }
             // If the REDUCE actions have higher precedence, remove the SHIFT action.
             else if shift_is_less && !shift_is_more {
                 entry.actions.pop();
                 conflicting_items.retain(|item| item.is_done());
+                self.parse_table.resolved_conflict_count += 1;
             }
             // If the SHIFT and REDUCE actions have the same precedence, consider
             // the REDUCE actions' associativity.
@@ -808,9 +878,11 @@ impl<'a> ParseTableBuilder<'a> {
                     (true, false, false) => {
                         entry.actions.pop();
                         conflicting_items.retain(|item| item.is_done());
+                        self.parse_table.resolved_conflict_count += 1;
                     }
                     (false, false, true) => {
                         entry.actions.drain(0..entry.actions.len() - 1);
+                        self.parse_table.resolved_conflict_count += 1;
                     }
                     _ => {}
                 }
@@ -1149,6 +1221,56 @@ pub fn build_parse_table<'a>(
         lexical_grammar,
         item_set_builder,
         variable_info,
+        thread_count(),
     )
     .build(diagnostics)
 }
+
+fn thread_count() -> NonZeroUsize {
+    env::var(THREAD_COUNT_VAR)
+        .ok()
+        .and_then(|count| count.parse().ok())
+        .or_else(|| thread::available_parallelism().ok())
+        .unwrap_or(NonZeroUsize::MIN)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, path::Path};
+
+    use super::*;
+    use crate::{
+        node_types::get_variable_info, parse_grammar::parse_grammar,
+        prepare_grammar::prepare_grammar,
+    };
+
+    #[test]
+    fn test_parallel_closures_match_serial_build() {
+        let grammar_path = Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("../../test/fixtures/grammars/javascript/src/grammar.json");
+        let grammar_json = fs::read_to_string(grammar_path).unwrap();
+        let input_grammar = parse_grammar(&grammar_json, &mut Vec::new()).unwrap();
+        let (syntax_grammar, lexical_grammar, inlines, simple_aliases) =
+            prepare_grammar(&input_grammar, &mut Vec::new()).unwrap();
+        let variable_info =
+            get_variable_info(&syntax_grammar, &lexical_grammar, &simple_aliases).unwrap();
+
+        let build = |thread_count| {
+            ParseTableBuilder::new(
+                &syntax_grammar,
+                &lexical_grammar,
+                ParseItemSetBuilder::new(&syntax_grammar, &lexical_grammar, &inlines),
+                &variable_info,
+                NonZeroUsize::new(thread_count).unwrap(),
+            )
+            .build(&mut Vec::new())
+            .unwrap()
+        };
+
+        let (serial_table, serial_state_info) = build(1);
+        let (parallel_table, parallel_state_info) = build(4);
+        assert!(serial_table.states.len() > 4 * CLOSURE_BATCH_SIZE_PER_THREAD);
+        assert_eq!(parallel_table, serial_table);
+        assert_eq!(parallel_state_info, serial_state_info);
+    }
+}
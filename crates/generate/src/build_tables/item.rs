@@ -10,7 +10,7 @@ use crate::{
         LexicalGrammar, NO_RESERVED_WORDS, Production, ProductionStep, ReservedWordSetId,
         SyntaxGrammar,
     },
-    rules::{Associativity, Precedence, Symbol, SymbolType, TokenSet},
+    rules::{Associativity, DetachedTokenSet, Precedence, Symbol, SymbolType, TokenSet},
 };
 
 static START_PRODUCTION: LazyLock<Production> = LazyLock::new(|| Production {
@@ -70,6 +70,13 @@ pub struct ParseItemSetEntry<'a> {
     pub following_reserved_word_set: ReservedWordSetId,
 }
 
+/// A copy of a [`ParseItemSet`] whose lookaheads aren't tied to the current thread,
+/// so that it can be sent to another thread.
+#[derive(Clone, Debug, PartialEq, Eq, Default)]
+pub struct DetachedParseItemSet<'a> {
+    entries: Vec<(ParseItem<'a>, DetachedTokenSet, ReservedWordSetId)>,
+}
+
 /// A [`ParseItemSetCore`] is like a [`ParseItemSet`], but without the lookahead
 /// information. Parse states with the same core are candidates for merging.
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -192,6 +199,36 @@ impl<'a> ParseItemSet<'a> {
             entries: self.entries.iter().map(|e| e.item).collect(),
         }
     }
+
+    #[must_use]
+    pub fn detach(&self) -> DetachedParseItemSet<'a> {
+        DetachedParseItemSet {
+            entries: self
+                .entries
+                .iter()
+                .map(|e| (e.item, e.lookaheads.detach(), e.following_reserved_word_set))
+                .collect(),
+        }
+    }
+}
+
+impl<'a> DetachedParseItemSet<'a> {
+    #[must_use]
+    pub fn attach(&self) -> ParseItemSet<'a> {
+        ParseItemSet {
+            entries: self
+                .entries
+                .iter()
+                .map(
+                    |(item, lookaheads, following_reserved_word_set)| ParseItemSetEntry {
+                        item: *item,
+                        lookaheads: lookaheads.attach(),
+                        following_reserved_word_set: *following_reserved_word_set,
+                    },
+                )
+                .collect(),
+        }
+    }
 }
 
 impl fmt::Display for ParseItemDisplay<'_> {
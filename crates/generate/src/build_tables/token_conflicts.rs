@@ -5,6 +5,7 @@ use rustc_hash::FxHashSet;
 use bitflags::bitflags;
 
 use crate::{
+    TokenConflictResolution,
     build_tables::item::TokenSetDisplay,
     grammars::{LexicalGrammar, SyntaxGrammar},
     nfa::{CharacterSet, NfaCursor, NfaTransition},
@@ -21,6 +22,9 @@ bitflags! {
         const DOES_MATCH_SEPARATORS       = 1 << 3;
         const MATCHES_SAME_STRING         = 1 << 4;
         const MATCHES_DIFFERENT_STRING    = 1 << 5;
+        /// Set along with `MATCHES_SAME_STRING` when the tokens have the same precedence,
+        /// so that the preferred token is only chosen by the order of the tokens.
+        const MATCHES_SAME_STRING_BY_ORDER = 1 << 6;
     }
 }
 
@@ -120,9 +124,37 @@ impl<'a> TokenConflictMap<'a> {
     /// is preferred over token `j`?
     #[must_use]
     pub fn has_same_conflict_status(&self, a: usize, b: usize, other: usize) -> bool {
-        let left = &self.status_matrix[matrix_index(self.n, a, other)];
-        let right = &self.status_matrix[matrix_index(self.n, b, other)];
-        left == right
+        let left = self.status_matrix[matrix_index(self.n, a, other)];
+        let right = self.status_matrix[matrix_index(self.n, b, other)];
+        left.difference(TokenConflictStatus::MATCHES_SAME_STRING_BY_ORDER)
+            == right.difference(TokenConflictStatus::MATCHES_SAME_STRING_BY_ORDER)
+    }
+
+    /// List every ordered pair of tokens `(i, j)` where token `i` is preferred over token
+    /// `j` for some input, along with how the lexer tells the two apart.
+    #[must_use]
+    pub fn conflicts(&self) -> Vec<(usize, usize, TokenConflictResolution)> {
+        let mut result = Vec::new();
+        for i in 0..self.n {
+            for j in 0..self.n {
+                let status = self.status_matrix[matrix_index(self.n, i, j)];
+                let resolution = if status.intersects(
+                    TokenConflictStatus::MATCHES_SAME_STRING_BY_ORDER
+                        | TokenConflictStatus::DOES_MATCH_VALID_CONT
+                        | TokenConflictStatus::DOES_MATCH_SEPARATORS,
+                ) {
+                    TokenConflictResolution::Unresolved
+                } else if status.contains(TokenConflictStatus::MATCHES_SAME_STRING) {
+                    TokenConflictResolution::Precedence
+                } else if status.contains(TokenConflictStatus::DOES_MATCH_CONTINUATION) {
+                    TokenConflictResolution::FollowingTokens
+                } else {
+                    continue;
+                };
+                result.push((i, j, resolution));
+            }
+        }
+        result
     }
 
     /// Does token `i` match any strings that token `j` does *not* match?
@@ -410,10 +442,17 @@ fn compute_conflict_status(
                     completion = Some((id, precedence));
                 }
 
+                let mut status = TokenConflictStatus::MATCHES_SAME_STRING;
+                if precedence == prev_precedence
+                    && grammar.variables[id].implicit_precedence
+                        == grammar.variables[prev_id].implicit_precedence
+                {
+                    status.insert(TokenConflictStatus::MATCHES_SAME_STRING_BY_ORDER);
+                }
                 if preferred_id == i {
-                    result.0.insert(TokenConflictStatus::MATCHES_SAME_STRING);
+                    result.0.insert(status);
                 } else {
-                    result.1.insert(TokenConflictStatus::MATCHES_SAME_STRING);
+                    result.1.insert(status);
                 }
             } else {
                 completion = Some((id, precedence));
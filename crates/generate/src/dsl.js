@@ -170,7 +170,7 @@ function reserved(wordset, rule) {
   }
   return {
     type: "RESERVED",
-    content: normalize(rule),
+    content: rule === undefined ? blank() : normalize(rule),
     context_name: wordset,
   }
 }
@@ -261,6 +261,8 @@ function grammar(baseGrammar, options) {
       supertypes: [],
       precedences: [],
       reserved: {},
+      descriptions: {},
+      token_groups: [],
     };
   } else {
     baseGrammar = baseGrammar.grammar;
@@ -406,6 +408,28 @@ function grammar(baseGrammar, options) {
     });
   }
 
+  let token_groups = baseGrammar.token_groups ?? [];
+  if (options.token_groups) {
+    if (typeof options.token_groups !== "function") {
+      throw new Error("Grammar's 'token_groups' property must be a function.");
+    }
+
+    const baseTokenGroupRules = token_groups.map(group => group.map(sym));
+    const tokenGroupRules = options.token_groups.call(ruleBuilder, ruleBuilder, baseTokenGroupRules);
+
+    if (!Array.isArray(tokenGroupRules)) {
+      throw new Error("Grammar's token_groups must be an array of arrays of rules.");
+    }
+
+    token_groups = tokenGroupRules.map(group => {
+      if (!Array.isArray(group)) {
+        throw new Error("Grammar's token_groups must be an array of arrays of rules.");
+      }
+
+      return group.map(symbol => normalize(symbol).name);
+    });
+  }
+
   let inline = baseGrammar.inline;
   if (options.inline) {
     if (typeof options.inline !== "function") {
@@ -470,6 +494,20 @@ function grammar(baseGrammar, options) {
     });
   }
 
+  let descriptions = baseGrammar.descriptions ?? {};
+  if (options.descriptions) {
+    if (typeof options.descriptions !== "object") {
+      throw new Error("Grammar's 'descriptions' property must be an object.");
+    }
+    descriptions = { ...descriptions };
+    for (const [ruleName, description] of Object.entries(options.descriptions)) {
+      if (typeof description !== "string") {
+        throw new Error(`Grammar's description for rule '${ruleName}' must be a string.`);
+      }
+      descriptions[ruleName] = description;
+    }
+  }
+
   if (Object.keys(rules).length === 0) {
     throw new Error("Grammar must have at least one rule.");
   }
@@ -487,6 +525,8 @@ function grammar(baseGrammar, options) {
       inline,
       supertypes,
       reserved,
+      descriptions: Object.keys(descriptions).length > 0 ? descriptions : undefined,
+      token_groups: token_groups.length > 0 ? token_groups : undefined,
     },
   };
 }
@@ -26,20 +26,26 @@ pub mod parse_grammar;
 mod prepare_grammar;
 #[cfg(feature = "qjs-rt")]
 mod quickjs;
+mod railroad;
 mod render;
 mod rules;
 mod tables;
 
 pub use build_tables::ParseTableBuilderError;
-use build_tables::build_tables;
-use grammars::{InlinedProductionMap, InputGrammar, LexicalGrammar, SyntaxGrammar};
+use build_tables::{build_tables, render_lex_dfa_dot};
+use grammars::{InlinedProductionMap, InputGrammar, LexicalGrammar, SyntaxGrammar, VariableType};
+#[cfg(feature = "load")]
+pub use node_types::{
+    BreakingChange, FieldInfoJSON, NodeInfoJSON, NodeTypeJSON, NodeTypes, NodeTypesError,
+};
 pub use node_types::{InvalidSupertypeError, SuperTypeCycleError, VariableInfoError};
 pub use parse_grammar::ParseGrammarError;
 use parse_grammar::parse_grammar;
-pub use prepare_grammar::PrepareGrammarError;
-use prepare_grammar::prepare_grammar;
-use render::render_c_code;
-pub use render::{ABI_VERSION_MAX, ABI_VERSION_MIN, RenderError};
+pub use prepare_grammar::{InternSymbolsError, PrepareGrammarError, validate_grammar};
+use prepare_grammar::{prepare_grammar, prepare_grammar_with_opts};
+use railroad::render_railroad_svg;
+pub use render::{ABI_VERSION_MAX, ABI_VERSION_MIN, RenderError, SPLIT_FILE_NAMES};
+use render::{GENERATED_BANNER, RenderedParser, render_c_code};
 
 struct JSONOutput {
     #[cfg(feature = "load")]
@@ -49,12 +55,17 @@ struct JSONOutput {
     inlines: InlinedProductionMap,
     simple_aliases: BTreeMap<Symbol, Alias>,
     variable_info: Vec<VariableInfo>,
+    auxiliary_rules: usize,
 }
 
 struct GeneratedParser {
     c_code: String,
+    #[cfg_attr(not(feature = "load"), expect(dead_code))]
+    split_files: Vec<(&'static str, String)>,
     #[cfg(feature = "load")]
     node_types_json: String,
+    #[cfg_attr(not(feature = "load"), expect(dead_code))]
+    report: GenerateReport,
 }
 
 // NOTE: This constant must be kept in sync with the definition of
@@ -91,6 +102,11 @@ pub enum GenerateError {
     ParseVersion(#[from] ParseVersionError),
     #[error(transparent)]
     SuperTypeCycle(#[from] SuperTypeCycleError),
+    #[error("Warnings are denied, and the grammar has {} warning{}", .0.len(), if .0.len() == 1 { "" } else { "s" })]
+    DeniedWarnings(Vec<Diagnostic>),
+    #[cfg(feature = "load")]
+    #[error("Failed to parse {CONFLICT_NOTES_FILE_NAME} -- {0}")]
+    ConflictNotes(String),
 }
 
 #[derive(Debug, Error)]
@@ -239,12 +255,13 @@ bitflags! {
     #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
     pub struct OptLevel: u32 {
         const MergeStates = 1 << 0;
+        const DeduplicateSmallStates = 1 << 1;
     }
 }
 
 impl Default for OptLevel {
     fn default() -> Self {
-        Self::MergeStates
+        Self::MergeStates | Self::DeduplicateSmallStates
     }
 }
 
@@ -255,6 +272,13 @@ pub enum Diagnostic {
     UnarySeq { name: Option<String> },
     EmptyStringMatch(String),
     UnsupportedRegexFlag { flag: char, pattern: String },
+    StaleConflict { conflict: Vec<String>, note: String },
+    UndeclaredConflictNote(Vec<String>),
+    WordTokenShadow { name: String },
+    ConstantRule { name: String, value: String },
+    TokenGroupOverlap { first: String, second: String },
+    UnusedRule { name: String },
+    UnusedPrecedence { name: String, hint: Option<String> },
 }
 
 impl std::fmt::Display for Diagnostic {
@@ -299,14 +323,412 @@ impl std::fmt::Display for Diagnostic {
             Self::UnsupportedRegexFlag { flag, pattern } => {
                 write!(f, "unsupported regex flag `{flag}` in pattern `{pattern}`")?;
             }
+            Self::StaleConflict { conflict, note } => {
+                write!(
+                    f,
+                    "unnecessary conflict: `{}`. it was declared because: {note}",
+                    conflict.join("`, `")
+                )?;
+            }
+            Self::UndeclaredConflictNote(conflict) => {
+                write!(
+                    f,
+                    "{CONFLICT_NOTES_FILE_NAME} has a note for the conflict `{}`, which is not declared in the grammar",
+                    conflict.join("`, `")
+                )?;
+            }
+            Self::WordTokenShadow { name } => {
+                write!(
+                    f,
+                    "token `{name}` only matches strings that the word token also matches, \
+                     so it may be treated as a keyword",
+                )?;
+            }
+            Self::ConstantRule { name, value } => {
+                write!(
+                    f,
+                    "rule `{name}` only matches the string {value:?}. \
+                     consider using a token or an alias instead.",
+                )?;
+            }
+            Self::TokenGroupOverlap { first, second } => {
+                write!(
+                    f,
+                    "tokens `{first}` and `{second}` are in the same token group, \
+                     but can match the same string",
+                )?;
+            }
+            Self::UnusedRule { name } => {
+                write!(f, "rule `{name}` is never used")?;
+            }
+            Self::UnusedPrecedence { name, hint } => {
+                write!(f, "precedence `{name}` is never used by any rule")?;
+                if let Some(hint) = hint {
+                    write!(f, ". did you mean `{hint}`?")?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Diagnostic {
+    /// The name of the rule that this diagnostic refers to, if any.
+    #[must_use]
+    pub fn rule_name(&self) -> Option<&str> {
+        match self {
+            Self::UnaryChoice { name } | Self::UnarySeq { name } => name.as_deref(),
+            Self::EmptyStringMatch(rule)
+            | Self::WordTokenShadow { name: rule }
+            | Self::ConstantRule { name: rule, .. }
+            | Self::TokenGroupOverlap { first: rule, .. }
+            | Self::UnusedRule { name: rule } => Some(rule),
+            Self::UnnecessaryConflicts(_)
+            | Self::UnsupportedRegexFlag { .. }
+            | Self::StaleConflict { .. }
+            | Self::UndeclaredConflictNote(_)
+            | Self::UnusedPrecedence { .. } => None,
+        }
+    }
+
+    /// Convert this warning to the format used by `tree-sitter generate --diagnostics json`.
+    ///
+    /// If the source of the grammar file is given, the diagnostic's range will point to the
+    /// definition of its rule, or to the pattern that it refers to.
+    #[must_use]
+    pub fn to_json(&self, grammar_source: Option<&str>) -> DiagnosticJSON {
+        let rule = self.rule_name().map(str::to_string);
+        let range = grammar_source.and_then(|source| match self {
+            Self::UnsupportedRegexFlag { pattern, .. } => {
+                source.find(pattern.as_str()).map(|start| DiagnosticRange {
+                    start,
+                    end: start + pattern.len(),
+                })
+            }
+            _ => rule
+                .as_deref()
+                .and_then(|rule| DiagnosticRange::for_rule(source, rule)),
+        });
+        DiagnosticJSON {
+            severity: DiagnosticSeverity::Warning,
+            message: self.to_string(),
+            rule,
+            range,
         }
+    }
+}
+
+/// The name of the optional file, alongside `grammar.js`, that explains why each of a
+/// grammar's declared conflicts is needed.
+pub const CONFLICT_NOTES_FILE_NAME: &str = "conflicts.json";
+
+/// An explanation of why one of a grammar's declared conflicts is needed.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConflictNote {
+    pub conflict: Vec<String>,
+    pub note: String,
+}
+
+/// Attach conflict notes to the warnings produced while generating a parser for the given
+/// grammar.
+///
+/// Every unnecessary conflict that has a note is reported as a [`Diagnostic::StaleConflict`]
+/// including that note, rather than as part of [`Diagnostic::UnnecessaryConflicts`]. Notes for
+/// conflicts that the grammar doesn't declare are reported as well, so that they can be removed.
+pub fn apply_conflict_notes(
+    grammar: &InputGrammar,
+    notes: &[ConflictNote],
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    let sorted = |conflict: &[String]| {
+        let mut conflict = conflict.to_vec();
+        conflict.sort_unstable();
+        conflict
+    };
+    let declared_conflicts = grammar
+        .expected_conflicts
+        .iter()
+        .map(|conflict| sorted(conflict))
+        .collect::<Vec<_>>();
+    let note_for = |conflict: &[String]| {
+        let conflict = sorted(conflict);
+        notes.iter().find(|note| sorted(&note.conflict) == conflict)
+    };
+
+    let mut stale_conflicts = Vec::new();
+    diagnostics.retain_mut(|diagnostic| {
+        if let Diagnostic::UnnecessaryConflicts(conflicts) = diagnostic {
+            conflicts.retain(|conflict| {
+                let Some(note) = note_for(conflict) else {
+                    return true;
+                };
+                stale_conflicts.push(Diagnostic::StaleConflict {
+                    conflict: conflict.clone(),
+                    note: note.note.clone(),
+                });
+                false
+            });
+            !conflicts.is_empty()
+        } else {
+            true
+        }
+    });
+    diagnostics.extend(stale_conflicts);
+
+    for note in notes {
+        if !declared_conflicts.contains(&sorted(&note.conflict)) {
+            diagnostics.push(Diagnostic::UndeclaredConflictNote(note.conflict.clone()));
+        }
+    }
+}
+
+/// Treat any warnings collected during generation as an error, so that a grammar with
+/// warnings can be rejected.
+pub fn deny_warnings(diagnostics: &[Diagnostic]) -> GenerateResult<()> {
+    if diagnostics.is_empty() {
         Ok(())
+    } else {
+        Err(GenerateError::DeniedWarnings(diagnostics.to_vec()))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DiagnosticSeverity {
+    Error,
+    Warning,
+}
+
+/// A byte range within the grammar file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DiagnosticRange {
+    pub start: usize,
+    pub end: usize,
+}
+
+/// A warning or error from parser generation, in a form that editors can display.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DiagnosticJSON {
+    pub severity: DiagnosticSeverity,
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rule: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub range: Option<DiagnosticRange>,
+}
+
+impl DiagnosticRange {
+    /// Find the name of the given rule where it is defined as a property in the grammar's
+    /// `rules` object, e.g. `rule_name: $ => ...`.
+    fn for_rule(source: &str, rule: &str) -> Option<Self> {
+        let is_identifier_char = |c: char| c.is_alphanumeric() || c == '_' || c == '$';
+        source.match_indices(rule).find_map(|(start, _)| {
+            let end = start + rule.len();
+            let before = source[..start].chars().next_back();
+            let after = source[end..].trim_start_matches(['"', '\'']).trim_start();
+            (!before.is_some_and(is_identifier_char) && after.starts_with(':'))
+                .then_some(Self { start, end })
+        })
+    }
+
+    /// Find the first reference to the given symbol within the definition of the given rule.
+    fn for_reference(source: &str, rule: &str, symbol: &str) -> Option<Self> {
+        let is_identifier_char = |c: char| c.is_alphanumeric() || c == '_' || c == '$';
+        let rule_range = Self::for_rule(source, rule)?;
+        let definition_end = Self::definition_end(source, rule_range.end);
+        source[rule_range.end..definition_end]
+            .match_indices(symbol)
+            .map(|(start, _)| rule_range.end + start)
+            .find(|start| {
+                let end = start + symbol.len();
+                !source[..*start]
+                    .chars()
+                    .next_back()
+                    .is_some_and(is_identifier_char)
+                    && !source[end..].chars().next().is_some_and(is_identifier_char)
+            })
+            .map(|start| Self {
+                start,
+                end: start + symbol.len(),
+            })
+    }
+
+    /// Find the end of the definition that follows a rule's name at `start`, which is the
+    /// comma or closing bracket after it at the same nesting depth, i.e. where the next rule's
+    /// name begins. Brackets within strings, comments, and regex literals are skipped.
+    fn definition_end(source: &str, start: usize) -> usize {
+        let bytes = source.as_bytes();
+        let skip_past = |from: usize, terminator: &[u8]| {
+            bytes[from..]
+                .windows(terminator.len())
+                .position(|window| window == terminator)
+                .map_or(bytes.len(), |i| from + i + terminator.len())
+        };
+
+        // Find the delimiter that closes the string or regex literal that starts at `from`. In
+        // a regex literal, a `/` within a character class doesn't end the literal.
+        let literal_end = |from: usize| {
+            let delimiter = bytes[from];
+            let mut in_class = false;
+            let mut i = from + 1;
+            while i < bytes.len() && (bytes[i] != delimiter || in_class) {
+                match bytes[i] {
+                    b'\\' => i += 1,
+                    b'[' if delimiter == b'/' => in_class = true,
+                    b']' if delimiter == b'/' => in_class = false,
+                    _ => {}
+                }
+                i += 1;
+            }
+            i
+        };
+
+        let mut depth = 0usize;
+        let mut previous = b':';
+        let mut i = start;
+        while i < bytes.len() {
+            let c = bytes[i];
+            match c {
+                b'/' if bytes.get(i + 1) == Some(&b'/') => {
+                    i = skip_past(i, b"\n");
+                    continue;
+                }
+                b'/' if bytes.get(i + 1) == Some(&b'*') => {
+                    i = skip_past(i + 2, b"*/");
+                    continue;
+                }
+                b'"' | b'\'' | b'`' => i = literal_end(i),
+                // A `/` starts a regex literal rather than a division where an operand is
+                // expected.
+                b'/' if b"(,=:[!&|?{};".contains(&previous) => i = literal_end(i),
+                b'(' | b'[' | b'{' => depth += 1,
+                b')' | b']' | b'}' | b',' if depth == 0 => return i,
+                b')' | b']' | b'}' => depth -= 1,
+                _ => {}
+            }
+            if !c.is_ascii_whitespace() {
+                previous = c;
+            }
+            i += 1;
+        }
+        bytes.len()
+    }
+}
+
+impl GenerateError {
+    /// The name of the rule whose definition contains the problem, if any.
+    #[must_use]
+    pub fn rule_name(&self) -> Option<&str> {
+        match self {
+            Self::Prepare(PrepareGrammarError::InternSymbols(error)) => error.rule_name(),
+            _ => None,
+        }
+    }
+
+    /// Convert this error to the format used by `tree-sitter generate --diagnostics json`.
+    ///
+    /// If the source of the grammar file is given, the error's range will point to the
+    /// reference to an undefined symbol, or else to the definition of the error's rule.
+    #[must_use]
+    pub fn to_json(&self, grammar_source: Option<&str>) -> DiagnosticJSON {
+        let rule = self.rule_name().map(str::to_string);
+        let range = grammar_source
+            .zip(rule.as_deref())
+            .and_then(|(source, rule)| {
+                match self {
+                    Self::Prepare(PrepareGrammarError::InternSymbols(
+                        InternSymbolsError::Undefined { name, .. },
+                    )) => DiagnosticRange::for_reference(source, rule, name),
+                    _ => None,
+                }
+                .or_else(|| DiagnosticRange::for_rule(source, rule))
+            });
+        DiagnosticJSON {
+            severity: DiagnosticSeverity::Error,
+            message: self.to_string(),
+            rule,
+            range,
+        }
+    }
+}
+
+impl From<&GenerateError> for DiagnosticJSON {
+    fn from(error: &GenerateError) -> Self {
+        error.to_json(None)
+    }
+}
+
+/// A summary of a generated parser, giving an overview of the grammar's size.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GenerateReport {
+    pub name: String,
+    pub rules: usize,
+    /// The number of rules that were added to match repetitions.
+    pub auxiliary_rules: usize,
+    pub tokens: usize,
+    pub external_tokens: usize,
+    pub supertypes: usize,
+    pub parse_states: usize,
+    pub lex_states: usize,
+    pub resolved_conflicts: usize,
+    /// The size of the generated `parser.c`, along with any files its tables were split into,
+    /// in bytes.
+    pub parser_size: usize,
+    /// The pairs of tokens that match overlapping strings, and how the lexer resolves them.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub token_conflicts: Vec<TokenConflict>,
+}
+
+/// A pair of tokens that can match overlapping strings, as listed in a [`GenerateReport`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TokenConflict {
+    /// The token that the lexer prefers when both tokens are valid.
+    pub token: String,
+    /// The token that `token` conflicts with.
+    pub other: String,
+    pub resolution: TokenConflictResolution,
+}
+
+/// How the lexer tells apart the two tokens of a [`TokenConflict`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TokenConflictResolution {
+    /// Both tokens match some of the same strings, and `token` has a higher precedence,
+    /// either explicitly or because it is a string rather than a pattern.
+    Precedence,
+    /// `token` matches strings that start with a match of `other`, but only continues those
+    /// matches with characters that can't follow `other`.
+    FollowingTokens,
+    /// Nothing in the grammar decides between the tokens. Either both match the same string
+    /// with the same precedence, in which case the token that is defined first is preferred,
+    /// or `token` continues a match of `other` with a character that can also follow `other`,
+    /// in which case the longer match is preferred.
+    Unresolved,
+}
+
+impl std::fmt::Display for GenerateReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Summary of the `{}` grammar:", self.name)?;
+        for (label, value) in [
+            ("Rules", self.rules),
+            ("Auxiliary rules", self.auxiliary_rules),
+            ("Tokens", self.tokens),
+            ("External tokens", self.external_tokens),
+            ("Supertypes", self.supertypes),
+            ("Parse states", self.parse_states),
+            ("Lex states", self.lex_states),
+            ("Resolved conflicts", self.resolved_conflicts),
+        ] {
+            writeln!(f, "  {label:<20}{value}")?;
+        }
+        write!(f, "  {:<20}{} bytes", "parser.c size", self.parser_size)
     }
 }
 
 #[cfg(feature = "load")]
 #[expect(
     clippy::too_many_arguments,
+    clippy::fn_params_excessive_bools,
     reason = "all parameters are required for parser generation"
 )]
 pub fn generate_parser_in_directory<T, U, V>(
@@ -315,9 +737,16 @@ pub fn generate_parser_in_directory<T, U, V>(
     grammar_path: Option<V>,
     abi_version: usize,
     report_symbol_name: Option<&str>,
+    lex_dfa_path: Option<&Path>,
+    railroad_path: Option<&Path>,
     js_runtime: Option<&str>,
     generate_parser: bool,
     optimizations: OptLevel,
+    cxx: bool,
+    lexer_only: bool,
+    split_tables: bool,
+    strict: bool,
+    report: Option<&mut GenerateReport>,
     diagnostics: &mut Vec<Diagnostic>,
 ) -> GenerateResult<()>
 where
@@ -368,17 +797,27 @@ where
         .map_err(|e| GenerateError::IO(IoError::new(e, Some(src_path.as_path()))))?;
 
     if grammar_path.file_name().unwrap() != "grammar.json" {
-        fs::write(src_path.join("grammar.json"), &grammar_json)
-            .map_err(|e| GenerateError::IO(IoError::new(e, Some(src_path.as_path()))))?;
+        write_file_if_changed(&src_path.join("grammar.json"), &grammar_json)?;
     }
 
     // If our job is only to generate `grammar.json` and not `parser.c`, stop here.
     let input_grammar = parse_grammar(&grammar_json, diagnostics)?;
 
+    if let Some(lex_dfa_path) = lex_dfa_path {
+        // Diagnostics are reported by the main generation pass below.
+        let (_, lexical_grammar, _, _) = prepare_grammar(&input_grammar, &mut Vec::new())?;
+        write_file(lex_dfa_path, render_lex_dfa_dot(&lexical_grammar))?;
+    }
+
+    if let Some(railroad_path) = railroad_path {
+        write_file(railroad_path, render_railroad_svg(&input_grammar))?;
+    }
+
     if !generate_parser {
         let node_types_json =
-            generate_node_types_from_grammar(&input_grammar, diagnostics)?.node_types_json;
-        write_file(&src_path.join("node-types.json"), node_types_json)?;
+            generate_node_types_from_grammar(&input_grammar, lexer_only, strict, diagnostics)?
+                .node_types_json;
+        write_file_if_changed(&src_path.join("node-types.json"), node_types_json)?;
         return Ok(());
     }
 
@@ -387,23 +826,57 @@ where
     // Generate the parser and related files.
     let GeneratedParser {
         c_code,
+        split_files,
         node_types_json,
+        report: generated_report,
     } = generate_parser_for_grammar_with_opts(
         &input_grammar,
         abi_version,
         semantic_version.map(|v| (v.major as u8, v.minor as u8, v.patch as u8)),
         report_symbol_name,
         optimizations,
+        cxx,
+        lexer_only,
+        split_tables,
+        strict,
         diagnostics,
     )?;
 
-    write_file(&src_path.join("parser.c"), c_code)?;
-    write_file(&src_path.join("node-types.json"), node_types_json)?;
+    let conflict_notes_path = repo_path.join(CONFLICT_NOTES_FILE_NAME);
+    if conflict_notes_path.exists() {
+        let notes = fs::read_to_string(&conflict_notes_path)
+            .map_err(|e| GenerateError::IO(IoError::new(e, Some(conflict_notes_path.as_path()))))?;
+        let notes = serde_json::from_str::<Vec<ConflictNote>>(&notes)
+            .map_err(|e| GenerateError::ConflictNotes(e.to_string()))?;
+        apply_conflict_notes(&input_grammar, &notes, diagnostics);
+    }
+
+    if let Some(report) = report {
+        *report = generated_report;
+    }
+
+    write_file_if_changed(&src_path.join("parser.c"), c_code)?;
+    for (file_name, contents) in &split_files {
+        write_file_if_changed(&src_path.join(file_name), contents)?;
+    }
+    // Remove the files from a previous split generation, so they aren't compiled along with
+    // the new parser.
+    for file_name in SPLIT_FILE_NAMES {
+        let path = src_path.join(file_name);
+        if !split_files.iter().any(|(name, _)| *name == file_name)
+            && fs::read_to_string(&path)
+                .is_ok_and(|contents| contents.starts_with(GENERATED_BANNER))
+        {
+            fs::remove_file(&path)
+                .map_err(|e| GenerateError::IO(IoError::new(e, Some(path.as_path()))))?;
+        }
+    }
+    write_file_if_changed(&src_path.join("node-types.json"), node_types_json)?;
     fs::create_dir_all(&header_path)
         .map_err(|e| GenerateError::IO(IoError::new(e, Some(header_path.as_path()))))?;
-    write_file(&header_path.join("alloc.h"), ALLOC_HEADER)?;
-    write_file(&header_path.join("array.h"), ARRAY_HEADER)?;
-    write_file(&header_path.join("parser.h"), PARSER_HEADER)?;
+    write_file_if_changed(&header_path.join("alloc.h"), ALLOC_HEADER)?;
+    write_file_if_changed(&header_path.join("array.h"), ARRAY_HEADER)?;
+    write_file_if_changed(&header_path.join("parser.h"), PARSER_HEADER)?;
 
     Ok(())
 }
@@ -420,17 +893,196 @@ pub fn generate_parser_for_grammar(
         semantic_version,
         None,
         OptLevel::default(),
+        false,
+        false,
+        false,
+        false,
         diagnostics,
     )?;
     Ok((input_grammar.name, parser.c_code))
 }
 
+/// The files that [`generate_to_memory`] generates for a grammar.
+#[cfg(feature = "load")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GeneratedFiles {
+    /// The grammar's name.
+    pub name: String,
+    /// The contents of `src/parser.c`.
+    pub parser_c: String,
+    /// The contents of `src/node-types.json`.
+    pub node_types_json: String,
+    /// The contents of `src/grammar.json`.
+    pub grammar_json: String,
+}
+
+/// Generate a parser for the given grammar JSON, returning the generated files instead of
+/// writing them to a directory.
+///
+/// The headers that the parser includes don't depend on the grammar, so they aren't returned;
+/// they are available as [`ALLOC_HEADER`], [`ARRAY_HEADER`], and [`PARSER_HEADER`].
+#[cfg(feature = "load")]
+pub fn generate_to_memory(
+    grammar_json: &str,
+    semantic_version: Option<(u8, u8, u8)>,
+    diagnostics: &mut Vec<Diagnostic>,
+) -> GenerateResult<GeneratedFiles> {
+    let input_grammar = parse_grammar(grammar_json, diagnostics)?;
+    let parser = generate_parser_for_grammar_with_opts(
+        &input_grammar,
+        LANGUAGE_VERSION,
+        semantic_version,
+        None,
+        OptLevel::default(),
+        false,
+        false,
+        false,
+        false,
+        diagnostics,
+    )?;
+    Ok(GeneratedFiles {
+        name: input_grammar.name,
+        parser_c: parser.c_code,
+        node_types_json: parser.node_types_json,
+        grammar_json: grammar_json.to_string(),
+    })
+}
+
+/// A grammar rule that parsing can exercise, as listed by [`coverable_rules_for_grammar`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CoverableRule {
+    /// The rule's name in the grammar.
+    pub name: String,
+    /// The symbol that the parser reduces, or the lexer recognizes, when the rule is exercised.
+    pub symbol: CoverableSymbol,
+    /// The symbols whose presence also means that the rule was exercised.
+    ///
+    /// The parser skips reducing the alternatives of a hidden rule that consist of a single
+    /// symbol, so for such a rule, these are the symbols that make up those alternatives.
+    pub unit_symbols: Vec<CoverableSymbol>,
+}
+
+/// A symbol of a generated parser, named the way that the parser logs it.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+pub struct CoverableSymbol {
+    /// The symbol's name, which is the alias's name if the symbol is aliased everywhere it's
+    /// used.
+    pub name: String,
+    /// Whether the symbol is a token, which is lexed instead of being reduced.
+    pub is_token: bool,
+}
+
+/// List the rules of a grammar that parsing can exercise, in the order they're defined.
+///
+/// Rules that are inlined are left out, because the parser never reduces them on their own,
+/// and so are the auxiliary rules that are created for repetitions and string literals.
+pub fn coverable_rules_for_grammar(grammar_json: &str) -> GenerateResult<Vec<CoverableRule>> {
+    let mut diagnostics = Vec::new();
+    let input_grammar = parse_grammar(grammar_json, &mut diagnostics)?;
+    let (syntax_grammar, lexical_grammar, _, simple_aliases) =
+        prepare_grammar(&input_grammar, &mut diagnostics)?;
+
+    let symbol_for = |symbol: Symbol| {
+        let name = if let Some(alias) = simple_aliases.get(&symbol) {
+            &alias.value
+        } else if symbol.is_terminal() {
+            &lexical_grammar.variables[symbol.index].name
+        } else if symbol.is_external() {
+            &syntax_grammar.external_tokens[symbol.index].name
+        } else {
+            &syntax_grammar.variables[symbol.index].name
+        };
+        CoverableSymbol {
+            name: name.clone(),
+            is_token: !symbol.is_non_terminal(),
+        }
+    };
+
+    // These are the same conditions under which the parse table's unit reductions are removed.
+    let aliased_symbols = syntax_grammar
+        .variables
+        .iter()
+        .flat_map(|variable| &variable.productions)
+        .flat_map(|production| &production.steps)
+        .filter(|step| step.alias.is_some())
+        .map(|step| step.symbol)
+        .collect::<Vec<_>>();
+    let has_unit_reductions_removed = |symbol: Symbol| {
+        symbol.is_non_terminal()
+            && syntax_grammar.variables[symbol.index].kind != VariableType::Named
+            && !simple_aliases.contains_key(&symbol)
+            && !syntax_grammar.supertype_symbols.contains(&symbol)
+            && !syntax_grammar.extra_symbols.contains(&symbol)
+            && !aliased_symbols.contains(&symbol)
+    };
+    let unit_symbols = |symbol: Symbol| {
+        let mut result = Vec::new();
+        if !has_unit_reductions_removed(symbol) {
+            return result;
+        }
+        let mut stack = vec![symbol];
+        let mut visited = vec![symbol];
+        while let Some(symbol) = stack.pop() {
+            for production in &syntax_grammar.variables[symbol.index].productions {
+                let [step] = production.steps.as_slice() else {
+                    continue;
+                };
+                if step.alias.is_some()
+                    || step.field_name.is_some()
+                    || visited.contains(&step.symbol)
+                {
+                    continue;
+                }
+                visited.push(step.symbol);
+                let is_inlined = syntax_grammar.variables_to_inline.contains(&step.symbol);
+                if is_inlined || has_unit_reductions_removed(step.symbol) {
+                    stack.push(step.symbol);
+                }
+                if !is_inlined {
+                    result.push(symbol_for(step.symbol));
+                }
+            }
+        }
+        result.sort_unstable();
+        result
+    };
+
+    let tokens = lexical_grammar
+        .variables
+        .iter()
+        .enumerate()
+        .filter(|(_, variable)| variable.kind != VariableType::Anonymous)
+        .map(|(index, variable)| (variable.name.as_str(), Symbol::terminal(index)));
+    let rules = syntax_grammar
+        .variables
+        .iter()
+        .enumerate()
+        .map(|(index, variable)| (variable.name.as_str(), Symbol::non_terminal(index)))
+        .filter(|(_, symbol)| !syntax_grammar.variables_to_inline.contains(symbol));
+    let symbols = tokens.chain(rules).collect::<BTreeMap<_, _>>();
+
+    Ok(input_grammar
+        .variables
+        .iter()
+        .filter_map(|variable| {
+            let symbol = *symbols.get(variable.name.as_str())?;
+            Some(CoverableRule {
+                name: variable.name.clone(),
+                symbol: symbol_for(symbol),
+                unit_symbols: unit_symbols(symbol),
+            })
+        })
+        .collect())
+}
+
 fn generate_node_types_from_grammar(
     input_grammar: &InputGrammar,
+    lexer_only: bool,
+    strict: bool,
     diagnostics: &mut Vec<Diagnostic>,
 ) -> GenerateResult<JSONOutput> {
-    let (syntax_grammar, lexical_grammar, inlines, simple_aliases) =
-        prepare_grammar(input_grammar, diagnostics)?;
+    let (syntax_grammar, lexical_grammar, inlines, simple_aliases, auxiliary_rules) =
+        prepare_grammar_with_opts(input_grammar, lexer_only, strict, diagnostics)?;
     let variable_info =
         node_types::get_variable_info(&syntax_grammar, &lexical_grammar, &simple_aliases)?;
 
@@ -449,26 +1101,42 @@ fn generate_node_types_from_grammar(
         inlines,
         simple_aliases,
         variable_info,
+        auxiliary_rules,
     })
 }
 
+#[expect(
+    clippy::too_many_arguments,
+    clippy::fn_params_excessive_bools,
+    reason = "all parameters are required for parser generation"
+)]
 fn generate_parser_for_grammar_with_opts(
     input_grammar: &InputGrammar,
     abi_version: usize,
     semantic_version: Option<(u8, u8, u8)>,
     report_symbol_name: Option<&str>,
     optimizations: OptLevel,
+    cxx: bool,
+    lexer_only: bool,
+    split_tables: bool,
+    strict: bool,
     diagnostics: &mut Vec<Diagnostic>,
 ) -> GenerateResult<GeneratedParser> {
+    // Reject an unsupported ABI version before doing the work of building the parse tables.
+    if !(ABI_VERSION_MIN..=ABI_VERSION_MAX).contains(&abi_version) {
+        Err(RenderError::ABI(abi_version))?;
+    }
+
     let JSONOutput {
         syntax_grammar,
         lexical_grammar,
         inlines,
         simple_aliases,
         variable_info,
+        auxiliary_rules,
         #[cfg(feature = "load")]
         node_types_json,
-    } = generate_node_types_from_grammar(input_grammar, diagnostics)?;
+    } = generate_node_types_from_grammar(input_grammar, lexer_only, strict, diagnostics)?;
     let supertype_symbol_map =
         node_types::get_supertype_symbol_map(&syntax_grammar, &simple_aliases, &variable_info);
     let tables = build_tables(
@@ -481,7 +1149,31 @@ fn generate_parser_for_grammar_with_opts(
         optimizations,
         diagnostics,
     )?;
-    let c_code = render_c_code(
+    let mut report = GenerateReport {
+        name: input_grammar.name.clone(),
+        rules: input_grammar.variables.len(),
+        auxiliary_rules,
+        tokens: lexical_grammar.variables.len(),
+        external_tokens: syntax_grammar.external_tokens.len(),
+        supertypes: syntax_grammar.supertype_symbols.len(),
+        parse_states: tables.parse_table.states.len(),
+        lex_states: tables.main_lex_table.states.len() + tables.keyword_lex_table.states.len(),
+        resolved_conflicts: tables.parse_table.resolved_conflict_count,
+        parser_size: 0,
+        token_conflicts: tables
+            .token_conflicts
+            .iter()
+            .map(|&(i, j, resolution)| TokenConflict {
+                token: lexical_grammar.variables[i].name.clone(),
+                other: lexical_grammar.variables[j].name.clone(),
+                resolution,
+            })
+            .collect(),
+    };
+    let RenderedParser {
+        c_code,
+        split_files,
+    } = render_c_code(
         &input_grammar.name,
         tables,
         syntax_grammar,
@@ -490,11 +1182,21 @@ fn generate_parser_for_grammar_with_opts(
         abi_version,
         semantic_version,
         supertype_symbol_map,
+        optimizations,
+        cxx,
+        split_tables,
     )?;
+    report.parser_size = c_code.len()
+        + split_files
+            .iter()
+            .map(|(_, contents)| contents.len())
+            .sum::<usize>();
     Ok(GeneratedParser {
         c_code,
+        split_files,
         #[cfg(feature = "load")]
         node_types_json,
+        report,
     })
 }
 
@@ -685,9 +1387,22 @@ pub fn write_file(path: &Path, body: impl AsRef<[u8]>) -> GenerateResult<()> {
     fs::write(path, body).map_err(|e| GenerateError::IO(IoError::new(e, Some(path))))
 }
 
+/// Write a generated file, unless it already exists with exactly the same contents.
+///
+/// Leaving unchanged files alone avoids updating their modification times, which
+/// would otherwise trigger needless rebuilds.
+#[cfg(feature = "load")]
+fn write_file_if_changed(path: &Path, body: impl AsRef<[u8]>) -> GenerateResult<()> {
+    if fs::read(path).is_ok_and(|existing| existing == body.as_ref()) {
+        return Ok(());
+    }
+    write_file(path, body)
+}
+
 #[cfg(test)]
 mod tests {
     use super::{LANGUAGE_VERSION, PARSER_HEADER};
+
     #[test]
     fn test_language_versions_are_in_sync() {
         let api_h = include_str!("../../../lib/include/tree_sitter/api.h");
@@ -710,4 +1425,790 @@ mod tests {
             "parser.h.inc is out of sync with lib/src/parser.h. Run: cp lib/src/parser.h crates/generate/src/parser.h.inc"
         );
     }
+
+    #[cfg(feature = "load")]
+    #[test]
+    fn test_regenerating_unchanged_files() {
+        use std::{
+            fs,
+            path::Path,
+            time::{Duration, SystemTime},
+        };
+
+        use super::{OptLevel, generate_parser_in_directory};
+
+        let dir = tempfile::tempdir().unwrap();
+        let src_path = dir.path().join("src");
+        fs::create_dir_all(&src_path).unwrap();
+        let grammar_path = src_path.join("grammar.json");
+        let grammar_json = |rule: &str| {
+            format!(
+                r#"{{
+                    "name": "the_language",
+                    "rules": {{
+                        "source_file": {{"type": "SYMBOL", "name": "word"}},
+                        "word": {{"type": "PATTERN", "value": "{rule}"}}
+                    }}
+                }}"#
+            )
+        };
+        let generate = || {
+            generate_parser_in_directory(
+                dir.path(),
+                None::<&Path>,
+                Some(&grammar_path),
+                LANGUAGE_VERSION,
+                None,
+                None,
+                None,
+                None,
+                true,
+                OptLevel::default(),
+                false,
+                false,
+                false,
+                false,
+                None,
+                &mut Vec::new(),
+            )
+            .unwrap();
+        };
+
+        fs::write(&grammar_path, grammar_json("[a-z]+")).unwrap();
+        generate();
+
+        // Backdate the generated files, so that any rewrite would update their
+        // modification times.
+        let generated_paths = [
+            src_path.join("parser.c"),
+            src_path.join("node-types.json"),
+            src_path.join("tree_sitter").join("parser.h"),
+        ];
+        let old_time = SystemTime::now() - Duration::from_secs(3600);
+        for path in &generated_paths {
+            fs::File::options()
+                .write(true)
+                .open(path)
+                .unwrap()
+                .set_modified(old_time)
+                .unwrap();
+        }
+        let modified = |path: &Path| fs::metadata(path).unwrap().modified().unwrap();
+
+        generate();
+        for path in &generated_paths {
+            assert_eq!(modified(path), old_time, "{} was rewritten", path.display());
+        }
+
+        // Changing the grammar rewrites the parser, but not its unchanged headers.
+        fs::write(&grammar_path, grammar_json("[a-z0-9]+")).unwrap();
+        generate();
+        assert_ne!(modified(&generated_paths[0]), old_time);
+        assert_eq!(modified(&generated_paths[1]), old_time);
+        assert_eq!(modified(&generated_paths[2]), old_time);
+    }
+
+    #[test]
+    fn test_diagnostics_json() {
+        use super::{DiagnosticJSON, generate_parser_for_grammar};
+
+        let grammar_js = r"module.exports = grammar({
+  name: 'the_language',
+  rules: {
+    source_file: $ => repeat($.keyword),
+    keyword: $ => choice('if'),
+  },
+});
+";
+        let grammar_json = r#"{
+            "name": "the_language",
+            "rules": {
+                "source_file": {"type": "REPEAT", "content": {"type": "SYMBOL", "name": "keyword"}},
+                "keyword": {"type": "CHOICE", "members": [{"type": "STRING", "value": "if"}]}
+            }
+        }"#;
+
+        let mut diagnostics = Vec::new();
+        generate_parser_for_grammar(grammar_json, None, &mut diagnostics).unwrap();
+        let json = diagnostics
+            .iter()
+            .map(|d| d.to_json(Some(grammar_js)))
+            .collect::<Vec<DiagnosticJSON>>();
+        assert_eq!(
+            serde_json::to_string_pretty(&json).unwrap(),
+            r#"[
+  {
+    "severity": "warning",
+    "message": "rule keyword contains a `choice` rule with a single element. this is unnecessary.",
+    "rule": "keyword",
+    "range": {
+      "start": 107,
+      "end": 114
+    }
+  },
+  {
+    "severity": "warning",
+    "message": "rule `keyword` only matches the string \"if\". consider using a token or an alias instead.",
+    "rule": "keyword",
+    "range": {
+      "start": 107,
+      "end": 114
+    }
+  }
+]"#
+        );
+        assert_eq!(&grammar_js[107..114], "keyword");
+    }
+
+    #[test]
+    fn test_error_json() {
+        use super::{DiagnosticRange, generate_parser_for_grammar};
+
+        let grammar_js = r"module.exports = grammar({
+  name: 'the_language',
+  rules: {
+    source_file: $ => repeat($.statement),
+    statement: $ => seq('let', $.expresion, ';'),
+    expression: $ => /[a-z]+/,
+  },
+});
+";
+        let grammar_json = r#"{
+            "name": "the_language",
+            "rules": {
+                "source_file": {"type": "REPEAT", "content": {"type": "SYMBOL", "name": "statement"}},
+                "statement": {"type": "SEQ", "members": [
+                    {"type": "STRING", "value": "let"},
+                    {"type": "SYMBOL", "name": "expresion"},
+                    {"type": "STRING", "value": ";"}
+                ]},
+                "expression": {"type": "PATTERN", "value": "[a-z]+"}
+            }
+        }"#;
+
+        let error = generate_parser_for_grammar(grammar_json, None, &mut Vec::new()).unwrap_err();
+        assert_eq!(error.rule_name(), Some("statement"));
+
+        let json = error.to_json(Some(grammar_js));
+        assert_eq!(
+            json.message,
+            "Undefined symbol `expresion` in rule `statement`"
+        );
+        assert_eq!(json.rule.as_deref(), Some("statement"));
+        let range = json.range.unwrap();
+        assert_eq!(&grammar_js[range.start..range.end], "expresion");
+        assert_eq!(
+            grammar_js[..range.start].lines().last(),
+            Some("    statement: $ => seq('let', $.")
+        );
+
+        let range = error.to_json(Some(grammar_json)).range.unwrap();
+        assert_eq!(&grammar_json[range.start..range.end], "expresion");
+
+        // If the rule's definition doesn't mention the symbol, the range points to the rule
+        // rather than to a mention in a later rule. Brackets and commas within strings,
+        // comments, and regexes don't end the definition early.
+        let grammar_js = r"module.exports = grammar({
+  name: 'the_language',
+  rules: {
+    source_file: $ => repeat($.statement),
+    statement: $ => seq('let', /[,)]/, $['expres' + 'ion'], // (
+      ')', /* ] */ ';'),
+    expression: $ => /[a-z]+/, // not an expresion
+  },
+});
+";
+        let range = error.to_json(Some(grammar_js)).range.unwrap();
+        assert_eq!(&grammar_js[range.start..range.end], "statement");
+        let statement_end = DiagnosticRange::definition_end(grammar_js, range.end);
+        assert!(grammar_js[..statement_end].ends_with("';')"));
+
+        // Without the grammar's source, the error has no range.
+        assert!(error.to_json(None).range.is_none());
+    }
+
+    #[test]
+    fn test_denying_warnings() {
+        use super::{Diagnostic, GenerateError, deny_warnings, generate_parser_for_grammar};
+
+        let grammar_json = |rule: &str| {
+            format!(
+                r#"{{
+                    "name": "the_language",
+                    "rules": {{
+                        "source_file": {{"type": "REPEAT", "content": {{"type": "SYMBOL", "name": "word"}}}},
+                        "word": {rule}
+                    }}
+                }}"#
+            )
+        };
+
+        let mut diagnostics = Vec::new();
+        generate_parser_for_grammar(
+            &grammar_json(r#"{"type": "PATTERN", "value": "[a-z]+"}"#),
+            None,
+            &mut diagnostics,
+        )
+        .unwrap();
+        assert!(deny_warnings(&diagnostics).is_ok());
+
+        let mut diagnostics = Vec::new();
+        generate_parser_for_grammar(
+            &grammar_json(
+                r#"{"type": "SEQ", "members": [{"type": "PATTERN", "value": "[a-z]+", "flags": "x"}]}"#,
+            ),
+            None,
+            &mut diagnostics,
+        )
+        .unwrap();
+        let error = deny_warnings(&diagnostics).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Warnings are denied, and the grammar has 2 warnings"
+        );
+        let GenerateError::DeniedWarnings(warnings) = error else {
+            panic!("unexpected error {error:?}");
+        };
+        assert!(matches!(
+            warnings.as_slice(),
+            [
+                Diagnostic::UnsupportedRegexFlag { flag: 'x', .. },
+                Diagnostic::UnarySeq { .. }
+            ]
+        ));
+    }
+
+    #[cfg(feature = "load")]
+    #[test]
+    fn test_conflict_notes() {
+        use std::{fs, path::Path};
+
+        use super::{Diagnostic, OptLevel, generate_parser_in_directory};
+
+        let dir = tempfile::tempdir().unwrap();
+        let src_path = dir.path().join("src");
+        fs::create_dir_all(&src_path).unwrap();
+        let rule = |text: &str| {
+            format!(
+                r#"{{"type": "SEQ", "members": [{{"type": "STRING", "value": "{text}"}}, {{"type": "STRING", "value": ";"}}]}}"#
+            )
+        };
+        fs::write(
+            src_path.join("grammar.json"),
+            format!(
+                r#"{{
+                    "name": "the_language",
+                    "rules": {{
+                        "source_file": {{"type": "CHOICE", "members": [
+                            {{"type": "SYMBOL", "name": "a"}},
+                            {{"type": "SYMBOL", "name": "b"}},
+                            {{"type": "SYMBOL", "name": "c"}}
+                        ]}},
+                        "a": {},
+                        "b": {},
+                        "c": {}
+                    }},
+                    "conflicts": [["b", "a"], ["a", "c"]]
+                }}"#,
+                rule("a"),
+                rule("b"),
+                rule("c"),
+            ),
+        )
+        .unwrap();
+        fs::write(
+            dir.path().join("conflicts.json"),
+            r#"[
+                {"conflict": ["a", "b"], "note": "a and b used to share a prefix"},
+                {"conflict": ["b", "c"], "note": "b and c are ambiguous"}
+            ]"#,
+        )
+        .unwrap();
+
+        let mut diagnostics = Vec::new();
+        generate_parser_in_directory(
+            dir.path(),
+            None::<&Path>,
+            Some(&src_path.join("grammar.json")),
+            LANGUAGE_VERSION,
+            None,
+            None,
+            None,
+            None,
+            true,
+            OptLevel::default(),
+            false,
+            false,
+            false,
+            false,
+            None,
+            &mut diagnostics,
+        )
+        .unwrap();
+
+        let messages = diagnostics
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            messages,
+            [
+                "unnecessary conflicts:\n  `a`, `c`",
+                "unnecessary conflict: `a`, `b`. it was declared because: a and b used to share a prefix",
+                "conflicts.json has a note for the conflict `b`, `c`, which is not declared in the grammar",
+            ]
+        );
+        assert!(matches!(
+            &diagnostics[1],
+            Diagnostic::StaleConflict { conflict, .. } if conflict == &["a", "b"]
+        ));
+    }
+
+    #[test]
+    fn test_generate_report() {
+        use std::{fs, path::Path};
+
+        use super::{GenerateReport, OptLevel, generate_parser_in_directory};
+
+        let dir = tempfile::tempdir().unwrap();
+        let src_path = dir.path().join("src");
+        fs::create_dir_all(&src_path).unwrap();
+        let binary = |precedence: u32, operator: &str| {
+            format!(
+                r#"{{"type": "PREC_LEFT", "value": {precedence}, "content": {{"type": "SEQ", "members": [
+                    {{"type": "SYMBOL", "name": "_expression"}},
+                    {{"type": "STRING", "value": "{operator}"}},
+                    {{"type": "SYMBOL", "name": "_expression"}}
+                ]}}}}"#
+            )
+        };
+        fs::write(
+            src_path.join("grammar.json"),
+            format!(
+                r#"{{
+                    "name": "the_language",
+                    "rules": {{
+                        "program": {{"type": "REPEAT", "content": {{"type": "SYMBOL", "name": "_expression"}}}},
+                        "_expression": {{"type": "CHOICE", "members": [
+                            {{"type": "SYMBOL", "name": "binary_expression"}},
+                            {{"type": "SYMBOL", "name": "number"}}
+                        ]}},
+                        "binary_expression": {{"type": "CHOICE", "members": [{}, {}]}},
+                        "number": {{"type": "PATTERN", "value": "\\d+"}}
+                    }},
+                    "externals": [{{"type": "SYMBOL", "name": "comment"}}],
+                    "supertypes": ["_expression"]
+                }}"#,
+                binary(1, "+"),
+                binary(2, "*"),
+            ),
+        )
+        .unwrap();
+
+        let mut report = GenerateReport::default();
+        generate_parser_in_directory(
+            dir.path(),
+            None::<&Path>,
+            Some(&src_path.join("grammar.json")),
+            LANGUAGE_VERSION,
+            None,
+            None,
+            None,
+            None,
+            true,
+            OptLevel::default(),
+            false,
+            false,
+            false,
+            false,
+            Some(&mut report),
+            &mut Vec::new(),
+        )
+        .unwrap();
+
+        let parser_size = fs::read(src_path.join("parser.c")).unwrap().len();
+        assert_eq!(report.name, "the_language");
+        assert_eq!(report.rules, 4);
+        assert_eq!(report.auxiliary_rules, 1);
+        assert_eq!(report.tokens, 3);
+        assert_eq!(report.external_tokens, 1);
+        assert_eq!(report.supertypes, 1);
+        assert_eq!(report.resolved_conflicts, 4);
+        assert_eq!(report.parser_size, parser_size);
+        assert!(report.parse_states > 0);
+        assert!(report.lex_states > 0);
+
+        let text = report.to_string();
+        assert!(text.starts_with("Summary of the `the_language` grammar:\n"));
+        assert!(text.contains("  Auxiliary rules     1\n"));
+        assert!(text.contains("  Resolved conflicts  4\n"));
+        assert!(text.ends_with(&format!("  parser.c size       {parser_size} bytes")));
+    }
+
+    #[test]
+    fn test_generate_unique_symbol_identifiers() {
+        use super::generate_parser_for_grammar;
+
+        // `-` and `DASH`, `a b` and `ab`, and `a-b` and `a_DASHb` each sanitize to the same
+        // identifier.
+        let grammar = r#"{
+            "name": "colliding_names",
+            "rules": {
+                "program": {"type": "SEQ", "members": [
+                    {"type": "STRING", "value": "-"},
+                    {"type": "STRING", "value": "DASH"},
+                    {"type": "STRING", "value": "a b"},
+                    {"type": "STRING", "value": "ab"},
+                    {"type": "STRING", "value": "a-b"},
+                    {"type": "STRING", "value": "a_DASHb"}
+                ]}
+            }
+        }"#;
+        let (_, c_code) = generate_parser_for_grammar(grammar, None, &mut Vec::new()).unwrap();
+
+        let enum_start = c_code.find("enum ts_symbol_identifiers {").unwrap();
+        let enum_end = enum_start + c_code[enum_start..].find("};").unwrap();
+        let identifiers = c_code[enum_start..enum_end]
+            .lines()
+            .skip(1)
+            .filter_map(|line| line.trim().split_once(" = ").map(|(id, _)| id))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            identifiers,
+            [
+                "anon_sym_DASH",
+                "anon_sym_DASH2",
+                "anon_sym_ab",
+                "anon_sym_ab2",
+                "anon_sym_a_DASHb",
+                "anon_sym_a_DASHb2",
+                "sym_program",
+            ]
+        );
+
+        // Generating the same grammar again produces the same identifiers.
+        let (_, c_code_again) =
+            generate_parser_for_grammar(grammar, None, &mut Vec::new()).unwrap();
+        assert_eq!(c_code, c_code_again);
+    }
+
+    #[test]
+    fn test_generate_abi_versions() {
+        use super::{
+            ABI_VERSION_MAX, ABI_VERSION_MIN, GenerateError, OptLevel, RenderError,
+            generate_parser_for_grammar_with_opts, parse_grammar,
+        };
+
+        let grammar = parse_grammar(
+            r#"{
+                "name": "abi_versions",
+                "rules": {
+                    "program": {"type": "STRING", "value": "x"}
+                }
+            }"#,
+            &mut Vec::new(),
+        )
+        .unwrap();
+        let generate = |abi_version| {
+            generate_parser_for_grammar_with_opts(
+                &grammar,
+                abi_version,
+                None,
+                None,
+                OptLevel::default(),
+                false,
+                false,
+                false,
+                false,
+                &mut Vec::new(),
+            )
+        };
+
+        for abi_version in [ABI_VERSION_MIN, ABI_VERSION_MAX] {
+            let c_code = generate(abi_version).unwrap().c_code;
+            assert!(c_code.contains(&format!("#define LANGUAGE_VERSION {abi_version}\n")));
+        }
+
+        for abi_version in [ABI_VERSION_MIN - 1, ABI_VERSION_MAX + 1] {
+            let Err(GenerateError::Render(error @ RenderError::ABI(version))) =
+                generate(abi_version)
+            else {
+                panic!("expected an ABI error for version {abi_version}");
+            };
+            assert_eq!(version, abi_version);
+            assert_eq!(
+                error.to_string(),
+                format!(
+                    "This version of Tree-sitter can only generate parsers with ABI version \
+                     {ABI_VERSION_MIN} - {ABI_VERSION_MAX}, not {abi_version}"
+                )
+            );
+        }
+    }
+
+    #[test]
+    fn test_generate_unique_cxx_identifiers() {
+        use super::{OptLevel, generate_parser_for_grammar_with_opts, parse_grammar};
+
+        // Collapsing runs of underscores for C++ maps `a__b` and `a_b` to the same identifier,
+        // and the anonymous aliases `a-b` and `a_DASHb` sanitize to the same identifier.
+        let grammar = parse_grammar(
+            r#"{
+            "name": "colliding_names",
+            "externals": [
+                {"type": "SYMBOL", "name": "ext__a"},
+                {"type": "SYMBOL", "name": "ext_a"}
+            ],
+            "rules": {
+                "program": {"type": "SEQ", "members": [
+                    {"type": "SYMBOL", "name": "a__b"},
+                    {"type": "SYMBOL", "name": "a_b"},
+                    {"type": "SYMBOL", "name": "ext__a"},
+                    {"type": "SYMBOL", "name": "ext_a"},
+                    {"type": "ALIAS", "named": false, "value": "a-b",
+                     "content": {"type": "STRING", "value": "x"}},
+                    {"type": "ALIAS", "named": false, "value": "a_DASHb",
+                     "content": {"type": "STRING", "value": "y"}},
+                    {"type": "STRING", "value": "x"},
+                    {"type": "STRING", "value": "y"}
+                ]},
+                "a__b": {"type": "STRING", "value": "b"},
+                "a_b": {"type": "STRING", "value": "c"}
+            }
+        }"#,
+            &mut Vec::new(),
+        )
+        .unwrap();
+        let c_code = generate_parser_for_grammar_with_opts(
+            &grammar,
+            LANGUAGE_VERSION,
+            None,
+            None,
+            OptLevel::default(),
+            true,
+            false,
+            false,
+            false,
+            &mut Vec::new(),
+        )
+        .unwrap()
+        .c_code;
+
+        let enum_identifiers = |name: &str| {
+            let enum_start = c_code.find(&format!("enum {name} {{")).unwrap();
+            let enum_end = enum_start + c_code[enum_start..].find("};").unwrap();
+            c_code[enum_start..enum_end]
+                .lines()
+                .skip(1)
+                .filter_map(|line| line.trim().split_once(" = ").map(|(id, _)| id))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            enum_identifiers("ts_symbol_identifiers"),
+            [
+                "anon_sym_x",
+                "anon_sym_y",
+                "sym_a_b",
+                "sym_a_b2",
+                "sym_ext_a",
+                "sym_ext_a2",
+                "sym_program",
+                "anon_alias_sym_a_DASHb",
+                "anon_alias_sym_a_DASHb2",
+            ]
+        );
+        assert_eq!(
+            enum_identifiers("ts_external_scanner_symbol_identifiers"),
+            ["ts_external_token_ext_a", "ts_external_token_ext_a2"]
+        );
+    }
+
+    #[cfg(feature = "load")]
+    #[test]
+    fn test_generate_report_token_conflicts() {
+        use super::{
+            OptLevel, TokenConflict, TokenConflictResolution,
+            generate_parser_for_grammar_with_opts, parse_grammar,
+        };
+
+        // `letters` matches a subset of `identifier`'s strings and nothing gives either one
+        // precedence, the `if` keyword is preferred over `identifier` by its implicit precedence, and
+        // `float` extends `integer` with a `.`, which never follows an `integer`.
+        let grammar = parse_grammar(
+            r#"{
+                "name": "token_conflicts",
+                "rules": {
+                    "program": {"type": "CHOICE", "members": [
+                        {"type": "SEQ", "members": [
+                            {"type": "STRING", "value": "x"},
+                            {"type": "SYMBOL", "name": "identifier"}
+                        ]},
+                        {"type": "SEQ", "members": [
+                            {"type": "STRING", "value": "y"},
+                            {"type": "SYMBOL", "name": "letters"}
+                        ]},
+                        {"type": "SEQ", "members": [
+                            {"type": "STRING", "value": "if"},
+                            {"type": "SYMBOL", "name": "integer"}
+                        ]},
+                        {"type": "SYMBOL", "name": "float"}
+                    ]},
+                    "identifier": {"type": "PATTERN", "value": "[a-z]+"},
+                    "letters": {"type": "PATTERN", "value": "[a-c]+"},
+                    "integer": {"type": "PATTERN", "value": "\\d+"},
+                    "float": {"type": "PATTERN", "value": "\\d+\\.\\d+"}
+                }
+            }"#,
+            &mut Vec::new(),
+        )
+        .unwrap();
+        let report = generate_parser_for_grammar_with_opts(
+            &grammar,
+            LANGUAGE_VERSION,
+            None,
+            None,
+            OptLevel::default(),
+            false,
+            false,
+            false,
+            false,
+            &mut Vec::new(),
+        )
+        .unwrap()
+        .report;
+
+        let conflict = |token: &str, other: &str, resolution| TokenConflict {
+            token: token.to_string(),
+            other: other.to_string(),
+            resolution,
+        };
+        let conflicts = &report.token_conflicts;
+        assert!(conflicts.contains(&conflict(
+            "identifier",
+            "letters",
+            TokenConflictResolution::Unresolved
+        )));
+        assert!(conflicts.contains(&conflict(
+            "if",
+            "identifier",
+            TokenConflictResolution::Precedence
+        )));
+        assert!(conflicts.contains(&conflict(
+            "float",
+            "integer",
+            TokenConflictResolution::FollowingTokens
+        )));
+
+        // Tokens that never match overlapping strings aren't listed.
+        assert!(!conflicts.iter().any(|c| {
+            let names = [c.token.as_str(), c.other.as_str()];
+            names.contains(&"integer") && names.contains(&"identifier")
+        }));
+
+        let json = serde_json::to_value(conflicts).unwrap();
+        assert!(json.as_array().unwrap().contains(&serde_json::json!({
+            "token": "identifier",
+            "other": "letters",
+            "resolution": "unresolved"
+        })));
+    }
+
+    #[test]
+    fn test_coverable_rules_for_grammar() {
+        use super::{CoverableRule, CoverableSymbol, coverable_rules_for_grammar};
+
+        let rules = coverable_rules_for_grammar(
+            r#"{
+                "name": "the_language",
+                "rules": {
+                    "program": {"type": "REPEAT", "content": {"type": "SYMBOL", "name": "_statement"}},
+                    "_statement": {"type": "CHOICE", "members": [
+                        {"type": "SYMBOL", "name": "assignment"},
+                        {"type": "SYMBOL", "name": "value_statement"}
+                    ]},
+                    "assignment": {"type": "SEQ", "members": [
+                        {"type": "SYMBOL", "name": "identifier"},
+                        {"type": "STRING", "value": "="},
+                        {"type": "SYMBOL", "name": "_value"},
+                        {"type": "STRING", "value": ";"}
+                    ]},
+                    "value_statement": {"type": "SEQ", "members": [
+                        {"type": "SYMBOL", "name": "_value"},
+                        {"type": "STRING", "value": ";"}
+                    ]},
+                    "_value": {"type": "CHOICE", "members": [
+                        {"type": "SYMBOL", "name": "identifier"},
+                        {"type": "ALIAS", "named": true, "value": "number", "content": {"type": "SYMBOL", "name": "_digits"}}
+                    ]},
+                    "identifier": {"type": "PATTERN", "value": "[a-z]+"},
+                    "_digits": {"type": "PATTERN", "value": "\\d+"}
+                },
+                "inline": ["_value"]
+            }"#,
+        )
+        .unwrap();
+
+        let symbol = |name: &str, is_token| CoverableSymbol {
+            name: name.to_string(),
+            is_token,
+        };
+        let rule = |name: &str, symbol, unit_symbols| CoverableRule {
+            name: name.to_string(),
+            symbol,
+            unit_symbols,
+        };
+        assert_eq!(
+            rules,
+            [
+                rule("program", symbol("program", false), vec![]),
+                // The parser doesn't reduce the hidden rule when it consists of just one of
+                // its alternatives.
+                rule(
+                    "_statement",
+                    symbol("_statement", false),
+                    vec![
+                        symbol("assignment", false),
+                        symbol("value_statement", false)
+                    ],
+                ),
+                rule("assignment", symbol("assignment", false), vec![]),
+                rule("value_statement", symbol("value_statement", false), vec![]),
+                rule("identifier", symbol("identifier", true), vec![]),
+                // The token is aliased everywhere it's used, so that's its name in the parser.
+                rule("_digits", symbol("number", true), vec![]),
+            ]
+        );
+    }
+
+    #[cfg(feature = "load")]
+    #[test]
+    fn test_generate_to_memory() {
+        use super::generate_to_memory;
+
+        let grammar_json = r#"{
+            "name": "the_language",
+            "rules": {
+                "source_file": {"type": "REPEAT", "content": {"type": "SYMBOL", "name": "word"}},
+                "word": {"type": "PATTERN", "value": "[a-z]+"}
+            }
+        }"#;
+        let files = generate_to_memory(grammar_json, None, &mut Vec::new()).unwrap();
+
+        assert_eq!(files.name, "the_language");
+        assert!(
+            files
+                .parser_c
+                .contains("const TSLanguage *tree_sitter_the_language(void) {")
+        );
+        assert_eq!(files.grammar_json, grammar_json);
+
+        let node_types = serde_json::from_str::<serde_json::Value>(&files.node_types_json).unwrap();
+        let kinds = node_types
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|node_type| node_type["type"].as_str().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(kinds, ["source_file", "word"]);
+    }
 }
@@ -3,6 +3,8 @@ use std::{
     fmt,
 };
 
+use serde::{Deserialize, Serialize};
+
 use crate::node_types::ChildType;
 
 use super::{
@@ -10,7 +12,7 @@ use super::{
     rules::{Alias, Associativity, Precedence, Rule, Symbol, TokenSet},
 };
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 pub enum VariableType {
     Hidden,
     Auxiliary,
@@ -20,14 +22,14 @@ pub enum VariableType {
 
 // Input grammar
 
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Variable {
     pub name: String,
     pub kind: VariableType,
     pub rule: Rule,
 }
 
-#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 pub enum PrecedenceEntry {
     Name(String),
     Symbol(String),
@@ -41,13 +43,18 @@ pub struct InputGrammar {
     pub expected_conflicts: Vec<Vec<String>>,
     pub precedence_orderings: Vec<Vec<PrecedenceEntry>>,
     pub external_tokens: Vec<Rule>,
+    /// The kinds of the named external tokens that were marked as hidden or visible explicitly.
+    /// Other external tokens are hidden if their names start with an underscore.
+    pub external_token_kinds: BTreeMap<String, VariableType>,
     pub variables_to_inline: Vec<String>,
     pub supertype_symbols: Vec<String>,
     pub word_token: Option<String>,
     pub reserved_words: Vec<ReservedWordContext<Rule>>,
+    pub descriptions: BTreeMap<String, String>,
+    pub token_groups: Vec<Vec<String>>,
 }
 
-#[derive(Debug, Default, PartialEq, Eq)]
+#[derive(Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
 pub struct ReservedWordContext<T> {
     pub name: String,
     pub reserved_words: Vec<T>,
@@ -123,7 +130,7 @@ pub struct SyntaxVariable {
     pub productions: Vec<Production>,
 }
 
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct ExternalToken {
     pub name: String,
     pub kind: VariableType,
@@ -141,6 +148,7 @@ pub struct SyntaxGrammar {
     pub word_token: Option<Symbol>,
     pub precedence_orderings: Vec<Vec<PrecedenceEntry>>,
     pub reserved_word_sets: Vec<TokenSet>,
+    pub descriptions: Vec<(Symbol, String)>,
 }
 
 #[cfg(test)]
@@ -276,6 +284,10 @@ impl SyntaxVariable {
     }
 }
 
+// SAFETY: The production pointers are only used as keys, to identify productions by address.
+// They are never dereferenced.
+unsafe impl Sync for InlinedProductionMap {}
+
 impl InlinedProductionMap {
     #[must_use]
     pub fn inlined_productions<'a>(
@@ -11,6 +11,8 @@ use super::{
     grammars::{LexicalGrammar, SyntaxGrammar, VariableType},
     rules::{Alias, AliasMap, Symbol, SymbolType},
 };
+#[cfg(feature = "load")]
+use super::{nfa::NfaCursor, prepare_grammar::is_token_subset, rules::TokenSet};
 
 #[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum ChildType {
@@ -32,38 +34,45 @@ pub struct VariableInfo {
     pub has_multi_step_production: bool,
 }
 
-#[derive(Debug, Serialize, PartialEq, Eq, Default, PartialOrd, Ord)]
+/// A node type, as it appears in `node-types.json`.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Default, PartialOrd, Ord)]
 #[cfg(feature = "load")]
 pub struct NodeInfoJSON {
     #[serde(rename = "type")]
-    kind: String,
-    named: bool,
-    #[serde(skip_serializing_if = "std::ops::Not::not")]
-    root: bool,
-    #[serde(skip_serializing_if = "std::ops::Not::not")]
-    extra: bool,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    fields: Option<BTreeMap<String, FieldInfoJSON>>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    children: Option<FieldInfoJSON>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    subtypes: Option<Vec<NodeTypeJSON>>,
+    pub kind: String,
+    pub named: bool,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub root: bool,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub extra: bool,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub keyword: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fields: Option<BTreeMap<String, FieldInfoJSON>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub children: Option<FieldInfoJSON>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub subtypes: Option<Vec<NodeTypeJSON>>,
 }
 
-#[derive(Clone, Debug, Serialize, PartialEq, Eq, PartialOrd, Ord, Hash)]
+/// A reference to a node type, by its kind and whether it is named.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash)]
 #[cfg(feature = "load")]
 pub struct NodeTypeJSON {
     #[serde(rename = "type")]
-    kind: String,
-    named: bool,
+    pub kind: String,
+    pub named: bool,
 }
 
-#[derive(Debug, Serialize, PartialEq, Eq, PartialOrd, Ord)]
+/// The types of the nodes that can appear in a field, or as a node's children.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
 #[cfg(feature = "load")]
 pub struct FieldInfoJSON {
-    multiple: bool,
-    required: bool,
-    types: Vec<NodeTypeJSON>,
+    pub multiple: bool,
+    pub required: bool,
+    pub types: Vec<NodeTypeJSON>,
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
@@ -510,6 +519,389 @@ impl std::fmt::Display for SuperTypeCycleError {
     }
 }
 
+#[cfg(feature = "load")]
+#[derive(Debug, Error, Serialize, Deserialize)]
+pub enum NodeTypesError {
+    #[error("Invalid node types JSON -- {0}")]
+    JSON(String),
+    #[error("Node type `{}` is listed more than once", .0.kind)]
+    DuplicateType(NodeTypeJSON),
+    #[error("Node type `{}` is referenced by `{referrer}`, but is not listed", .node_type.kind)]
+    UnknownType {
+        node_type: NodeTypeJSON,
+        referrer: String,
+    },
+}
+
+/// A difference between two versions of a `node-types.json` file, as found by
+/// [`NodeTypes::breaking_changes`].
+#[cfg(feature = "load")]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Error)]
+pub enum BreakingChange {
+    #[error("Node type `{}` was removed", .0.kind)]
+    KindRemoved(NodeTypeJSON),
+    #[error("Node type `{}` was added", .0.kind)]
+    KindAdded(NodeTypeJSON),
+    #[error("Field `{field}` of `{kind}` was removed")]
+    FieldRemoved { kind: String, field: String },
+    #[error("Field `{field}` of `{kind}` was added")]
+    FieldAdded { kind: String, field: String },
+    #[error("Field `{field}` of `{kind}` became required")]
+    FieldBecameRequired { kind: String, field: String },
+    #[error("Field `{field}` of `{kind}` can now contain multiple nodes")]
+    FieldBecameMultiple { kind: String, field: String },
+    #[error("Node type `{}` can no longer appear in field `{field}` of `{kind}`", .node_type.kind)]
+    FieldTypeRemoved {
+        kind: String,
+        field: String,
+        node_type: NodeTypeJSON,
+    },
+    #[error("Node type `{}` can now appear in field `{field}` of `{kind}`", .node_type.kind)]
+    FieldTypeAdded {
+        kind: String,
+        field: String,
+        node_type: NodeTypeJSON,
+    },
+    #[error("Children of `{kind}` outside of fields were removed")]
+    ChildrenRemoved { kind: String },
+    #[error("Children of `{kind}` outside of fields were added")]
+    ChildrenAdded { kind: String },
+    #[error("Children of `{kind}` outside of fields became required")]
+    ChildrenBecameRequired { kind: String },
+    #[error("Children of `{kind}` outside of fields can now contain multiple nodes")]
+    ChildrenBecameMultiple { kind: String },
+    #[error("Node type `{}` can no longer appear as a child of `{kind}`", .node_type.kind)]
+    ChildTypeRemoved {
+        kind: String,
+        node_type: NodeTypeJSON,
+    },
+    #[error("Node type `{}` can now appear as a child of `{kind}`", .node_type.kind)]
+    ChildTypeAdded {
+        kind: String,
+        node_type: NodeTypeJSON,
+    },
+    #[error("Node type `{}` is no longer a subtype of `{supertype}`", .subtype.kind)]
+    SubtypeRemoved {
+        supertype: String,
+        subtype: NodeTypeJSON,
+    },
+    #[error("Node type `{}` is now a subtype of `{supertype}`", .subtype.kind)]
+    SubtypeAdded {
+        supertype: String,
+        subtype: NodeTypeJSON,
+    },
+}
+
+#[cfg(feature = "load")]
+impl BreakingChange {
+    /// Check if this change can break code that was written against the older node types.
+    /// Removals, and fields or children becoming required or multiple, are breaking.
+    /// Additions are compatible.
+    #[must_use]
+    pub const fn is_breaking(&self) -> bool {
+        matches!(
+            self,
+            Self::KindRemoved(_)
+                | Self::FieldRemoved { .. }
+                | Self::FieldBecameRequired { .. }
+                | Self::FieldBecameMultiple { .. }
+                | Self::FieldTypeRemoved { .. }
+                | Self::ChildrenRemoved { .. }
+                | Self::ChildrenBecameRequired { .. }
+                | Self::ChildrenBecameMultiple { .. }
+                | Self::ChildTypeRemoved { .. }
+                | Self::SubtypeRemoved { .. }
+        )
+    }
+}
+
+/// The contents of a `node-types.json` file, indexed for looking up the relationships
+/// between node types.
+#[cfg(feature = "load")]
+#[derive(Debug, Default)]
+pub struct NodeTypes {
+    nodes: Vec<NodeInfoJSON>,
+    indices_by_type: FxHashMap<NodeTypeJSON, usize>,
+}
+
+#[cfg(feature = "load")]
+impl NodeTypes {
+    /// Parse the contents of a `node-types.json` file, checking that every node type
+    /// that is referenced by a field, children list, or supertype is listed.
+    pub fn from_json(json: &str) -> Result<Self, NodeTypesError> {
+        let nodes = serde_json::from_str::<Vec<NodeInfoJSON>>(json)
+            .map_err(|e| NodeTypesError::JSON(e.to_string()))?;
+
+        let mut indices_by_type = FxHashMap::default();
+        for (i, node) in nodes.iter().enumerate() {
+            let node_type = NodeTypeJSON {
+                kind: node.kind.clone(),
+                named: node.named,
+            };
+            if indices_by_type.insert(node_type.clone(), i).is_some() {
+                return Err(NodeTypesError::DuplicateType(node_type));
+            }
+        }
+
+        for node in &nodes {
+            let referenced_types = node
+                .fields
+                .iter()
+                .flat_map(BTreeMap::values)
+                .chain(&node.children)
+                .flat_map(|info| &info.types)
+                .chain(node.subtypes.iter().flatten());
+            for node_type in referenced_types {
+                if !indices_by_type.contains_key(node_type) {
+                    return Err(NodeTypesError::UnknownType {
+                        node_type: node_type.clone(),
+                        referrer: node.kind.clone(),
+                    });
+                }
+            }
+        }
+
+        Ok(Self {
+            nodes,
+            indices_by_type,
+        })
+    }
+
+    /// All of the node types, in the order that they are listed.
+    #[must_use]
+    pub fn nodes(&self) -> &[NodeInfoJSON] {
+        &self.nodes
+    }
+
+    /// Get the node type with the given kind and namedness.
+    #[must_use]
+    pub fn node(&self, kind: &str, named: bool) -> Option<&NodeInfoJSON> {
+        let node_type = NodeTypeJSON {
+            kind: kind.to_string(),
+            named,
+        };
+        self.indices_by_type
+            .get(&node_type)
+            .map(|&i| &self.nodes[i])
+    }
+
+    /// Check if there is a named node type with the given kind.
+    #[must_use]
+    pub fn is_named(&self, kind: &str) -> bool {
+        self.node(kind, true).is_some()
+    }
+
+    /// Get the subtypes that are directly listed for the given supertype.
+    #[must_use]
+    pub fn subtypes_of(&self, supertype: &str) -> &[NodeTypeJSON] {
+        self.node(supertype, true)
+            .and_then(|node| node.subtypes.as_deref())
+            .unwrap_or_default()
+    }
+
+    /// Get the kinds of all of the supertypes that the given node type belongs to,
+    /// either directly or through another supertype.
+    #[must_use]
+    pub fn supertypes_of(&self, kind: &str, named: bool) -> Vec<&str> {
+        let mut result = Vec::new();
+        let mut stack = vec![NodeTypeJSON {
+            kind: kind.to_string(),
+            named,
+        }];
+        while let Some(node_type) = stack.pop() {
+            for node in &self.nodes {
+                if node
+                    .subtypes
+                    .as_ref()
+                    .is_some_and(|subtypes| subtypes.contains(&node_type))
+                    && !result.contains(&node.kind.as_str())
+                {
+                    result.push(node.kind.as_str());
+                    stack.push(NodeTypeJSON {
+                        kind: node.kind.clone(),
+                        named: node.named,
+                    });
+                }
+            }
+        }
+        result
+    }
+
+    /// Get the names of the fields of the named node type with the given kind.
+    #[must_use]
+    pub fn fields_of(&self, kind: &str) -> Vec<&str> {
+        self.node(kind, true)
+            .and_then(|node| node.fields.as_ref())
+            .map(|fields| fields.keys().map(String::as_str).collect())
+            .unwrap_or_default()
+    }
+
+    /// Get the types of the nodes that can appear in the given field of the named
+    /// node type with the given kind.
+    #[must_use]
+    pub fn field(&self, kind: &str, field_name: &str) -> Option<&FieldInfoJSON> {
+        self.node(kind, true)?.fields.as_ref()?.get(field_name)
+    }
+
+    /// Find the changes from these node types to `other`, a newer version of them.
+    ///
+    /// Both breaking and compatible changes are returned. Use
+    /// [`BreakingChange::is_breaking`] to tell them apart.
+    #[must_use]
+    pub fn breaking_changes(&self, other: &Self) -> Vec<BreakingChange> {
+        let mut result = Vec::new();
+        for node in &self.nodes {
+            let Some(other_node) = other.node(&node.kind, node.named) else {
+                result.push(BreakingChange::KindRemoved(NodeTypeJSON {
+                    kind: node.kind.clone(),
+                    named: node.named,
+                }));
+                continue;
+            };
+
+            let fields = node.fields.as_ref();
+            let other_fields = other_node.fields.as_ref();
+            for (field, info) in fields.into_iter().flatten() {
+                let kind = node.kind.clone();
+                let field = field.clone();
+                let Some(other_info) = other_fields.and_then(|fields| fields.get(&field)) else {
+                    result.push(BreakingChange::FieldRemoved { kind, field });
+                    continue;
+                };
+                result.extend(
+                    child_info_changes(info, other_info).map(|change| match change {
+                        ChildInfoChange::BecameRequired => BreakingChange::FieldBecameRequired {
+                            kind: kind.clone(),
+                            field: field.clone(),
+                        },
+                        ChildInfoChange::BecameMultiple => BreakingChange::FieldBecameMultiple {
+                            kind: kind.clone(),
+                            field: field.clone(),
+                        },
+                        ChildInfoChange::TypeRemoved(node_type) => {
+                            BreakingChange::FieldTypeRemoved {
+                                kind: kind.clone(),
+                                field: field.clone(),
+                                node_type,
+                            }
+                        }
+                        ChildInfoChange::TypeAdded(node_type) => BreakingChange::FieldTypeAdded {
+                            kind: kind.clone(),
+                            field: field.clone(),
+                            node_type,
+                        },
+                    }),
+                );
+            }
+            for field in other_fields.into_iter().flat_map(BTreeMap::keys) {
+                if !fields.is_some_and(|fields| fields.contains_key(field)) {
+                    result.push(BreakingChange::FieldAdded {
+                        kind: node.kind.clone(),
+                        field: field.clone(),
+                    });
+                }
+            }
+
+            let kind = node.kind.clone();
+            match (&node.children, &other_node.children) {
+                (Some(_), None) => result.push(BreakingChange::ChildrenRemoved { kind }),
+                (None, Some(_)) => result.push(BreakingChange::ChildrenAdded { kind }),
+                (Some(info), Some(other_info)) => {
+                    result.extend(child_info_changes(info, other_info).map(
+                        |change| match change {
+                            ChildInfoChange::BecameRequired => {
+                                BreakingChange::ChildrenBecameRequired { kind: kind.clone() }
+                            }
+                            ChildInfoChange::BecameMultiple => {
+                                BreakingChange::ChildrenBecameMultiple { kind: kind.clone() }
+                            }
+                            ChildInfoChange::TypeRemoved(node_type) => {
+                                BreakingChange::ChildTypeRemoved {
+                                    kind: kind.clone(),
+                                    node_type,
+                                }
+                            }
+                            ChildInfoChange::TypeAdded(node_type) => {
+                                BreakingChange::ChildTypeAdded {
+                                    kind: kind.clone(),
+                                    node_type,
+                                }
+                            }
+                        },
+                    ));
+                }
+                (None, None) => {}
+            }
+
+            let subtypes = node.subtypes.as_deref().unwrap_or_default();
+            let other_subtypes = other_node.subtypes.as_deref().unwrap_or_default();
+            for subtype in subtypes {
+                if !other_subtypes.contains(subtype) {
+                    result.push(BreakingChange::SubtypeRemoved {
+                        supertype: node.kind.clone(),
+                        subtype: subtype.clone(),
+                    });
+                }
+            }
+            for subtype in other_subtypes {
+                if !subtypes.contains(subtype) {
+                    result.push(BreakingChange::SubtypeAdded {
+                        supertype: node.kind.clone(),
+                        subtype: subtype.clone(),
+                    });
+                }
+            }
+        }
+
+        for node in &other.nodes {
+            if self.node(&node.kind, node.named).is_none() {
+                result.push(BreakingChange::KindAdded(NodeTypeJSON {
+                    kind: node.kind.clone(),
+                    named: node.named,
+                }));
+            }
+        }
+        result
+    }
+}
+
+/// A change between two versions of a field, or of a node type's children outside of fields.
+#[cfg(feature = "load")]
+enum ChildInfoChange {
+    BecameRequired,
+    BecameMultiple,
+    TypeRemoved(NodeTypeJSON),
+    TypeAdded(NodeTypeJSON),
+}
+
+/// Find the changes from `info` to `other`, a newer version of it. Like a field becoming
+/// optional, becoming single isn't reported.
+#[cfg(feature = "load")]
+fn child_info_changes<'a>(
+    info: &'a FieldInfoJSON,
+    other: &'a FieldInfoJSON,
+) -> impl Iterator<Item = ChildInfoChange> + 'a {
+    let became_required =
+        (!info.required && other.required).then_some(ChildInfoChange::BecameRequired);
+    let became_multiple =
+        (!info.multiple && other.multiple).then_some(ChildInfoChange::BecameMultiple);
+    let removed = info
+        .types
+        .iter()
+        .filter(|node_type| !other.types.contains(node_type))
+        .map(|node_type| ChildInfoChange::TypeRemoved(node_type.clone()));
+    let added = other
+        .types
+        .iter()
+        .filter(|node_type| !info.types.contains(node_type))
+        .map(|node_type| ChildInfoChange::TypeAdded(node_type.clone()));
+    became_required
+        .into_iter()
+        .chain(became_multiple)
+        .chain(removed)
+        .chain(added)
+}
+
 #[cfg(feature = "load")]
 pub fn generate_node_types_json(
     syntax_grammar: &SyntaxGrammar,
@@ -612,6 +1004,8 @@ pub fn generate_node_types_json(
                         named: true,
                         root: false,
                         extra: extra_names.contains(&variable.name),
+                        keyword: false,
+                        description: None,
                         fields: None,
                         children: None,
                         subtypes: None,
@@ -662,6 +1056,8 @@ pub fn generate_node_types_json(
                         named: is_named,
                         root: i == 0,
                         extra: extra_names.contains(&kind),
+                        keyword: false,
+                        description: None,
                         fields: Some(BTreeMap::new()),
                         children: None,
                         subtypes: None,
@@ -795,6 +1191,8 @@ pub fn generate_node_types_json(
                             named: true,
                             root: false,
                             extra: extra_names.contains(&name),
+                            keyword: false,
+                            description: None,
                             fields: None,
                             children: None,
                             subtypes: None,
@@ -813,6 +1211,8 @@ pub fn generate_node_types_json(
                 named: false,
                 root: false,
                 extra: extra_names.contains(&name),
+                keyword: false,
+                description: None,
                 fields: None,
                 children: None,
                 subtypes: None,
@@ -821,6 +1221,30 @@ pub fn generate_node_types_json(
         }
     }
 
+    for index in keyword_token_indices(syntax_grammar, lexical_grammar) {
+        let node_type = child_type_to_node_type(&ChildType::Normal(Symbol::terminal(index)));
+        if node_type.named {
+            if let Some(node_type_json) = node_types_json.get_mut(&node_type.kind) {
+                node_type_json.keyword = true;
+            }
+        } else {
+            for node_type_json in &mut anonymous_node_types {
+                if node_type_json.kind == node_type.kind {
+                    node_type_json.keyword = true;
+                }
+            }
+        }
+    }
+
+    for (symbol, description) in &syntax_grammar.descriptions {
+        let node_type = child_type_to_node_type(&ChildType::Normal(*symbol));
+        if let Some(node_type_json) = node_types_json.get_mut(&node_type.kind)
+            && node_type_json.named == node_type.named
+        {
+            node_type_json.description = Some(description.clone());
+        }
+    }
+
     let mut result = node_types_json.into_iter().map(|e| e.1).collect::<Vec<_>>();
     result.extend(anonymous_node_types);
     result.sort_unstable_by(|a, b| {
@@ -841,6 +1265,30 @@ pub fn generate_node_types_json(
     Ok(result)
 }
 
+/// Find the tokens that editors should treat as keywords: the anonymous tokens that can be matched
+/// via the word token during keyword extraction, and the tokens in reserved word sets.
+#[cfg(feature = "load")]
+fn keyword_token_indices(
+    syntax_grammar: &SyntaxGrammar,
+    lexical_grammar: &LexicalGrammar,
+) -> BTreeSet<usize> {
+    let mut result = syntax_grammar
+        .reserved_word_sets
+        .iter()
+        .flat_map(TokenSet::terminals)
+        .map(|symbol| symbol.index)
+        .collect::<BTreeSet<_>>();
+    if let Some(word_token) = syntax_grammar.word_token.filter(Symbol::is_terminal) {
+        let mut cursor = NfaCursor::new(&lexical_grammar.nfa, Vec::new());
+        result.extend((0..lexical_grammar.variables.len()).filter(|i| {
+            *i != word_token.index
+                && lexical_grammar.variables[*i].kind == VariableType::Anonymous
+                && is_token_subset(lexical_grammar, &mut cursor, *i, word_token.index)
+        }));
+    }
+    result
+}
+
 #[cfg(feature = "load")]
 fn process_supertypes(info: &mut FieldInfoJSON, subtype_map: &[(NodeTypeJSON, Vec<NodeTypeJSON>)]) {
     for (supertype, subtypes) in subtype_map {
@@ -893,7 +1341,8 @@ mod tests {
     use super::*;
     use crate::{
         grammars::{
-            InputGrammar, LexicalVariable, Production, ProductionStep, SyntaxVariable, Variable,
+            InputGrammar, LexicalVariable, Production, ProductionStep, ReservedWordContext,
+            SyntaxVariable, Variable,
         },
         prepare_grammar::prepare_grammar,
         rules::Rule,
@@ -937,6 +1386,8 @@ mod tests {
                 named: true,
                 root: true,
                 extra: false,
+                keyword: false,
+                description: None,
                 subtypes: None,
                 children: None,
                 fields: Some(
@@ -976,6 +1427,8 @@ mod tests {
                 named: false,
                 root: false,
                 extra: false,
+                keyword: false,
+                description: None,
                 subtypes: None,
                 children: None,
                 fields: None
@@ -988,6 +1441,8 @@ mod tests {
                 named: true,
                 root: false,
                 extra: false,
+                keyword: false,
+                description: None,
                 subtypes: None,
                 children: None,
                 fields: None
@@ -1037,6 +1492,8 @@ mod tests {
                 named: true,
                 root: true,
                 extra: false,
+                keyword: false,
+                description: None,
                 subtypes: None,
                 children: None,
                 fields: Some(
@@ -1076,6 +1533,8 @@ mod tests {
                 named: false,
                 root: false,
                 extra: false,
+                keyword: false,
+                description: None,
                 subtypes: None,
                 children: None,
                 fields: None
@@ -1088,6 +1547,8 @@ mod tests {
                 named: true,
                 root: false,
                 extra: false,
+                keyword: false,
+                description: None,
                 subtypes: None,
                 children: None,
                 fields: None
@@ -1100,6 +1561,8 @@ mod tests {
                 named: true,
                 root: false,
                 extra: true,
+                keyword: false,
+                description: None,
                 subtypes: None,
                 children: None,
                 fields: None
@@ -1149,6 +1612,8 @@ mod tests {
                 named: true,
                 root: true,
                 extra: false,
+                keyword: false,
+                description: None,
                 subtypes: None,
                 children: None,
                 fields: Some(
@@ -1188,6 +1653,8 @@ mod tests {
                 named: true,
                 root: false,
                 extra: true,
+                keyword: false,
+                description: None,
                 subtypes: None,
                 children: None,
                 fields: Some(BTreeMap::default())
@@ -1200,6 +1667,8 @@ mod tests {
                 named: false,
                 root: false,
                 extra: false,
+                keyword: false,
+                description: None,
                 subtypes: None,
                 children: None,
                 fields: None
@@ -1212,6 +1681,8 @@ mod tests {
                 named: true,
                 root: false,
                 extra: false,
+                keyword: false,
+                description: None,
                 subtypes: None,
                 children: None,
                 fields: None
@@ -1260,6 +1731,8 @@ mod tests {
                 named: true,
                 root: false,
                 extra: false,
+                keyword: false,
+                description: None,
                 fields: None,
                 children: None,
                 subtypes: Some(vec![
@@ -1285,6 +1758,8 @@ mod tests {
                 named: true,
                 root: true,
                 extra: false,
+                keyword: false,
+                description: None,
                 subtypes: None,
                 children: None,
                 fields: Some(
@@ -1306,6 +1781,185 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_node_types_with_descriptions() {
+        let node_types = get_node_types(&InputGrammar {
+            supertype_symbols: vec!["_v2".to_string()],
+            variables: vec![
+                Variable::named(
+                    "v1",
+                    Rule::seq(vec![Rule::named("_v2"), Rule::named("_v4")]),
+                ),
+                Variable::hidden(
+                    "_v2",
+                    Rule::choice(vec![Rule::named("v3"), Rule::string("*")]),
+                ),
+                Variable::named("v3", Rule::pattern("[a-z]+", "")),
+                Variable::hidden("_v4", Rule::string(";")),
+            ],
+            descriptions: [
+                ("v1", "A top-level node"),
+                ("_v2", "Any value"),
+                ("v3", "An identifier"),
+                ("_v4", "A hidden token"),
+            ]
+            .into_iter()
+            .map(|(name, description)| (name.to_string(), description.to_string()))
+            .collect(),
+            ..Default::default()
+        })
+        .unwrap();
+
+        let descriptions = node_types
+            .iter()
+            .map(|node| (node.kind.as_str(), node.description.as_deref()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            descriptions,
+            [
+                ("_v2", Some("Any value")),
+                ("v1", Some("A top-level node")),
+                ("*", None),
+                (";", None),
+                ("v3", Some("An identifier")),
+            ]
+        );
+
+        let json = serde_json::to_value(&node_types[4]).unwrap();
+        assert_eq!(json["description"], "An identifier");
+        let json = serde_json::to_value(&node_types[2]).unwrap();
+        assert!(json.get("description").is_none());
+    }
+
+    #[test]
+    fn test_node_types_with_keywords() {
+        let node_types = get_node_types(&InputGrammar {
+            word_token: Some("identifier".to_string()),
+            reserved_words: vec![ReservedWordContext {
+                name: "global".to_string(),
+                reserved_words: vec![Rule::string("Var")],
+            }],
+            variables: vec![
+                Variable::named(
+                    "program",
+                    Rule::repeat(Rule::choice(vec![
+                        Rule::named("if_statement"),
+                        Rule::named("declaration"),
+                    ])),
+                ),
+                Variable::named(
+                    "if_statement",
+                    Rule::seq(vec![
+                        Rule::string("if"),
+                        Rule::named("identifier"),
+                        Rule::string("then"),
+                    ]),
+                ),
+                Variable::named(
+                    "declaration",
+                    Rule::seq(vec![
+                        Rule::string("Var"),
+                        Rule::named("identifier"),
+                        Rule::string("="),
+                        Rule::named("number"),
+                    ]),
+                ),
+                Variable::named("identifier", Rule::pattern("[a-z]+", "")),
+                Variable::named("number", Rule::pattern("[0-9]+", "")),
+            ],
+            extra_symbols: vec![Rule::pattern("\\s", "")],
+            ..Default::default()
+        })
+        .unwrap();
+
+        let keywords = node_types
+            .iter()
+            .filter(|node_type| node_type.keyword)
+            .map(|node_type| node_type.kind.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(keywords, ["Var", "if", "then"]);
+
+        let json = serde_json::to_value(&node_types).unwrap();
+        let if_json = json
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|node_type| node_type["type"] == "if")
+            .unwrap();
+        assert_eq!(if_json["keyword"], true);
+        let number_json = json
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|node_type| node_type["type"] == "number")
+            .unwrap();
+        assert!(number_json.get("keyword").is_none());
+    }
+
+    /// Inlining a member of a supertype must not drop that member's own
+    /// alternatives from the supertype's subtypes.
+    #[test]
+    fn test_node_types_with_inlined_supertype_member() {
+        let node_types = get_node_types(&InputGrammar {
+            supertype_symbols: vec!["_v2".to_string()],
+            variables_to_inline: vec!["_v3".to_string()],
+            variables: vec![
+                Variable {
+                    name: "v1".to_string(),
+                    kind: VariableType::Named,
+                    rule: Rule::named("_v2"),
+                },
+                Variable {
+                    name: "_v2".to_string(),
+                    kind: VariableType::Hidden,
+                    rule: Rule::choice(vec![Rule::named("v4"), Rule::named("_v3")]),
+                },
+                Variable {
+                    name: "_v3".to_string(),
+                    kind: VariableType::Hidden,
+                    rule: Rule::choice(vec![Rule::named("v5"), Rule::named("v6")]),
+                },
+                Variable {
+                    name: "v4".to_string(),
+                    kind: VariableType::Named,
+                    rule: Rule::string("x"),
+                },
+                Variable {
+                    name: "v5".to_string(),
+                    kind: VariableType::Named,
+                    rule: Rule::string("y"),
+                },
+                Variable {
+                    name: "v6".to_string(),
+                    kind: VariableType::Named,
+                    rule: Rule::string("z"),
+                },
+            ],
+            ..Default::default()
+        })
+        .unwrap();
+
+        assert_eq!(node_types[0].kind, "_v2");
+        assert_eq!(
+            node_types[0].subtypes,
+            Some(vec![
+                NodeTypeJSON {
+                    kind: "v4".to_string(),
+                    named: true,
+                },
+                NodeTypeJSON {
+                    kind: "v5".to_string(),
+                    named: true,
+                },
+                NodeTypeJSON {
+                    kind: "v6".to_string(),
+                    named: true,
+                },
+            ])
+        );
+        assert!(node_types.iter().all(|node_type| node_type.kind != "_v3"));
+    }
+
     /// A supertype whose only child is a hidden external token
     /// xgust not cause generation to panic. The subtype map must
     /// skip entries with empty subtypes to avoid a lookup failure
@@ -1393,6 +2047,8 @@ mod tests {
                 named: true,
                 root: true,
                 extra: false,
+                keyword: false,
+                description: None,
                 subtypes: None,
                 children: Some(FieldInfoJSON {
                     multiple: true,
@@ -1432,6 +2088,8 @@ mod tests {
                 named: true,
                 root: false,
                 extra: false,
+                keyword: false,
+                description: None,
                 subtypes: None,
                 children: Some(FieldInfoJSON {
                     multiple: false,
@@ -1479,6 +2137,8 @@ mod tests {
                 named: true,
                 root: true,
                 extra: false,
+                keyword: false,
+                description: None,
                 subtypes: None,
                 children: Some(FieldInfoJSON {
                     multiple: true,
@@ -1555,6 +2215,8 @@ mod tests {
                 named: true,
                 root: false,
                 extra: false,
+                keyword: false,
+                description: None,
                 subtypes: None,
                 children: None,
                 fields: None,
@@ -1567,6 +2229,8 @@ mod tests {
                 named: true,
                 root: false,
                 extra: false,
+                keyword: false,
+                description: None,
                 subtypes: None,
                 children: None,
                 fields: None,
@@ -1611,6 +2275,8 @@ mod tests {
                 named: true,
                 root: true,
                 extra: false,
+                keyword: false,
+                description: None,
                 subtypes: None,
                 children: Some(FieldInfoJSON {
                     multiple: true,
@@ -1661,6 +2327,8 @@ mod tests {
                 named: true,
                 root: true,
                 extra: false,
+                keyword: false,
+                description: None,
                 fields: Some(BTreeMap::new()),
                 children: None,
                 subtypes: None
@@ -1720,6 +2388,8 @@ mod tests {
                     named: true,
                     root: false,
                     extra: false,
+                    keyword: false,
+                    description: None,
                     subtypes: None,
                     children: None,
                     fields: Some(
@@ -1777,6 +2447,8 @@ mod tests {
                     named: true,
                     root: true,
                     extra: false,
+                    keyword: false,
+                    description: None,
                     subtypes: None,
                     // Only one node
                     children: Some(FieldInfoJSON {
@@ -1834,6 +2506,8 @@ mod tests {
                 named: true,
                 root: false,
                 extra: false,
+                keyword: false,
+                description: None,
                 subtypes: None,
                 children: Some(FieldInfoJSON {
                     multiple: true,
@@ -2150,6 +2824,405 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_loading_node_types() {
+        let node_types = get_node_types(&InputGrammar {
+            supertype_symbols: vec!["_statement".to_string(), "_expression".to_string()],
+            variables: vec![
+                Variable::named("program", Rule::repeat(Rule::named("_statement"))),
+                Variable::hidden(
+                    "_statement",
+                    Rule::choice(vec![
+                        Rule::named("expression_statement"),
+                        Rule::named("return_statement"),
+                    ]),
+                ),
+                Variable::hidden(
+                    "_expression",
+                    Rule::choice(vec![Rule::named("identifier"), Rule::named("call")]),
+                ),
+                Variable::named(
+                    "expression_statement",
+                    Rule::seq(vec![Rule::named("_expression"), Rule::string(";")]),
+                ),
+                Variable::named(
+                    "return_statement",
+                    Rule::seq(vec![
+                        Rule::string("return"),
+                        Rule::named("_expression"),
+                        Rule::string(";"),
+                    ]),
+                ),
+                Variable::named(
+                    "call",
+                    Rule::seq(vec![
+                        Rule::field("function".to_string(), Rule::named("_expression")),
+                        Rule::string("("),
+                        Rule::field(
+                            "argument".to_string(),
+                            Rule::choice(vec![Rule::named("_expression"), Rule::Blank]),
+                        ),
+                        Rule::string(")"),
+                    ]),
+                ),
+                Variable::named("identifier", Rule::pattern("[a-z]+", "")),
+            ],
+            ..Default::default()
+        })
+        .unwrap();
+        let json = serde_json::to_string_pretty(&node_types).unwrap();
+
+        let node_types = NodeTypes::from_json(&json).unwrap();
+        assert_eq!(node_types.nodes().len(), 11);
+        assert!(node_types.is_named("call"));
+        assert!(node_types.is_named("_expression"));
+        assert!(!node_types.is_named("return"));
+        assert!(node_types.node("return", false).is_some());
+        assert!(node_types.node("program", true).unwrap().root);
+
+        assert_eq!(
+            node_types.supertypes_of("identifier", true),
+            ["_expression"]
+        );
+        assert_eq!(node_types.supertypes_of("call", true), ["_expression"]);
+        assert_eq!(
+            node_types.supertypes_of("return_statement", true),
+            ["_statement"]
+        );
+        assert!(node_types.supertypes_of("program", true).is_empty());
+        assert!(node_types.supertypes_of("return", false).is_empty());
+        assert_eq!(
+            node_types
+                .subtypes_of("_statement")
+                .iter()
+                .map(|t| t.kind.as_str())
+                .collect::<Vec<_>>(),
+            ["expression_statement", "return_statement"]
+        );
+
+        assert_eq!(node_types.fields_of("call"), ["argument", "function"]);
+        assert!(node_types.fields_of("return_statement").is_empty());
+        let argument = node_types.field("call", "argument").unwrap();
+        assert!(!argument.required);
+        assert!(!argument.multiple);
+        assert_eq!(
+            argument.types,
+            [NodeTypeJSON {
+                kind: "_expression".to_string(),
+                named: true,
+            }]
+        );
+        assert!(node_types.field("call", "name").is_none());
+        assert!(
+            node_types
+                .node("return_statement", true)
+                .unwrap()
+                .children
+                .is_some()
+        );
+
+        assert!(matches!(
+            NodeTypes::from_json(r#"[{"type": "a", "named": true}, {"type": "a", "named": true}]"#),
+            Err(NodeTypesError::DuplicateType(NodeTypeJSON { kind, named: true })) if kind == "a"
+        ));
+        assert_eq!(
+            NodeTypes::from_json(
+                r#"[{"type": "a", "named": true, "subtypes": [{"type": "b", "named": true}]}]"#
+            )
+            .unwrap_err()
+            .to_string(),
+            "Node type `b` is referenced by `a`, but is not listed"
+        );
+        assert!(matches!(
+            NodeTypes::from_json(r#"{"type": "a"}"#),
+            Err(NodeTypesError::JSON(_))
+        ));
+    }
+
+    #[test]
+    fn test_node_types_breaking_changes() {
+        fn changes(old: &str, new: &str) -> Vec<BreakingChange> {
+            NodeTypes::from_json(old)
+                .unwrap()
+                .breaking_changes(&NodeTypes::from_json(new).unwrap())
+        }
+
+        fn node_type(kind: &str) -> NodeTypeJSON {
+            NodeTypeJSON {
+                kind: kind.to_string(),
+                named: true,
+            }
+        }
+
+        let base = r#"[
+            {"type": "_expression", "named": true, "subtypes": [
+                {"type": "call", "named": true},
+                {"type": "identifier", "named": true}
+            ]},
+            {"type": "call", "named": true, "fields": {
+                "function": {"multiple": false, "required": true, "types": [
+                    {"type": "identifier", "named": true}
+                ]},
+                "argument": {"multiple": false, "required": false, "types": [
+                    {"type": "_expression", "named": true}
+                ]}
+            }},
+            {"type": "identifier", "named": true},
+            {"type": "number", "named": true}
+        ]"#;
+        assert!(changes(base, base).is_empty());
+
+        // Removing a kind, including one that is referenced elsewhere, is breaking.
+        let without_number = base.replace(
+            r#",
+            {"type": "number", "named": true}"#,
+            "",
+        );
+        let removed = changes(base, &without_number);
+        assert_eq!(removed, [BreakingChange::KindRemoved(node_type("number"))]);
+        assert!(removed[0].is_breaking());
+        assert_eq!(removed[0].to_string(), "Node type `number` was removed");
+
+        // Adding it back is compatible.
+        let added = changes(&without_number, base);
+        assert_eq!(added, [BreakingChange::KindAdded(node_type("number"))]);
+        assert!(!added[0].is_breaking());
+
+        // Removing a field is breaking, and adding one is compatible.
+        let without_argument = base.replace(
+            r#",
+                "argument": {"multiple": false, "required": false, "types": [
+                    {"type": "_expression", "named": true}
+                ]}"#,
+            "",
+        );
+        let kind = "call".to_string();
+        let name = "argument".to_string();
+        assert_eq!(
+            changes(base, &without_argument),
+            [BreakingChange::FieldRemoved {
+                kind: kind.clone(),
+                field: name.clone(),
+            }]
+        );
+        assert!(changes(base, &without_argument)[0].is_breaking());
+        assert_eq!(
+            changes(&without_argument, base),
+            [BreakingChange::FieldAdded {
+                kind: kind.clone(),
+                field: name.clone(),
+            }]
+        );
+        assert!(!changes(&without_argument, base)[0].is_breaking());
+
+        // A field becoming required is breaking, but the reverse is not reported.
+        let required_argument = base.replace(
+            r#""argument": {"multiple": false, "required": false"#,
+            r#""argument": {"multiple": false, "required": true"#,
+        );
+        let tightened = changes(base, &required_argument);
+        assert_eq!(
+            tightened,
+            [BreakingChange::FieldBecameRequired {
+                kind: kind.clone(),
+                field: name.clone(),
+            }]
+        );
+        assert!(tightened[0].is_breaking());
+        assert!(changes(&required_argument, base).is_empty());
+
+        // Likewise, a field becoming multiple is breaking, but becoming single is not reported.
+        let multiple_argument = base.replace(
+            r#""argument": {"multiple": false"#,
+            r#""argument": {"multiple": true"#,
+        );
+        let loosened = changes(base, &multiple_argument);
+        assert_eq!(
+            loosened,
+            [BreakingChange::FieldBecameMultiple {
+                kind: kind.clone(),
+                field: name,
+            }]
+        );
+        assert!(loosened[0].is_breaking());
+        assert_eq!(
+            loosened[0].to_string(),
+            "Field `argument` of `call` can now contain multiple nodes"
+        );
+        assert!(changes(&multiple_argument, base).is_empty());
+
+        // Narrowing the types of a field is breaking, and widening them is compatible.
+        let number_function = base.replace(
+            r#""function": {"multiple": false, "required": true, "types": [
+                    {"type": "identifier", "named": true}"#,
+            r#""function": {"multiple": false, "required": true, "types": [
+                    {"type": "number", "named": true}"#,
+        );
+        let name = "function".to_string();
+        let retyped = changes(base, &number_function);
+        assert_eq!(
+            retyped,
+            [
+                BreakingChange::FieldTypeRemoved {
+                    kind: kind.clone(),
+                    field: name.clone(),
+                    node_type: node_type("identifier"),
+                },
+                BreakingChange::FieldTypeAdded {
+                    kind,
+                    field: name,
+                    node_type: node_type("number"),
+                },
+            ]
+        );
+        assert!(retyped[0].is_breaking());
+        assert!(!retyped[1].is_breaking());
+
+        // Removing a subtype from a supertype is breaking, and adding one is compatible.
+        let without_call_subtype = base.replace(
+            r#"{"type": "call", "named": true},
+                {"type": "identifier", "named": true}
+            ]}"#,
+            r#"{"type": "identifier", "named": true}
+            ]}"#,
+        );
+        let removed_subtype = changes(base, &without_call_subtype);
+        assert_eq!(
+            removed_subtype,
+            [BreakingChange::SubtypeRemoved {
+                supertype: "_expression".to_string(),
+                subtype: node_type("call"),
+            }]
+        );
+        assert!(removed_subtype[0].is_breaking());
+        let added_subtype = changes(&without_call_subtype, base);
+        assert_eq!(
+            added_subtype,
+            [BreakingChange::SubtypeAdded {
+                supertype: "_expression".to_string(),
+                subtype: node_type("call"),
+            }]
+        );
+        assert!(!added_subtype[0].is_breaking());
+
+        // Removing a supertype removes its kind.
+        let without_supertype = r#"[
+            {"type": "call", "named": true, "fields": {
+                "function": {"multiple": false, "required": true, "types": [
+                    {"type": "identifier", "named": true}
+                ]}
+            }},
+            {"type": "identifier", "named": true},
+            {"type": "number", "named": true}
+        ]"#;
+        let changes = changes(base, without_supertype);
+        assert_eq!(
+            changes,
+            [
+                BreakingChange::KindRemoved(node_type("_expression")),
+                BreakingChange::FieldRemoved {
+                    kind: "call".to_string(),
+                    field: "argument".to_string(),
+                },
+            ]
+        );
+        assert!(changes.iter().all(BreakingChange::is_breaking));
+    }
+
+    #[test]
+    fn test_node_types_breaking_changes_to_children() {
+        fn changes(old: &str, new: &str) -> Vec<BreakingChange> {
+            NodeTypes::from_json(old)
+                .unwrap()
+                .breaking_changes(&NodeTypes::from_json(new).unwrap())
+        }
+
+        let base = r#"[
+            {"type": "block", "named": true, "children": {
+                "multiple": false, "required": false, "types": [
+                    {"type": "identifier", "named": true}
+                ]
+            }},
+            {"type": "identifier", "named": true},
+            {"type": "number", "named": true}
+        ]"#;
+        let kind = "block".to_string();
+        assert!(changes(base, base).is_empty());
+
+        // Removing the children is breaking, and adding them is compatible.
+        let without_children = base.replace(
+            r#", "children": {
+                "multiple": false, "required": false, "types": [
+                    {"type": "identifier", "named": true}
+                ]
+            }"#,
+            "",
+        );
+        let removed = changes(base, &without_children);
+        assert_eq!(
+            removed,
+            [BreakingChange::ChildrenRemoved { kind: kind.clone() }]
+        );
+        assert!(removed[0].is_breaking());
+        let added = changes(&without_children, base);
+        assert_eq!(
+            added,
+            [BreakingChange::ChildrenAdded { kind: kind.clone() }]
+        );
+        assert!(!added[0].is_breaking());
+
+        // The children becoming required or multiple is breaking, but the reverse is not
+        // reported.
+        let tightened = base.replace(
+            r#""multiple": false, "required": false"#,
+            r#""multiple": true, "required": true"#,
+        );
+        let tightened_changes = changes(base, &tightened);
+        assert_eq!(
+            tightened_changes,
+            [
+                BreakingChange::ChildrenBecameRequired { kind: kind.clone() },
+                BreakingChange::ChildrenBecameMultiple { kind: kind.clone() },
+            ]
+        );
+        assert!(tightened_changes.iter().all(BreakingChange::is_breaking));
+        assert!(changes(&tightened, base).is_empty());
+
+        // Narrowing the types of the children is breaking, and widening them is compatible.
+        let number_children = base.replace(
+            r#""types": [
+                    {"type": "identifier", "named": true}"#,
+            r#""types": [
+                    {"type": "number", "named": true}"#,
+        );
+        let retyped = changes(base, &number_children);
+        assert_eq!(
+            retyped,
+            [
+                BreakingChange::ChildTypeRemoved {
+                    kind: kind.clone(),
+                    node_type: NodeTypeJSON {
+                        kind: "identifier".to_string(),
+                        named: true,
+                    },
+                },
+                BreakingChange::ChildTypeAdded {
+                    kind,
+                    node_type: NodeTypeJSON {
+                        kind: "number".to_string(),
+                        named: true,
+                    },
+                },
+            ]
+        );
+        assert!(retyped[0].is_breaking());
+        assert!(!retyped[1].is_breaking());
+        assert_eq!(
+            retyped[0].to_string(),
+            "Node type `identifier` can no longer appear as a child of `block`"
+        );
+    }
+
     fn get_node_types(grammar: &InputGrammar) -> SuperTypeCycleResult<Vec<NodeInfoJSON>> {
         let (syntax_grammar, lexical_grammar, _, default_aliases) =
             prepare_grammar(grammar, &mut Vec::new()).unwrap();
@@ -1,3 +1,5 @@
+use std::collections::BTreeMap;
+
 use rustc_hash::{FxHashMap, FxHashSet};
 
 use regex::Regex;
@@ -82,6 +84,15 @@ enum RuleJSON {
     },
 }
 
+/// An entry in the `externals` array, which is a rule that may be marked as hidden or visible
+/// regardless of its name.
+#[derive(Deserialize)]
+struct ExternalJSON {
+    #[serde(flatten)]
+    rule: RuleJSON,
+    hidden: Option<bool>,
+}
+
 #[derive(Deserialize)]
 #[serde(untagged)]
 enum PrecedenceValueJSON {
@@ -98,7 +109,7 @@ pub struct GrammarJSON {
     #[serde(default)]
     conflicts: Vec<Vec<String>>,
     #[serde(default)]
-    externals: Vec<RuleJSON>,
+    externals: Vec<ExternalJSON>,
     #[serde(default)]
     extras: Vec<RuleJSON>,
     #[serde(default)]
@@ -109,6 +120,12 @@ pub struct GrammarJSON {
     word: Option<String>,
     #[serde(default)]
     reserved: Map<String, Value>,
+    #[serde(default)]
+    descriptions: BTreeMap<String, String>,
+    #[serde(default)]
+    token_groups: Vec<Vec<String>>,
+    #[serde(default)]
+    extends: Option<Box<Self>>,
 }
 
 pub type ParseGrammarResult<T> = Result<T, ParseGrammarError>;
@@ -121,6 +138,8 @@ pub enum ParseGrammarError {
     InvalidExtra,
     #[error("Invalid rule in precedences array. Only strings and symbols are allowed")]
     Unexpected,
+    #[error("Only symbols in the `externals` array can be marked as hidden")]
+    InvalidHiddenExternal,
     #[error("Reserved word sets must be arrays")]
     InvalidReservedWordSet,
     #[error("Grammar Error: Unexpected rule `{0}` in `token()` call")]
@@ -156,13 +175,88 @@ fn rule_is_referenced(rule: &Rule, target: &str, is_external: bool) -> bool {
 }
 
 impl InputGrammar {
+    /// Merge a base grammar into this grammar, which extends it.
+    ///
+    /// The base grammar's rules come first, so the base's start rule remains the start rule
+    /// unless it is overridden. A rule of this grammar with the same name as one of the base's
+    /// rules takes its place, and every reference to that name refers to the override. The
+    /// base's version of an overridden rule is still available as the hidden rule
+    /// `_base_<name>`, so that the override can build on it. The other properties are merged
+    /// by keeping the base's entries and adding this grammar's.
+    fn extend(self, base: Self) -> Self {
+        fn merge<T: PartialEq>(mut base: Vec<T>, derived: Vec<T>) -> Vec<T> {
+            for item in derived {
+                if !base.contains(&item) {
+                    base.push(item);
+                }
+            }
+            base
+        }
+
+        let mut derived_variables = self.variables;
+        let mut variables = Vec::with_capacity(base.variables.len() + derived_variables.len());
+        let mut base_variables = Vec::new();
+        for variable in base.variables {
+            if let Some(index) = derived_variables
+                .iter()
+                .position(|v| v.name == variable.name)
+            {
+                variables.push(derived_variables.remove(index));
+                base_variables.push(Variable {
+                    name: format!("_base_{}", variable.name),
+                    ..variable
+                });
+            } else {
+                variables.push(variable);
+            }
+        }
+        variables.extend(derived_variables);
+        for variable in base_variables {
+            if !variables.iter().any(|v| v.name == variable.name) {
+                variables.push(variable);
+            }
+        }
+
+        let mut reserved_words = base.reserved_words;
+        for set in self.reserved_words {
+            if let Some(base_set) = reserved_words.iter_mut().find(|s| s.name == set.name) {
+                *base_set = set;
+            } else {
+                reserved_words.push(set);
+            }
+        }
+
+        let mut descriptions = base.descriptions;
+        descriptions.extend(self.descriptions);
+
+        let mut external_token_kinds = base.external_token_kinds;
+        external_token_kinds.extend(self.external_token_kinds);
+
+        Self {
+            name: self.name,
+            variables,
+            extra_symbols: merge(base.extra_symbols, self.extra_symbols),
+            expected_conflicts: merge(base.expected_conflicts, self.expected_conflicts),
+            precedence_orderings: merge(base.precedence_orderings, self.precedence_orderings),
+            external_tokens: merge(base.external_tokens, self.external_tokens),
+            external_token_kinds,
+            variables_to_inline: merge(base.variables_to_inline, self.variables_to_inline),
+            supertype_symbols: merge(base.supertype_symbols, self.supertype_symbols),
+            word_token: self.word_token.or(base.word_token),
+            reserved_words,
+            descriptions,
+            token_groups: merge(base.token_groups, self.token_groups),
+        }
+    }
+
     /// Strip unused rules from the grammar and clean up references to them
     /// in the surrounding config. (conflicts, supertypes, inline, extras,
     /// externals, precedences).
     ///
     /// A variable is "used" if it is the start rule, the word token, named in
     /// `extras`/`externals`, or transitively reachable via rule references from
-    /// any of the above.
+    /// any of the above. Unused visible rules are reported as
+    /// [`Diagnostic::UnusedRule`].
     fn normalize(mut self, diagnostics: &mut Vec<Diagnostic>) -> Self {
         // Compute the used set via forward DFS from the implicit roots
         // (start rule, word_token, refs in extras and externals).
@@ -243,9 +337,27 @@ impl InputGrammar {
             .map(|v| v.name.clone())
             .collect();
         self.variables.retain(|v| used.contains(v.name.as_str()));
+
+        // Report the dropped rules that were most likely left behind by accident. Hidden
+        // rules are skipped, since they include the `_base_` copies of overridden rules,
+        // and so are rules that the grammar still mentions in `inline` or `conflicts`.
+        for name in &dropped {
+            if name.starts_with('_')
+                || self.variables_to_inline.contains(name)
+                || self.expected_conflicts.iter().any(|c| c.contains(name))
+            {
+                continue;
+            }
+            diagnostics.push(Diagnostic::UnusedRule { name: name.clone() });
+        }
+
         for name in &dropped {
             self.expected_conflicts.retain(|r| !r.contains(name));
             self.supertype_symbols.retain(|r| r != name);
+            self.descriptions.remove(name);
+            for group in &mut self.token_groups {
+                group.retain(|r| r != name);
+            }
             self.variables_to_inline.retain(|r| r != name);
             self.extra_symbols
                 .retain(|r| !rule_is_referenced(r, name, true));
@@ -296,6 +408,17 @@ pub(crate) fn parse_grammar(
     diagnostics: &mut Vec<Diagnostic>,
 ) -> ParseGrammarResult<InputGrammar> {
     let grammar_json = serde_json::from_str::<GrammarJSON>(input)?;
+    Ok(parse_grammar_json(grammar_json, diagnostics)?.normalize(diagnostics))
+}
+
+fn parse_grammar_json(
+    grammar_json: GrammarJSON,
+    diagnostics: &mut Vec<Diagnostic>,
+) -> ParseGrammarResult<InputGrammar> {
+    let base_grammar = grammar_json
+        .extends
+        .map(|base| parse_grammar_json(*base, diagnostics))
+        .transpose()?;
 
     let extra_symbols =
         grammar_json
@@ -312,10 +435,24 @@ pub(crate) fn parse_grammar(
                 ParseGrammarResult::Ok(acc)
             })?;
 
+    let mut external_token_kinds = BTreeMap::new();
     let external_tokens = grammar_json
         .externals
         .into_iter()
-        .map(|e| parse_rule(e, false, diagnostics))
+        .map(|external| {
+            if let Some(hidden) = external.hidden {
+                let RuleJSON::SYMBOL { name } = &external.rule else {
+                    Err(ParseGrammarError::InvalidHiddenExternal)?
+                };
+                let kind = if hidden {
+                    VariableType::Hidden
+                } else {
+                    VariableType::Named
+                };
+                external_token_kinds.insert(name.clone(), kind);
+            }
+            parse_rule(external.rule, false, diagnostics)
+        })
         .collect::<ParseGrammarResult<Vec<_>>>()?;
 
     let mut precedence_orderings = Vec::with_capacity(grammar_json.precedences.len());
@@ -376,10 +513,15 @@ pub(crate) fn parse_grammar(
         variables,
         extra_symbols,
         external_tokens,
+        external_token_kinds,
         reserved_words,
-    }
-    .normalize(diagnostics);
-    Ok(grammar)
+        descriptions: grammar_json.descriptions,
+        token_groups: grammar_json.token_groups,
+    };
+    Ok(match base_grammar {
+        Some(base_grammar) => grammar.extend(base_grammar),
+        None => grammar,
+    })
 }
 
 fn parse_rule(
@@ -519,4 +661,168 @@ mod tests {
             ]
         );
     }
+
+    #[test]
+    fn test_parse_grammar_with_hidden_externals() {
+        let grammar = parse_grammar(
+            r#"{
+            "name": "my_lang",
+            "externals": [
+                {"type": "SYMBOL", "name": "comment", "hidden": true},
+                {"type": "SYMBOL", "name": "_string", "hidden": false},
+                {"type": "SYMBOL", "name": "_newline"}
+            ],
+            "rules": {
+                "file": {"type": "SYMBOL", "name": "_string"}
+            }
+        }"#,
+            &mut Vec::new(),
+        )
+        .unwrap();
+        assert_eq!(
+            grammar.external_tokens,
+            [
+                Rule::named("comment"),
+                Rule::named("_string"),
+                Rule::named("_newline")
+            ]
+        );
+        assert_eq!(
+            grammar.external_token_kinds,
+            BTreeMap::from([
+                ("comment".to_string(), VariableType::Hidden),
+                ("_string".to_string(), VariableType::Named),
+            ])
+        );
+
+        let error = parse_grammar(
+            r#"{
+            "name": "my_lang",
+            "externals": [{"type": "STRING", "value": "}", "hidden": true}],
+            "rules": {
+                "file": {"type": "STRING", "value": "a"}
+            }
+        }"#,
+            &mut Vec::new(),
+        )
+        .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Only symbols in the `externals` array can be marked as hidden"
+        );
+    }
+
+    #[test]
+    fn test_parse_grammar_reports_unused_rules() {
+        let mut diagnostics = Vec::new();
+        let grammar = parse_grammar(
+            r#"{
+            "name": "my_lang",
+            "conflicts": [["expression", "old_expression"]],
+            "inline": ["old_statement"],
+            "rules": {
+                "file": {
+                    "type": "REPEAT",
+                    "content": {
+                        "type": "SYMBOL",
+                        "name": "expression"
+                    }
+                },
+                "expression": {
+                    "type": "STRING",
+                    "value": "foo"
+                },
+                "old_thing": {
+                    "type": "STRING",
+                    "value": "bar"
+                },
+                "old_expression": {
+                    "type": "STRING",
+                    "value": "baz"
+                },
+                "old_statement": {
+                    "type": "STRING",
+                    "value": "qux"
+                },
+                "_old_helper": {
+                    "type": "STRING",
+                    "value": "quux"
+                }
+            }
+        }"#,
+            &mut diagnostics,
+        )
+        .unwrap();
+
+        assert_eq!(
+            grammar
+                .variables
+                .iter()
+                .map(|v| v.name.as_str())
+                .collect::<Vec<_>>(),
+            ["file", "expression"]
+        );
+        assert_eq!(
+            diagnostics
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>(),
+            ["rule `old_thing` is never used"]
+        );
+    }
+
+    #[test]
+    fn test_parse_grammar_keeps_rules_reachable_through_cycles() {
+        let mut diagnostics = Vec::new();
+        let grammar = parse_grammar(
+            r#"{
+            "name": "my_lang",
+            "rules": {
+                "file": {"type": "REPEAT", "content": {"type": "SYMBOL", "name": "statement"}},
+                "statement": {
+                    "type": "CHOICE",
+                    "members": [
+                        {"type": "SYMBOL", "name": "expression"},
+                        {"type": "SYMBOL", "name": "block"}
+                    ]
+                },
+                "block": {"type": "SEQ", "members": [
+                    {"type": "STRING", "value": "{"},
+                    {"type": "REPEAT", "content": {"type": "SYMBOL", "name": "statement"}},
+                    {"type": "STRING", "value": "}"}
+                ]},
+                "expression": {"type": "SYMBOL", "name": "identifier"},
+                "identifier": {"type": "PATTERN", "value": "[a-z]+"},
+                "old_list": {"type": "SYMBOL", "name": "old_item"},
+                "old_item": {"type": "SEQ", "members": [
+                    {"type": "SYMBOL", "name": "identifier"},
+                    {"type": "SYMBOL", "name": "old_list"}
+                ]}
+            }
+        }"#,
+            &mut diagnostics,
+        )
+        .unwrap();
+
+        // `statement` and `block` refer to each other, and everything that they refer to is
+        // kept. The rules that only refer to each other and to used rules are dropped.
+        assert_eq!(
+            grammar
+                .variables
+                .iter()
+                .map(|v| v.name.as_str())
+                .collect::<Vec<_>>(),
+            ["file", "statement", "block", "expression", "identifier"]
+        );
+        assert_eq!(
+            diagnostics
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>(),
+            [
+                "rule `old_list` is never used",
+                "rule `old_item` is never used"
+            ]
+        );
+    }
 }
@@ -18,25 +18,34 @@ pub use flatten_grammar::FlattenGrammarError;
 use indexmap::IndexMap;
 pub use intern_symbols::InternSymbolsError;
 pub use process_inlines::ProcessInlinesError;
+use regex_syntax::{
+    ParserBuilder, ast,
+    hir::{Hir, HirKind, Look},
+};
 use rustc_hash::{FxHashMap, FxHashSet};
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
 pub use self::expand_tokens::expand_tokens;
 use self::{
-    expand_repeats::expand_repeats, extract_default_aliases::extract_default_aliases,
-    extract_tokens::extract_tokens, flatten_grammar::flatten_grammar,
-    intern_symbols::intern_symbols, process_inlines::process_inlines,
+    expand_repeats::expand_repeats,
+    extract_default_aliases::extract_default_aliases,
+    extract_tokens::extract_tokens,
+    flatten_grammar::flatten_grammar,
+    intern_symbols::{intern_symbols, validate_symbols},
+    process_inlines::process_inlines,
 };
 use super::{
     grammars::{
         ExternalToken, InlinedProductionMap, InputGrammar, LexicalGrammar, PrecedenceEntry,
-        SyntaxGrammar, Variable,
+        SyntaxGrammar, Variable, VariableType,
     },
-    rules::{AliasMap, Precedence, Rule, Symbol},
+    nfa::NfaCursor,
+    rules::{AliasMap, Precedence, Rule, Symbol, SymbolType},
 };
 use crate::{Diagnostic, grammars::ReservedWordContext};
 
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct IntermediateGrammar<T, U> {
     variables: Vec<Variable>,
     extra_symbols: Vec<T>,
@@ -47,13 +56,15 @@ pub struct IntermediateGrammar<T, U> {
     supertype_symbols: Vec<Symbol>,
     word_token: Option<Symbol>,
     reserved_word_sets: Vec<ReservedWordContext<T>>,
+    descriptions: Vec<(Symbol, String)>,
+    token_groups: Vec<Vec<Symbol>>,
 }
 
 pub type InternedGrammar = IntermediateGrammar<Rule, Variable>;
 
 pub type ExtractedSyntaxGrammar = IntermediateGrammar<Symbol, ExternalToken>;
 
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct ExtractedLexicalGrammar {
     pub variables: Vec<Variable>,
     pub separators: Vec<Rule>,
@@ -71,10 +82,75 @@ impl<T, U> Default for IntermediateGrammar<T, U> {
             supertype_symbols: Vec::default(),
             word_token: Option::default(),
             reserved_word_sets: Vec::default(),
+            descriptions: Vec::default(),
+            token_groups: Vec::default(),
         }
     }
 }
 
+// These are only compiled for tests, where they record and reload the input of individual
+// passes. The intermediate grammars are made of the crate's internal `grammars` and `rules`
+// types, which aren't part of its public API, so nothing outside of this crate could produce
+// or consume their JSON.
+#[cfg(test)]
+impl<T, U> IntermediateGrammar<T, U>
+where
+    T: Serialize + serde::de::DeserializeOwned,
+    U: Serialize + serde::de::DeserializeOwned,
+{
+    /// Serialize this grammar to JSON, so that the rest of the pipeline can be
+    /// run on it in isolation.
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        serde_json::to_string_pretty(self)
+    }
+
+    /// Load a grammar that was previously serialized with [`Self::to_json`].
+    pub fn from_json(json: &str) -> serde_json::Result<Self> {
+        serde_json::from_str(json)
+    }
+}
+
+impl InternedGrammar {
+    /// Get every symbol that is transitively referenced by the rule for the
+    /// given symbol.
+    ///
+    /// The given symbol is only included in the result if it is part of a
+    /// cycle, i.e. if its rule can reach itself.
+    #[cfg_attr(not(test), expect(dead_code))]
+    pub fn reachable_from(&self, symbol: Symbol) -> FxHashSet<Symbol> {
+        fn add_referenced_symbols(rule: &Rule, f: &mut impl FnMut(Symbol)) {
+            match rule {
+                Rule::Symbol(symbol) => f(*symbol),
+                Rule::Choice(rules) | Rule::Seq(rules) => {
+                    for rule in rules {
+                        add_referenced_symbols(rule, f);
+                    }
+                }
+                Rule::Metadata { rule, .. } | Rule::Repeat(rule) | Rule::Reserved { rule, .. } => {
+                    add_referenced_symbols(rule, f);
+                }
+                Rule::Blank | Rule::String(_) | Rule::Pattern(..) | Rule::NamedSymbol(_) => {}
+            }
+        }
+
+        let mut result = FxHashSet::default();
+        let mut stack = vec![symbol];
+        while let Some(symbol) = stack.pop() {
+            let rule = match symbol.kind {
+                SymbolType::NonTerminal => &self.variables[symbol.index].rule,
+                SymbolType::External => &self.external_tokens[symbol.index].rule,
+                _ => continue,
+            };
+            add_referenced_symbols(rule, &mut |referenced| {
+                if result.insert(referenced) {
+                    stack.push(referenced);
+                }
+            });
+        }
+        result
+    }
+}
+
 pub type PrepareGrammarResult<T> = Result<T, PrepareGrammarError>;
 
 #[derive(Debug, Error, Serialize, Deserialize)]
@@ -82,6 +158,7 @@ pub type PrepareGrammarResult<T> = Result<T, PrepareGrammarError>;
 pub enum PrepareGrammarError {
     ValidatePrecedences(#[from] ValidatePrecedenceError),
     ValidateIndirectRecursion(#[from] IndirectRecursionError),
+    ValidatePatterns(#[from] UnsupportedPatternError),
     InternSymbols(#[from] InternSymbolsError),
     ExtractTokens(#[from] ExtractTokensError),
     FlattenGrammar(#[from] FlattenGrammarError),
@@ -114,6 +191,30 @@ impl std::fmt::Display for IndirectRecursionError {
     }
 }
 
+#[derive(Debug, Error, Serialize, Deserialize)]
+pub struct UnsupportedPatternError {
+    /// The rule that contains the pattern, or `None` if the pattern is one of the grammar's
+    /// extras.
+    pub rule: Option<String>,
+    pub pattern: String,
+    pub feature: String,
+}
+
+impl std::fmt::Display for UnsupportedPatternError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(rule) = &self.rule {
+            write!(f, "Rule `{rule}`: ")?;
+        } else {
+            write!(f, "Extras: ")?;
+        }
+        write!(
+            f,
+            "{} are not supported in token patterns (in `{}`)",
+            self.feature, self.pattern
+        )
+    }
+}
+
 #[derive(Debug, Error, Serialize, Deserialize)]
 pub struct UndeclaredPrecedenceError {
     pub precedence: String,
@@ -148,6 +249,20 @@ impl std::fmt::Display for ConflictingPrecedenceOrderingError {
     }
 }
 
+/// Check an input grammar for problems with its symbols, reporting all of them at once.
+///
+/// These are the problems that would cause [`prepare_grammar`] to fail while interning
+/// symbols, such as hidden start rules, duplicate rule names, or references to undefined
+/// symbols. Unlike [`prepare_grammar`], this reports every problem instead of only the first.
+pub fn validate_grammar(input_grammar: &InputGrammar) -> Result<(), Vec<InternSymbolsError>> {
+    let errors = validate_symbols(input_grammar);
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
 /// Transform an input grammar into separate components that are ready
 /// for parse table construction.
 pub fn prepare_grammar(
@@ -158,18 +273,321 @@ pub fn prepare_grammar(
     LexicalGrammar,
     InlinedProductionMap,
     AliasMap,
+)> {
+    let (syntax_grammar, lexical_grammar, inlines, default_aliases, _) =
+        prepare_grammar_with_opts(input_grammar, false, false, diagnostics)?;
+    Ok((syntax_grammar, lexical_grammar, inlines, default_aliases))
+}
+
+/// Transform an input grammar like [`prepare_grammar`], with options.
+///
+/// If `lexer_only` is set, the lexical grammar is the same, but the syntax grammar is replaced
+/// with a single rule that matches any sequence of tokens. See [`lexer_only_grammar`].
+///
+/// If `strict` is set, a `seq` or `choice` with a single element is reported even where it
+/// is replaced by its element, and so has no effect on the parser.
+///
+/// Along with the components, this returns the number of auxiliary rules that were created
+/// to match repetitions.
+pub fn prepare_grammar_with_opts(
+    input_grammar: &InputGrammar,
+    lexer_only: bool,
+    strict: bool,
+    diagnostics: &mut Vec<Diagnostic>,
+) -> PrepareGrammarResult<(
+    SyntaxGrammar,
+    LexicalGrammar,
+    InlinedProductionMap,
+    AliasMap,
+    usize,
 )> {
     validate_precedences(input_grammar)?;
     validate_indirect_recursion(input_grammar)?;
+    validate_patterns(input_grammar)?;
 
-    let interned_grammar = intern_symbols(input_grammar, diagnostics)?;
-    let (syntax_grammar, lexical_grammar) = extract_tokens(interned_grammar)?;
-    let syntax_grammar = expand_repeats(syntax_grammar);
+    let interned_grammar = intern_symbols(input_grammar, strict, diagnostics)?;
+    find_constant_rules(&interned_grammar, diagnostics);
+    let (mut syntax_grammar, mut lexical_grammar) = extract_tokens(interned_grammar)?;
+    if lexer_only {
+        syntax_grammar = lexer_only_grammar(syntax_grammar, &mut lexical_grammar);
+    }
+    let token_groups = mem::take(&mut syntax_grammar.token_groups);
+    let (syntax_grammar, repeat_count) = expand_repeats(syntax_grammar);
     let mut syntax_grammar = flatten_grammar(syntax_grammar)?;
+    let shadow_candidates = lexical_grammar
+        .variables
+        .iter()
+        .map(can_shadow_word_token)
+        .collect::<Vec<_>>();
     let lexical_grammar = expand_tokens(lexical_grammar)?;
+    find_word_token_shadows(
+        &lexical_grammar,
+        syntax_grammar.word_token,
+        &shadow_candidates,
+        diagnostics,
+    );
+    find_token_group_overlaps(&lexical_grammar, &token_groups, diagnostics);
     let default_aliases = extract_default_aliases(&mut syntax_grammar, &lexical_grammar);
     let inlines = process_inlines(&syntax_grammar, &lexical_grammar)?;
-    Ok((syntax_grammar, lexical_grammar, inlines, default_aliases))
+    Ok((
+        syntax_grammar,
+        lexical_grammar,
+        inlines,
+        default_aliases,
+        repeat_count,
+    ))
+}
+
+/// Replace the rules of a syntax grammar with a single rule that matches any sequence of
+/// its tokens.
+///
+/// The start rule keeps its name, and its children are the input's tokens, in order. Hidden
+/// and auxiliary tokens are made visible so that they appear in the token stream. Extras
+/// that aren't tokens and external tokens are dropped, since they can't be matched without
+/// the rest of the grammar or the external scanner.
+fn lexer_only_grammar(
+    syntax_grammar: ExtractedSyntaxGrammar,
+    lexical_grammar: &mut ExtractedLexicalGrammar,
+) -> ExtractedSyntaxGrammar {
+    let extra_symbols = syntax_grammar
+        .extra_symbols
+        .into_iter()
+        .filter(Symbol::is_terminal)
+        .collect::<Vec<_>>();
+    let tokens = (0..lexical_grammar.variables.len())
+        .map(Symbol::terminal)
+        .filter(|symbol| !extra_symbols.contains(symbol))
+        .map(Rule::Symbol)
+        .collect::<Vec<_>>();
+    for variable in &mut lexical_grammar.variables {
+        if matches!(
+            variable.kind,
+            VariableType::Hidden | VariableType::Auxiliary
+        ) {
+            variable.kind = VariableType::Named;
+        }
+    }
+
+    let rule = if tokens.is_empty() {
+        Rule::Blank
+    } else {
+        Rule::choice(vec![Rule::repeat(Rule::choice(tokens)), Rule::Blank])
+    };
+    let name = syntax_grammar
+        .variables
+        .into_iter()
+        .next()
+        .map_or_else(|| "source_file".to_string(), |variable| variable.name);
+    ExtractedSyntaxGrammar {
+        variables: vec![Variable {
+            name,
+            kind: VariableType::Named,
+            rule,
+        }],
+        extra_symbols,
+        precedence_orderings: syntax_grammar.precedence_orderings,
+        word_token: syntax_grammar.word_token,
+        reserved_word_sets: syntax_grammar.reserved_word_sets,
+        descriptions: syntax_grammar
+            .descriptions
+            .into_iter()
+            .filter(|(symbol, _)| symbol.is_terminal())
+            .collect(),
+        token_groups: syntax_grammar.token_groups,
+        ..Default::default()
+    }
+}
+
+/// Warn about visible rules that can only ever match one fixed string. Unless the rule is
+/// already a single token, it produces a node with a lone anonymous child, which is better
+/// expressed by the string itself, a `token`, or an `alias`.
+fn find_constant_rules(grammar: &InternedGrammar, diagnostics: &mut Vec<Diagnostic>) {
+    for variable in &grammar.variables {
+        if variable.kind != VariableType::Named || matches!(variable.rule, Rule::String(_)) {
+            continue;
+        }
+        if let Some(value) = constant_string(&variable.rule) {
+            diagnostics.push(Diagnostic::ConstantRule {
+                name: variable.name.clone(),
+                value: value.clone(),
+            });
+        }
+    }
+}
+
+/// If this rule always matches the same single string, return that string.
+fn constant_string(rule: &Rule) -> Option<&String> {
+    match rule {
+        Rule::String(value) => Some(value),
+        Rule::Metadata { params, rule } if !params.is_token => constant_string(rule),
+        Rule::Choice(rules) | Rule::Seq(rules) if rules.len() == 1 => constant_string(&rules[0]),
+        _ => None,
+    }
+}
+
+/// Warn about tokens that can only match strings that the word token also matches. During
+/// keyword extraction, such a token is lexed as the word token and then treated as a keyword,
+/// which is expected for literal strings like `"if"`, but rarely for named patterns.
+fn find_word_token_shadows(
+    lexical_grammar: &LexicalGrammar,
+    word_token: Option<Symbol>,
+    candidates: &[bool],
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    let Some(word_token) = word_token.filter(Symbol::is_terminal) else {
+        return;
+    };
+
+    let mut cursor = NfaCursor::new(&lexical_grammar.nfa, Vec::new());
+    for (i, variable) in lexical_grammar.variables.iter().enumerate() {
+        if i != word_token.index
+            && candidates[i]
+            && is_token_subset(lexical_grammar, &mut cursor, i, word_token.index)
+        {
+            diagnostics.push(Diagnostic::WordTokenShadow {
+                name: variable.name.clone(),
+            });
+        }
+    }
+}
+
+/// Warn about pairs of tokens in the same token group that can match the same string. The
+/// grammar author has declared that the tokens in each group are mutually exclusive.
+fn find_token_group_overlaps(
+    lexical_grammar: &LexicalGrammar,
+    token_groups: &[Vec<Symbol>],
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    let mut cursor = NfaCursor::new(&lexical_grammar.nfa, Vec::new());
+    for group in token_groups {
+        for (i, first) in group.iter().enumerate() {
+            for second in &group[i + 1..] {
+                if first != second
+                    && do_tokens_overlap(lexical_grammar, &mut cursor, first.index, second.index)
+                {
+                    diagnostics.push(Diagnostic::TokenGroupOverlap {
+                        first: lexical_grammar.variables[first.index].name.clone(),
+                        second: lexical_grammar.variables[second.index].name.clone(),
+                    });
+                }
+            }
+        }
+    }
+}
+
+/// Is there any string that is matched by both token `i` and token `j`?
+fn do_tokens_overlap(
+    lexical_grammar: &LexicalGrammar,
+    cursor: &mut NfaCursor,
+    i: usize,
+    j: usize,
+) -> bool {
+    let mut visited_state_sets = FxHashSet::default();
+    let mut state_set_queue = vec![vec![
+        lexical_grammar.variables[i].start_state,
+        lexical_grammar.variables[j].start_state,
+    ]];
+    while let Some(state_set) = state_set_queue.pop() {
+        cursor.reset(state_set);
+        let (mut completes_i, mut completes_j) = (false, false);
+        for (id, _) in cursor.completions() {
+            completes_i |= id == i;
+            completes_j |= id == j;
+        }
+        if completes_i && completes_j {
+            return true;
+        }
+
+        for transition in cursor.transitions() {
+            if transition.is_separator {
+                continue;
+            }
+            let mut has_i = false;
+            let mut has_j = false;
+            for state_id in &transition.states {
+                match lexical_grammar.variable_index_for_nfa_state(*state_id) {
+                    id if id == i => has_i = true,
+                    id if id == j => has_j = true,
+                    _ => {}
+                }
+            }
+            if has_i && has_j && visited_state_sets.insert(transition.states.clone()) {
+                state_set_queue.push(transition.states);
+            }
+        }
+    }
+    false
+}
+
+/// Does every string matched by token `i` also match token `j`?
+pub fn is_token_subset(
+    lexical_grammar: &LexicalGrammar,
+    cursor: &mut NfaCursor,
+    i: usize,
+    j: usize,
+) -> bool {
+    let mut visited_state_sets = FxHashSet::default();
+    let mut state_set_queue = vec![vec![
+        lexical_grammar.variables[i].start_state,
+        lexical_grammar.variables[j].start_state,
+    ]];
+    while let Some(state_set) = state_set_queue.pop() {
+        cursor.reset(state_set);
+        let (mut completes_i, mut completes_j) = (false, false);
+        for (id, _) in cursor.completions() {
+            completes_i |= id == i;
+            completes_j |= id == j;
+        }
+        if completes_i && !completes_j {
+            return false;
+        }
+
+        for transition in cursor.transitions() {
+            if transition.is_separator {
+                continue;
+            }
+            let mut has_i = false;
+            let mut has_j = false;
+            for state_id in &transition.states {
+                match lexical_grammar.variable_index_for_nfa_state(*state_id) {
+                    id if id == i => has_i = true,
+                    id if id == j => has_j = true,
+                    _ => {}
+                }
+            }
+            if has_i && !has_j {
+                return false;
+            }
+            if has_i && visited_state_sets.insert(transition.states.clone()) {
+                state_set_queue.push(transition.states);
+            }
+        }
+    }
+    true
+}
+
+/// Only check tokens that are declared as rules of their own. Anonymous tokens are usually
+/// keywords, and immediate tokens are commonly used for suffixes that look like words.
+fn can_shadow_word_token(variable: &Variable) -> bool {
+    let is_immediate = matches!(
+        &variable.rule,
+        Rule::Metadata { params, .. } if params.is_main_token
+    );
+    matches!(variable.kind, VariableType::Named | VariableType::Hidden)
+        && !is_immediate
+        && !is_literal(&variable.rule)
+}
+
+/// Does this token only match a fixed set of literal strings?
+fn is_literal(rule: &Rule) -> bool {
+    match rule {
+        Rule::Blank | Rule::String(_) => true,
+        // Patterns like `/var/i` are used for case-insensitive keywords.
+        Rule::Pattern(value, _) => value.chars().all(|c| c.is_alphanumeric() || c == '_'),
+        Rule::Metadata { rule, .. } => is_literal(rule),
+        Rule::Choice(rules) | Rule::Seq(rules) => rules.iter().all(is_literal),
+        _ => false,
+    }
 }
 
 /// Check for indirect recursion cycles in the grammar that can cause infinite loops while
@@ -249,6 +667,76 @@ fn get_cycle<'a>(
     None
 }
 
+/// Check that no pattern uses a regex feature that the lexer can't match. These would also
+/// fail when the lexical grammar is built, but by then, the patterns have been extracted into
+/// tokens whose names don't always match the rules that they came from.
+fn validate_patterns(grammar: &InputGrammar) -> Result<(), UnsupportedPatternError> {
+    fn validate(rule_name: Option<&str>, rule: &Rule) -> Result<(), UnsupportedPatternError> {
+        match rule {
+            Rule::Pattern(pattern, _) => {
+                if let Some(feature) = unsupported_regex_feature(pattern) {
+                    Err(UnsupportedPatternError {
+                        rule: rule_name.map(str::to_string),
+                        pattern: pattern.clone(),
+                        feature: feature.to_string(),
+                    })?;
+                }
+                Ok(())
+            }
+            Rule::Repeat(rule) | Rule::Metadata { rule, .. } | Rule::Reserved { rule, .. } => {
+                validate(rule_name, rule)
+            }
+            Rule::Seq(elements) | Rule::Choice(elements) => {
+                elements.iter().try_for_each(|e| validate(rule_name, e))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    for variable in &grammar.variables {
+        validate(Some(&variable.name), &variable.rule)?;
+    }
+    for rule in &grammar.extra_symbols {
+        validate(None, rule)?;
+    }
+    Ok(())
+}
+
+/// Describe the first regex feature in the pattern that can't be compiled into the lexer.
+/// Patterns that are invalid for other reasons are reported when the lexer is built.
+fn unsupported_regex_feature(pattern: &str) -> Option<&'static str> {
+    fn find_assertion(hir: &Hir) -> Option<&'static str> {
+        match hir.kind() {
+            HirKind::Look(
+                Look::Start
+                | Look::End
+                | Look::StartLF
+                | Look::EndLF
+                | Look::StartCRLF
+                | Look::EndCRLF,
+            ) => Some("anchors"),
+            HirKind::Look(_) => Some("word boundary assertions"),
+            HirKind::Repetition(repetition) => find_assertion(&repetition.sub),
+            HirKind::Capture(capture) => find_assertion(&capture.sub),
+            HirKind::Concat(hirs) | HirKind::Alternation(hirs) => {
+                hirs.iter().find_map(find_assertion)
+            }
+            HirKind::Empty | HirKind::Literal(_) | HirKind::Class(_) => None,
+        }
+    }
+
+    let mut parser = ParserBuilder::new().utf8(false).build();
+    match parser.parse(pattern) {
+        Ok(hir) => find_assertion(&hir),
+        Err(regex_syntax::Error::Parse(error)) => match error.kind() {
+            ast::ErrorKind::UnsupportedBackreference => Some("backreferences"),
+            ast::ErrorKind::UnsupportedLookAround => Some("lookahead and lookbehind assertions"),
+            _ => None,
+        },
+        Err(_) => None,
+    }
+}
+
 /// Check that all of the named precedences used in the grammar are declared
 /// within the `precedences` lists, and also that there are no conflicting
 /// precedence orderings declared in those lists.
@@ -334,7 +822,283 @@ fn validate_precedences(grammar: &InputGrammar) -> ValidatePrecedenceResult<()>
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::grammars::VariableType;
+
+    #[test]
+    fn test_interned_grammar_json_round_trip() {
+        let mut input_grammar = InputGrammar {
+            variables: vec![
+                Variable::named(
+                    "program",
+                    Rule::repeat(Rule::choice(vec![
+                        Rule::named("_expression"),
+                        Rule::named("comment"),
+                    ])),
+                ),
+                Variable::named(
+                    "_expression",
+                    Rule::choice(vec![Rule::named("identifier"), Rule::named("call")]),
+                ),
+                Variable::named(
+                    "call",
+                    Rule::prec_left(
+                        Precedence::Integer(1),
+                        Rule::seq(vec![
+                            Rule::field("function".to_string(), Rule::named("_expression")),
+                            Rule::string("("),
+                            Rule::string(")"),
+                        ]),
+                    ),
+                ),
+                Variable::named("identifier", Rule::pattern("[a-z]+", "")),
+            ],
+            extra_symbols: vec![Rule::pattern("\\s", "")],
+            supertype_symbols: vec!["_expression".to_string()],
+            word_token: Some("identifier".to_string()),
+            ..Default::default()
+        };
+        input_grammar.external_tokens.push(Rule::named("comment"));
+
+        let interned_grammar = intern_symbols(&input_grammar, false, &mut Vec::new()).unwrap();
+        let json = interned_grammar.to_json().unwrap();
+        let reloaded_grammar = InternedGrammar::from_json(&json).unwrap();
+        assert_eq!(reloaded_grammar, interned_grammar);
+
+        // The reloaded grammar can be fed to the next stage of the pipeline.
+        let (syntax_grammar, lexical_grammar) = extract_tokens(reloaded_grammar).unwrap();
+        let (expected_syntax_grammar, expected_lexical_grammar) =
+            extract_tokens(interned_grammar).unwrap();
+        assert_eq!(syntax_grammar, expected_syntax_grammar);
+        assert_eq!(lexical_grammar, expected_lexical_grammar);
+        assert_eq!(
+            ExtractedSyntaxGrammar::from_json(&syntax_grammar.to_json().unwrap()).unwrap(),
+            syntax_grammar,
+        );
+    }
+
+    #[test]
+    fn test_interned_grammar_reachable_from() {
+        let input_grammar = InputGrammar {
+            variables: vec![
+                Variable::named("program", Rule::repeat(Rule::named("statement"))),
+                Variable::named(
+                    "statement",
+                    Rule::seq(vec![Rule::named("expression"), Rule::string(";")]),
+                ),
+                Variable::named(
+                    "expression",
+                    Rule::choice(vec![
+                        Rule::named("identifier"),
+                        Rule::named("block"),
+                        Rule::named("heredoc"),
+                    ]),
+                ),
+                Variable::named(
+                    "block",
+                    Rule::seq(vec![
+                        Rule::string("{"),
+                        Rule::repeat(Rule::named("statement")),
+                        Rule::string("}"),
+                    ]),
+                ),
+                Variable::named("identifier", Rule::pattern("[a-z]+", "")),
+                Variable::named("unused", Rule::named("identifier")),
+            ],
+            external_tokens: vec![Rule::named("heredoc")],
+            ..Default::default()
+        };
+        let grammar = intern_symbols(&input_grammar, false, &mut Vec::new()).unwrap();
+        let symbol = |name: &str| {
+            grammar
+                .variables
+                .iter()
+                .position(|v| v.name == name)
+                .map_or_else(
+                    || {
+                        let index = grammar.external_tokens.iter().position(|v| v.name == name);
+                        Symbol::external(index.unwrap())
+                    },
+                    Symbol::non_terminal,
+                )
+        };
+        let symbols = |names: &[&str]| names.iter().map(|name| symbol(name)).collect();
+
+        // `statement` is part of a cycle through `expression` and `block`, so
+        // it is reachable from itself.
+        assert_eq!(
+            grammar.reachable_from(symbol("statement")),
+            symbols(&["statement", "expression", "identifier", "block", "heredoc"])
+        );
+        assert_eq!(
+            grammar.reachable_from(symbol("program")),
+            symbols(&["statement", "expression", "identifier", "block", "heredoc"])
+        );
+        assert_eq!(
+            grammar.reachable_from(symbol("unused")),
+            symbols(&["identifier"])
+        );
+        assert!(grammar.reachable_from(symbol("identifier")).is_empty());
+    }
+
+    #[test]
+    fn test_word_token_shadows() {
+        let grammar = InputGrammar {
+            variables: vec![
+                Variable::named(
+                    "program",
+                    Rule::repeat(Rule::choice(vec![
+                        Rule::named("identifier"),
+                        Rule::named("label"),
+                        Rule::named("type_name"),
+                        Rule::named("number"),
+                        Rule::named("boolean"),
+                        Rule::string("if"),
+                    ])),
+                ),
+                Variable::named("identifier", Rule::pattern("[a-z_]+", "")),
+                Variable::named("label", Rule::pattern("[a-z]+", "")),
+                Variable::named("type_name", Rule::pattern("[A-Z][a-z]*", "")),
+                Variable::named("number", Rule::pattern("\\d+", "")),
+                Variable::named(
+                    "boolean",
+                    Rule::token(Rule::choice(vec![
+                        Rule::string("true"),
+                        Rule::string("false"),
+                    ])),
+                ),
+            ],
+            extra_symbols: vec![Rule::pattern("\\s", "")],
+            word_token: Some("identifier".to_string()),
+            ..Default::default()
+        };
+
+        let mut diagnostics = Vec::new();
+        prepare_grammar(&grammar, &mut diagnostics).unwrap();
+        assert!(matches!(
+            diagnostics.as_slice(),
+            [Diagnostic::WordTokenShadow { name }] if name == "label"
+        ));
+        assert_eq!(
+            diagnostics[0].to_string(),
+            "token `label` only matches strings that the word token also matches, \
+             so it may be treated as a keyword"
+        );
+
+        // Without a word token, there is nothing to shadow.
+        let grammar = InputGrammar {
+            word_token: None,
+            ..grammar
+        };
+        diagnostics.clear();
+        prepare_grammar(&grammar, &mut diagnostics).unwrap();
+        assert!(diagnostics.is_empty());
+    }
+
+    #[test]
+    fn test_constant_rules() {
+        let grammar = InputGrammar {
+            variables: vec![
+                Variable::named(
+                    "program",
+                    Rule::repeat(Rule::choice(vec![
+                        Rule::named("block"),
+                        Rule::named("begin"),
+                        Rule::named("end"),
+                        Rule::named("keyword"),
+                    ])),
+                ),
+                Variable::named(
+                    "block",
+                    Rule::seq(vec![Rule::named("begin"), Rule::named("end")]),
+                ),
+                Variable::named("begin", Rule::seq(vec![Rule::string("begin")])),
+                Variable::named("end", Rule::string("end")),
+                Variable::named("keyword", Rule::token(Rule::string("do"))),
+                Variable::hidden(
+                    "_then",
+                    Rule::prec(Precedence::Integer(1), Rule::string("then")),
+                ),
+            ],
+            ..Default::default()
+        };
+
+        let mut diagnostics = Vec::new();
+        prepare_grammar(&grammar, &mut diagnostics).unwrap();
+        assert!(matches!(
+            diagnostics.as_slice(),
+            [
+                Diagnostic::UnarySeq { .. },
+                Diagnostic::ConstantRule { name, value },
+            ] if name == "begin" && value == "begin"
+        ));
+        assert_eq!(
+            diagnostics[1].to_string(),
+            "rule `begin` only matches the string \"begin\". \
+             consider using a token or an alias instead."
+        );
+    }
+
+    #[test]
+    fn test_token_group_overlaps() {
+        let mut grammar = InputGrammar {
+            variables: vec![
+                Variable::named(
+                    "program",
+                    Rule::repeat(Rule::choice(vec![
+                        Rule::named("integer"),
+                        Rule::named("float"),
+                        Rule::named("identifier"),
+                        Rule::named("type_name"),
+                        Rule::named("pair"),
+                    ])),
+                ),
+                Variable::named(
+                    "pair",
+                    Rule::seq(vec![Rule::named("identifier"), Rule::named("integer")]),
+                ),
+                Variable::named("integer", Rule::pattern("[0-9]+", "")),
+                Variable::named("float", Rule::pattern("[0-9]+\\.[0-9]+", "")),
+                Variable::named("identifier", Rule::pattern("[a-z_]+", "")),
+                Variable::named("type_name", Rule::pattern("[a-z]+_t", "")),
+            ],
+            token_groups: vec![
+                vec!["integer".to_string(), "float".to_string()],
+                vec![
+                    "integer".to_string(),
+                    "identifier".to_string(),
+                    "type_name".to_string(),
+                ],
+            ],
+            ..Default::default()
+        };
+
+        // Only `identifier` and `type_name` can match the same string, such as `size_t`.
+        let mut diagnostics = Vec::new();
+        prepare_grammar(&grammar, &mut diagnostics).unwrap();
+        assert!(matches!(
+            diagnostics.as_slice(),
+            [Diagnostic::TokenGroupOverlap { first, second }]
+                if first == "identifier" && second == "type_name"
+        ));
+        assert_eq!(
+            diagnostics[0].to_string(),
+            "tokens `identifier` and `type_name` are in the same token group, \
+             but can match the same string"
+        );
+
+        grammar.token_groups = vec![vec!["integer".to_string(), "pair".to_string()]];
+        let result = prepare_grammar(&grammar, &mut Vec::new());
+        assert_eq!(
+            result.err().unwrap().to_string(),
+            "Rule 'pair' in a token group must be a token"
+        );
+
+        grammar.token_groups = vec![vec!["integer".to_string(), "number".to_string()]];
+        let result = prepare_grammar(&grammar, &mut Vec::new());
+        assert_eq!(
+            result.err().unwrap().to_string(),
+            "Undefined symbol `number` in grammar's token groups"
+        );
+    }
 
     #[test]
     fn test_validate_precedences_with_undeclared_precedence() {
@@ -419,4 +1183,62 @@ mod tests {
             "Conflicting orderings for precedences 'a' and 'b'",
         );
     }
+
+    #[test]
+    fn test_validate_patterns() {
+        let grammar = InputGrammar {
+            variables: vec![
+                Variable::named(
+                    "program",
+                    Rule::repeat(Rule::choice(vec![
+                        Rule::named("identifier"),
+                        Rule::named("string"),
+                    ])),
+                ),
+                Variable::named("identifier", Rule::pattern(r"[a-z_]\w*", "")),
+                Variable::named(
+                    "string",
+                    Rule::token(Rule::seq(vec![
+                        Rule::pattern(r#"(["'])"#, ""),
+                        Rule::pattern(r"[^\\]*\1", ""),
+                    ])),
+                ),
+            ],
+            ..Default::default()
+        };
+        assert_eq!(
+            validate_patterns(&grammar).unwrap_err().to_string(),
+            r"Rule `string`: backreferences are not supported in token patterns (in `[^\\]*\1`)",
+        );
+
+        let grammar = InputGrammar {
+            variables: vec![Variable::named("keyword", Rule::pattern("if(?=[ (])", ""))],
+            ..Default::default()
+        };
+        assert_eq!(
+            validate_patterns(&grammar).unwrap_err().to_string(),
+            "Rule `keyword`: lookahead and lookbehind assertions are not supported in token \
+             patterns (in `if(?=[ (])`)",
+        );
+
+        let grammar = InputGrammar {
+            variables: vec![Variable::named("word", Rule::pattern("[a-z]+", ""))],
+            extra_symbols: vec![Rule::pattern(r"^\s+", "")],
+            ..Default::default()
+        };
+        assert_eq!(
+            validate_patterns(&grammar).unwrap_err().to_string(),
+            r"Extras: anchors are not supported in token patterns (in `^\s+`)",
+        );
+
+        let grammar = InputGrammar {
+            variables: vec![
+                Variable::named("word", Rule::pattern(r"[a-z]+\d*", "i")),
+                Variable::named("number", Rule::pattern(r"(0x)?[\da-f]+|\p{Nd}+", "")),
+            ],
+            extra_symbols: vec![Rule::pattern(r"\s", "")],
+            ..Default::default()
+        };
+        assert!(validate_patterns(&grammar).is_ok());
+    }
 }
@@ -102,7 +102,14 @@ impl Expander {
     }
 }
 
-pub(super) fn expand_repeats(mut grammar: ExtractedSyntaxGrammar) -> ExtractedSyntaxGrammar {
+/// Replace every repetition in the grammar with a reference to an auxiliary rule that
+/// matches the repeated content as a binary tree.
+///
+/// Repetitions with the same content share a single auxiliary rule. Along with the
+/// expanded grammar, this returns the number of auxiliary rules that were created.
+pub(super) fn expand_repeats(
+    mut grammar: ExtractedSyntaxGrammar,
+) -> (ExtractedSyntaxGrammar, usize) {
     let mut expander = Expander {
         variable_name: String::new(),
         repeat_count_in_variable: 0,
@@ -123,8 +130,9 @@ pub(super) fn expand_repeats(mut grammar: ExtractedSyntaxGrammar) -> ExtractedSy
         }
     }
 
+    let auxiliary_variable_count = expander.auxiliary_variables.len();
     grammar.variables.extend(expander.auxiliary_variables);
-    grammar
+    (grammar, auxiliary_variable_count)
 }
 
 #[cfg(test)]
@@ -134,7 +142,7 @@ mod tests {
     #[test]
     fn test_basic_repeat_expansion() {
         // Repeats nested inside of sequences and choices are expanded.
-        let grammar = expand_repeats(build_grammar(vec![Variable::named(
+        let (grammar, repeat_count) = expand_repeats(build_grammar(vec![Variable::named(
             "rule0",
             Rule::seq(vec![
                 Rule::terminal(10),
@@ -173,12 +181,13 @@ mod tests {
                 ),
             ]
         );
+        assert_eq!(repeat_count, 2);
     }
 
     #[test]
     fn test_repeat_deduplication() {
         // Terminal 4 appears inside of a repeat in three different places.
-        let grammar = expand_repeats(build_grammar(vec![
+        let (grammar, repeat_count) = expand_repeats(build_grammar(vec![
             Variable::named(
                 "rule0",
                 Rule::choice(vec![
@@ -216,11 +225,12 @@ mod tests {
                 )
             ]
         );
+        assert_eq!(repeat_count, 1);
     }
 
     #[test]
     fn test_expansion_of_nested_repeats() {
-        let grammar = expand_repeats(build_grammar(vec![Variable::named(
+        let (grammar, repeat_count) = expand_repeats(build_grammar(vec![Variable::named(
             "rule0",
             Rule::seq(vec![
                 Rule::terminal(10),
@@ -254,11 +264,12 @@ mod tests {
                 ),
             ]
         );
+        assert_eq!(repeat_count, 2);
     }
 
     #[test]
     fn test_expansion_of_repeats_at_top_of_hidden_rules() {
-        let grammar = expand_repeats(build_grammar(vec![
+        let (grammar, repeat_count) = expand_repeats(build_grammar(vec![
             Variable::named("rule0", Rule::non_terminal(1)),
             Variable::hidden(
                 "_rule1",
@@ -280,6 +291,65 @@ mod tests {
                 ),
             ]
         );
+        assert_eq!(repeat_count, 0);
+    }
+
+    #[test]
+    fn test_repeat_deduplication_of_compound_content() {
+        let content = || {
+            Rule::seq(vec![
+                Rule::terminal(1),
+                Rule::field("f".to_string(), Rule::terminal(2)),
+            ])
+        };
+
+        // Repeats whose content has the same structure share one auxiliary rule, even
+        // when they appear in different variables.
+        let (grammar, repeat_count) = expand_repeats(build_grammar(vec![
+            Variable::named("rule0", Rule::repeat(content())),
+            Variable::named(
+                "rule1",
+                Rule::seq(vec![Rule::terminal(3), Rule::repeat(content())]),
+            ),
+        ]));
+        assert_eq!(repeat_count, 1);
+        assert_eq!(
+            grammar.variables,
+            vec![
+                Variable::named("rule0", Rule::non_terminal(2)),
+                Variable::named(
+                    "rule1",
+                    Rule::seq(vec![Rule::terminal(3), Rule::non_terminal(2)])
+                ),
+                Variable::auxiliary(
+                    "rule0_repeat1",
+                    Rule::choice(vec![
+                        Rule::seq(vec![Rule::non_terminal(2), Rule::non_terminal(2)]),
+                        content(),
+                    ])
+                ),
+            ]
+        );
+
+        // Content that differs only in its metadata is not shared.
+        let (grammar, repeat_count) = expand_repeats(build_grammar(vec![
+            Variable::named("rule0", Rule::repeat(content())),
+            Variable::named(
+                "rule1",
+                Rule::repeat(Rule::seq(vec![
+                    Rule::terminal(1),
+                    Rule::field("g".to_string(), Rule::terminal(2)),
+                ])),
+            ),
+        ]));
+        assert_eq!(repeat_count, 2);
+        assert_eq!(
+            grammar.variables[..2],
+            [
+                Variable::named("rule0", Rule::non_terminal(2)),
+                Variable::named("rule1", Rule::non_terminal(3)),
+            ]
+        );
     }
 
     fn build_grammar(variables: Vec<Variable>) -> ExtractedSyntaxGrammar {
@@ -25,12 +25,18 @@ unless they are used only as the grammar's start rule.
     SupertypeTerminal(String),
     #[error("Rule '{0}' cannot be used as both an external token and a non-terminal rule")]
     ExternalTokenNonTerminal(String),
+    #[error("Rule `{rule}` is invalid: token() cannot contain a reference to a rule (`{symbol}`)")]
+    NonTerminalInToken { rule: String, symbol: String },
     #[error("Non-symbol rules cannot be used as external tokens")]
     NonSymbolExternalToken,
     #[error(transparent)]
     WordToken(NonTerminalWordTokenError),
     #[error("Reserved word '{0}' must be a token")]
     NonTokenReservedWord(String),
+    #[error("Rule '{0}' in a token group must be a token")]
+    NonTokenInTokenGroup(String),
+    #[error("External token '{0}' cannot be used in a token group")]
+    ExternalTokenInTokenGroup(String),
 }
 
 #[derive(Debug, Error, Serialize, Deserialize)]
@@ -60,6 +66,15 @@ impl std::fmt::Display for NonTerminalWordTokenError {
 pub(super) fn extract_tokens(
     mut grammar: InternedGrammar,
 ) -> ExtractTokensResult<(ExtractedSyntaxGrammar, ExtractedLexicalGrammar)> {
+    for variable in grammar.variables.iter().chain(&grammar.external_tokens) {
+        if let Some(symbol) = non_terminal_in_token(&variable.rule, false) {
+            Err(ExtractTokensError::NonTerminalInToken {
+                rule: variable.name.clone(),
+                symbol: grammar.variables[symbol.index].name.clone(),
+            })?;
+        }
+    }
+
     let mut extractor = TokenExtractor {
         current_variable_name: String::new(),
         current_variable_token_count: 0,
@@ -143,6 +158,32 @@ pub(super) fn extract_tokens(
         }
     }
 
+    let mut token_groups = Vec::with_capacity(grammar.token_groups.len());
+    for group in grammar.token_groups {
+        let mut extracted_group = Vec::with_capacity(group.len());
+        for symbol in group {
+            let new_symbol = symbol_replacer.replace_symbol(symbol);
+            if new_symbol.is_non_terminal() {
+                Err(ExtractTokensError::NonTokenInTokenGroup(
+                    variables[new_symbol.index].name.clone(),
+                ))?;
+            }
+            if new_symbol.is_external() {
+                Err(ExtractTokensError::ExternalTokenInTokenGroup(
+                    grammar.external_tokens[new_symbol.index].name.clone(),
+                ))?;
+            }
+            extracted_group.push(new_symbol);
+        }
+        token_groups.push(extracted_group);
+    }
+
+    let descriptions = grammar
+        .descriptions
+        .into_iter()
+        .map(|(symbol, description)| (symbol_replacer.replace_symbol(symbol), description))
+        .collect();
+
     let variables_to_inline = grammar
         .variables_to_inline
         .into_iter()
@@ -251,6 +292,8 @@ pub(super) fn extract_tokens(
             word_token,
             precedence_orderings: grammar.precedence_orderings,
             reserved_word_sets: reserved_word_contexts,
+            descriptions,
+            token_groups,
         },
         ExtractedLexicalGrammar {
             variables: lexical_variables,
@@ -259,6 +302,20 @@ pub(super) fn extract_tokens(
     ))
 }
 
+/// Find a reference to a non-terminal within a `token()` or `token.immediate()` in the given
+/// rule. Tokens are matched by the lexer, so they can only contain strings and patterns.
+fn non_terminal_in_token(rule: &Rule, in_token: bool) -> Option<Symbol> {
+    match rule {
+        Rule::Symbol(symbol) if in_token && symbol.is_non_terminal() => Some(*symbol),
+        Rule::Choice(elements) | Rule::Seq(elements) => elements
+            .iter()
+            .find_map(|element| non_terminal_in_token(element, in_token)),
+        Rule::Metadata { params, rule } => non_terminal_in_token(rule, in_token || params.is_token),
+        Rule::Repeat(rule) | Rule::Reserved { rule, .. } => non_terminal_in_token(rule, in_token),
+        _ => None,
+    }
+}
+
 struct TokenExtractor {
     current_variable_name: String,
     current_variable_token_count: usize,
@@ -608,6 +665,65 @@ mod test {
         );
     }
 
+    #[test]
+    fn test_error_on_non_terminal_in_token() {
+        let grammar = build_grammar(vec![
+            Variable::named(
+                "rule_0",
+                Rule::seq(vec![
+                    Rule::string("a"),
+                    Rule::immediate_token(Rule::seq(vec![
+                        Rule::string("b"),
+                        Rule::choice(vec![Rule::Blank, Rule::repeat(Rule::non_terminal(1))]),
+                    ])),
+                ]),
+            ),
+            Variable::named("something", Rule::pattern("c+", "")),
+        ]);
+
+        let err = extract_tokens(grammar).err().unwrap();
+        assert_eq!(
+            err.to_string(),
+            "Rule `rule_0` is invalid: token() cannot contain a reference to a rule (`something`)"
+        );
+    }
+
+    #[test]
+    fn test_extraction_of_lexical_token() {
+        let (syntax_grammar, lexical_grammar) = extract_tokens(build_grammar(vec![
+            Variable::named(
+                "rule_0",
+                Rule::seq(vec![Rule::non_terminal(1), Rule::non_terminal(1)]),
+            ),
+            Variable::named(
+                "rule_1",
+                Rule::token(Rule::seq(vec![
+                    Rule::string("b"),
+                    Rule::repeat(Rule::pattern("c+", "")),
+                ])),
+            ),
+        ]))
+        .unwrap();
+
+        assert_eq!(
+            syntax_grammar.variables,
+            vec![Variable::named(
+                "rule_0",
+                Rule::seq(vec![Rule::terminal(0), Rule::terminal(0)]),
+            )]
+        );
+        assert_eq!(
+            lexical_grammar.variables,
+            vec![Variable::named(
+                "rule_1",
+                Rule::seq(vec![
+                    Rule::string("b"),
+                    Rule::repeat(Rule::pattern("c+", "")),
+                ]),
+            )]
+        );
+    }
+
     #[test]
     fn test_extraction_on_hidden_terminal() {
         let (syntax_grammar, lexical_grammar) = extract_tokens(build_grammar(vec![
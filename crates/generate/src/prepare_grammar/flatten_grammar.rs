@@ -297,6 +297,7 @@ pub(super) fn flatten_grammar(
         word_token: grammar.word_token,
         reserved_word_sets,
         variables,
+        descriptions: grammar.descriptions,
     })
 }
 
@@ -523,6 +524,8 @@ mod tests {
             supertype_symbols: Vec::new(),
             word_token: None,
             reserved_word_sets: Vec::new(),
+            descriptions: Vec::new(),
+            token_groups: Vec::new(),
             variables: vec![Variable {
                 name: "test".to_string(),
                 kind: VariableType::Named,
@@ -1,53 +1,204 @@
+use rustc_hash::FxHashSet;
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
 use super::InternedGrammar;
 use crate::{
     Diagnostic,
-    grammars::{InputGrammar, ReservedWordContext, Variable, VariableType},
-    rules::{Rule, Symbol},
+    grammars::{InputGrammar, PrecedenceEntry, ReservedWordContext, Variable, VariableType},
+    rules::{MetadataParams, Precedence, Rule, Symbol},
 };
 
 pub type InternSymbolsResult<T> = Result<T, InternSymbolsError>;
 
-#[derive(Debug, Error, Serialize, Deserialize)]
+#[derive(Debug, Error, Serialize, Deserialize, PartialEq, Eq)]
 pub enum InternSymbolsError {
     #[error("A grammar's start rule must be visible.")]
     HiddenStartRule,
-    #[error("Undefined symbol `{0}`")]
-    Undefined(String),
-    #[error("Undefined symbol `{0}` in grammar's supertypes array")]
-    UndefinedSupertype(String),
-    #[error("Undefined symbol `{0}` in grammar's conflicts array")]
-    UndefinedConflict(String),
-    #[error("Undefined symbol `{0}` as grammar's word token")]
-    UndefinedWordToken(String),
+    #[error("A grammar's start rule `{0}` cannot be used as an extra")]
+    StartRuleInExtras(String),
+    #[error("Duplicate rule name `{0}`")]
+    DuplicateRule(String),
+    #[error(
+        "Undefined symbol `{name}`{location}{suggestion}",
+        location = in_rule(.rule.as_deref()),
+        suggestion = did_you_mean(.suggestion.as_ref())
+    )]
+    Undefined {
+        name: String,
+        /// The rule whose definition refers to the symbol, if the reference is within a rule.
+        rule: Option<String>,
+        suggestion: Option<String>,
+    },
+    #[error("Undefined symbol `{0}` in grammar's supertypes array{suggestion}", suggestion = did_you_mean(.1.as_ref()))]
+    UndefinedSupertype(String, Option<String>),
+    #[error("Undefined symbol `{0}` in grammar's conflicts array{suggestion}", suggestion = did_you_mean(.1.as_ref()))]
+    UndefinedConflict(String, Option<String>),
+    #[error("Undefined symbol `{0}` as grammar's word token{suggestion}", suggestion = did_you_mean(.1.as_ref()))]
+    UndefinedWordToken(String, Option<String>),
+    #[error("Undefined reserved word context `{0}`")]
+    UndefinedReservedContext(String),
+    #[error("Reserved word set `{0}` includes itself")]
+    ReservedSetCycle(String),
+    #[error("Duplicate reserved word set `{0}`")]
+    DuplicateReservedWordSet(String),
+    #[error("Reserved word set `{set}` includes `{symbol}`, which is not a token")]
+    NonTerminalReservedWord { set: String, symbol: String },
+    #[error("Undefined symbol `{0}` in grammar's descriptions")]
+    UndefinedDescription(String),
+    #[error("Undefined symbol `{0}` in grammar's token groups")]
+    UndefinedTokenGroupMember(String),
+}
+
+/// Replace every `reserved(name)` entry in each reserved word set with the
+/// entries of the set called `name`, so that each set contains the union of its
+/// own words and the words of the sets it includes.
+fn expand_reserved_word_sets(
+    sets: &[ReservedWordContext<Rule>],
+) -> InternSymbolsResult<Vec<ReservedWordContext<Rule>>> {
+    fn expand(
+        sets: &[ReservedWordContext<Rule>],
+        index: usize,
+        path: &mut Vec<usize>,
+        words: &mut Vec<Rule>,
+    ) -> InternSymbolsResult<()> {
+        if path.contains(&index) {
+            return Err(InternSymbolsError::ReservedSetCycle(
+                sets[index].name.clone(),
+            ));
+        }
+        path.push(index);
+        for rule in &sets[index].reserved_words {
+            if let Some(name) = included_reserved_set(rule) {
+                // Undefined set names are reported as undefined reserved word contexts.
+                if let Some(included) = sets.iter().position(|set| set.name == name) {
+                    expand(sets, included, path, words)?;
+                }
+            } else if !words.contains(rule) {
+                words.push(rule.clone());
+            }
+        }
+        path.pop();
+        Ok(())
+    }
+
+    (0..sets.len())
+        .map(|index| {
+            let mut reserved_words = Vec::new();
+            expand(sets, index, &mut Vec::new(), &mut reserved_words)?;
+            Ok(ReservedWordContext {
+                name: sets[index].name.clone(),
+                reserved_words,
+            })
+        })
+        .collect()
+}
+
+/// An entry of the form `reserved(name)` within a reserved word set refers to
+/// the set called `name` rather than to a word.
+fn included_reserved_set(rule: &Rule) -> Option<&str> {
+    match rule {
+        Rule::Reserved { rule, context_name } if **rule == Rule::Blank => Some(context_name),
+        _ => None,
+    }
+}
+
+impl InternSymbolsError {
+    /// The name of the rule whose definition contains the problem, if any.
+    #[must_use]
+    pub fn rule_name(&self) -> Option<&str> {
+        match self {
+            Self::Undefined { rule, .. } => rule.as_deref(),
+            _ => None,
+        }
+    }
+}
+
+fn in_rule(rule: Option<&str>) -> String {
+    rule.map(|rule| format!(" in rule `{rule}`"))
+        .unwrap_or_default()
+}
+
+fn did_you_mean(suggestion: Option<&String>) -> String {
+    suggestion
+        .map(|name| format!(". Did you mean `{name}`?"))
+        .unwrap_or_default()
+}
+
+/// Find the candidate that is closest to `name` by edit distance, if any is close
+/// enough that `name` is likely to be a typo of it.
+fn suggest_name<'a>(name: &str, candidates: impl IntoIterator<Item = &'a str>) -> Option<String> {
+    let max_distance = (name.chars().count() / 3).min(2);
+    let mut best = None;
+    for candidate in candidates {
+        let distance = edit_distance(name, candidate);
+        if distance <= max_distance && best.is_none_or(|(_, d)| distance < d) {
+            best = Some((candidate, distance));
+        }
+    }
+    best.map(|(candidate, _)| candidate.to_string())
+}
+
+/// Compute the Levenshtein distance between two strings.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b = b.chars().collect::<Vec<_>>();
+    let mut previous_row = (0..=b.len()).collect::<Vec<_>>();
+    let mut current_row = vec![0; b.len() + 1];
+    for (i, a_char) in a.chars().enumerate() {
+        current_row[0] = i + 1;
+        for (j, b_char) in b.iter().enumerate() {
+            let substitution_cost = usize::from(a_char != *b_char);
+            current_row[j + 1] = (previous_row[j] + substitution_cost)
+                .min(previous_row[j + 1] + 1)
+                .min(current_row[j] + 1);
+        }
+        std::mem::swap(&mut previous_row, &mut current_row);
+    }
+    previous_row[b.len()]
 }
 
 pub(super) fn intern_symbols(
     grammar: &InputGrammar,
+    strict: bool,
     diagnostics: &mut Vec<Diagnostic>,
 ) -> InternSymbolsResult<InternedGrammar> {
-    let interner = Interner { grammar };
-
-    if variable_type_for_name(&grammar.variables[0].name) == VariableType::Hidden {
-        Err(InternSymbolsError::HiddenStartRule)?;
+    if let Some(error) = validate_symbols(grammar).into_iter().next() {
+        return Err(error);
     }
 
+    // Every name has been checked above, so a failed lookup can only mean that the checks
+    // and the interning below disagree. Report it rather than panicking.
+    let interner = Interner { grammar, strict };
+    let intern_name = |name: &str| {
+        interner
+            .intern_name(name)
+            .ok_or_else(|| interner.undefined_error(name, None))
+    };
+
     let mut variables = Vec::with_capacity(grammar.variables.len());
     for variable in &grammar.variables {
         variables.push(Variable {
             name: variable.name.clone(),
             kind: variable_type_for_name(&variable.name),
-            rule: interner.intern_rule(&variable.rule, Some(&variable.name), diagnostics)?,
+            rule: interner.intern_rule(
+                &variable.rule,
+                Some(&variable.name),
+                RulePosition::Root,
+                diagnostics,
+            )?,
         });
     }
 
     let mut external_tokens = Vec::with_capacity(grammar.external_tokens.len());
     for external_token in &grammar.external_tokens {
-        let rule = interner.intern_rule(external_token, None, diagnostics)?;
+        let rule = interner.intern_rule(external_token, None, RulePosition::Root, diagnostics)?;
         let (name, kind) = if let Rule::NamedSymbol(name) = external_token {
-            (name.clone(), variable_type_for_name(name))
+            let kind = grammar
+                .external_token_kinds
+                .get(name)
+                .copied()
+                .unwrap_or_else(|| variable_type_for_name(name));
+            (name.clone(), kind)
         } else {
             (String::new(), VariableType::Anonymous)
         };
@@ -56,41 +207,55 @@ pub(super) fn intern_symbols(
 
     let mut extra_symbols = Vec::with_capacity(grammar.extra_symbols.len());
     for extra_token in &grammar.extra_symbols {
-        extra_symbols.push(interner.intern_rule(extra_token, None, diagnostics)?);
+        extra_symbols.push(interner.intern_rule(
+            extra_token,
+            None,
+            RulePosition::Root,
+            diagnostics,
+        )?);
     }
 
     let mut supertype_symbols = Vec::with_capacity(grammar.supertype_symbols.len());
     for supertype_symbol_name in &grammar.supertype_symbols {
-        supertype_symbols.push(interner.intern_name(supertype_symbol_name).ok_or_else(|| {
-            InternSymbolsError::UndefinedSupertype(supertype_symbol_name.clone())
-        })?);
+        supertype_symbols.push(intern_name(supertype_symbol_name)?);
+    }
+
+    let mut descriptions = Vec::with_capacity(grammar.descriptions.len());
+    for (name, description) in &grammar.descriptions {
+        descriptions.push((intern_name(name)?, description.clone()));
     }
 
     let mut reserved_words = Vec::with_capacity(grammar.reserved_words.len());
     for reserved_word_set in &grammar.reserved_words {
         let mut interned_set = Vec::with_capacity(reserved_word_set.reserved_words.len());
         for rule in &reserved_word_set.reserved_words {
-            interned_set.push(interner.intern_rule(rule, None, diagnostics)?);
+            interned_set.push(interner.intern_rule(rule, None, RulePosition::Root, diagnostics)?);
         }
         reserved_words.push(ReservedWordContext {
             name: reserved_word_set.name.clone(),
             reserved_words: interned_set,
         });
     }
+    let reserved_words = expand_reserved_word_sets(&reserved_words)?;
 
     let mut expected_conflicts = Vec::with_capacity(grammar.expected_conflicts.len());
     for conflict in &grammar.expected_conflicts {
         let mut interned_conflict = Vec::with_capacity(conflict.len());
         for name in conflict {
-            interned_conflict.push(
-                interner
-                    .intern_name(name)
-                    .ok_or_else(|| InternSymbolsError::UndefinedConflict(name.clone()))?,
-            );
+            interned_conflict.push(intern_name(name)?);
         }
         expected_conflicts.push(interned_conflict);
     }
 
+    let mut token_groups = Vec::with_capacity(grammar.token_groups.len());
+    for group in &grammar.token_groups {
+        let mut interned_group = Vec::with_capacity(group.len());
+        for name in group {
+            interned_group.push(intern_name(name)?);
+        }
+        token_groups.push(interned_group);
+    }
+
     let mut variables_to_inline = Vec::new();
     for name in &grammar.variables_to_inline {
         if let Some(symbol) = interner.intern_name(name) {
@@ -98,18 +263,29 @@ pub(super) fn intern_symbols(
         }
     }
 
-    let word_token = if let Some(name) = grammar.word_token.as_ref() {
-        Some(
-            interner
-                .intern_name(name)
-                .ok_or_else(|| InternSymbolsError::UndefinedWordToken(name.clone()))?,
-        )
-    } else {
-        None
-    };
+    let word_token = grammar.word_token.as_deref().map(intern_name).transpose()?;
+
+    // An unused name that closely matches a used one is most likely a typo, which would leave
+    // the used name out of the ordering, so the warning suggests the used name. This isn't an
+    // error, because published grammars have unused names in their orderings: JavaScript and
+    // TypeScript both order `binary_compare`, which none of their rules use.
+    for (name, hint) in unused_precedence_names(grammar) {
+        diagnostics.push(Diagnostic::UnusedPrecedence {
+            name: name.to_string(),
+            hint,
+        });
+    }
 
+    // Supertypes are hidden, because their children appear in their place in the tree. Only
+    // supertypes that refer to rules have a variable to hide; an external token that is
+    // listed as a supertype has an index among the external tokens, not the rules.
+    let supertype_indices = supertype_symbols
+        .iter()
+        .filter(|symbol| symbol.is_non_terminal())
+        .map(|symbol| symbol.index)
+        .collect::<FxHashSet<_>>();
     for (i, variable) in variables.iter_mut().enumerate() {
-        if supertype_symbols.contains(&Symbol::non_terminal(i)) {
+        if supertype_indices.contains(&i) {
             variable.kind = VariableType::Hidden;
         }
     }
@@ -124,11 +300,199 @@ pub(super) fn intern_symbols(
         word_token,
         precedence_orderings: grammar.precedence_orderings.clone(),
         reserved_word_sets: reserved_words,
+        descriptions,
+        token_groups,
     })
 }
 
+/// Check a grammar for every problem that would prevent its symbols from being interned.
+///
+/// [`intern_symbols`] runs these same checks and fails with the first problem found, so the
+/// errors are listed in the order in which they would stop interning.
+pub(super) fn validate_symbols(grammar: &InputGrammar) -> Vec<InternSymbolsError> {
+    let interner = Interner {
+        grammar,
+        strict: false,
+    };
+    let mut errors = Vec::new();
+
+    if grammar
+        .variables
+        .first()
+        .is_some_and(|variable| variable_type_for_name(&variable.name) == VariableType::Hidden)
+    {
+        errors.push(InternSymbolsError::HiddenStartRule);
+    }
+
+    let mut names = FxHashSet::default();
+    for variable in &grammar.variables {
+        if !names.insert(variable.name.as_str()) {
+            push_error(
+                &mut errors,
+                InternSymbolsError::DuplicateRule(variable.name.clone()),
+            );
+        }
+    }
+
+    let mut reserved_word_set_names = FxHashSet::default();
+    for set in &grammar.reserved_words {
+        if !reserved_word_set_names.insert(set.name.as_str()) {
+            push_error(
+                &mut errors,
+                InternSymbolsError::DuplicateReservedWordSet(set.name.clone()),
+            );
+        }
+    }
+
+    for variable in &grammar.variables {
+        interner.collect_rule_errors(&variable.rule, Some(&variable.name), &mut errors);
+    }
+    for rule in grammar.external_tokens.iter().chain(&grammar.extra_symbols) {
+        interner.collect_rule_errors(rule, None, &mut errors);
+    }
+
+    if let Some(start) = grammar.variables.first()
+        && grammar
+            .extra_symbols
+            .iter()
+            .any(|rule| matches!(rule, Rule::NamedSymbol(name) if *name == start.name))
+    {
+        errors.push(InternSymbolsError::StartRuleInExtras(start.name.clone()));
+    }
+
+    for name in &grammar.supertype_symbols {
+        if interner.intern_name(name).is_none() {
+            push_error(
+                &mut errors,
+                InternSymbolsError::UndefinedSupertype(name.clone(), interner.suggest_name(name)),
+            );
+        }
+    }
+
+    for name in grammar.descriptions.keys() {
+        if interner.intern_name(name).is_none() {
+            push_error(
+                &mut errors,
+                InternSymbolsError::UndefinedDescription(name.clone()),
+            );
+        }
+    }
+
+    for set in &grammar.reserved_words {
+        for rule in &set.reserved_words {
+            if let Some(error) = interner.check_reserved_word(&set.name, rule) {
+                push_error(&mut errors, error);
+            }
+            interner.collect_rule_errors(rule, None, &mut errors);
+        }
+    }
+    if let Err(error) = expand_reserved_word_sets(&grammar.reserved_words) {
+        push_error(&mut errors, error);
+    }
+
+    for name in grammar.expected_conflicts.iter().flatten() {
+        if interner.intern_name(name).is_none() {
+            push_error(
+                &mut errors,
+                InternSymbolsError::UndefinedConflict(name.clone(), interner.suggest_name(name)),
+            );
+        }
+    }
+
+    for name in grammar.token_groups.iter().flatten() {
+        if interner.intern_name(name).is_none() {
+            push_error(
+                &mut errors,
+                InternSymbolsError::UndefinedTokenGroupMember(name.clone()),
+            );
+        }
+    }
+
+    if let Some(name) = &grammar.word_token
+        && interner.intern_name(name).is_none()
+    {
+        errors.push(InternSymbolsError::UndefinedWordToken(
+            name.clone(),
+            interner.suggest_name(name),
+        ));
+    }
+
+    errors
+}
+
+/// Find the named precedences in the grammar's orderings that aren't used by any rule, along
+/// with the used name that each one is most likely a misspelling of, if any.
+fn unused_precedence_names(grammar: &InputGrammar) -> Vec<(&str, Option<String>)> {
+    fn collect_names<'a>(rule: &'a Rule, names: &mut FxHashSet<&'a str>) {
+        match rule {
+            Rule::Choice(elements) | Rule::Seq(elements) => {
+                for element in elements {
+                    collect_names(element, names);
+                }
+            }
+            Rule::Metadata { params, rule } => {
+                if let Precedence::Name(name) = &params.precedence {
+                    names.insert(name);
+                }
+                collect_names(rule, names);
+            }
+            Rule::Repeat(rule) | Rule::Reserved { rule, .. } => collect_names(rule, names),
+            _ => {}
+        }
+    }
+
+    let mut used_names = FxHashSet::default();
+    for rule in grammar
+        .variables
+        .iter()
+        .map(|variable| &variable.rule)
+        .chain(&grammar.external_tokens)
+        .chain(&grammar.extra_symbols)
+        .chain(
+            grammar
+                .reserved_words
+                .iter()
+                .flat_map(|set| &set.reserved_words),
+        )
+    {
+        collect_names(rule, &mut used_names);
+    }
+
+    let mut unused_names = Vec::<(&str, Option<String>)>::new();
+    for entry in grammar.precedence_orderings.iter().flatten() {
+        if let PrecedenceEntry::Name(name) = entry
+            && !used_names.contains(name.as_str())
+            && unused_names.iter().all(|(unused, _)| unused != name)
+        {
+            unused_names.push((name, suggest_name(name, used_names.iter().copied())));
+        }
+    }
+    unused_names
+}
+
+fn push_error(errors: &mut Vec<InternSymbolsError>, error: InternSymbolsError) {
+    if !errors.contains(&error) {
+        errors.push(error);
+    }
+}
+
 struct Interner<'a> {
     grammar: &'a InputGrammar,
+    /// Whether to report single-element rules that are replaced by their element.
+    strict: bool,
+}
+
+/// Where a rule appears, which determines whether a `seq` or `choice` with a single element
+/// can be replaced by that element without changing the generated parser.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum RulePosition {
+    /// The entire rule of a variable, extra, external token, or reserved word. Unwrapping a
+    /// single element here can turn the whole rule into a token, which is extracted differently.
+    Root,
+    /// Within another rule, where a single-element `seq` or `choice` has no effect.
+    Nested,
+    /// Within a `token(...)`, whose exact contents determine how the token is named and shared.
+    InToken,
 }
 
 impl Interner<'_> {
@@ -136,46 +500,168 @@ impl Interner<'_> {
         &self,
         rule: &Rule,
         name: Option<&str>,
+        position: RulePosition,
         diagnostics: &mut Vec<Diagnostic>,
     ) -> InternSymbolsResult<Rule> {
+        let inner_position = if position == RulePosition::InToken {
+            RulePosition::InToken
+        } else {
+            RulePosition::Nested
+        };
         match rule {
-            Rule::Choice(elements) => {
-                Self::check_single(elements, name, true, diagnostics);
+            Rule::Choice(elements) | Rule::Seq(elements) => {
+                let is_choice = matches!(rule, Rule::Choice(_));
                 let mut result = Vec::with_capacity(elements.len());
                 for element in elements {
-                    result.push(self.intern_rule(element, name, diagnostics)?);
+                    result.push(self.intern_rule(element, name, inner_position, diagnostics)?);
                 }
-                Ok(Rule::Choice(result))
-            }
-            Rule::Seq(elements) => {
-                Self::check_single(elements, name, false, diagnostics);
-                let mut result = Vec::with_capacity(elements.len());
-                for element in elements {
-                    result.push(self.intern_rule(element, name, diagnostics)?);
+                if result.len() == 1 && position == RulePosition::Nested {
+                    if self.strict {
+                        Self::check_single(&result, name, is_choice, diagnostics);
+                    }
+                    return Ok(result.pop().unwrap());
                 }
-                Ok(Rule::Seq(result))
+                Self::check_single(&result, name, is_choice, diagnostics);
+                Ok(if is_choice {
+                    Rule::Choice(result)
+                } else {
+                    Rule::Seq(result)
+                })
             }
             Rule::Repeat(content) => Ok(Rule::Repeat(Box::new(self.intern_rule(
                 content,
                 name,
+                inner_position,
                 diagnostics,
             )?))),
             Rule::Metadata { rule, params } => Ok(Rule::Metadata {
-                rule: Box::new(self.intern_rule(rule, name, diagnostics)?),
+                rule: Box::new(self.intern_rule(
+                    rule,
+                    name,
+                    if params.is_token {
+                        RulePosition::InToken
+                    } else {
+                        inner_position
+                    },
+                    diagnostics,
+                )?),
                 params: params.clone(),
             }),
+            Rule::Reserved { context_name, .. } if !self.has_reserved_context(context_name) => Err(
+                InternSymbolsError::UndefinedReservedContext(context_name.clone()),
+            ),
             Rule::Reserved { rule, context_name } => Ok(Rule::Reserved {
-                rule: Box::new(self.intern_rule(rule, name, diagnostics)?),
+                rule: Box::new(self.intern_rule(rule, name, inner_position, diagnostics)?),
                 context_name: context_name.clone(),
             }),
-            Rule::NamedSymbol(name) => self.intern_name(name).map_or_else(
-                || Err(InternSymbolsError::Undefined(name.clone())),
+            Rule::NamedSymbol(symbol_name) => self.intern_name(symbol_name).map_or_else(
+                || Err(self.undefined_error(symbol_name, name)),
                 |symbol| Ok(Rule::Symbol(symbol)),
             ),
             _ => Ok(rule.clone()),
         }
     }
 
+    fn collect_rule_errors(
+        &self,
+        rule: &Rule,
+        name: Option<&str>,
+        errors: &mut Vec<InternSymbolsError>,
+    ) {
+        match rule {
+            Rule::Choice(elements) | Rule::Seq(elements) => {
+                for element in elements {
+                    self.collect_rule_errors(element, name, errors);
+                }
+            }
+            Rule::Repeat(rule) | Rule::Metadata { rule, .. } => {
+                self.collect_rule_errors(rule, name, errors);
+            }
+            Rule::Reserved { rule, context_name } => {
+                if !self.has_reserved_context(context_name) {
+                    push_error(
+                        errors,
+                        InternSymbolsError::UndefinedReservedContext(context_name.clone()),
+                    );
+                }
+                self.collect_rule_errors(rule, name, errors);
+            }
+            Rule::NamedSymbol(symbol_name) if self.intern_name(symbol_name).is_none() => {
+                push_error(errors, self.undefined_error(symbol_name, name));
+            }
+            _ => {}
+        }
+    }
+
+    fn undefined_error(&self, symbol_name: &str, rule_name: Option<&str>) -> InternSymbolsError {
+        InternSymbolsError::Undefined {
+            name: symbol_name.to_string(),
+            rule: rule_name.map(str::to_string),
+            suggestion: self.suggest_name(symbol_name),
+        }
+    }
+
+    /// Reserved words must be tokens. A word that refers to a rule is only a token if the
+    /// rule is replaced by its token when tokens are extracted from the grammar. That requires
+    /// the rule's entire body to be a string, a pattern, or a `token(...)` that appears nowhere
+    /// else in the grammar, since a token that is shared with other rules stays separate from
+    /// the rule. The start rule is never replaced, and neither is a hidden rule whose token is
+    /// a string, because the string's own name is kept for the token.
+    fn check_reserved_word(&self, set_name: &str, rule: &Rule) -> Option<InternSymbolsError> {
+        let Rule::NamedSymbol(name) = rule else {
+            return None;
+        };
+        let (index, variable) = self
+            .grammar
+            .variables
+            .iter()
+            .enumerate()
+            .find(|(_, v)| v.name == *name)?;
+        let is_token = extracted_token(&variable.rule).is_some_and(|(token, is_string)| {
+            index > 0
+                && !(is_string && variable_type_for_name(name) == VariableType::Hidden)
+                && self.token_usage_count(token) == 1
+        });
+        (!is_token).then(|| InternSymbolsError::NonTerminalReservedWord {
+            set: set_name.to_string(),
+            symbol: name.clone(),
+        })
+    }
+
+    /// Count the places in the grammar's rules and external tokens where the given token is
+    /// extracted, which all share a single token.
+    fn token_usage_count(&self, token: &Rule) -> usize {
+        fn count(rule: &Rule, token: &Rule) -> usize {
+            if let Some((extracted, _)) = extracted_token(rule) {
+                return usize::from(extracted == token);
+            }
+            match rule {
+                Rule::Choice(elements) | Rule::Seq(elements) => {
+                    elements.iter().map(|element| count(element, token)).sum()
+                }
+                Rule::Repeat(rule) | Rule::Metadata { rule, .. } | Rule::Reserved { rule, .. } => {
+                    count(rule, token)
+                }
+                _ => 0,
+            }
+        }
+
+        self.grammar
+            .variables
+            .iter()
+            .map(|variable| &variable.rule)
+            .chain(&self.grammar.external_tokens)
+            .map(|rule| count(rule, token))
+            .sum()
+    }
+
+    fn has_reserved_context(&self, context_name: &str) -> bool {
+        self.grammar
+            .reserved_words
+            .iter()
+            .any(|set| set.name == context_name)
+    }
+
     fn intern_name(&self, symbol: &str) -> Option<Symbol> {
         for (i, variable) in self.grammar.variables.iter().enumerate() {
             if variable.name == symbol {
@@ -194,8 +680,22 @@ impl Interner<'_> {
         None
     }
 
+    fn suggest_name(&self, name: &str) -> Option<String> {
+        let variable_names = self.grammar.variables.iter().map(|v| v.name.as_str());
+        let external_names = self.grammar.external_tokens.iter().filter_map(|rule| {
+            if let Rule::NamedSymbol(name) = rule {
+                Some(name.as_str())
+            } else {
+                None
+            }
+        });
+        suggest_name(name, variable_names.chain(external_names))
+    }
+
     // In the case of a seq or choice rule of 1 element in a hidden rule, weird
     // inconsistent behavior with queries can occur. So we should warn the user about it.
+    // Such rules are only left in place at the root of a rule or within a token. Elsewhere,
+    // they are replaced by their element, and only reported in strict mode.
     fn check_single(
         elements: &[Rule],
         name: Option<&str>,
@@ -213,6 +713,29 @@ impl Interner<'_> {
     }
 }
 
+/// If the given rule is extracted as a single token, return the rule that the token consists
+/// of, the way that tokens are compared when they are extracted, and whether it's a string.
+fn extracted_token(rule: &Rule) -> Option<(&Rule, bool)> {
+    match rule {
+        Rule::String(_) => Some((rule, true)),
+        Rule::Pattern(..) => Some((rule, false)),
+        Rule::Metadata {
+            params,
+            rule: inner,
+        } if params.is_token => {
+            let mut params = params.clone();
+            params.is_token = false;
+            let token = if params == MetadataParams::default() {
+                inner.as_ref()
+            } else {
+                rule
+            };
+            Some((token, matches!(**inner, Rule::String(_))))
+        }
+        _ => None,
+    }
+}
+
 fn variable_type_for_name(name: &str) -> VariableType {
     if name.starts_with('_') {
         VariableType::Hidden
@@ -223,6 +746,8 @@ fn variable_type_for_name(name: &str) -> VariableType {
 
 #[cfg(test)]
 mod tests {
+    use std::collections::BTreeMap;
+
     use super::*;
 
     #[test]
@@ -233,6 +758,7 @@ mod tests {
                 Variable::named("y", Rule::named("_z")),
                 Variable::named("_z", Rule::string("a")),
             ]),
+            false,
             &mut Vec::new(),
         )
         .unwrap();
@@ -266,7 +792,7 @@ mod tests {
             .external_tokens
             .extend(vec![Rule::named("y"), Rule::named("z")]);
 
-        let grammar = intern_symbols(&input_grammar, &mut Vec::new()).unwrap();
+        let grammar = intern_symbols(&input_grammar, false, &mut Vec::new()).unwrap();
 
         // Variable `y` is referred to by its internal index.
         // Variable `z` is referred to by its external index.
@@ -296,16 +822,810 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_single_element_seq_warning() {
+        let mut diagnostics = Vec::new();
+        intern_symbols(
+            &build_grammar(vec![
+                Variable::named("x", Rule::seq(vec![Rule::named("_y")])),
+                Variable::named("_y", Rule::seq(vec![Rule::string("a")])),
+            ]),
+            false,
+            &mut diagnostics,
+        )
+        .unwrap();
+
+        // Only single-element rules wrapping a string or pattern are reported.
+        assert!(matches!(
+            diagnostics.as_slice(),
+            [Diagnostic::UnarySeq { name: Some(name) }] if name == "_y"
+        ));
+    }
+
+    #[test]
+    fn test_unwrapping_single_element_rules() {
+        let input_grammar = build_grammar(vec![
+            Variable::named(
+                "x",
+                Rule::seq(vec![
+                    Rule::seq(vec![Rule::named("y")]),
+                    Rule::choice(vec![Rule::string("a")]),
+                    Rule::repeat(Rule::seq(vec![Rule::seq(vec![Rule::named("y")])])),
+                ]),
+            ),
+            Variable::named(
+                "y",
+                Rule::prec(Precedence::Integer(1), Rule::seq(vec![Rule::named("_z")])),
+            ),
+            Variable::named("_z", Rule::token(Rule::seq(vec![Rule::string("b")]))),
+        ]);
+        let mut diagnostics = Vec::new();
+        let grammar = intern_symbols(&input_grammar, false, &mut diagnostics).unwrap();
+
+        assert_eq!(
+            grammar.variables,
+            vec![
+                Variable::named(
+                    "x",
+                    Rule::seq(vec![
+                        Rule::non_terminal(1),
+                        Rule::string("a"),
+                        Rule::repeat(Rule::non_terminal(1)),
+                    ])
+                ),
+                // The metadata that wrapped the single-element rule is kept.
+                Variable::named(
+                    "y",
+                    Rule::prec(Precedence::Integer(1), Rule::non_terminal(2))
+                ),
+                // The contents of tokens are left as they are.
+                Variable::hidden("_z", Rule::token(Rule::seq(vec![Rule::string("b")]))),
+            ]
+        );
+        assert!(matches!(
+            diagnostics.as_slice(),
+            [Diagnostic::UnarySeq { name: Some(name) }] if name == "_z"
+        ));
+
+        // In strict mode, the rules that were unwrapped are reported as well, and are still
+        // unwrapped.
+        let mut diagnostics = Vec::new();
+        let strict_grammar = intern_symbols(&input_grammar, true, &mut diagnostics).unwrap();
+        assert_eq!(strict_grammar.variables, grammar.variables);
+        assert!(matches!(
+            diagnostics.as_slice(),
+            [
+                Diagnostic::UnaryChoice { name: Some(choice_name) },
+                Diagnostic::UnarySeq { name: Some(seq_name) },
+            ] if choice_name == "x" && seq_name == "_z"
+        ));
+    }
+
+    #[test]
+    fn test_interning_aliased_symbols() {
+        let mut input_grammar = build_grammar(vec![
+            Variable::named(
+                "x",
+                Rule::seq(vec![
+                    Rule::alias(Rule::named("y"), "a".to_string(), true),
+                    Rule::alias(Rule::named("z"), "b".to_string(), false),
+                ]),
+            ),
+            Variable::named("y", Rule::string("c")),
+        ]);
+        input_grammar.external_tokens.push(Rule::named("z"));
+
+        // The alias metadata survives interning, and its target resolves to
+        // either the internal rule or the external token.
+        let grammar = intern_symbols(&input_grammar, false, &mut Vec::new()).unwrap();
+        assert_eq!(
+            grammar.variables[0].rule,
+            Rule::seq(vec![
+                Rule::alias(Rule::non_terminal(1), "a".to_string(), true),
+                Rule::alias(Rule::external(0), "b".to_string(), false),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_grammar_with_aliased_undefined_symbol() {
+        let input_grammar = build_grammar(vec![
+            Variable::named(
+                "x",
+                Rule::alias(Rule::named("identifer"), "a".to_string(), true),
+            ),
+            Variable::named("identifier", Rule::pattern("[a-z]+", "")),
+        ]);
+
+        let result = intern_symbols(&input_grammar, false, &mut Vec::new());
+        assert_eq!(
+            result,
+            Err(InternSymbolsError::Undefined {
+                name: "identifer".to_string(),
+                rule: Some("x".to_string()),
+                suggestion: Some("identifier".to_string())
+            })
+        );
+        assert_eq!(
+            validate_symbols(&input_grammar),
+            vec![InternSymbolsError::Undefined {
+                name: "identifer".to_string(),
+                rule: Some("x".to_string()),
+                suggestion: Some("identifier".to_string())
+            }]
+        );
+    }
+
     #[test]
     fn test_grammar_with_undefined_symbols() {
         let result = intern_symbols(
             &build_grammar(vec![Variable::named("x", Rule::named("y"))]),
+            false,
             &mut Vec::new(),
         );
 
         assert!(result.is_err(), "Expected an error but got none");
         let e = result.err().unwrap();
-        assert_eq!(e.to_string(), "Undefined symbol `y`");
+        assert_eq!(e.to_string(), "Undefined symbol `y` in rule `x`");
+    }
+
+    #[test]
+    fn test_undefined_symbol_nested_in_seq() {
+        let input_grammar = build_grammar(vec![
+            Variable::named("program", Rule::repeat(Rule::named("statement"))),
+            Variable::named(
+                "statement",
+                Rule::seq(vec![
+                    Rule::string("let"),
+                    Rule::field("value".to_string(), Rule::named("expresion")),
+                    Rule::string(";"),
+                ]),
+            ),
+            Variable::named("expression", Rule::pattern("[a-z]+", "")),
+        ]);
+
+        let error = intern_symbols(&input_grammar, false, &mut Vec::new())
+            .err()
+            .unwrap();
+        assert_eq!(error.rule_name(), Some("statement"));
+        assert_eq!(
+            error.to_string(),
+            "Undefined symbol `expresion` in rule `statement`. Did you mean `expression`?"
+        );
+        assert_eq!(validate_symbols(&input_grammar), vec![error]);
+    }
+
+    #[test]
+    fn test_grammar_with_duplicate_rules() {
+        // The duplicate is reported before any rules are interned, so the undefined
+        // symbol in the second definition is never reached.
+        let result = intern_symbols(
+            &build_grammar(vec![
+                Variable::named("program", Rule::named("expression")),
+                Variable::named("expression", Rule::string("a")),
+                Variable::named("expression", Rule::named("undefined")),
+            ]),
+            false,
+            &mut Vec::new(),
+        );
+        assert_eq!(
+            result,
+            Err(InternSymbolsError::DuplicateRule("expression".to_string()))
+        );
+        assert_eq!(
+            result.err().unwrap().to_string(),
+            "Duplicate rule name `expression`"
+        );
+    }
+
+    #[test]
+    fn test_undefined_symbol_suggestions() {
+        let mut input_grammar = build_grammar(vec![
+            Variable::named("program", Rule::named("expresion")),
+            Variable::named("expression", Rule::named("identifier")),
+            Variable::named("identifier", Rule::pattern("[a-z]+", "")),
+            Variable::named("_statement", Rule::named("expression")),
+        ]);
+        input_grammar.external_tokens.push(Rule::named("heredoc"));
+        input_grammar
+            .supertype_symbols
+            .push("_statment".to_string());
+        input_grammar
+            .expected_conflicts
+            .push(vec!["expression".to_string(), "heredocs".to_string()]);
+        input_grammar.word_token = Some("identifer".to_string());
+
+        assert_eq!(
+            validate_symbols(&input_grammar)
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>(),
+            [
+                "Undefined symbol `expresion` in rule `program`. Did you mean `expression`?",
+                "Undefined symbol `_statment` in grammar's supertypes array. \
+                 Did you mean `_statement`?",
+                "Undefined symbol `heredocs` in grammar's conflicts array. \
+                 Did you mean `heredoc`?",
+                "Undefined symbol `identifer` as grammar's word token. \
+                 Did you mean `identifier`?",
+            ]
+        );
+        assert_eq!(
+            intern_symbols(&input_grammar, false, &mut Vec::new()),
+            Err(InternSymbolsError::Undefined {
+                name: "expresion".to_string(),
+                rule: Some("program".to_string()),
+                suggestion: Some("expression".to_string())
+            })
+        );
+
+        // Names that are very different from every existing name get no suggestion.
+        let input_grammar = build_grammar(vec![
+            Variable::named("program", Rule::named("statement")),
+            Variable::named("expression", Rule::string("a")),
+        ]);
+        assert_eq!(
+            intern_symbols(&input_grammar, false, &mut Vec::new())
+                .err()
+                .unwrap()
+                .to_string(),
+            "Undefined symbol `statement` in rule `program`"
+        );
+    }
+
+    #[test]
+    fn test_suggest_name() {
+        let candidates = ["expression", "identifier", "statement", "x"];
+        assert_eq!(
+            suggest_name("expresion", candidates),
+            Some("expression".to_string())
+        );
+        assert_eq!(
+            suggest_name("identifeir", candidates),
+            Some("identifier".to_string())
+        );
+        assert_eq!(
+            suggest_name("statment", candidates),
+            Some("statement".to_string())
+        );
+        assert_eq!(suggest_name("expr", candidates), None);
+        assert_eq!(suggest_name("comment", candidates), None);
+        assert_eq!(suggest_name("y", candidates), None);
+        assert_eq!(suggest_name("expression", []), None);
+    }
+
+    #[test]
+    fn test_grammar_with_undefined_reserved_context() {
+        let mut input_grammar = build_grammar(vec![
+            Variable::named(
+                "x",
+                Rule::Reserved {
+                    rule: Box::new(Rule::named("y")),
+                    context_name: "bogus".to_string(),
+                },
+            ),
+            Variable::named("y", Rule::pattern("[a-z]+", "")),
+        ]);
+        input_grammar.reserved_words.push(ReservedWordContext {
+            name: "global".to_string(),
+            reserved_words: vec![Rule::string("if")],
+        });
+
+        let result = intern_symbols(&input_grammar, false, &mut Vec::new());
+        assert!(matches!(
+            result,
+            Err(InternSymbolsError::UndefinedReservedContext(ref name)) if name == "bogus"
+        ));
+        assert_eq!(
+            result.err().unwrap().to_string(),
+            "Undefined reserved word context `bogus`"
+        );
+    }
+
+    #[test]
+    fn test_validating_grammar_with_multiple_problems() {
+        let undefined = |name: &str, rule: &str| InternSymbolsError::Undefined {
+            name: name.to_string(),
+            rule: Some(rule.to_string()),
+            suggestion: None,
+        };
+        let mut input_grammar = build_grammar(vec![
+            Variable::named(
+                "_start",
+                Rule::seq(vec![
+                    Rule::named("a"),
+                    Rule::Reserved {
+                        rule: Box::new(Rule::named("b")),
+                        context_name: "bogus".to_string(),
+                    },
+                ]),
+            ),
+            Variable::named("a", Rule::choice(vec![Rule::named("b"), Rule::named("c")])),
+            Variable::named("a", Rule::string("a")),
+        ]);
+        input_grammar
+            .supertype_symbols
+            .push("_expression".to_string());
+        input_grammar
+            .expected_conflicts
+            .push(vec!["a".to_string(), "d".to_string()]);
+        input_grammar.word_token = Some("identifier".to_string());
+
+        assert_eq!(
+            validate_symbols(&input_grammar),
+            vec![
+                InternSymbolsError::HiddenStartRule,
+                InternSymbolsError::DuplicateRule("a".to_string()),
+                InternSymbolsError::UndefinedReservedContext("bogus".to_string()),
+                undefined("b", "_start"),
+                undefined("b", "a"),
+                undefined("c", "a"),
+                InternSymbolsError::UndefinedSupertype("_expression".to_string(), None),
+                InternSymbolsError::UndefinedConflict("d".to_string(), None),
+                InternSymbolsError::UndefinedWordToken("identifier".to_string(), None),
+            ]
+        );
+
+        // A grammar that interns successfully has nothing to report.
+        let valid_grammar = build_grammar(vec![
+            Variable::named("x", Rule::named("y")),
+            Variable::named("y", Rule::string("a")),
+        ]);
+        assert!(validate_symbols(&valid_grammar).is_empty());
+        assert!(intern_symbols(&valid_grammar, false, &mut Vec::new()).is_ok());
+    }
+
+    #[test]
+    fn test_grammar_with_start_rule_in_extras() {
+        let mut input_grammar = build_grammar(vec![
+            Variable::named("program", Rule::repeat(Rule::named("statement"))),
+            Variable::named("statement", Rule::string(";")),
+        ]);
+        input_grammar.extra_symbols = vec![Rule::pattern("\\s", ""), Rule::named("program")];
+
+        let result = intern_symbols(&input_grammar, false, &mut Vec::new());
+        assert_eq!(
+            result.err(),
+            Some(InternSymbolsError::StartRuleInExtras("program".to_string()))
+        );
+        assert_eq!(
+            validate_symbols(&input_grammar),
+            vec![InternSymbolsError::StartRuleInExtras("program".to_string())]
+        );
+
+        // Other rules can still be used as extras.
+        input_grammar.extra_symbols = vec![Rule::named("statement")];
+        assert!(intern_symbols(&input_grammar, false, &mut Vec::new()).is_ok());
+    }
+
+    #[test]
+    fn test_reserved_word_set_including_another_set() {
+        let mut input_grammar = build_grammar(vec![
+            Variable::named("x", Rule::named("identifier")),
+            Variable::named("identifier", Rule::pattern("[a-z]+", "")),
+        ]);
+        input_grammar.reserved_words = vec![
+            ReservedWordContext {
+                name: "global".to_string(),
+                reserved_words: vec![
+                    Rule::Reserved {
+                        rule: Box::new(Rule::Blank),
+                        context_name: "statement".to_string(),
+                    },
+                    Rule::string("if"),
+                    Rule::string("class"),
+                ],
+            },
+            ReservedWordContext {
+                name: "statement".to_string(),
+                reserved_words: vec![Rule::string("if"), Rule::string("while")],
+            },
+        ];
+
+        let grammar = intern_symbols(&input_grammar, false, &mut Vec::new()).unwrap();
+        assert_eq!(
+            grammar.reserved_word_sets,
+            vec![
+                ReservedWordContext {
+                    name: "global".to_string(),
+                    reserved_words: vec![
+                        Rule::string("if"),
+                        Rule::string("while"),
+                        Rule::string("class"),
+                    ],
+                },
+                ReservedWordContext {
+                    name: "statement".to_string(),
+                    reserved_words: vec![Rule::string("if"), Rule::string("while")],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_reserved_word_sets_including_each_other() {
+        let include = |name: &str| Rule::Reserved {
+            rule: Box::new(Rule::Blank),
+            context_name: name.to_string(),
+        };
+        let mut input_grammar = build_grammar(vec![Variable::named("x", Rule::string("a"))]);
+        input_grammar.reserved_words = vec![
+            ReservedWordContext {
+                name: "a".to_string(),
+                reserved_words: vec![include("b")],
+            },
+            ReservedWordContext {
+                name: "b".to_string(),
+                reserved_words: vec![Rule::string("if"), include("a")],
+            },
+        ];
+
+        let error = || InternSymbolsError::ReservedSetCycle("a".to_string());
+        assert_eq!(
+            intern_symbols(&input_grammar, false, &mut Vec::new()).err(),
+            Some(error())
+        );
+        assert_eq!(validate_symbols(&input_grammar), vec![error()]);
+    }
+
+    #[test]
+    fn test_grammar_with_undefined_description() {
+        let mut input_grammar = build_grammar(vec![Variable::named("x", Rule::string("a"))]);
+        input_grammar
+            .descriptions
+            .insert("x".to_string(), "An x".to_string());
+        input_grammar
+            .descriptions
+            .insert("y".to_string(), "A y".to_string());
+
+        let error = || InternSymbolsError::UndefinedDescription("y".to_string());
+        assert_eq!(
+            intern_symbols(&input_grammar, false, &mut Vec::new()).err(),
+            Some(error())
+        );
+        assert_eq!(validate_symbols(&input_grammar), vec![error()]);
+    }
+
+    #[test]
+    fn test_grammar_with_duplicate_reserved_word_sets() {
+        let mut input_grammar = build_grammar(vec![Variable::named("x", Rule::string("a"))]);
+        input_grammar.reserved_words = vec![
+            ReservedWordContext {
+                name: "global".to_string(),
+                reserved_words: vec![Rule::string("if")],
+            },
+            ReservedWordContext {
+                name: "global".to_string(),
+                reserved_words: vec![Rule::string("while")],
+            },
+        ];
+
+        let error = || InternSymbolsError::DuplicateReservedWordSet("global".to_string());
+        assert_eq!(
+            intern_symbols(&input_grammar, false, &mut Vec::new()).err(),
+            Some(error())
+        );
+        assert_eq!(validate_symbols(&input_grammar), vec![error()]);
+    }
+
+    #[test]
+    fn test_grammar_with_non_terminal_reserved_word() {
+        let mut input_grammar = build_grammar(vec![
+            Variable::named(
+                "x",
+                Rule::choice(vec![Rule::named("keyword"), Rule::named("y")]),
+            ),
+            Variable::named("keyword", Rule::string("if")),
+            Variable::named("y", Rule::token(Rule::string("while"))),
+            Variable::named(
+                "statement_keyword",
+                Rule::choice(vec![Rule::string("for"), Rule::string("do")]),
+            ),
+        ]);
+        input_grammar.reserved_words = vec![ReservedWordContext {
+            name: "global".to_string(),
+            reserved_words: vec![
+                Rule::named("keyword"),
+                Rule::named("y"),
+                Rule::string("class"),
+                Rule::named("statement_keyword"),
+            ],
+        }];
+
+        let error = || InternSymbolsError::NonTerminalReservedWord {
+            set: "global".to_string(),
+            symbol: "statement_keyword".to_string(),
+        };
+        assert_eq!(
+            intern_symbols(&input_grammar, false, &mut Vec::new()).err(),
+            Some(error())
+        );
+        assert_eq!(validate_symbols(&input_grammar), vec![error()]);
+
+        // Rules whose entire body is a token are allowed.
+        input_grammar.reserved_words[0].reserved_words.pop();
+        assert!(intern_symbols(&input_grammar, false, &mut Vec::new()).is_ok());
+        assert!(validate_symbols(&input_grammar).is_empty());
+
+        // Unless the same token is also used elsewhere, which keeps the rule a non-terminal
+        // that refers to the shared token. Hidden rules are only replaced by their tokens if
+        // the tokens are patterns, because strings keep their own names.
+        input_grammar.variables[0].rule = Rule::choice(vec![
+            Rule::named("keyword"),
+            Rule::named("y"),
+            Rule::seq(vec![Rule::token(Rule::string("while")), Rule::string("if")]),
+        ]);
+        input_grammar.variables.extend([
+            Variable::named("_hidden_keyword", Rule::string("do")),
+            Variable::named("_identifier", Rule::pattern("[a-z]+", "")),
+        ]);
+        input_grammar.reserved_words[0]
+            .reserved_words
+            .extend([Rule::named("_hidden_keyword"), Rule::named("_identifier")]);
+        assert_eq!(
+            validate_symbols(&input_grammar),
+            ["keyword", "y", "_hidden_keyword"].map(|symbol| {
+                InternSymbolsError::NonTerminalReservedWord {
+                    set: "global".to_string(),
+                    symbol: symbol.to_string(),
+                }
+            })
+        );
+    }
+
+    #[test]
+    fn test_interning_grammar_that_extends_another() {
+        use crate::parse_grammar::parse_grammar;
+
+        let input_grammar = parse_grammar(
+            r#"{
+                "name": "derived",
+                "extends": {
+                    "name": "base",
+                    "rules": {
+                        "program": {"type": "REPEAT1", "content": {"type": "SYMBOL", "name": "statement"}},
+                        "statement": {"type": "SEQ", "members": [
+                            {"type": "SYMBOL", "name": "expression"},
+                            {"type": "STRING", "value": ";"}
+                        ]},
+                        "expression": {"type": "SYMBOL", "name": "identifier"},
+                        "identifier": {"type": "PATTERN", "value": "[a-z]+"}
+                    },
+                    "externals": [{"type": "SYMBOL", "name": "heredoc"}],
+                    "supertypes": ["expression"]
+                },
+                "rules": {
+                    "expression": {"type": "CHOICE", "members": [
+                        {"type": "SYMBOL", "name": "_base_expression"},
+                        {"type": "SYMBOL", "name": "number"}
+                    ]},
+                    "number": {"type": "PATTERN", "value": "\\d+"}
+                },
+                "externals": [
+                    {"type": "SYMBOL", "name": "heredoc"},
+                    {"type": "SYMBOL", "name": "template"}
+                ]
+            }"#,
+            &mut Vec::new(),
+        )
+        .unwrap();
+        assert_eq!(input_grammar.name, "derived");
+
+        let grammar = intern_symbols(&input_grammar, false, &mut Vec::new()).unwrap();
+        assert_eq!(
+            grammar.variables,
+            vec![
+                Variable::named("program", Rule::repeat(Rule::non_terminal(1))),
+                Variable::named(
+                    "statement",
+                    Rule::seq(vec![Rule::non_terminal(2), Rule::string(";")])
+                ),
+                // The override takes the base rule's place, and the supertype makes it hidden.
+                Variable::hidden(
+                    "expression",
+                    Rule::choice(vec![Rule::non_terminal(5), Rule::non_terminal(4)])
+                ),
+                Variable::named("identifier", Rule::pattern("[a-z]+", "")),
+                Variable::named("number", Rule::pattern("\\d+", "")),
+                Variable::hidden("_base_expression", Rule::non_terminal(3)),
+            ]
+        );
+        assert_eq!(
+            grammar
+                .external_tokens
+                .iter()
+                .map(|token| token.name.as_str())
+                .collect::<Vec<_>>(),
+            ["heredoc", "template"]
+        );
+        assert_eq!(grammar.supertype_symbols, [Symbol::non_terminal(2)]);
+
+        // The base's version of a rule is dropped if nothing refers to it.
+        let input_grammar = parse_grammar(
+            r#"{
+                "name": "derived",
+                "extends": {
+                    "name": "base",
+                    "rules": {
+                        "program": {"type": "SYMBOL", "name": "identifier"},
+                        "identifier": {"type": "PATTERN", "value": "[a-z]+"}
+                    }
+                },
+                "rules": {
+                    "identifier": {"type": "PATTERN", "value": "[a-z_]+"}
+                }
+            }"#,
+            &mut Vec::new(),
+        )
+        .unwrap();
+        let grammar = intern_symbols(&input_grammar, false, &mut Vec::new()).unwrap();
+        assert_eq!(
+            grammar.variables,
+            vec![
+                Variable::named("program", Rule::non_terminal(1)),
+                Variable::named("identifier", Rule::pattern("[a-z_]+", "")),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_interning_supertypes() {
+        let mut input_grammar = build_grammar(vec![
+            Variable::named("program", Rule::named("expression")),
+            Variable::named(
+                "expression",
+                Rule::choice(vec![Rule::named("identifier"), Rule::named("number")]),
+            ),
+            Variable::named("identifier", Rule::pattern("[a-z]+", "")),
+            Variable::named("number", Rule::pattern("[0-9]+", "")),
+        ]);
+        input_grammar.external_tokens = vec![Rule::named("comment"), Rule::named("string")];
+        // The external token `string` has the same index among the external tokens as
+        // `expression` has among the rules.
+        input_grammar.supertype_symbols = vec!["expression".to_string(), "string".to_string()];
+
+        let grammar = intern_symbols(&input_grammar, false, &mut Vec::new()).unwrap();
+        assert_eq!(
+            grammar.supertype_symbols,
+            [Symbol::non_terminal(1), Symbol::external(1)]
+        );
+        assert_eq!(
+            grammar
+                .variables
+                .iter()
+                .map(|variable| (variable.name.as_str(), variable.kind))
+                .collect::<Vec<_>>(),
+            [
+                ("program", VariableType::Named),
+                ("expression", VariableType::Hidden),
+                ("identifier", VariableType::Named),
+                ("number", VariableType::Named),
+            ]
+        );
+        assert_eq!(
+            grammar
+                .external_tokens
+                .iter()
+                .map(|token| (token.name.as_str(), token.kind))
+                .collect::<Vec<_>>(),
+            [
+                ("comment", VariableType::Named),
+                ("string", VariableType::Named)
+            ]
+        );
+
+        // If the external token's index were taken as a rule's index, this would hide the
+        // start rule.
+        input_grammar.supertype_symbols = vec!["comment".to_string()];
+        let grammar = intern_symbols(&input_grammar, false, &mut Vec::new()).unwrap();
+        assert_eq!(grammar.supertype_symbols, [Symbol::external(0)]);
+        assert!(
+            grammar
+                .variables
+                .iter()
+                .all(|variable| variable.kind == VariableType::Named)
+        );
+    }
+
+    #[test]
+    fn test_grammar_with_unused_precedence() {
+        let mut input_grammar = build_grammar(vec![
+            Variable::named(
+                "x",
+                Rule::choice(vec![
+                    Rule::prec(Precedence::Name("binary".to_string()), Rule::named("y")),
+                    Rule::prec(Precedence::Name("unary".to_string()), Rule::named("y")),
+                ]),
+            ),
+            Variable::named("y", Rule::string("a")),
+        ]);
+        input_grammar.precedence_orderings = vec![vec![
+            PrecedenceEntry::Name("unary".to_string()),
+            PrecedenceEntry::Name("binray".to_string()),
+            PrecedenceEntry::Symbol("y".to_string()),
+        ]];
+
+        // Unused names are warnings, which suggest the used name that they most likely
+        // misspell.
+        let mut diagnostics = Vec::new();
+        assert!(intern_symbols(&input_grammar, false, &mut diagnostics).is_ok());
+        assert!(validate_symbols(&input_grammar).is_empty());
+        assert_eq!(
+            diagnostics
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>(),
+            ["precedence `binray` is never used by any rule. did you mean `binary`?"]
+        );
+
+        input_grammar.precedence_orderings[0][1] = PrecedenceEntry::Name("postfix".to_string());
+        let mut diagnostics = Vec::new();
+        assert!(intern_symbols(&input_grammar, false, &mut diagnostics).is_ok());
+        assert_eq!(
+            diagnostics
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>(),
+            ["precedence `postfix` is never used by any rule"]
+        );
+
+        input_grammar.precedence_orderings[0][1] = PrecedenceEntry::Name("binary".to_string());
+        let mut diagnostics = Vec::new();
+        assert!(intern_symbols(&input_grammar, false, &mut diagnostics).is_ok());
+        assert!(diagnostics.is_empty());
+    }
+
+    #[test]
+    fn test_interning_external_token_kinds() {
+        let mut input_grammar = build_grammar(vec![Variable::named(
+            "program",
+            Rule::repeat(Rule::choice(vec![
+                Rule::named("line_comment"),
+                Rule::named("_newline"),
+                Rule::named("string"),
+                Rule::named("_heredoc"),
+            ])),
+        )]);
+        input_grammar.external_tokens = vec![
+            Rule::named("line_comment"),
+            Rule::named("_newline"),
+            Rule::named("string"),
+            Rule::named("_heredoc"),
+            Rule::string("}"),
+        ];
+
+        // Without explicit kinds, external tokens are hidden only if their names start with
+        // an underscore.
+        let kinds = |input_grammar: &InputGrammar| {
+            intern_symbols(input_grammar, false, &mut Vec::new())
+                .unwrap()
+                .external_tokens
+                .into_iter()
+                .map(|token| (token.name, token.kind))
+                .collect::<Vec<_>>()
+        };
+        let expected = |line_comment, heredoc| {
+            vec![
+                ("line_comment".to_string(), line_comment),
+                ("_newline".to_string(), VariableType::Hidden),
+                ("string".to_string(), VariableType::Named),
+                ("_heredoc".to_string(), heredoc),
+                (String::new(), VariableType::Anonymous),
+            ]
+        };
+        assert_eq!(
+            kinds(&input_grammar),
+            expected(VariableType::Named, VariableType::Hidden)
+        );
+
+        input_grammar.external_token_kinds = BTreeMap::from([
+            ("line_comment".to_string(), VariableType::Hidden),
+            ("_heredoc".to_string(), VariableType::Named),
+        ]);
+        assert_eq!(
+            kinds(&input_grammar),
+            expected(VariableType::Hidden, VariableType::Named)
+        );
     }
 
     fn build_grammar(variables: Vec<Variable>) -> InputGrammar {
@@ -0,0 +1,347 @@
+use std::fmt::Write;
+
+use crate::{grammars::InputGrammar, rules::Rule};
+
+const CHAR_WIDTH: usize = 8;
+const BOX_HEIGHT: usize = 22;
+const BOX_PADDING: usize = 10;
+const ARC_RADIUS: usize = 10;
+const HORIZONTAL_GAP: usize = 10;
+const VERTICAL_GAP: usize = 10;
+const MARGIN: usize = 20;
+const TITLE_HEIGHT: usize = 24;
+
+const STYLE: &str = "\
+path { fill: none; stroke: #333; stroke-width: 2; }
+rect { stroke: #333; stroke-width: 2; }
+rect.terminal { fill: #ffffe0; }
+rect.nonterminal { fill: #e0f0ff; }
+text { font-family: monospace; font-size: 13px; text-anchor: middle; }
+text.rule-name { font-weight: bold; text-anchor: start; }";
+
+/// A railroad diagram for a single rule. Each diagram is drawn along a horizontal
+/// baseline, and extends `up` units above and `down` units below it.
+enum Diagram {
+    Skip,
+    Terminal(String),
+    NonTerminal(String),
+    Sequence(Vec<Self>),
+    Choice(Vec<Self>),
+    Loop(Box<Self>),
+}
+
+/// Render a railroad diagram for each of the grammar's visible rules, combined
+/// into a single SVG document.
+pub fn render_railroad_svg(grammar: &InputGrammar) -> String {
+    let rules = grammar
+        .variables
+        .iter()
+        .filter(|variable| !variable.name.starts_with('_'))
+        .map(|variable| (variable.name.as_str(), Diagram::for_rule(&variable.rule)))
+        .collect::<Vec<_>>();
+
+    let width = rules
+        .iter()
+        .map(|(name, diagram)| (diagram.width() + 2 * ARC_RADIUS).max(name.len() * CHAR_WIDTH))
+        .max()
+        .unwrap_or(0)
+        + 2 * MARGIN;
+    let height = rules
+        .iter()
+        .map(|(_, diagram)| TITLE_HEIGHT + diagram.up() + diagram.down() + MARGIN)
+        .sum::<usize>()
+        + MARGIN;
+
+    let mut result = String::new();
+    writeln!(
+        &mut result,
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{width}\" height=\"{height}\" viewBox=\"0 0 {width} {height}\">"
+    )
+    .unwrap();
+    writeln!(&mut result, "<style>\n{STYLE}\n</style>").unwrap();
+
+    let mut top = MARGIN;
+    for (name, diagram) in &rules {
+        let name = escape_xml(name);
+        let y = top + TITLE_HEIGHT + diagram.up();
+        let end = MARGIN + ARC_RADIUS + diagram.width();
+        writeln!(&mut result, "<g class=\"rule\" id=\"rule-{name}\">").unwrap();
+        writeln!(
+            &mut result,
+            "<text class=\"rule-name\" x=\"{MARGIN}\" y=\"{}\">{name}</text>",
+            top + 14
+        )
+        .unwrap();
+        // Mark the start and end of the rule with short vertical bars.
+        writeln!(
+            &mut result,
+            "<path d=\"M{MARGIN} {} v16 M{MARGIN} {y} h{ARC_RADIUS} M{end} {y} h{ARC_RADIUS} m0 -8 v16\"/>",
+            y - 8,
+        )
+        .unwrap();
+        diagram.draw(MARGIN + ARC_RADIUS, y, &mut result);
+        writeln!(&mut result, "</g>").unwrap();
+        top = y + diagram.down() + MARGIN;
+    }
+
+    result.push_str("</svg>\n");
+    result
+}
+
+impl Diagram {
+    fn for_rule(rule: &Rule) -> Self {
+        match rule {
+            Rule::Blank => Self::Skip,
+            Rule::String(value) => Self::Terminal(value.clone()),
+            Rule::Pattern(value, flags) => Self::Terminal(format!("/{value}/{flags}")),
+            Rule::NamedSymbol(name) => Self::NonTerminal(name.clone()),
+            Rule::Symbol(_) => unreachable!("input grammars only contain named symbols"),
+            Rule::Choice(elements) => {
+                let mut branches = Vec::with_capacity(elements.len());
+                if elements.contains(&Rule::Blank) {
+                    branches.push(Self::Skip);
+                }
+                branches.extend(
+                    elements
+                        .iter()
+                        .filter(|element| **element != Rule::Blank)
+                        .map(Self::for_rule),
+                );
+                if branches.len() == 1 {
+                    branches.pop().unwrap()
+                } else {
+                    Self::Choice(branches)
+                }
+            }
+            Rule::Seq(elements) => {
+                let mut items = elements.iter().map(Self::for_rule).collect::<Vec<_>>();
+                if items.len() == 1 {
+                    items.pop().unwrap()
+                } else {
+                    Self::Sequence(items)
+                }
+            }
+            Rule::Repeat(content) => Self::Loop(Box::new(Self::for_rule(content))),
+            Rule::Metadata { params, rule } => match &params.alias {
+                Some(alias) if alias.is_named => Self::NonTerminal(alias.value.clone()),
+                Some(alias) => Self::Terminal(alias.value.clone()),
+                None => Self::for_rule(rule),
+            },
+            Rule::Reserved { rule, .. } => Self::for_rule(rule),
+        }
+    }
+
+    fn width(&self) -> usize {
+        match self {
+            Self::Skip => 0,
+            Self::Terminal(text) | Self::NonTerminal(text) => {
+                text.chars().count() * CHAR_WIDTH + 2 * BOX_PADDING
+            }
+            Self::Sequence(items) => {
+                items.iter().map(Self::width).sum::<usize>()
+                    + HORIZONTAL_GAP * items.len().saturating_sub(1)
+            }
+            Self::Choice(branches) => {
+                branches.iter().map(Self::width).max().unwrap_or(0) + 4 * ARC_RADIUS
+            }
+            Self::Loop(content) => content.width() + 4 * ARC_RADIUS,
+        }
+    }
+
+    fn up(&self) -> usize {
+        match self {
+            Self::Skip => 0,
+            Self::Terminal(_) | Self::NonTerminal(_) => BOX_HEIGHT / 2,
+            Self::Sequence(items) => items.iter().map(Self::up).max().unwrap_or(0),
+            Self::Choice(branches) => branches.first().map_or(0, Self::up),
+            Self::Loop(content) => content.up(),
+        }
+    }
+
+    fn down(&self) -> usize {
+        match self {
+            Self::Skip => 0,
+            Self::Terminal(_) | Self::NonTerminal(_) => BOX_HEIGHT / 2,
+            Self::Sequence(items) => items.iter().map(Self::down).max().unwrap_or(0),
+            Self::Choice(_) => self
+                .branch_offsets()
+                .last()
+                .map_or(0, |(offset, branch)| offset + branch.down()),
+            Self::Loop(content) => Self::loop_offset(content),
+        }
+    }
+
+    /// The vertical distance from the baseline of a choice to the baseline of each
+    /// of its branches.
+    fn branch_offsets(&self) -> Vec<(usize, &Self)> {
+        let Self::Choice(branches) = self else {
+            return Vec::new();
+        };
+        let mut result = Vec::with_capacity(branches.len());
+        let mut offset = 0;
+        for (i, branch) in branches.iter().enumerate() {
+            if i > 0 {
+                let previous = &branches[i - 1];
+                offset += (previous.down() + VERTICAL_GAP + branch.up()).max(2 * ARC_RADIUS);
+            }
+            result.push((offset, branch));
+        }
+        result
+    }
+
+    /// The vertical distance from the baseline of a loop to its return path.
+    fn loop_offset(content: &Self) -> usize {
+        (content.down() + VERTICAL_GAP).max(2 * ARC_RADIUS)
+    }
+
+    /// Draw this diagram with its baseline starting at the given point.
+    fn draw(&self, x: usize, y: usize, output: &mut String) {
+        const R: usize = ARC_RADIUS;
+        match self {
+            Self::Skip => {}
+            Self::Terminal(text) | Self::NonTerminal(text) => {
+                let (class, corner_radius) = if matches!(self, Self::Terminal(_)) {
+                    ("terminal", BOX_HEIGHT / 2)
+                } else {
+                    ("nonterminal", 0)
+                };
+                writeln!(
+                    output,
+                    "<rect class=\"{class}\" x=\"{x}\" y=\"{}\" width=\"{}\" height=\"{BOX_HEIGHT}\" rx=\"{corner_radius}\"/>",
+                    y - BOX_HEIGHT / 2,
+                    self.width(),
+                )
+                .unwrap();
+                writeln!(
+                    output,
+                    "<text x=\"{}\" y=\"{}\">{}</text>",
+                    x + self.width() / 2,
+                    y + 4,
+                    escape_xml(text),
+                )
+                .unwrap();
+            }
+            Self::Sequence(items) => {
+                let mut x = x;
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
+                        writeln!(output, "<path d=\"M{x} {y} h{HORIZONTAL_GAP}\"/>").unwrap();
+                        x += HORIZONTAL_GAP;
+                    }
+                    item.draw(x, y, output);
+                    x += item.width();
+                }
+            }
+            Self::Choice(_) => {
+                let width = self.width();
+                let end = x + width;
+                for (offset, branch) in self.branch_offsets() {
+                    let branch_end = x + 2 * R + branch.width();
+                    if offset == 0 {
+                        writeln!(
+                            output,
+                            "<path d=\"M{x} {y} h{} M{branch_end} {y} H{end}\"/>",
+                            2 * R
+                        )
+                        .unwrap();
+                    } else {
+                        let branch_y = y + offset;
+                        writeln!(
+                            output,
+                            "<path d=\"M{x} {y} q{R} 0 {R} {R} V{} q0 {R} {R} {R} M{branch_end} {branch_y} H{} q{R} 0 {R} -{R} V{} q0 -{R} {R} -{R}\"/>",
+                            branch_y - R,
+                            end - 2 * R,
+                            y + R,
+                        )
+                        .unwrap();
+                    }
+                    branch.draw(x + 2 * R, y + offset, output);
+                }
+            }
+            Self::Loop(content) => {
+                let content_end = x + 2 * R + content.width();
+                let loop_y = y + Self::loop_offset(content);
+                writeln!(
+                    output,
+                    "<path d=\"M{x} {y} h{} M{content_end} {y} h{} M{content_end} {y} q{R} 0 {R} {R} V{} q0 {R} -{R} {R} H{} q-{R} 0 -{R} -{R} V{} q0 -{R} {R} -{R}\"/>",
+                    2 * R,
+                    2 * R,
+                    loop_y - R,
+                    x + 2 * R,
+                    y + R,
+                )
+                .unwrap();
+                content.draw(x + 2 * R, y, output);
+            }
+        }
+    }
+}
+
+fn escape_xml(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => result.push_str("&amp;"),
+            '<' => result.push_str("&lt;"),
+            '>' => result.push_str("&gt;"),
+            '"' => result.push_str("&quot;"),
+            _ => result.push(c),
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grammars::Variable;
+
+    #[test]
+    fn test_render_railroad_svg() {
+        let grammar = InputGrammar {
+            name: "the_language".to_string(),
+            variables: vec![
+                Variable::named(
+                    "program",
+                    Rule::choice(vec![Rule::repeat(Rule::named("_statement")), Rule::Blank]),
+                ),
+                Variable::hidden(
+                    "_statement",
+                    Rule::choice(vec![Rule::named("assignment"), Rule::named("call")]),
+                ),
+                Variable::named(
+                    "assignment",
+                    Rule::seq(vec![
+                        Rule::named("identifier"),
+                        Rule::string("<-"),
+                        Rule::named("call"),
+                    ]),
+                ),
+                Variable::named(
+                    "call",
+                    Rule::seq(vec![
+                        Rule::named("identifier"),
+                        Rule::string("("),
+                        Rule::choice(vec![Rule::named("identifier"), Rule::Blank]),
+                        Rule::string(")"),
+                    ]),
+                ),
+                Variable::named("identifier", Rule::pattern("[a-z]+", "")),
+            ],
+            ..Default::default()
+        };
+
+        let svg = render_railroad_svg(&grammar);
+        assert!(svg.starts_with("<svg "));
+        assert!(svg.ends_with("</svg>\n"));
+
+        for name in ["program", "assignment", "call", "identifier"] {
+            assert!(svg.contains(&format!("<g class=\"rule\" id=\"rule-{name}\">")));
+            assert!(svg.contains(&format!(">{name}</text>")));
+        }
+        assert!(!svg.contains("id=\"rule-_statement\""));
+        assert!(svg.contains(">_statement</text>"));
+        assert!(svg.contains(">&lt;-</text>"));
+        assert!(svg.contains(">/[a-z]+/</text>"));
+    }
+}
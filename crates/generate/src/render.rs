@@ -7,13 +7,13 @@ use std::{
 
 use rustc_hash::{FxHashMap, FxHashSet};
 
-use crate::LANGUAGE_VERSION;
+use crate::{LANGUAGE_VERSION, OptLevel};
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
 use super::{
     build_tables::Tables,
-    grammars::{ExternalToken, LexicalGrammar, SyntaxGrammar, VariableType},
+    grammars::{LexicalGrammar, SyntaxGrammar, VariableType},
     nfa::CharacterSet,
     node_types::ChildType,
     rules::{Alias, AliasMap, Symbol, SymbolType, TokenSet},
@@ -28,6 +28,34 @@ pub const ABI_VERSION_MIN: usize = 14;
 pub const ABI_VERSION_MAX: usize = LANGUAGE_VERSION;
 const ABI_VERSION_WITH_RESERVED_WORDS: usize = 15;
 
+/// The first line of every generated C file.
+pub const GENERATED_BANNER: &str = "/* Automatically @generated by tree-sitter */";
+
+/// The header that is shared by all of a parser's files when its tables are split into
+/// separate translation units.
+pub const SPLIT_HEADER_FILE_NAME: &str = "parser_shared.h";
+pub const SPLIT_LEX_FILE_NAME: &str = "parser_lex.c";
+pub const SPLIT_PARSE_TABLE_FILE_NAME: &str = "parser_parse_table.c";
+pub const SPLIT_PARSE_ACTIONS_FILE_NAME: &str = "parser_parse_actions.c";
+pub const SPLIT_FILE_NAMES: [&str; 4] = [
+    SPLIT_HEADER_FILE_NAME,
+    SPLIT_LEX_FILE_NAME,
+    SPLIT_PARSE_TABLE_FILE_NAME,
+    SPLIT_PARSE_ACTIONS_FILE_NAME,
+];
+
+/// The names of the lex functions and tables that are defined in their own translation units
+/// when a parser's tables are split. They are given external linkage, so they are prefixed
+/// with the language name to avoid conflicts with other parsers in the same library.
+const SPLIT_SYMBOL_NAMES: [&str; 6] = [
+    "ts_lex",
+    "ts_lex_keywords",
+    "ts_parse_table",
+    "ts_small_parse_table",
+    "ts_small_parse_table_map",
+    "ts_parse_actions",
+];
+
 pub type RenderResult<T> = Result<T, RenderError>;
 
 #[derive(Debug, Error, Serialize, Deserialize)]
@@ -64,6 +92,22 @@ macro_rules! add_line {
     }
 }
 
+// C++ only accepts designated array initializers that cover every index in
+// order, so when generating C++-compatible code, fill the elements that precede
+// the given index with zeroes.
+macro_rules! add_array_padding {
+    ($this:tt, $next_index:expr, $index:expr, $filler:expr) => {{
+        let index = $index;
+        if $this.cxx {
+            while $next_index < index {
+                add_line!($this, "{},", $filler);
+                $next_index += 1;
+            }
+        }
+        $next_index = index + 1;
+    }};
+}
+
 macro_rules! indent {
     ($this:tt) => {
         $this.indent_level += 1;
@@ -94,6 +138,7 @@ struct Generator {
     symbol_order: FxHashMap<Symbol, usize>,
     symbol_ids: FxHashMap<Symbol, String>,
     alias_ids: FxHashMap<Alias, String>,
+    external_token_ids: Vec<String>,
     unique_aliases: Vec<Alias>,
     symbol_map: FxHashMap<Symbol, Symbol>,
     reserved_word_sets: Vec<TokenSet>,
@@ -103,6 +148,16 @@ struct Generator {
     supertype_map: BTreeMap<String, Vec<ChildType>>,
     abi_version: usize,
     metadata: Option<Metadata>,
+    deduplicate_small_states: bool,
+    cxx: bool,
+    split_tables: bool,
+}
+
+/// The C source of a generated parser. When the parser's tables are split, `split_files`
+/// contains the shared header and the additional translation units, keyed by file name.
+pub struct RenderedParser {
+    pub c_code: String,
+    pub split_files: Vec<(&'static str, String)>,
 }
 
 struct LargeCharacterSetInfo {
@@ -118,13 +173,26 @@ struct Metadata {
 }
 
 impl Generator {
-    fn generate(mut self) -> RenderResult<String> {
+    fn generate(mut self) -> RenderResult<RenderedParser> {
         self.init();
         self.add_header();
+        if self.split_tables {
+            self.add_include_guard_start();
+        }
         self.add_includes();
         self.add_pragmas();
         self.add_stats();
         self.add_symbol_enum();
+
+        let mut split_files = Vec::new();
+        if self.split_tables {
+            self.add_split_declarations();
+            self.add_include_guard_end();
+            split_files.push((SPLIT_HEADER_FILE_NAME, std::mem::take(&mut self.buffer)));
+            self.add_header();
+            self.add_split_header_include();
+        }
+
         self.add_symbol_names_list();
         self.add_unique_symbol_map();
         self.add_symbol_metadata_list();
@@ -167,6 +235,10 @@ impl Generator {
             self.add_character_set(ix);
         }
         self.buffer.push_str(&lex_functions);
+        if self.split_tables {
+            let lex_file = self.split_off_file(buffer_offset_before_lex_functions, true);
+            split_files.push((SPLIT_LEX_FILE_NAME, lex_file));
+        }
 
         self.add_lex_modes();
 
@@ -175,7 +247,19 @@ impl Generator {
             self.add_reserved_word_sets();
         }
 
-        self.add_parse_table()?;
+        let buffer_offset_before_parse_table = self.buffer.len();
+        let parse_table_entries = self.add_parse_table()?;
+        if self.split_tables {
+            let parse_table_file = self.split_off_file(buffer_offset_before_parse_table, false);
+            split_files.push((SPLIT_PARSE_TABLE_FILE_NAME, parse_table_file));
+        }
+
+        let buffer_offset_before_parse_actions = self.buffer.len();
+        self.add_parse_action_list(parse_table_entries);
+        if self.split_tables {
+            let parse_actions_file = self.split_off_file(buffer_offset_before_parse_actions, false);
+            split_files.push((SPLIT_PARSE_ACTIONS_FILE_NAME, parse_actions_file));
+        }
 
         if !self.syntax_grammar.external_tokens.is_empty() {
             self.add_external_token_enum();
@@ -185,7 +269,24 @@ impl Generator {
 
         self.add_parser_export();
 
-        Ok(self.buffer)
+        Ok(RenderedParser {
+            c_code: self.buffer,
+            split_files,
+        })
+    }
+
+    /// Move everything that was written after the given offset into a separate translation
+    /// unit, which includes the shared header.
+    fn split_off_file(&mut self, offset: usize, is_lexer: bool) -> String {
+        let body = self.buffer.split_off(offset);
+        self.add_header();
+        self.add_split_header_include();
+        if is_lexer {
+            self.add_lexer_optimization_pragmas();
+        }
+        let mut file = self.buffer.split_off(offset);
+        file.push_str(&body);
+        file
     }
 
     fn init(&mut self) {
@@ -197,6 +298,18 @@ impl Generator {
             Symbol::end_of_nonterminal_extra(),
             self.symbol_ids[&Symbol::end()].clone(),
         );
+        self.external_token_ids = self
+            .syntax_grammar
+            .external_tokens
+            .iter()
+            .map(|token| {
+                let id = self.identifier(format!(
+                    "ts_external_token_{}",
+                    Self::sanitize_identifier(&token.name)
+                ));
+                Self::unique_identifier(id, &mut symbol_identifiers)
+            })
+            .collect();
 
         self.symbol_map = FxHashMap::default();
 
@@ -254,7 +367,9 @@ impl Generator {
 
             for alias in &production_info.alias_sequence {
                 // Generate a mapping from aliases to C identifiers.
-                if let Some(alias) = &alias {
+                if let Some(alias) = &alias
+                    && !self.alias_ids.contains_key(alias)
+                {
                     // Some aliases match an existing symbol in the grammar.
                     let alias_id = if let Some(existing_symbol) =
                         self.symbols_for_alias(alias).first()
@@ -268,14 +383,15 @@ impl Generator {
                             self.unique_aliases.insert(i, alias.clone());
                         }
 
-                        if alias.is_named {
+                        let id = self.identifier(if alias.is_named {
                             format!("alias_sym_{}", Self::sanitize_identifier(&alias.value))
                         } else {
                             format!("anon_alias_sym_{}", Self::sanitize_identifier(&alias.value))
-                        }
+                        });
+                        Self::unique_identifier(id, &mut symbol_identifiers)
                     };
 
-                    self.alias_ids.entry(alias.clone()).or_insert(alias_id);
+                    self.alias_ids.insert(alias.clone(), alias_id);
                 }
             }
         }
@@ -340,7 +456,7 @@ impl Generator {
     }
 
     fn add_header(&mut self) {
-        add_line!(self, "/* Automatically @generated by tree-sitter */");
+        add_line!(self, "{GENERATED_BANNER}");
         add_line!(self, "");
     }
 
@@ -349,6 +465,88 @@ impl Generator {
         add_line!(self, "");
     }
 
+    fn include_guard_name(&self) -> String {
+        format!(
+            "TREE_SITTER_{}_PARSER_SHARED_H_",
+            self.language_name.to_ascii_uppercase()
+        )
+    }
+
+    fn add_include_guard_start(&mut self) {
+        let guard = self.include_guard_name();
+        add_line!(self, "#ifndef {guard}");
+        add_line!(self, "#define {guard}");
+        add_line!(self, "");
+    }
+
+    fn add_include_guard_end(&mut self) {
+        add_line!(self, "#endif  // {}", self.include_guard_name());
+    }
+
+    fn add_split_header_include(&mut self) {
+        add_line!(self, "#include \"{SPLIT_HEADER_FILE_NAME}\"");
+        add_line!(self, "");
+    }
+
+    /// Declare the lex functions and tables that are defined in other translation units.
+    fn add_split_declarations(&mut self) {
+        add_line!(self, "#if defined(__GNUC__) || defined(__clang__)");
+        add_line!(
+            self,
+            "#define TS_HIDDEN __attribute__((visibility(\"hidden\")))"
+        );
+        add_line!(self, "#else");
+        add_line!(self, "#define TS_HIDDEN");
+        add_line!(self, "#endif");
+        add_line!(self, "");
+
+        for name in SPLIT_SYMBOL_NAMES {
+            add_line!(
+                self,
+                "#define {name} tree_sitter_{}_{}",
+                self.language_name,
+                name.trim_start_matches("ts_")
+            );
+        }
+        add_line!(self, "");
+
+        add_line!(
+            self,
+            "TS_HIDDEN bool ts_lex(TSLexer *lexer, TSStateId state);"
+        );
+        if self.syntax_grammar.word_token.is_some() {
+            add_line!(
+                self,
+                "TS_HIDDEN bool ts_lex_keywords(TSLexer *lexer, TSStateId state);"
+            );
+        }
+        add_line!(
+            self,
+            "TS_HIDDEN extern const uint16_t ts_parse_table[LARGE_STATE_COUNT][SYMBOL_COUNT];"
+        );
+        if self.large_state_count < self.parse_table.states.len() {
+            add_line!(
+                self,
+                "TS_HIDDEN extern const uint16_t ts_small_parse_table[];"
+            );
+            add_line!(
+                self,
+                "TS_HIDDEN extern const uint32_t ts_small_parse_table_map[];"
+            );
+        }
+        add_line!(
+            self,
+            "TS_HIDDEN extern const TSParseActionEntry ts_parse_actions[];"
+        );
+        add_line!(self, "");
+    }
+
+    /// The storage class of the lex functions and tables, which need external linkage when
+    /// they are split into separate translation units.
+    const fn split_storage_class(&self) -> &'static str {
+        if self.split_tables { "" } else { "static " }
+    }
+
     fn add_pragmas(&mut self) {
         add_line!(self, "#if defined(__GNUC__) || defined(__clang__)");
         add_line!(
@@ -358,6 +556,13 @@ impl Generator {
         add_line!(self, "#endif");
         add_line!(self, "");
 
+        // When the tables are split, this only applies to the lexer's translation unit.
+        if !self.split_tables {
+            self.add_lexer_optimization_pragmas();
+        }
+    }
+
+    fn add_lexer_optimization_pragmas(&mut self) {
         // Compiling large lexer functions can be very slow. Disabling optimizations
         // is not ideal, but only a very small fraction of overall parse time is
         // spent lexing, so the performance impact of this is negligible.
@@ -586,20 +791,25 @@ impl Generator {
             "static const TSSymbol ts_alias_sequences[PRODUCTION_ID_COUNT][MAX_ALIAS_SEQUENCE_LENGTH] = {{",
         );
         indent!(self);
+        let mut next_production_id = 0;
         for (i, production_info) in self.parse_table.production_infos.iter().enumerate() {
             if production_info.alias_sequence.is_empty() {
                 // Work around MSVC's intolerance of empty array initializers by
                 // explicitly zero-initializing the first element.
                 if i == 0 {
                     add_line!(self, "[0] = {{0}},");
+                    next_production_id = 1;
                 }
                 continue;
             }
 
+            add_array_padding!(self, next_production_id, i, "{0}");
             add_line!(self, "[{i}] = {{");
             indent!(self);
+            let mut next_step_index = 0;
             for (j, alias) in production_info.alias_sequence.iter().enumerate() {
                 if let Some(alias) = alias {
+                    add_array_padding!(self, next_step_index, j, "0");
                     add_line!(self, "[{j}] = {},", self.alias_ids[alias]);
                 }
             }
@@ -717,8 +927,10 @@ impl Generator {
             "static const TSMapSlice ts_field_map_slices[PRODUCTION_ID_COUNT] = {{",
         );
         indent!(self);
+        let mut next_production_id = 0;
         for (production_id, (row_id, length)) in field_map_ids.into_iter().enumerate() {
             if length > 0 {
+                add_array_padding!(self, next_production_id, production_id, "{0}");
                 add_line!(
                     self,
                     "[{production_id}] = {{.index = {row_id}, .length = {length}}},",
@@ -801,15 +1013,46 @@ impl Generator {
                     .collect::<BTreeSet<String>>(),
             );
         }
+        let mut slices = Vec::with_capacity(supertype_string_map.len());
         for (supertype, subtypes) in &supertype_string_map {
             let length = subtypes.len();
-            add_line!(
-                self,
-                "[{supertype}] = {{.index = {row_id}, .length = {length}}},",
-            );
+            slices.push((*supertype, row_id, length));
             row_id += length;
             supertype_ids.push(row_id);
         }
+        if self.cxx {
+            // The slices are indexed by symbol, so C++ needs them in symbol
+            // order rather than in alphabetical order.
+            let mut indexed = slices
+                .into_iter()
+                .map(|slice| {
+                    let index = self
+                        .symbol_ids
+                        .iter()
+                        .find_map(|(symbol, id)| {
+                            (*id == *slice.0).then(|| self.symbol_order[symbol])
+                        })
+                        .unwrap_or_default();
+                    (index, slice)
+                })
+                .collect::<Vec<_>>();
+            indexed.sort_unstable_by_key(|(index, _)| *index);
+            let mut next_symbol_index = 0;
+            for (index, (supertype, row_id, length)) in indexed {
+                add_array_padding!(self, next_symbol_index, index, "{0}");
+                add_line!(
+                    self,
+                    "[{supertype}] = {{.index = {row_id}, .length = {length}}},",
+                );
+            }
+        } else {
+            for (supertype, row_id, length) in slices {
+                add_line!(
+                    self,
+                    "[{supertype}] = {{.index = {row_id}, .length = {length}}},",
+                );
+            }
+        }
         dedent!(self);
         add_line!(self, "}};");
         add_line!(self, "");
@@ -838,7 +1081,8 @@ impl Generator {
     fn add_lex_function(&mut self, name: &str, lex_table: LexTable) {
         add_line!(
             self,
-            "static bool {name}(TSLexer *lexer, TSStateId state) {{",
+            "{}bool {name}(TSLexer *lexer, TSStateId state) {{",
+            self.split_storage_class()
         );
         indent!(self);
 
@@ -1206,6 +1450,9 @@ impl Generator {
             self.reserved_word_sets.len(),
         );
         indent!(self);
+        if self.cxx {
+            add_line!(self, "{{0}},");
+        }
         for (id, set) in self.reserved_word_sets.iter().enumerate() {
             if id == 0 {
                 continue;
@@ -1227,11 +1474,7 @@ impl Generator {
         add_line!(self, "enum ts_external_scanner_symbol_identifiers {{");
         indent!(self);
         for i in 0..self.syntax_grammar.external_tokens.len() {
-            add_line!(
-                self,
-                "{} = {i},",
-                Self::external_token_id(&self.syntax_grammar.external_tokens[i]),
-            );
+            add_line!(self, "{} = {i},", self.external_token_ids[i]);
         }
         dedent!(self);
         add_line!(self, "}};");
@@ -1252,7 +1495,7 @@ impl Generator {
             add_line!(
                 self,
                 "[{}] = {},",
-                Self::external_token_id(token),
+                self.external_token_ids[i],
                 self.symbol_ids[&id_token],
             );
         }
@@ -1268,16 +1511,16 @@ impl Generator {
             self.parse_table.external_lex_states.len(),
         );
         indent!(self);
+        let mut next_state_id = 0;
         for i in 0..self.parse_table.external_lex_states.len() {
             if !self.parse_table.external_lex_states[i].is_empty() {
+                add_array_padding!(self, next_state_id, i, "{0}");
                 add_line!(self, "[{i}] = {{");
                 indent!(self);
+                let mut next_token_index = 0;
                 for token in self.parse_table.external_lex_states[i].iter() {
-                    add_line!(
-                        self,
-                        "[{}] = true,",
-                        Self::external_token_id(&self.syntax_grammar.external_tokens[token.index])
-                    );
+                    add_array_padding!(self, next_token_index, token.index, "false");
+                    add_line!(self, "[{}] = true,", self.external_token_ids[token.index]);
                 }
                 dedent!(self);
                 add_line!(self, "}},");
@@ -1288,7 +1531,7 @@ impl Generator {
         add_line!(self, "");
     }
 
-    fn add_parse_table(&mut self) -> RenderResult<()> {
+    fn add_parse_table(&mut self) -> RenderResult<Vec<(usize, ParseTableEntry)>> {
         let mut parse_table_entries = FxHashMap::default();
         let mut next_parse_action_list_index = 0;
 
@@ -1304,7 +1547,8 @@ impl Generator {
 
         add_line!(
             self,
-            "static const uint16_t ts_parse_table[LARGE_STATE_COUNT][SYMBOL_COUNT] = {{",
+            "{}const uint16_t ts_parse_table[LARGE_STATE_COUNT][SYMBOL_COUNT] = {{",
+            self.split_storage_class()
         );
         indent!(self);
 
@@ -1330,25 +1574,52 @@ impl Generator {
             terminal_entries.sort_unstable_by_key(|e| self.symbol_order.get(e.0));
             nonterminal_entries.sort_unstable_by_key(|k| k.0);
 
-            for (symbol, action) in &nonterminal_entries {
-                add_line!(
-                    self,
-                    "[{}] = STATE({}),",
-                    self.symbol_ids[symbol],
-                    match action {
+            if self.cxx {
+                // C++ needs every row's entries in symbol order, with no gaps.
+                let mut entries =
+                    Vec::with_capacity(terminal_entries.len() + nonterminal_entries.len());
+                for (symbol, entry) in &terminal_entries {
+                    let entry_id = Self::get_parse_action_list_id(
+                        entry,
+                        &mut parse_table_entries,
+                        &mut next_parse_action_list_index,
+                    );
+                    entries.push((**symbol, format!("ACTIONS({entry_id})")));
+                }
+                for (symbol, action) in &nonterminal_entries {
+                    let state = match action {
                         GotoAction::Goto(state) => *state,
                         GotoAction::ShiftExtra => i,
-                    }
-                );
-            }
+                    };
+                    entries.push((**symbol, format!("STATE({state})")));
+                }
+                entries.sort_unstable_by_key(|(symbol, _)| self.symbol_order[symbol]);
+                let mut next_symbol_index = 0;
+                for (symbol, value) in entries {
+                    add_array_padding!(self, next_symbol_index, self.symbol_order[&symbol], "0");
+                    add_line!(self, "[{}] = {value},", self.symbol_ids[&symbol]);
+                }
+            } else {
+                for (symbol, action) in &nonterminal_entries {
+                    add_line!(
+                        self,
+                        "[{}] = STATE({}),",
+                        self.symbol_ids[symbol],
+                        match action {
+                            GotoAction::Goto(state) => *state,
+                            GotoAction::ShiftExtra => i,
+                        }
+                    );
+                }
 
-            for (symbol, entry) in &terminal_entries {
-                let entry_id = Self::get_parse_action_list_id(
-                    entry,
-                    &mut parse_table_entries,
-                    &mut next_parse_action_list_index,
-                );
-                add_line!(self, "[{}] = ACTIONS({entry_id}),", self.symbol_ids[symbol]);
+                for (symbol, entry) in &terminal_entries {
+                    let entry_id = Self::get_parse_action_list_id(
+                        entry,
+                        &mut parse_table_entries,
+                        &mut next_parse_action_list_index,
+                    );
+                    add_line!(self, "[{}] = ACTIONS({entry_id}),", self.symbol_ids[symbol]);
+                }
             }
 
             dedent!(self);
@@ -1360,7 +1631,11 @@ impl Generator {
         add_line!(self, "");
 
         if self.large_state_count < self.parse_table.states.len() {
-            add_line!(self, "static const uint16_t ts_small_parse_table[] = {{");
+            add_line!(
+                self,
+                "{}const uint16_t ts_small_parse_table[] = {{",
+                self.split_storage_class()
+            );
             indent!(self);
 
             let mut next_table_index = 0;
@@ -1371,8 +1646,14 @@ impl Generator {
                     .saturating_sub(self.large_state_count),
             );
             let mut symbols_by_value = FxHashMap::<(usize, SymbolType), Vec<Symbol>>::default();
-            for state in self.parse_table.states.iter().skip(self.large_state_count) {
-                small_state_indices.push(next_table_index);
+            let mut small_state_indices_by_row = FxHashMap::default();
+            for (state_id, state) in self
+                .parse_table
+                .states
+                .iter()
+                .enumerate()
+                .skip(self.large_state_count)
+            {
                 symbols_by_value.clear();
 
                 terminal_entries.clear();
@@ -1396,9 +1677,7 @@ impl Generator {
                 for (symbol, action) in &state.nonterminal_entries {
                     let state_id = match action {
                         GotoAction::Goto(i) => *i,
-                        GotoAction::ShiftExtra => {
-                            self.large_state_count + small_state_indices.len() - 1
-                        }
+                        GotoAction::ShiftExtra => state_id,
                     };
                     symbols_by_value
                         .entry((state_id, SymbolType::NonTerminal))
@@ -1407,10 +1686,25 @@ impl Generator {
                 }
 
                 let mut values_with_symbols = symbols_by_value.drain().collect::<Vec<_>>();
+                for (_, symbols) in &mut values_with_symbols {
+                    symbols.sort_unstable();
+                }
                 values_with_symbols.sort_unstable_by_key(|((value, kind), symbols)| {
                     (symbols.len(), *kind, *value, symbols[0])
                 });
 
+                // Many small states have exactly the same actions. Because the small parse
+                // table is accessed through an index map, these states can share a row.
+                if self.deduplicate_small_states {
+                    if let Some(&index) = small_state_indices_by_row.get(&values_with_symbols) {
+                        small_state_indices.push(index);
+                        continue;
+                    }
+                    small_state_indices_by_row
+                        .insert(values_with_symbols.clone(), next_table_index);
+                }
+                small_state_indices.push(next_table_index);
+
                 add_line!(
                     self,
                     "[{next_table_index}] = {},",
@@ -1427,9 +1721,8 @@ impl Generator {
                         add_line!(self, "ACTIONS({value}), {},", symbols.len());
                     }
 
-                    symbols.sort_unstable();
                     indent!(self);
-                    for symbol in symbols {
+                    for symbol in symbols.iter() {
                         add_line!(self, "{},", self.symbol_ids[symbol]);
                     }
                     dedent!(self);
@@ -1444,7 +1737,8 @@ impl Generator {
 
             add_line!(
                 self,
-                "static const uint32_t ts_small_parse_table_map[] = {{"
+                "{}const uint32_t ts_small_parse_table_map[] = {{",
+                self.split_storage_class()
             );
             indent!(self);
             for i in self.large_state_count..self.parse_table.states.len() {
@@ -1467,15 +1761,14 @@ impl Generator {
             .map(|(entry, i)| (i, entry))
             .collect::<Vec<_>>();
         parse_table_entries.sort_by_key(|(index, _)| *index);
-        self.add_parse_action_list(parse_table_entries);
-
-        Ok(())
+        Ok(parse_table_entries)
     }
 
     fn add_parse_action_list(&mut self, parse_table_entries: Vec<(usize, ParseTableEntry)>) {
         add_line!(
             self,
-            "static const TSParseActionEntry ts_parse_actions[] = {{"
+            "{}const TSParseActionEntry ts_parse_actions[] = {{",
+            self.split_storage_class()
         );
         indent!(self);
         for (i, entry) in parse_table_entries {
@@ -1579,7 +1872,7 @@ impl Generator {
         add_line!(self, ".state_count = STATE_COUNT,");
         add_line!(self, ".large_state_count = LARGE_STATE_COUNT,");
         add_line!(self, ".production_id_count = PRODUCTION_ID_COUNT,");
-        if self.abi_version >= ABI_VERSION_WITH_RESERVED_WORDS {
+        if self.abi_version >= ABI_VERSION_WITH_RESERVED_WORDS && !self.cxx {
             add_line!(self, ".supertype_count = SUPERTYPE_COUNT,");
         }
         add_line!(self, ".field_count = FIELD_COUNT,");
@@ -1603,7 +1896,10 @@ impl Generator {
             add_line!(self, ".field_map_slices = ts_field_map_slices,");
             add_line!(self, ".field_map_entries = ts_field_map_entries,");
         }
-        if !self.supertype_map.is_empty() && self.abi_version >= ABI_VERSION_WITH_RESERVED_WORDS {
+        if !self.supertype_map.is_empty()
+            && self.abi_version >= ABI_VERSION_WITH_RESERVED_WORDS
+            && !self.cxx
+        {
             add_line!(self, ".supertype_map_slices = ts_supertype_map_slices,");
             add_line!(self, ".supertype_map_entries = ts_supertype_map_entries,");
             add_line!(self, ".supertype_symbols = ts_supertype_symbols,");
@@ -1616,7 +1912,13 @@ impl Generator {
         }
 
         // Lexing
-        add_line!(self, ".lex_modes = (const void*)ts_lex_modes,");
+        if !self.cxx {
+            add_line!(self, ".lex_modes = (const void*)ts_lex_modes,");
+        } else if self.abi_version >= ABI_VERSION_WITH_RESERVED_WORDS {
+            add_line!(self, ".lex_modes = ts_lex_modes,");
+        } else {
+            add_line!(self, ".lex_modes = (const TSLexerMode *)ts_lex_modes,");
+        }
         add_line!(self, ".lex_fn = ts_lex,");
         if let Some(keyword_capture_token) = self.syntax_grammar.word_token {
             add_line!(self, ".keyword_lex_fn = ts_lex_keywords,");
@@ -1660,6 +1962,16 @@ impl Generator {
                     .unwrap()
             );
 
+            // C++ requires designated initializers to follow the declaration order.
+            if self.cxx {
+                add_line!(self, ".supertype_count = SUPERTYPE_COUNT,");
+                if !self.supertype_map.is_empty() {
+                    add_line!(self, ".supertype_symbols = ts_supertype_symbols,");
+                    add_line!(self, ".supertype_map_slices = ts_supertype_map_slices,");
+                    add_line!(self, ".supertype_map_entries = ts_supertype_map_entries,");
+                }
+            }
+
             let metadata = self.metadata.unwrap_or_default();
 
             add_line!(self, ".metadata = {{");
@@ -1711,39 +2023,60 @@ impl Generator {
         result
     }
 
-    fn external_token_id(token: &ExternalToken) -> String {
-        format!(
-            "ts_external_token_{}",
-            Self::sanitize_identifier(&token.name)
-        )
-    }
-
+    /// Choose the C identifier for a symbol, based on its sanitized name. Symbols are
+    /// assigned identifiers in the order of the parse table's symbols, which only depends on
+    /// the grammar, and a symbol whose identifier is already taken gets the smallest numeric
+    /// suffix that makes it unique. This keeps the identifiers stable between runs.
     fn assign_symbol_id(&mut self, symbol: Symbol, used_identifiers: &mut FxHashSet<String>) {
-        let mut id;
-        if symbol == Symbol::end() {
-            id = "ts_builtin_sym_end".to_string();
+        let id = if symbol == Symbol::end() {
+            "ts_builtin_sym_end".to_string()
         } else {
             let (name, kind) = self.metadata_for_symbol(symbol);
-            id = match kind {
+            self.identifier(match kind {
                 VariableType::Auxiliary => format!("aux_sym_{}", Self::sanitize_identifier(name)),
                 VariableType::Anonymous => format!("anon_sym_{}", Self::sanitize_identifier(name)),
                 VariableType::Hidden | VariableType::Named => {
                     format!("sym_{}", Self::sanitize_identifier(name))
                 }
-            };
+            })
+        };
+        let id = Self::unique_identifier(id, used_identifiers);
+        self.symbol_ids.insert(symbol, id);
+    }
+
+    /// Append the smallest numeric suffix to an identifier that makes it different from all of
+    /// the identifiers that are already used. Symbols, aliases, and external tokens all share
+    /// one set of identifiers, and this must be applied after [`Self::identifier`], which can
+    /// map different names to the same identifier.
+    fn unique_identifier(mut id: String, used_identifiers: &mut FxHashSet<String>) -> String {
+        let mut suffix_number = 1;
+        let mut suffix = String::new();
+        while used_identifiers.contains(&id) {
+            id.drain(id.len() - suffix.len()..);
+            suffix_number += 1;
+            suffix = suffix_number.to_string();
+            id += &suffix;
+        }
+        assert!(
+            used_identifiers.insert(id.clone()),
+            "duplicate identifier {id}"
+        );
+        id
+    }
 
-            let mut suffix_number = 1;
-            let mut suffix = String::new();
-            while used_identifiers.contains(&id) {
-                id.drain(id.len() - suffix.len()..);
-                suffix_number += 1;
-                suffix = suffix_number.to_string();
-                id += &suffix;
+    /// C++ reserves every identifier that contains a double underscore, so when
+    /// generating C++-compatible code, collapse runs of underscores.
+    fn identifier(&self, id: String) -> String {
+        if !self.cxx || !id.contains("__") {
+            return id;
+        }
+        let mut result = String::with_capacity(id.len());
+        for c in id.chars() {
+            if c != '_' || !result.ends_with('_') {
+                result.push(c);
             }
         }
-
-        used_identifiers.insert(id.clone());
-        self.symbol_ids.insert(symbol, id);
+        result
     }
 
     fn field_id(field_name: &str) -> String {
@@ -1942,6 +2275,7 @@ impl Generator {
 /// * `abi_version` - The language ABI version that should be generated. Usually you want
 ///   Tree-sitter's current version, but right after making an ABI change, it may be useful to
 ///   generate code with the previous ABI.
+/// * `cxx` - Whether the generated code should also compile cleanly as C++.
 #[expect(
     clippy::too_many_arguments,
     reason = "all parameters are required for code generation"
@@ -1955,7 +2289,10 @@ pub fn render_c_code(
     abi_version: usize,
     semantic_version: Option<(u8, u8, u8)>,
     supertype_symbol_map: BTreeMap<Symbol, Vec<ChildType>>,
-) -> RenderResult<String> {
+    optimizations: OptLevel,
+    cxx: bool,
+    split_tables: bool,
+) -> RenderResult<RenderedParser> {
     if !(ABI_VERSION_MIN..=ABI_VERSION_MAX).contains(&abi_version) {
         Err(RenderError::ABI(abi_version))?;
     }
@@ -1977,6 +2314,9 @@ pub fn render_c_code(
             patch,
         }),
         supertype_symbol_map,
+        deduplicate_small_states: optimizations.contains(OptLevel::DeduplicateSmallStates),
+        cxx,
+        split_tables,
         ..Default::default()
     }
     .generate()
@@ -2,7 +2,7 @@ use std::{collections::BTreeMap, fmt, hash::Hash};
 
 use serde::{Deserialize, Serialize};
 
-use super::bitvec::{BitVec, SetBitsIter};
+use super::bitvec::{BitVec, DetachedBitVec, SetBitsIter};
 use super::grammars::VariableType;
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
@@ -85,6 +85,27 @@ pub struct TokenSet {
     end_of_nonterminal_extra: bool,
 }
 
+/// A copy of a [`TokenSet`] that isn't tied to the current thread's [`BitVec`] arena.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct DetachedTokenSet {
+    terminal_bits: DetachedBitVec,
+    external_bits: DetachedBitVec,
+    eof: bool,
+    end_of_nonterminal_extra: bool,
+}
+
+impl DetachedTokenSet {
+    #[must_use]
+    pub fn attach(&self) -> TokenSet {
+        TokenSet {
+            terminal_bits: self.terminal_bits.attach(),
+            external_bits: self.external_bits.attach(),
+            eof: self.eof,
+            end_of_nonterminal_extra: self.end_of_nonterminal_extra,
+        }
+    }
+}
+
 impl fmt::Debug for TokenSet {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_list().entries(self.iter()).finish()
@@ -342,6 +363,17 @@ impl TokenSet {
         }
     }
 
+    /// Copy this set so that it can be sent to another thread.
+    #[must_use]
+    pub fn detach(&self) -> DetachedTokenSet {
+        DetachedTokenSet {
+            terminal_bits: self.terminal_bits.detach(),
+            external_bits: self.external_bits.detach(),
+            eof: self.eof,
+            end_of_nonterminal_extra: self.end_of_nonterminal_extra,
+        }
+    }
+
     pub fn iter(&self) -> impl Iterator<Item = Symbol> + '_ {
         SetBitsIter::new(self.terminal_bits.as_slice())
             .map(Symbol::terminal)
@@ -72,6 +72,8 @@ pub struct ParseTable {
     pub production_infos: Vec<ProductionInfo>,
     pub max_aliased_production_length: usize,
     pub external_lex_states: Vec<TokenSet>,
+    /// The number of conflicts that were resolved using precedence or associativity.
+    pub resolved_conflict_count: usize,
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
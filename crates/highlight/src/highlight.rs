@@ -102,11 +102,16 @@ pub enum Error {
 }
 
 /// Represents a single step in rendering a syntax-highlighted document.
+///
+/// `InjectionDepth` gives the injection depth of the layer that produced the following
+/// `HighlightStart` event, where the outermost language has a depth of zero. It is only
+/// emitted if enabled with [`Highlighter::set_report_injection_depth`].
 #[derive(Copy, Clone, Debug)]
 pub enum HighlightEvent {
     Source { start: usize, end: usize },
     HighlightStart(Highlight),
     HighlightEnd,
+    InjectionDepth(usize),
 }
 
 /// Contains the data needed to highlight code written in a particular language.
@@ -137,6 +142,7 @@ pub struct HighlightConfiguration {
 pub struct Highlighter {
     pub parser: Parser,
     cursors: Vec<QueryCursor>,
+    report_injection_depth: bool,
 }
 
 /// Converts a general-purpose syntax highlighting iterator into a sequence of lines of HTML.
@@ -148,6 +154,31 @@ pub struct HtmlRenderer {
     last_carriage_return: Option<usize>,
 }
 
+/// Converts a general-purpose syntax highlighting iterator into the encoded token array
+/// used by the Language Server Protocol's semantic token requests.
+///
+/// Each token is represented by five integers: the line (relative to the previous token),
+/// the start column (relative to the previous token if it's on the same line), the length,
+/// the token type, and the token modifiers. The token type is the index of the token's
+/// [`Highlight`], so the server's token legend should list the names passed to
+/// [`HighlightConfiguration::configure`] in the same order. No modifiers are set. Columns
+/// and lengths are measured in UTF-16 code units, and tokens that span multiple lines are
+/// split at each line break.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SemanticTokens {
+    pub data: Vec<u32>,
+}
+
+/// A change to an encoded [`SemanticTokens`] array, in the shape of the Language
+/// Server Protocol's `SemanticTokensEdit`: `delete_count` integers starting at
+/// `start` are replaced with `data`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SemanticTokensEdit {
+    pub start: u32,
+    pub delete_count: u32,
+    pub data: Vec<u32>,
+}
+
 #[derive(Debug)]
 struct LocalDef<'a> {
     name: &'a str,
@@ -176,6 +207,7 @@ where
     layers: Vec<HighlightIterLayer<'a>>,
     iter_count: usize,
     next_event: Option<HighlightEvent>,
+    queued_event: Option<HighlightEvent>,
     last_highlight_range: Option<(usize, usize, usize)>,
 }
 
@@ -284,6 +316,7 @@ impl Highlighter {
         Self {
             parser: Parser::new(),
             cursors: Vec::new(),
+            report_injection_depth: false,
         }
     }
 
@@ -291,7 +324,20 @@ impl Highlighter {
         &mut self.parser
     }
 
+    /// Set whether each `HighlightStart` event should be preceded by an
+    /// `InjectionDepth` event, indicating how deeply nested the injected
+    /// language that produced the highlight is.
+    pub const fn set_report_injection_depth(&mut self, report: bool) {
+        self.report_injection_depth = report;
+    }
+
     /// Iterate over the highlighted regions for a given slice of source code.
+    ///
+    /// The `injection_callback` is called with the name of each injected language, and
+    /// should return its configuration. If it returns `None`, the injection is skipped:
+    /// the injected text is emitted with only the highlights of the surrounding language,
+    /// and highlighting of the rest of the document continues as normal. This also applies
+    /// to injections nested within other injections.
     pub fn highlight<'a>(
         &'a mut self,
         config: &'a HighlightConfiguration,
@@ -328,6 +374,7 @@ impl Highlighter {
             iter_count: 0,
             layers,
             next_event: None,
+            queued_event: None,
             last_highlight_range: None,
         };
         result.sort_layers();
@@ -893,7 +940,7 @@ where
     fn next(&mut self) -> Option<Self::Item> {
         'main: loop {
             // If we've already determined the next highlight boundary, just return it.
-            if let Some(e) = self.next_event.take() {
+            if let Some(e) = self.next_event.take().or_else(|| self.queued_event.take()) {
                 return Some(Ok(e));
             }
 
@@ -1140,10 +1187,16 @@ where
 
             // Emit a scope start event and push the node's end position to the stack.
             if let Some(highlight) = reference_highlight.or(current_highlight) {
-                self.last_highlight_range = Some((range.start, range.end, layer.depth));
+                let depth = layer.depth;
+                self.last_highlight_range = Some((range.start, range.end, depth));
                 layer.highlight_end_stack.push(range.end);
-                return self
-                    .emit_event(range.start, Some(HighlightEvent::HighlightStart(highlight)));
+                let event = HighlightEvent::HighlightStart(highlight);
+                if self.highlighter.report_injection_depth {
+                    self.queued_event = Some(event);
+                    return self
+                        .emit_event(range.start, Some(HighlightEvent::InjectionDepth(depth)));
+                }
+                return self.emit_event(range.start, Some(event));
             }
 
             self.sort_layers();
@@ -1203,6 +1256,7 @@ impl HtmlRenderer {
                 Ok(HighlightEvent::Source { start, end }) => {
                     self.add_text(&source[start..end], &highlights, &attribute_callback);
                 }
+                Ok(HighlightEvent::InjectionDepth(_)) => {}
                 Err(a) => return Err(a),
             }
         }
@@ -1311,6 +1365,113 @@ impl HtmlRenderer {
     }
 }
 
+impl SemanticTokens {
+    pub fn new(
+        highlighter: impl Iterator<Item = Result<HighlightEvent, Error>>,
+        source: &[u8],
+    ) -> Result<Self, Error> {
+        let mut result = Self::default();
+        let mut highlights = Vec::new();
+        let mut previous_token_start = (0, 0);
+        let (mut row, mut column) = (0, 0);
+        for event in highlighter {
+            match event? {
+                HighlightEvent::HighlightStart(s) => highlights.push(s),
+                HighlightEvent::HighlightEnd => {
+                    highlights.pop();
+                }
+                HighlightEvent::Source { start, end } => {
+                    let highlight = highlights.last().copied();
+                    let mut token_start = column;
+                    // Carriage returns at the end of a line aren't part of the token.
+                    let mut token_end = column;
+                    for c in LossyUtf8::new(&source[start..end]).flat_map(str::chars) {
+                        if c == '\n' {
+                            if let Some(highlight) = highlight {
+                                result.push_token(
+                                    &mut previous_token_start,
+                                    row,
+                                    token_start..token_end,
+                                    highlight,
+                                );
+                            }
+                            row += 1;
+                            column = 0;
+                            token_start = 0;
+                            token_end = 0;
+                        } else {
+                            column += c.len_utf16() as u32;
+                            if c != '\r' {
+                                token_end = column;
+                            }
+                        }
+                    }
+                    if let Some(highlight) = highlight {
+                        result.push_token(
+                            &mut previous_token_start,
+                            row,
+                            token_start..token_end,
+                            highlight,
+                        );
+                    }
+                }
+                HighlightEvent::InjectionDepth(_) => {}
+            }
+        }
+        Ok(result)
+    }
+
+    /// Compute the edits that turn `previous` into these tokens, as needed for a
+    /// `semanticTokens/full/delta` response.
+    ///
+    /// Unchanged tokens produce no edits. Otherwise, a single edit replaces everything
+    /// between the integers that both arrays share at their start and end.
+    #[must_use]
+    pub fn delta_from(&self, previous: &Self) -> Vec<SemanticTokensEdit> {
+        let prefix_len = iter::zip(&previous.data, &self.data)
+            .take_while(|(a, b)| a == b)
+            .count();
+        if prefix_len == previous.data.len() && prefix_len == self.data.len() {
+            return Vec::new();
+        }
+        let suffix_len = iter::zip(previous.data.iter().rev(), self.data.iter().rev())
+            .take(previous.data.len().min(self.data.len()) - prefix_len)
+            .take_while(|(a, b)| a == b)
+            .count();
+        vec![SemanticTokensEdit {
+            start: prefix_len as u32,
+            delete_count: (previous.data.len() - prefix_len - suffix_len) as u32,
+            data: self.data[prefix_len..self.data.len() - suffix_len].to_vec(),
+        }]
+    }
+
+    fn push_token(
+        &mut self,
+        previous_token_start: &mut (u32, u32),
+        row: u32,
+        columns: ops::Range<u32>,
+        highlight: Highlight,
+    ) {
+        if columns.is_empty() {
+            return;
+        }
+        let (previous_row, previous_column) = *previous_token_start;
+        let column_delta = if row == previous_row {
+            columns.start - previous_column
+        } else {
+            columns.start
+        };
+        self.data.extend([
+            row - previous_row,
+            column_delta,
+            columns.end - columns.start,
+            highlight.0 as u32,
+            0,
+        ]);
+        *previous_token_start = (row, columns.start);
+    }
+}
+
 fn injection_for_match<'a>(
     config: &'a HighlightConfiguration,
     parent_name: Option<&'a str>,
@@ -43,6 +43,15 @@ static WASM_TOOL_LOCK: Mutex<()> = Mutex::new(());
 const WASI_SDK_VERSION: &str = include_str!("../wasi-sdk-version").trim_ascii();
 const BINARYEN_VERSION: &str = include_str!("../binaryen-version").trim_ascii();
 
+/// The files, besides `parser.c`, that `tree-sitter generate --split-tables` writes into a
+/// grammar's `src` directory. These must match the names used by `tree-sitter-generate`.
+const SPLIT_PARSER_FILE_NAMES: [&str; 4] = [
+    "parser_shared.h",
+    "parser_lex.c",
+    "parser_parse_table.c",
+    "parser_parse_actions.c",
+];
+
 #[cfg(all(target_os = "macos", target_arch = "aarch64"))]
 const ARCH_OS: Result<&str, LoaderError> = Ok("arm64-macos");
 #[cfg(all(target_os = "macos", target_arch = "x86_64"))]
@@ -658,6 +667,24 @@ pub struct LanguageConfiguration<'a> {
     _phantom: PhantomData<&'a ()>,
 }
 
+/// Metadata about a grammar that the [`Loader`] has found, as returned by
+/// [`Loader::discover`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DiscoveredGrammar {
+    /// The name of the language, as given in the grammar's `tree-sitter.json` or `grammar.json`.
+    pub name: String,
+    /// The directory containing the grammar's `src` directory.
+    pub path: PathBuf,
+    /// The scopes of the language configurations that use this grammar.
+    pub scopes: Vec<String>,
+    /// The file types of the language configurations that use this grammar.
+    pub file_types: Vec<String>,
+    /// The path at which the compiled grammar is cached.
+    pub library_path: PathBuf,
+    /// Whether the cached library exists and is newer than the grammar's sources.
+    pub is_compiled: bool,
+}
+
 pub struct Loader {
     pub parser_lib_path: PathBuf,
     languages_by_id: Vec<(PathBuf, OnceCell<Language>, Option<Vec<PathBuf>>)>,
@@ -895,6 +922,50 @@ impl Loader {
             .collect()
     }
 
+    /// List the grammars found so far by [`Loader::find_all_languages`] and
+    /// [`Loader::find_language_configurations_at_path`], without compiling or loading them.
+    #[must_use]
+    pub fn discover(&self) -> Vec<DiscoveredGrammar> {
+        self.languages_by_id
+            .iter()
+            .enumerate()
+            .map(|(id, (path, _, externals))| {
+                let configurations = self
+                    .language_configurations
+                    .iter()
+                    .filter(|c| c.language_id == id)
+                    .collect::<Vec<_>>();
+                let src_path = path.join("src");
+                let name = Self::grammar_json_name(&src_path.join("grammar.json"))
+                    .ok()
+                    .or_else(|| configurations.first().map(|c| c.language_name.clone()))
+                    .unwrap_or_default();
+                let library_path = self.library_path(&name);
+
+                let mut paths_to_check = vec![src_path.join("parser.c")];
+                paths_to_check.extend(self.get_split_parser_paths(&src_path));
+                paths_to_check.extend(self.get_scanner_path(&src_path));
+                paths_to_check.extend(externals.iter().flatten().map(|p| src_path.join(p)));
+                let is_compiled = !needs_recompile(&library_path, &paths_to_check).unwrap_or(true);
+
+                DiscoveredGrammar {
+                    name,
+                    path: path.clone(),
+                    scopes: configurations
+                        .iter()
+                        .filter_map(|c| c.scope.clone())
+                        .collect(),
+                    file_types: configurations
+                        .iter()
+                        .flat_map(|c| c.file_types.iter().cloned())
+                        .collect(),
+                    library_path,
+                    is_compiled,
+                }
+            })
+            .collect()
+    }
+
     pub fn language_configuration_for_scope(
         &self,
         scope: &str,
@@ -1083,14 +1154,8 @@ impl Loader {
         &self,
         mut config: CompileConfig,
     ) -> LoaderResult<Language> {
-        let mut lib_name = config.name.clone();
         let language_fn_name = format!("tree_sitter_{}", config.name.replace('-', "_"));
-        if self.debug_build {
-            lib_name.push_str(".debug._");
-        }
-
         if self.sanitize_build {
-            lib_name.push_str(".sanitize._");
             config.sanitize = true;
         }
 
@@ -1102,21 +1167,16 @@ impl Loader {
 
         let mut recompile = self.force_rebuild || config.output_path.is_some(); // if specified, always recompile
 
-        let output_path = config.output_path.unwrap_or_else(|| {
-            let mut path = self.parser_lib_path.join(lib_name);
-            path.set_extension(env::consts::DLL_EXTENSION);
-            #[cfg(feature = "wasm")]
-            if self.wasm_store.lock().unwrap().is_some() {
-                path.set_extension("wasm");
-            }
-            path
-        });
+        let output_path = config
+            .output_path
+            .unwrap_or_else(|| self.library_path(&config.name));
         config.output_path = Some(output_path.clone());
 
         let parser_path = config.src_path.join("parser.c");
         config.scanner_path = self.get_scanner_path(config.src_path);
 
         let mut paths_to_check = vec![parser_path];
+        paths_to_check.extend(self.get_split_parser_paths(config.src_path));
 
         if let Some(scanner_path) = config.scanner_path.as_ref() {
             paths_to_check.push(scanner_path.clone());
@@ -1214,6 +1274,25 @@ impl Loader {
         Self::load_language(&output_path, &language_fn_name)
     }
 
+    /// The path at which the compiled library for the language with the given name is cached.
+    fn library_path(&self, name: &str) -> PathBuf {
+        let mut lib_name = name.to_string();
+        if self.debug_build {
+            lib_name.push_str(".debug._");
+        }
+        if self.sanitize_build {
+            lib_name.push_str(".sanitize._");
+        }
+
+        let mut path = self.parser_lib_path.join(lib_name);
+        path.set_extension(env::consts::DLL_EXTENSION);
+        #[cfg(feature = "wasm")]
+        if self.wasm_store.lock().unwrap().is_some() {
+            path.set_extension("wasm");
+        }
+        path
+    }
+
     pub fn load_language(path: &Path, function_name: &str) -> LoaderResult<Language> {
         let library = unsafe { Library::new(path) }.map_err(|e| {
             LoaderError::Library(LibraryError {
@@ -1252,6 +1331,12 @@ impl Loader {
             .includes(&config.header_paths)
             .std("c11");
 
+        for path in self.get_split_parser_paths(config.src_path) {
+            if path.extension().is_some_and(|ext| ext == "c") {
+                cc_config.file(path);
+            }
+        }
+
         if let Some(scanner_path) = config.scanner_path.as_ref() {
             cc_config.file(scanner_path);
         }
@@ -1446,6 +1531,11 @@ impl Loader {
             ".",
             "parser.c",
         ]);
+        compile_command.args(
+            self.get_split_parser_paths(src_path)
+                .into_iter()
+                .filter(|path| path.extension().is_some_and(|ext| ext == "c")),
+        );
 
         if let Some(scanner_filename) = scanner_filename {
             compile_command.arg(scanner_filename);
@@ -2051,6 +2141,17 @@ impl Loader {
         let path = src_path.join("scanner.c");
         path.exists().then_some(path)
     }
+
+    /// The files that a parser's tables were split into by `tree-sitter generate --split-tables`,
+    /// including the header that they share.
+    #[must_use]
+    pub fn get_split_parser_paths(&self, src_path: &Path) -> Vec<PathBuf> {
+        SPLIT_PARSER_FILE_NAMES
+            .iter()
+            .map(|name| src_path.join(name))
+            .filter(|path| path.exists())
+            .collect()
+    }
 }
 
 impl LanguageConfiguration<'_> {
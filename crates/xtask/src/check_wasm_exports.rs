@@ -16,7 +16,7 @@ use notify_debouncer_full::new_debouncer;
 
 use crate::{CheckWasmExports, bail_on_err, watch_wasm};
 
-const EXCLUDES: [&str; 25] = [
+const EXCLUDES: &[&str] = &[
     // Unneeded because the JS side has its own way of implementing it
     "ts_node_child_by_field_name",
     "ts_node_edit",
@@ -26,6 +26,8 @@ const EXCLUDES: [&str; 25] = [
     "ts_node_eq",
     "ts_tree_cursor_current_field_name",
     "ts_lookahead_iterator_current_symbol_name",
+    // Exposed as `Node.id`, which is the address of the node's subtree
+    "ts_node_stable_id",
     // Not used in Wasm
     "ts_init",
     "ts_set_allocator",
@@ -38,8 +40,21 @@ const EXCLUDES: [&str; 25] = [
     "ts_tree_language",
     "ts_lookahead_iterator_language",
     "ts_parser_logger",
+    "ts_parser_error_recovery_mode",
+    "ts_parser_set_error_recovery_mode",
+    "ts_parser_extra_override",
+    "ts_parser_set_extra_override",
+    "ts_language_external_tokens",
+    "ts_language_lex",
+    "ts_parser_parse_stats",
     "ts_parser_parse_string",
     "ts_parser_parse_string_encoding",
+    // Not exposed in the web bindings yet
+    "ts_node_error_cost",
+    "ts_query_matches_anonymous_nodes",
+    "ts_query_cursor_set_skip_anonymous_nodes",
+    "ts_query_language",
+    "ts_query_steps_for_pattern",
     // Query cursor is not managed by user in web bindings
     "ts_query_cursor_delete",
     "ts_query_cursor_match_limit",
@@ -91,6 +91,16 @@ pub struct TSParseOptions {
     pub progress_callback:
         ::core::option::Option<unsafe extern "C" fn(state: *mut TSParseState) -> bool>,
 }
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct TSParseStats {
+    pub reused_node_count: u32,
+    pub lexed_token_count: u32,
+    pub error_recovery_count: u32,
+}
+pub const TSErrorRecoveryModeDefault: TSErrorRecoveryMode = 0;
+pub const TSErrorRecoveryModeConservative: TSErrorRecoveryMode = 1;
+pub type TSErrorRecoveryMode = ::core::ffi::c_uint;
 pub const TSLogTypeParse: TSLogType = 0;
 pub const TSLogTypeLex: TSLogType = 1;
 pub type TSLogType = ::core::ffi::c_uint;
@@ -161,6 +171,17 @@ pub struct TSQueryPredicateStep {
     pub type_: TSQueryPredicateStepType,
     pub value_id: u32,
 }
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct TSQueryPatternStep {
+    pub symbol: TSSymbol,
+    pub field: TSFieldId,
+    pub depth: u16,
+    pub capture_ids: [u16; 3usize],
+    pub capture_count: u8,
+    pub is_named: bool,
+    pub is_optional: bool,
+}
 pub const TSQueryErrorNone: TSQueryError = 0;
 pub const TSQueryErrorSyntax: TSQueryError = 1;
 pub const TSQueryErrorNodeType: TSQueryError = 2;
@@ -270,6 +291,30 @@ unsafe extern "C" {
     #[doc = " Set the file descriptor to which the parser should write debugging graphs\n during parsing. The graphs are formatted in the DOT language. You may want\n to pipe these graphs directly to a `dot(1)` process in order to generate\n SVG output. You can turn off this logging by passing a negative number."]
     pub fn ts_parser_print_dot_graphs(self_: *mut TSParser, fd: ::core::ffi::c_int);
 }
+unsafe extern "C" {
+    #[doc = " Get statistics about the parser's most recent parse.\n\n The counters are reset whenever a new parse begins, and accumulate across\n calls that resume a parse which was halted by the progress callback:\n 1. `reused_node_count`: The number of visible nodes reused from the old tree.\n 2. `lexed_token_count`: The number of tokens produced by the lexer.\n 3. `error_recovery_count`: The number of times the parser had to handle a\n    syntax error."]
+    pub fn ts_parser_parse_stats(self_: *const TSParser) -> TSParseStats;
+}
+unsafe extern "C" {
+    #[doc = " Set whether the parser may insert `MISSING` nodes when recovering from\n syntax errors.\n\n 1. `TSErrorRecoveryModeDefault`: The parser may insert zero-width `MISSING`\n    nodes for tokens that would make the input valid, in addition to skipping\n    invalid text by wrapping it in `ERROR` nodes.\n 2. `TSErrorRecoveryModeConservative`: The parser never inserts `MISSING`\n    nodes. This yields larger `ERROR` nodes, but no structure that depends on\n    tokens that aren't actually present in the input.\n\n This is the only difference between the modes. The rest of error recovery,\n such as skipping tokens and choosing between recovery strategies by their\n cost, works the same way in both.\n\n The mode takes effect for the next call to one of the parsing functions."]
+    pub fn ts_parser_set_error_recovery_mode(self_: *mut TSParser, mode: TSErrorRecoveryMode);
+}
+unsafe extern "C" {
+    #[doc = " Get the parser's current error recovery mode."]
+    pub fn ts_parser_error_recovery_mode(self_: *const TSParser) -> TSErrorRecoveryMode;
+}
+unsafe extern "C" {
+    #[doc = " Set the symbols of extra nodes, like comments, that the trees produced by\n the parser should not report as extra.\n\n This override only affects reporting. The parser still shifts these nodes as\n extras, because extras are compiled into the language's parse table, so the\n shape of the tree is unchanged: the nodes can still appear anywhere, and are\n never stored in a field. Only the result of [`ts_node_is_extra`] changes for\n them, so that code which skips extra nodes treats them like the rest of the\n tree.\n\n The parser does *not* take ownership of the symbols; it copies them. Pass a\n `count` of zero to remove the override. The override takes effect for the\n next call to one of the parsing functions."]
+    pub fn ts_parser_set_extra_override(
+        self_: *mut TSParser,
+        symbols: *const TSSymbol,
+        count: u32,
+    );
+}
+unsafe extern "C" {
+    #[doc = " Get the symbols of extra nodes that the parser's trees do not report as\n extra, in ascending order.\n\n The returned pointer is owned by the parser. The caller should not free it\n or write to it. The length of the array will be written to the given\n `count` pointer."]
+    pub fn ts_parser_extra_override(self_: *const TSParser, count: *mut u32) -> *const TSSymbol;
+}
 unsafe extern "C" {
     #[doc = " Create a shallow copy of the syntax tree. This is very fast.\n\n You need to copy a syntax tree in order to use it on more than one thread at\n a time, as syntax trees are not thread safe."]
     pub fn ts_tree_copy(self_: *const TSTree) -> *mut TSTree;
@@ -367,7 +412,7 @@ unsafe extern "C" {
     pub fn ts_node_is_missing(self_: TSNode) -> bool;
 }
 unsafe extern "C" {
-    #[doc = " Check if the node is *extra*. Extra nodes represent things like comments,\n which are not required by the grammar, but can appear anywhere."]
+    #[doc = " Check if the node is *extra*. Extra nodes represent things like comments,\n which are not required by the grammar, but can appear anywhere.\n\n Nodes whose symbols were passed to [`ts_parser_set_extra_override`] when the\n tree was parsed are not reported as extra."]
     pub fn ts_node_is_extra(self_: TSNode) -> bool;
 }
 unsafe extern "C" {
@@ -382,6 +427,10 @@ unsafe extern "C" {
     #[doc = " Check if the node is a syntax error."]
     pub fn ts_node_is_error(self_: TSNode) -> bool;
 }
+unsafe extern "C" {
+    #[doc = " Get the cost that the parser assigned to the syntax errors within the node.\n\n The cost grows with the number of characters, lines, and nodes that were\n skipped, and with the number of missing nodes that were inserted, in order\n to recover from errors. It is zero if the node doesn't contain any errors.\n This can be used to rank errors by how much of the input they affect."]
+    pub fn ts_node_error_cost(self_: TSNode) -> u32;
+}
 unsafe extern "C" {
     #[doc = " Get this node's parse state."]
     pub fn ts_node_parse_state(self_: TSNode) -> TSStateId;
@@ -466,6 +515,10 @@ unsafe extern "C" {
     #[doc = " Get the node's number of descendants, including one for the node itself."]
     pub fn ts_node_descendant_count(self_: TSNode) -> u32;
 }
+unsafe extern "C" {
+    #[doc = " Get an identifier for the subtree that backs the node, which stays the same\n when that subtree is reused by an incremental parse, even if the node's\n position or parent changes.\n\n Small leaf nodes are stored inline within their parent, and have no such\n identifier. For these, this returns `NULL`."]
+    pub fn ts_node_stable_id(self_: TSNode) -> *const ::core::ffi::c_void;
+}
 unsafe extern "C" {
     #[doc = " Get the smallest node within this node that spans the given range of bytes\n or (row, column) positions."]
     pub fn ts_node_descendant_for_byte_range(self_: TSNode, start: u32, end: u32) -> TSNode;
@@ -600,6 +653,10 @@ unsafe extern "C" {
     #[doc = " Create a copy of a query."]
     pub fn ts_query_copy(self_: *const TSQuery) -> *mut TSQuery;
 }
+unsafe extern "C" {
+    #[doc = " Get the language that the query was created for."]
+    pub fn ts_query_language(self_: *const TSQuery) -> *const TSLanguage;
+}
 unsafe extern "C" {
     #[doc = " Get the number of patterns, captures, or string literals in the query."]
     pub fn ts_query_pattern_count(self_: *const TSQuery) -> u32;
@@ -635,6 +692,19 @@ unsafe extern "C" {
 unsafe extern "C" {
     pub fn ts_query_is_pattern_guaranteed_at_step(self_: *const TSQuery, byte_offset: u32) -> bool;
 }
+unsafe extern "C" {
+    #[doc = " Check if the query could be affected by anonymous nodes, either because one\n of its patterns can match an anonymous node, or because it uses an anchor or\n quantifier whose behavior depends on anonymous siblings.\n\n Queries for which this returns `false` can skip anonymous nodes entirely.\n See [`ts_query_cursor_set_skip_anonymous_nodes`]."]
+    pub fn ts_query_matches_anonymous_nodes(self_: *const TSQuery) -> bool;
+}
+unsafe extern "C" {
+    #[doc = " Get the node steps of a given pattern in the query.\n\n Each node in the pattern's S-expression corresponds to one step, in the\n order in which the nodes appear. The `symbol` of a wildcard step is zero,\n and `is_optional` is set for steps that are part of an alternation or a\n quantifier that allows them to be skipped.\n\n Returns the number of steps in the pattern, and writes up to `length` of\n them into the `steps` buffer, which may be `NULL`."]
+    pub fn ts_query_steps_for_pattern(
+        self_: *const TSQuery,
+        pattern_index: u32,
+        steps: *mut TSQueryPatternStep,
+        length: u32,
+    ) -> u32;
+}
 unsafe extern "C" {
     #[doc = " Get the name and length of one of the query's captures, or one of the\n query's string literals. Each capture and string is associated with a\n numeric id based on the order that it appeared in the query's source."]
     pub fn ts_query_capture_name_for_id(
@@ -753,6 +823,10 @@ unsafe extern "C" {
     #[doc = " Set the maximum start depth for a query cursor.\n\n This prevents cursors from exploring children nodes at a certain depth.\n Note if a pattern includes many children, then they will still be checked.\n\n The zero max start depth value can be used as a special behavior and\n it helps to destructure a subtree by staying on a node and using captures\n for interested parts. Note that the zero max start depth only limits a search\n depth for a pattern's root node but other nodes that are parts of the pattern\n may be searched at any depth as defined by the pattern structure.\n\n Set to `UINT32_MAX` to remove the maximum start depth."]
     pub fn ts_query_cursor_set_max_start_depth(self_: *mut TSQueryCursor, max_start_depth: u32);
 }
+unsafe extern "C" {
+    #[doc = " Set whether a query cursor should skip over anonymous leaf nodes.\n\n This is a hint: it only takes effect for queries that can't be affected by\n anonymous nodes, as reported by [`ts_query_matches_anonymous_nodes`], so the\n matches are the same either way. For such queries, skipping the anonymous\n tokens can make the traversal substantially faster on token-dense trees."]
+    pub fn ts_query_cursor_set_skip_anonymous_nodes(self_: *mut TSQueryCursor, skip: bool);
+}
 unsafe extern "C" {
     #[doc = " Get another reference to the given language."]
     pub fn ts_language_copy(self_: *const TSLanguage) -> *const TSLanguage;
@@ -801,6 +875,13 @@ unsafe extern "C" {
     #[doc = " Get a list of all supertype symbols for the language."]
     pub fn ts_language_supertypes(self_: *const TSLanguage, length: *mut u32) -> *const TSSymbol;
 }
+unsafe extern "C" {
+    #[doc = " Get a list of the symbols for the language's external tokens.\n\n The symbols are ordered by their index in the grammar's `externals` array,\n which is also the order of the `valid_symbols` array that is passed to the\n external scanner."]
+    pub fn ts_language_external_tokens(
+        self_: *const TSLanguage,
+        length: *mut u32,
+    ) -> *const TSSymbol;
+}
 unsafe extern "C" {
     #[doc = " Get a list of all subtype symbol ids for a given supertype symbol.\n\n See [`ts_language_supertypes`] for fetching all supertype symbols."]
     pub fn ts_language_subtypes(
@@ -836,6 +917,18 @@ unsafe extern "C" {
         symbol: TSSymbol,
     ) -> TSStateId;
 }
+unsafe extern "C" {
+    #[doc = " Run the language's generated lexer on the given UTF8 string, as the parser\n would when looking for a token in the given parse state.\n\n If a token is found, this returns `true` and writes the token's symbol and\n byte range to the given pointers. The symbol is the token's grammar symbol,\n as returned by [`ts_node_grammar_symbol`], and is `0` at the end of the\n string. Any leading whitespace and other skipped characters are excluded\n from the range. The language's external scanner,\n if it has one, is never called. This always returns `false` for Wasm\n languages.\n\n This is useful for testing a grammar's tokenization independently of\n parsing."]
+    pub fn ts_language_lex(
+        self_: *const TSLanguage,
+        state: TSStateId,
+        string: *const ::core::ffi::c_char,
+        length: u32,
+        symbol: *mut TSSymbol,
+        start_byte: *mut u32,
+        end_byte: *mut u32,
+    ) -> bool;
+}
 unsafe extern "C" {
     #[doc = " Get the name of this language. This returns `NULL` in older parsers."]
     pub fn ts_language_name(self_: *const TSLanguage) -> *const ::core::ffi::c_char;
@@ -21,7 +21,14 @@ unsafe extern "C" {
     pub(crate) fn _ts_dup(handle: *mut std::os::raw::c_void) -> std::os::raw::c_int;
 }
 
-use core::{marker::PhantomData, mem::ManuallyDrop, ptr::NonNull, str};
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+use core::{
+    marker::PhantomData,
+    mem::{self, ManuallyDrop},
+    ptr::NonNull,
+    str,
+};
 
 use crate::{
     Language, LookaheadIterator, Node, ParseState, Parser, Query, QueryCursor, QueryCursorState,
@@ -96,13 +103,17 @@ impl Tree {
     /// `ptr` must be non-null.
     #[must_use]
     pub const unsafe fn from_raw(ptr: *mut TSTree) -> Self {
-        Self(unsafe { NonNull::new_unchecked(ptr) })
+        Self(unsafe { NonNull::new_unchecked(ptr) }, None)
     }
 
     /// Consumes the [`Tree`], returning a raw pointer to the underlying C structure.
+    ///
+    /// Any source text owned by the tree is dropped.
     #[must_use]
     pub fn into_raw(self) -> *mut TSTree {
-        ManuallyDrop::new(self).0.as_ptr()
+        let mut tree = ManuallyDrop::new(self);
+        drop(tree.1.take());
+        tree.0.as_ptr()
     }
 }
 
@@ -169,13 +180,16 @@ impl QueryCursor {
     pub const unsafe fn from_raw(ptr: *mut TSQueryCursor) -> Self {
         Self {
             ptr: unsafe { NonNull::new_unchecked(ptr) },
+            byte_ranges: Vec::new(),
         }
     }
 
     /// Consumes the [`QueryCursor`], returning a raw pointer to the underlying C structure.
     #[must_use]
     pub fn into_raw(self) -> *mut TSQueryCursor {
-        ManuallyDrop::new(self).ptr.as_ptr()
+        let mut cursor = ManuallyDrop::new(self);
+        drop(mem::take(&mut cursor.byte_ranges));
+        cursor.ptr.as_ptr()
     }
 }
 
@@ -3,12 +3,16 @@
 #![cfg_attr(docsrs, feature(doc_cfg))]
 
 pub mod ffi;
+mod log_event;
+#[cfg(feature = "std")]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+pub mod test_utils;
 mod util;
 
 #[cfg(not(feature = "std"))]
 extern crate alloc;
 #[cfg(not(feature = "std"))]
-use alloc::{boxed::Box, format, string::String, string::ToString, vec::Vec};
+use alloc::{boxed::Box, format, string::String, string::ToString, sync::Arc, vec, vec::Vec};
 use core::{
     ffi::{CStr, c_char, c_void},
     fmt::{self, Write},
@@ -26,7 +30,10 @@ use std::error;
 use std::os::fd::AsRawFd;
 #[cfg(all(windows, feature = "std"))]
 use std::os::windows::io::AsRawHandle;
+#[cfg(feature = "std")]
+use std::sync::Arc;
 
+pub use log_event::ParseLogEvent;
 pub use streaming_iterator::{StreamingIterator, StreamingIteratorMut};
 use tree_sitter_language::LanguageFn;
 
@@ -36,6 +43,12 @@ mod wasm_language;
 #[cfg_attr(docsrs, doc(cfg(feature = "wasm")))]
 pub use wasm_language::*;
 
+#[cfg(feature = "std")]
+mod parser_pool;
+#[cfg(feature = "std")]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+pub use parser_pool::{ParserPool, PooledParser};
+
 /// The latest ABI version that is supported by the current version of the
 /// library.
 ///
@@ -88,7 +101,7 @@ impl From<ffi::TSLanguageMetadata> for LanguageMetadata {
 
 /// A tree that represents the syntactic structure of a source code file.
 #[doc(alias = "TSTree")]
-pub struct Tree(NonNull<ffi::TSTree>);
+pub struct Tree(NonNull<ffi::TSTree>, Option<Arc<[u8]>>);
 
 /// A position in a multi-line text document, in terms of rows and columns.
 ///
@@ -168,6 +181,36 @@ impl InputEdit {
 #[repr(transparent)]
 pub struct Node<'tree>(ffi::TSNode, PhantomData<&'tree ()>);
 
+/// An edge between a node and one of its children, as returned by [`Tree::to_edges`].
+///
+/// Nodes are identified by their [`Node::id`]s.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TreeEdge {
+    pub parent_id: usize,
+    pub parent_kind: &'static str,
+    pub parent_range: Range,
+    pub child_id: usize,
+    pub child_kind: &'static str,
+    pub child_range: Range,
+    pub field_name: Option<&'static str>,
+}
+
+/// A set of node kinds, represented by their numerical ids, for quickly checking
+/// whether a node has one of several kinds.
+///
+/// Create one with [`Language::node_kind_set`] before walking a tree, so that
+/// each node can be checked without comparing strings.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct NodeKindSet {
+    words: Box<[u64]>,
+    // The id of `ERROR` nodes is the maximum id, far beyond the grammar's own node kinds, so
+    // it's tracked separately.
+    contains_error: bool,
+}
+
+/// The numerical id of `ERROR` nodes.
+const ERROR_NODE_KIND_ID: u16 = u16::MAX;
+
 /// A stateful object that this is used to produce a [`Tree`] based on some
 /// source code.
 #[doc(alias = "TSParser")]
@@ -178,6 +221,7 @@ pub struct Parser(NonNull<ffi::TSParser>);
 #[doc(alias = "TSLookaheadIterator")]
 pub struct LookaheadIterator(NonNull<ffi::TSLookaheadIterator>);
 struct LookaheadNamesIterator<'a>(&'a mut LookaheadIterator);
+struct LookaheadSymbolsIterator<'a>(&'a mut LookaheadIterator);
 
 /// A stateful object that is passed into a [`ParseProgressCallback`]
 /// to pass in the current state of the parser.
@@ -206,6 +250,21 @@ impl QueryCursorState {
     }
 }
 
+/// Statistics about a single parse, as returned by [`Parser::parse_with_stats`].
+#[cfg(feature = "std")]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ParseStats {
+    /// The number of visible nodes that were reused from the old tree.
+    pub reused_node_count: usize,
+    /// The number of tokens produced by the lexer.
+    pub lexed_token_count: usize,
+    /// The number of times the parser had to handle a syntax error.
+    pub error_recovery_count: usize,
+    /// The time spent parsing.
+    pub duration: std::time::Duration,
+}
+
 #[derive(Default)]
 pub struct ParseOptions<'a> {
     pub progress_callback: Option<ParseProgressCallback<'a>>,
@@ -292,12 +351,38 @@ impl Drop for QueryCursorOptionsDrop {
 }
 
 /// A type of log message.
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum LogType {
     Parse,
     Lex,
 }
 
+/// Whether a [`Parser`] may insert `MISSING` nodes when recovering from syntax errors.
+///
+/// This is the only difference between the modes. The rest of error recovery, such as
+/// skipping tokens and choosing between recovery strategies by their cost, works the same
+/// way in both.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ErrorRecoveryMode {
+    /// Insert zero-width `MISSING` nodes for tokens that would make the input valid, in
+    /// addition to wrapping invalid text in `ERROR` nodes.
+    #[default]
+    Default,
+    /// Never insert `MISSING` nodes. This produces larger `ERROR` nodes, but no structure
+    /// that depends on tokens which aren't present in the input.
+    Conservative,
+}
+
+/// Whether [`Node::for_each_descendant`] should visit the descendants of the
+/// node that was just passed to its callback.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Descend {
+    /// Visit the node's children, and their descendants.
+    Children,
+    /// Skip the node's subtree, and continue with its next sibling.
+    Skip,
+}
+
 type FieldId = NonZeroU16;
 
 /// A callback that receives log messages during parsing.
@@ -319,6 +404,16 @@ pub trait Decode {
 #[doc(alias = "TSTreeCursor")]
 pub struct TreeCursor<'tree>(ffi::TSTreeCursor, PhantomData<&'tree ()>);
 
+/// An index over the named nodes of a syntax [`Tree`], for answering many byte
+/// offset lookups quickly.
+///
+/// Building the index walks the whole tree once. Each lookup is then a binary
+/// search, rather than a descent from the root node.
+#[derive(Clone, Debug)]
+pub struct NodeIndex<'tree> {
+    boundaries: Vec<(usize, Option<Node<'tree>>)>,
+}
+
 /// A set of patterns that match nodes in a syntax tree.
 #[doc(alias = "TSQuery")]
 #[derive(Debug)]
@@ -336,6 +431,30 @@ pub struct Query {
     general_predicates: Box<[Box<[QueryPredicate]>]>,
 }
 
+/// The structural shape of a pattern within a [`Query`], as returned by
+/// [`Query::describe_pattern`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PatternShape {
+    /// The top-level nodes of the pattern. Most patterns have a single root.
+    pub roots: Vec<PatternNode>,
+}
+
+/// A node within a [`PatternShape`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PatternNode {
+    /// The kind of node to match, or `None` for a wildcard.
+    pub kind: Option<&'static str>,
+    pub is_named: bool,
+    /// The field that the node must be stored in within its parent.
+    pub field: Option<&'static str>,
+    /// The names of the captures applied to the node.
+    pub captures: Vec<&'static str>,
+    /// Whether the node can be skipped, because it's quantified with `?` or
+    /// `*`, or is one branch of an alternation.
+    pub is_optional: bool,
+    pub children: Vec<Self>,
+}
+
 /// A quantifier for captures
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
 pub enum CaptureQuantifier {
@@ -363,6 +482,68 @@ impl From<ffi::TSQuantifier> for CaptureQuantifier {
 #[doc(alias = "TSQueryCursor")]
 pub struct QueryCursor {
     ptr: NonNull<ffi::TSQueryCursor>,
+    byte_ranges: Vec<ops::Range<usize>>,
+}
+
+/// A stateful object for executing queries on several syntax trees at once,
+/// such as a document's host language tree and the trees of its injected
+/// languages.
+pub struct LayeredQueryCursor {
+    cursor: QueryCursor,
+}
+
+/// One of the syntax trees searched by a [`LayeredQueryCursor`], along with
+/// the query to run on it.
+#[derive(Clone, Copy)]
+pub struct QueryLayer<'query, 'tree> {
+    pub query: &'query Query,
+    pub node: Node<'tree>,
+    /// The byte offset of this layer's text within the full document. This is
+    /// zero for trees that were parsed from the full document using included
+    /// ranges.
+    pub byte_offset: usize,
+}
+
+/// A capture found by a [`LayeredQueryCursor`].
+#[derive(Clone, Debug)]
+pub struct LayeredQueryCapture<'tree> {
+    /// The index of the [`QueryLayer`] that this capture came from.
+    pub layer_index: usize,
+    pub pattern_index: usize,
+    pub capture: QueryCapture<'tree>,
+    /// The byte range of the captured node within the full document.
+    pub byte_range: ops::Range<usize>,
+}
+
+/// A [`Query`] whose matches are remembered for each byte range that it has
+/// been run on, so that repeatedly querying an unchanged document is cheap.
+pub struct CachedQuery {
+    query: Query,
+    cursor: QueryCursor,
+    entries: Vec<CachedQueryEntry>,
+}
+
+struct CachedQueryEntry {
+    byte_range: ops::Range<usize>,
+    /// The part of the document that the entry depends on: its byte range, and
+    /// the ranges of all of its captured nodes.
+    extent: ops::Range<usize>,
+    matches: Vec<CachedQueryMatch>,
+}
+
+/// A match stored by a [`CachedQuery`]. Unlike a [`QueryMatch`], this does not
+/// borrow the tree, so it describes its captures by their ranges.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CachedQueryMatch {
+    pub pattern_index: usize,
+    pub captures: Vec<CachedQueryCapture>,
+}
+
+/// A capture within a [`CachedQueryMatch`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CachedQueryCapture {
+    pub index: u32,
+    pub range: Range,
 }
 
 /// A key-value pair associated with a particular pattern in a [`Query`].
@@ -421,6 +602,21 @@ pub struct QueryCaptures<'query, 'tree, T: TextProvider<I>, I: AsRef<[u8]>> {
     _phantom: PhantomData<(&'tree (), I)>,
 }
 
+/// A sequence of [`QueryMatch`]es that satisfy a custom predicate, as returned by
+/// [`QueryMatches::filter_matches`].
+pub struct FilteredQueryMatches<'query, 'tree, T: TextProvider<I>, I: AsRef<[u8]>, F> {
+    matches: QueryMatches<'query, 'tree, T, I>,
+    predicate: F,
+}
+
+/// A source of the text of [`Node`]s, used to evaluate a [`Query`]'s text predicates.
+///
+/// This is implemented for `&[u8]` and for any closure of the form
+/// `FnMut(Node) -> impl Iterator<Item = impl AsRef<[u8]>>`. The closure form lets the text
+/// come from a non-contiguous buffer such as a rope, by returning the chunks that make up
+/// the node's byte range. The chunks are only requested for nodes whose captures take part
+/// in a text predicate, and the resulting captures are produced in the same order as with
+/// a contiguous buffer.
 pub trait TextProvider<I>
 where
     I: AsRef<[u8]>,
@@ -448,9 +644,36 @@ pub enum LanguageError {
     Wasm,
 }
 
+/// An error that occurred in [`Parser::parse_with_deadline`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParseError {
+    /// The deadline passed before parsing finished. Parsing the same text again with the same
+    /// parser resumes where it left off.
+    Timeout,
+    /// The parser has not yet had a language assigned with [`Parser::set_language`].
+    NoLanguage,
+    /// The parser stopped before it finished, for a reason other than the deadline passing.
+    Aborted,
+}
+
 /// An error that occurred in [`Parser::set_included_ranges`].
 #[derive(Debug, PartialEq, Eq)]
-pub struct IncludedRangesError(pub usize);
+pub struct IncludedRangesError {
+    /// The index of the first invalid range in the passed slice.
+    pub index: usize,
+    pub kind: IncludedRangesErrorKind,
+}
+
+/// The reason that a range passed to [`Parser::set_included_ranges`] is invalid.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum IncludedRangesErrorKind {
+    /// The range's end byte is before its start byte.
+    EndBeforeStart,
+    /// The range starts before the previous range starts.
+    OutOfOrder,
+    /// The range starts before the previous range ends.
+    Overlap,
+}
 
 /// An error that occurred when trying to create a [`Query`].
 #[derive(Debug, PartialEq, Eq)]
@@ -477,13 +700,15 @@ pub enum QueryErrorKind {
 /// The first item is the capture index
 /// The next is capture specific, depending on what item is expected
 /// The first bool is if the capture is positive
-/// The last item is a bool signifying whether or not it's meant to match
+/// The next item is a bool signifying whether or not it's meant to match
 /// any or all captures
+/// For the `eq?` predicates, the last bool is whether the comparison ignores case
 enum TextPredicateCapture {
-    EqString(u32, Box<str>, bool, bool),
-    EqCapture(u32, u32, bool, bool),
+    EqString(u32, Box<str>, bool, bool, bool),
+    EqCapture(u32, u32, bool, bool, bool),
     MatchString(u32, regex::bytes::Regex, bool, bool),
     AnyString(u32, Box<[Box<str>]>, bool),
+    NthChild(u32, i64),
 }
 
 // TODO: Remove this struct at some point. If `core::str::lossy::Utf8Lossy`
@@ -544,6 +769,9 @@ impl Language {
     }
 
     /// Get a list of all supertype symbols for the language.
+    ///
+    /// These are the hidden rules that the grammar lists in its `supertypes` array. Use
+    /// [`Language::subtypes_for_supertype`] to get the symbols that each of them groups.
     #[doc(alias = "ts_language_supertypes")]
     #[must_use]
     pub fn supertypes(&self) -> &[u16] {
@@ -558,7 +786,40 @@ impl Language {
         }
     }
 
+    /// Get a list of the symbols for the language's external tokens, in the
+    /// order that they appear in the grammar's `externals` array.
+    ///
+    /// The index of each symbol in this list is the index of the corresponding
+    /// entry in the `valid_symbols` array that is passed to the external scanner.
+    #[doc(alias = "ts_language_external_tokens")]
+    #[must_use]
+    pub fn external_tokens(&self) -> &[u16] {
+        let mut length = 0u32;
+        unsafe {
+            let ptr = ffi::ts_language_external_tokens(self.0, core::ptr::addr_of_mut!(length));
+            if length == 0 {
+                &[]
+            } else {
+                slice::from_raw_parts(ptr.cast_mut(), length as usize)
+            }
+        }
+    }
+
+    /// Get the names of the language's external tokens, in the order that they
+    /// appear in the grammar's `externals` array.
+    #[must_use]
+    pub fn external_token_names(&self) -> Vec<&'static str> {
+        self.external_tokens()
+            .iter()
+            .map(|symbol| self.node_kind_for_id(*symbol).unwrap_or_default())
+            .collect()
+    }
+
     /// Get a list of all subtype symbols for a given supertype symbol.
+    ///
+    /// The subtypes are the members of the supertype rule's choice, and can include other
+    /// supertypes. If the given symbol isn't a supertype, or the language was generated with an
+    /// ABI version older than 15, the list is empty.
     #[doc(alias = "ts_language_supertype_map")]
     #[must_use]
     pub fn subtypes_for_supertype(&self, supertype: u16) -> &[u16] {
@@ -595,6 +856,30 @@ impl Language {
         }
     }
 
+    /// Look up the numerical ids of the given node kinds, so that nodes can be
+    /// checked against them with [`NodeKindSet::contains`].
+    ///
+    /// Kinds that don't exist in this language are ignored. The `ERROR` kind can be included
+    /// to find the nodes where the parser recovered from syntax errors.
+    #[must_use]
+    pub fn node_kind_set(&self, kinds: &[&str], named: bool) -> NodeKindSet {
+        let count = self.node_kind_count();
+        let mut words = vec![0u64; count.div_ceil(64)];
+        let mut contains_error = false;
+        for kind in kinds {
+            let id = self.id_for_node_kind(kind, named);
+            if id == ERROR_NODE_KIND_ID {
+                contains_error = true;
+            } else if id != 0 && usize::from(id) < count {
+                words[usize::from(id) / 64] |= 1 << (id % 64);
+            }
+        }
+        NodeKindSet {
+            words: words.into_boxed_slice(),
+            contains_error,
+        }
+    }
+
     /// Check if the node type for the given numerical id is named (as opposed
     /// to an anonymous node type).
     #[must_use]
@@ -616,6 +901,9 @@ impl Language {
     }
 
     /// Get the number of distinct field names in this language.
+    ///
+    /// Field ids range from `1` to this count, inclusive. The id `0` is used to
+    /// indicate the absence of a field.
     #[doc(alias = "ts_language_field_count")]
     #[must_use]
     pub fn field_count(&self) -> usize {
@@ -623,6 +911,9 @@ impl Language {
     }
 
     /// Get the field name for the given numerical id.
+    ///
+    /// Returns `None` for `0`, which means "no field", and for ids that are greater
+    /// than [`field_count`](Language::field_count).
     #[doc(alias = "ts_language_field_name_for_id")]
     #[must_use]
     pub fn field_name_for_id(&self, field_id: u16) -> Option<&'static str> {
@@ -631,6 +922,10 @@ impl Language {
     }
 
     /// Get the numerical id for the given field name.
+    ///
+    /// Looking up the id once and comparing ids is cheaper than comparing field names
+    /// when the same field is checked repeatedly, e.g. with
+    /// [`Node::child_by_field_id`] or [`TreeCursor::field_id`].
     #[doc(alias = "ts_language_field_id_for_name")]
     #[must_use]
     pub fn field_id_for_name(&self, field_name: impl AsRef<[u8]>) -> Option<FieldId> {
@@ -649,14 +944,52 @@ impl Language {
     /// [`lookahead_iterator`](Language::lookahead_iterator) to
     /// generate completion suggestions or valid symbols in error nodes.
     ///
+    /// The symbol should be an id as it appears in the grammar, ignoring aliases, such
+    /// as [`Node::grammar_id`]. Returns 0 if the symbol can't follow the given state, or
+    /// if either of them is not valid for this language.
+    ///
     /// Example:
     /// ```ignore
     /// let state = language.next_state(node.parse_state(), node.grammar_id());
     /// ```
     #[doc(alias = "ts_language_next_state")]
     #[must_use]
-    pub fn next_state(&self, state: u16, id: u16) -> u16 {
-        unsafe { ffi::ts_language_next_state(self.0, state, id) }
+    pub fn next_state(&self, state: u16, symbol: u16) -> u16 {
+        unsafe { ffi::ts_language_next_state(self.0, state, symbol) }
+    }
+
+    /// Run this language's generated lexer on the start of the given text, as the
+    /// parser would when looking for a token in the given parse state.
+    ///
+    /// Returns the grammar id of the token that was found, as with [`Node::grammar_id`],
+    /// along with its byte range, which excludes any leading whitespace. At the end
+    /// of the text, the id is `0`. The external scanner is never called, so
+    /// this is mainly useful for testing a grammar's tokenization on its own.
+    /// Returns `None` if no token matches, or if this is a Wasm language.
+    ///
+    /// Example:
+    /// ```ignore
+    /// let (kind_id, range) = language.lex(1, b"let x = 1;").unwrap();
+    /// assert_eq!(language.node_kind_for_id(kind_id), Some("let"));
+    /// ```
+    #[doc(alias = "ts_language_lex")]
+    #[must_use]
+    pub fn lex(&self, state: u16, text: &[u8]) -> Option<(u16, ops::Range<usize>)> {
+        let mut symbol = 0;
+        let mut start_byte = 0;
+        let mut end_byte = 0;
+        let found = unsafe {
+            ffi::ts_language_lex(
+                self.0,
+                state,
+                text.as_ptr().cast::<c_char>(),
+                text.len() as u32,
+                core::ptr::addr_of_mut!(symbol),
+                core::ptr::addr_of_mut!(start_byte),
+                core::ptr::addr_of_mut!(end_byte),
+            )
+        };
+        found.then_some((symbol, start_byte as usize..end_byte as usize))
     }
 
     /// Create a new lookahead iterator for this language and parse state.
@@ -698,6 +1031,25 @@ impl Drop for Language {
     }
 }
 
+impl NodeKindSet {
+    /// Check if the set contains the node kind with the given numerical id.
+    #[must_use]
+    pub fn contains_id(&self, id: u16) -> bool {
+        if id == ERROR_NODE_KIND_ID {
+            return self.contains_error;
+        }
+        self.words
+            .get(usize::from(id) / 64)
+            .is_some_and(|word| word & (1 << (id % 64)) != 0)
+    }
+
+    /// Check if the given node's kind is in the set.
+    #[must_use]
+    pub fn contains(&self, node: &Node) -> bool {
+        self.contains_id(node.kind_id())
+    }
+}
+
 impl Deref for LanguageRef<'_> {
     type Target = Language;
 
@@ -811,6 +1163,17 @@ impl Parser {
         unsafe { ffi::ts_parser_set_logger(self.0.as_ptr(), c_logger) };
     }
 
+    /// Set a logging callback that receives each of the parser's log messages as a
+    /// [`ParseLogEvent`], rather than as text.
+    ///
+    /// This replaces any logger set with [`Parser::set_logger`]. To remove it, call
+    /// [`Parser::set_logger`] with `None`.
+    pub fn set_structured_logger(&mut self, mut logger: impl FnMut(ParseLogEvent)) {
+        self.set_logger(Some(Box::new(move |log_type, message| {
+            logger(ParseLogEvent::parse(log_type, message));
+        })));
+    }
+
     /// Set the destination to which the parser should write debugging graphs
     /// during parsing. The graphs are formatted in the DOT language. You may
     /// want to pipe these graphs directly to a `dot(1)` process in order to
@@ -850,6 +1213,63 @@ impl Parser {
         unsafe { ffi::ts_parser_print_dot_graphs(self.0.as_ptr(), -1) }
     }
 
+    /// Get the parser's current error recovery mode.
+    #[doc(alias = "ts_parser_error_recovery_mode")]
+    #[must_use]
+    pub fn error_recovery(&self) -> ErrorRecoveryMode {
+        match unsafe { ffi::ts_parser_error_recovery_mode(self.0.as_ptr()) } {
+            ffi::TSErrorRecoveryModeConservative => ErrorRecoveryMode::Conservative,
+            _ => ErrorRecoveryMode::Default,
+        }
+    }
+
+    /// Set whether the parser may insert `MISSING` nodes when recovering from syntax errors.
+    ///
+    /// This doesn't change any other part of error recovery. The mode takes effect for the
+    /// next parse. See [`ErrorRecoveryMode`] for the trade-offs.
+    #[doc(alias = "ts_parser_set_error_recovery_mode")]
+    pub fn set_error_recovery(&mut self, mode: ErrorRecoveryMode) {
+        let mode = match mode {
+            ErrorRecoveryMode::Default => ffi::TSErrorRecoveryModeDefault,
+            ErrorRecoveryMode::Conservative => ffi::TSErrorRecoveryModeConservative,
+        };
+        unsafe { ffi::ts_parser_set_error_recovery_mode(self.0.as_ptr(), mode) }
+    }
+
+    /// Get the kinds of extra nodes that this parser's trees don't report as extra, in
+    /// ascending order.
+    #[doc(alias = "ts_parser_extra_override")]
+    #[must_use]
+    pub fn extra_override(&self) -> Vec<u16> {
+        let mut count = 0u32;
+        unsafe {
+            let ptr =
+                ffi::ts_parser_extra_override(self.0.as_ptr(), core::ptr::addr_of_mut!(count));
+            if ptr.is_null() {
+                return Vec::new();
+            }
+            slice::from_raw_parts(ptr, count as usize).to_vec()
+        }
+    }
+
+    /// Set the kinds of extra nodes, like comments, that this parser's trees shouldn't
+    /// report as extra.
+    ///
+    /// This override only affects reporting. The parser still shifts these nodes as extras,
+    /// because extras are compiled into the language's parse table, so the shape of the tree
+    /// is unchanged: the nodes can still appear anywhere, and are never stored in a field.
+    /// Only what [`Node::is_extra`] returns for them changes, so that code which skips extra
+    /// nodes treats them like the rest of the tree.
+    ///
+    /// The kinds are ids from [`Language::id_for_node_kind`]. The override takes effect for
+    /// the next parse, and an empty slice removes it.
+    #[doc(alias = "ts_parser_set_extra_override")]
+    pub fn set_extra_override(&mut self, kinds: &[u16]) {
+        unsafe {
+            ffi::ts_parser_set_extra_override(self.0.as_ptr(), kinds.as_ptr(), kinds.len() as u32);
+        }
+    }
+
     /// Parse a slice of UTF8 text.
     ///
     /// # Arguments:
@@ -860,6 +1280,9 @@ impl Parser {
     ///
     /// Returns a [`Tree`] if parsing succeeded, or `None` if:
     ///  * The parser has not yet had a language assigned with [`Parser::set_language`]
+    ///
+    /// The text is only borrowed, so the returned tree's [`Tree::source`] is `None`. Use
+    /// [`Parser::parse_owned`] to keep the text together with the tree.
     #[doc(alias = "ts_parser_parse")]
     pub fn parse(&mut self, text: impl AsRef<[u8]>, old_tree: Option<&Tree>) -> Option<Tree> {
         let bytes = text.as_ref();
@@ -877,6 +1300,197 @@ impl Parser {
         )
     }
 
+    /// Parse a UTF8 text buffer, taking ownership of it.
+    ///
+    /// This is the same as [`Parser::parse`], but the returned tree retains the text, which
+    /// can be retrieved with [`Tree::source`].
+    pub fn parse_owned(
+        &mut self,
+        text: impl Into<Vec<u8>>,
+        old_tree: Option<&Tree>,
+    ) -> Option<Tree> {
+        let source = Arc::<[u8]>::from(text.into());
+        let mut tree = self.parse(&source, old_tree)?;
+        tree.1 = Some(source);
+        Some(tree)
+    }
+
+    /// Apply a sequence of edits to a previous syntax tree, and then parse the edited
+    /// text once.
+    ///
+    /// The edits are applied in order, as if by calling [`Tree::edit`] for each one. This
+    /// means that the positions in each edit are relative to the text as it was *after*
+    /// the preceding edits. The given `old_tree` is not modified.
+    ///
+    /// Returns `None` in the same situations as [`Parser::parse`].
+    pub fn reparse_batch(
+        &mut self,
+        old_tree: &Tree,
+        edits: &[InputEdit],
+        new_text: impl AsRef<[u8]>,
+    ) -> Option<Tree> {
+        let mut tree = old_tree.clone();
+        for edit in edits {
+            tree.edit(edit);
+        }
+        self.parse(new_text, Some(&tree))
+    }
+
+    /// Parse a slice of UTF8 text, and collect statistics about the parse.
+    ///
+    /// This is the same as [`Parser::parse`], but also returns [`ParseStats`] which can be
+    /// used to understand the performance of a parse, such as how much of the old tree was
+    /// reused during an incremental parse.
+    #[doc(alias = "ts_parser_parse_stats")]
+    #[cfg(feature = "std")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+    pub fn parse_with_stats(
+        &mut self,
+        text: impl AsRef<[u8]>,
+        old_tree: Option<&Tree>,
+    ) -> (Option<Tree>, ParseStats) {
+        let start_time = std::time::Instant::now();
+        let tree = self.parse(text, old_tree);
+        let duration = start_time.elapsed();
+        let stats = unsafe { ffi::ts_parser_parse_stats(self.0.as_ptr()) };
+        (
+            tree,
+            ParseStats {
+                reused_node_count: stats.reused_node_count as usize,
+                lexed_token_count: stats.lexed_token_count as usize,
+                error_recovery_count: stats.error_recovery_count as usize,
+                duration,
+            },
+        )
+    }
+
+    /// Parse a slice of UTF8 text, stopping if it takes longer than a given deadline.
+    ///
+    /// This is the same as [`Parser::parse`], but returns [`ParseError::Timeout`] if the
+    /// deadline passes first. In that case, the parser keeps its partial state, so calling this
+    /// again with the same text and `old_tree` resumes the parse rather than starting it over.
+    /// Call [`Parser::reset`] to start over instead.
+    #[cfg(feature = "std")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+    pub fn parse_with_deadline(
+        &mut self,
+        text: impl AsRef<[u8]>,
+        old_tree: Option<&Tree>,
+        deadline: std::time::Instant,
+    ) -> Result<Tree, ParseError> {
+        if self.language().is_none() {
+            return Err(ParseError::NoLanguage);
+        }
+
+        let bytes = text.as_ref();
+        let len = bytes.len();
+        let mut timed_out = false;
+        let mut progress = |_: &ParseState| {
+            if std::time::Instant::now() >= deadline {
+                timed_out = true;
+                ControlFlow::Break(())
+            } else {
+                ControlFlow::Continue(())
+            }
+        };
+        let tree = self.parse_with_options(
+            &mut |i, _| {
+                if i < len {
+                    &bytes[i..]
+                } else {
+                    Default::default()
+                }
+            },
+            old_tree,
+            Some(ParseOptions::new().progress_callback(&mut progress)),
+        );
+        tree.ok_or(if timed_out {
+            ParseError::Timeout
+        } else {
+            ParseError::Aborted
+        })
+    }
+
+    /// Parse text from a reader that can seek, such as a file, without first reading all of it
+    /// into memory.
+    ///
+    /// The input is read in chunks as the parser asks for it, seeking to each requested offset.
+    /// The most recently read chunk is kept, so that repeated reads near the same offset don't
+    /// have to go back to the reader.
+    ///
+    /// Returns an error if the reader fails, and otherwise the same result as [`Parser::parse`].
+    #[cfg(feature = "std")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+    pub fn parse_reader<R: std::io::Read + std::io::Seek>(
+        &mut self,
+        mut reader: R,
+        old_tree: Option<&Tree>,
+    ) -> std::io::Result<Option<Tree>> {
+        use std::io::{Read, Seek, SeekFrom};
+
+        const CHUNK_SIZE: u64 = 64 * 1024;
+
+        /// The part of a chunk of input that starts at the requested offset.
+        struct ReaderChunk(Arc<[u8]>, usize);
+
+        impl AsRef<[u8]> for ReaderChunk {
+            fn as_ref(&self) -> &[u8] {
+                &self.0[self.1..]
+            }
+        }
+
+        // The reader's position is tracked, so that reading sequentially doesn't need to seek.
+        fn read_chunk<R: Read + Seek>(
+            reader: &mut R,
+            position: &mut Option<usize>,
+            offset: usize,
+        ) -> std::io::Result<Vec<u8>> {
+            if *position != Some(offset) {
+                *position = None;
+                reader.seek(SeekFrom::Start(offset as u64))?;
+            }
+            let mut buffer = Vec::new();
+            reader.by_ref().take(CHUNK_SIZE).read_to_end(&mut buffer)?;
+            *position = Some(offset + buffer.len());
+            Ok(buffer)
+        }
+
+        let mut chunk = Arc::<[u8]>::from([]);
+        let mut chunk_start = 0;
+        let mut position = None;
+        let mut error = None;
+        let tree = self.parse_with_options(
+            &mut |offset, _| {
+                let in_chunk = |chunk: &[u8], chunk_start| {
+                    offset >= chunk_start && offset < chunk_start + chunk.len()
+                };
+                if error.is_none() && !in_chunk(&chunk, chunk_start) {
+                    match read_chunk(&mut reader, &mut position, offset) {
+                        // An empty chunk marks the end of the input. The previous chunk is kept,
+                        // since the parser may still go back to it.
+                        Ok(bytes) if bytes.is_empty() => {}
+                        Ok(bytes) => {
+                            chunk = bytes.into();
+                            chunk_start = offset;
+                        }
+                        Err(e) => error = Some(e),
+                    }
+                }
+                if error.is_none() && in_chunk(&chunk, chunk_start) {
+                    ReaderChunk(chunk.clone(), offset - chunk_start)
+                } else {
+                    ReaderChunk(Arc::from([]), 0)
+                }
+            },
+            old_tree,
+            None,
+        );
+        match error {
+            Some(error) => Err(error),
+            None => Ok(tree),
+        }
+    }
+
     /// Parse text provided in chunks by a callback.
     ///
     /// # Arguments:
@@ -970,7 +1584,7 @@ impl Parser {
                 parse_options,
             );
 
-            NonNull::new(c_new_tree).map(Tree)
+            NonNull::new(c_new_tree).map(|ptr| Tree(ptr, None))
         }
     }
 
@@ -1099,7 +1713,7 @@ impl Parser {
                 parse_options,
             );
 
-            NonNull::new(c_new_tree).map(Tree)
+            NonNull::new(c_new_tree).map(|ptr| Tree(ptr, None))
         }
     }
 
@@ -1223,7 +1837,7 @@ impl Parser {
                 parse_options,
             );
 
-            NonNull::new(c_new_tree).map(Tree)
+            NonNull::new(c_new_tree).map(|ptr| Tree(ptr, None))
         }
     }
 
@@ -1340,7 +1954,7 @@ impl Parser {
                 parse_options,
             );
 
-            NonNull::new(c_new_tree).map(Tree)
+            NonNull::new(c_new_tree).map(|ptr| Tree(ptr, None))
         }
     }
 
@@ -1369,11 +1983,26 @@ impl Parser {
     /// ```text
     ///     ranges[i].end_byte <= ranges[i + 1].start_byte
     /// ```
-    /// If this requirement is not satisfied, method will return
-    /// [`IncludedRangesError`] error with an offset in the passed ranges
-    /// slice pointing to a first incorrect range.
+    /// Empty ranges are allowed. If this requirement is not satisfied, this method
+    /// returns an [`IncludedRangesError`] with the index of the first incorrect range
+    /// in the passed slice, and the reason that it is incorrect.
     #[doc(alias = "ts_parser_set_included_ranges")]
     pub fn set_included_ranges(&mut self, ranges: &[Range]) -> Result<(), IncludedRangesError> {
+        let mut previous_range: Option<&Range> = None;
+        for (index, range) in ranges.iter().enumerate() {
+            let kind = if range.end_byte < range.start_byte {
+                IncludedRangesErrorKind::EndBeforeStart
+            } else if previous_range.is_some_and(|prev| range.start_byte < prev.start_byte) {
+                IncludedRangesErrorKind::OutOfOrder
+            } else if previous_range.is_some_and(|prev| range.start_byte < prev.end_byte) {
+                IncludedRangesErrorKind::Overlap
+            } else {
+                previous_range = Some(range);
+                continue;
+            };
+            return Err(IncludedRangesError { index, kind });
+        }
+
         let ts_ranges = ranges.iter().copied().map(Into::into).collect::<Vec<_>>();
         let result = unsafe {
             ffi::ts_parser_set_included_ranges(
@@ -1382,19 +2011,8 @@ impl Parser {
                 ts_ranges.len() as u32,
             )
         };
-
-        if result {
-            Ok(())
-        } else {
-            let mut prev_end_byte = 0;
-            for (i, range) in ranges.iter().enumerate() {
-                if range.start_byte < prev_end_byte || range.end_byte < range.start_byte {
-                    return Err(IncludedRangesError(i));
-                }
-                prev_end_byte = range.end_byte;
-            }
-            Err(IncludedRangesError(0))
-        }
+        debug_assert!(result, "included ranges were validated above");
+        Ok(())
     }
 
     /// Get the ranges of text that the parser will include when parsing.
@@ -1466,43 +2084,180 @@ impl Tree {
     ///
     /// You must describe the edit both in terms of byte offsets and in terms of
     /// row/column coordinates.
+    ///
+    /// Because the tree no longer matches its original text, this clears the
+    /// tree's [`source`](Tree::source).
     #[doc(alias = "ts_tree_edit")]
     pub fn edit(&mut self, edit: &InputEdit) {
         let edit = edit.into();
         unsafe { ffi::ts_tree_edit(self.0.as_ptr(), &raw const edit) };
+        self.1 = None;
     }
 
-    /// Create a new [`TreeCursor`] starting from the root of the tree.
+    /// Get the source text that this tree was parsed from, if the tree owns it.
+    ///
+    /// This is only available for trees created with [`Parser::parse_owned`]
+    /// that haven't been edited since. Trees created by [`Parser::parse`] and
+    /// the other parsing methods only borrow their text, and return `None`.
     #[must_use]
-    pub fn walk(&self) -> TreeCursor {
-        self.root_node().walk()
+    pub fn source(&self) -> Option<&[u8]> {
+        self.1.as_deref()
     }
 
-    /// Compare this old edited syntax tree to a new syntax tree representing
-    /// the same document, returning a sequence of ranges whose syntactic
-    /// structure has changed.
+    /// Estimate the region of the document that will be reparsed after applying
+    /// the given edit to this tree, without editing the tree itself.
     ///
-    /// For this to work correctly, this syntax tree must have been edited such
-    /// that its ranges match up to the new tree. Generally, you'll want to
-    /// call this method right after calling one of the [`Parser::parse`]
-    /// functions. Call it on the old tree that was passed to parse, and
-    /// pass the new tree that was returned from `parse`.
-    #[doc(alias = "ts_tree_get_changed_ranges")]
+    /// The returned range is in the coordinates of the edited document. It spans
+    /// the edited text and the smallest node that contains every subtree affected
+    /// by the edit. Nodes outside of this range will be reused by the parser,
+    /// unless the edit changes how the text around them is parsed, such as when
+    /// opening an unterminated comment.
     #[must_use]
-    pub fn changed_ranges(&self, other: &Self) -> impl ExactSizeIterator<Item = Range> {
-        let mut count = 0u32;
-        unsafe {
-            let ptr = ffi::ts_tree_get_changed_ranges(
-                self.0.as_ptr(),
-                other.0.as_ptr(),
-                core::ptr::addr_of_mut!(count),
-            );
-            util::CBufferIter::new(ptr, count as usize).map(Into::into)
+    pub fn estimate_reparse_region(&self, edit: &InputEdit) -> Range {
+        let mut tree = self.clone();
+        tree.edit(edit);
+
+        let mut node = tree.root_node();
+        'outer: loop {
+            let mut changed_child = None;
+            for i in 0..node.child_count() {
+                let child = node.child(i).unwrap();
+                if child.has_changes() {
+                    if changed_child.is_some() {
+                        break 'outer;
+                    }
+                    changed_child = Some(child);
+                }
+            }
+            match changed_child {
+                Some(child)
+                    if child.start_byte() <= edit.start_byte
+                        && child.end_byte() >= edit.new_end_byte =>
+                {
+                    node = child;
+                }
+                _ => break,
+            }
+        }
+
+        let mut range = node.range();
+        if edit.start_byte < range.start_byte {
+            range.start_byte = edit.start_byte;
+            range.start_point = edit.start_position;
+        }
+        if edit.new_end_byte > range.end_byte {
+            range.end_byte = edit.new_end_byte;
+            range.end_point = edit.new_end_position;
         }
+        range
     }
 
-    /// Get the included ranges that were used to parse the syntax tree.
-    #[doc(alias = "ts_tree_included_ranges")]
+    /// Create a new [`TreeCursor`] starting from the root of the tree.
+    ///
+    /// To walk only part of the tree, use [`Node::walk`], whose cursor cannot move
+    /// outside of the given node.
+    #[must_use]
+    pub fn walk(&self) -> TreeCursor {
+        self.root_node().walk()
+    }
+
+    /// Get the last token in the tree that ends at or before the given byte offset.
+    ///
+    /// Unlike [`Node::descendant_for_byte_range`], which finds the node that contains
+    /// a range, this finds the closest preceding token in document order, even when it
+    /// belongs to a different subtree than the token at the offset. Tokens are the
+    /// leaves of the tree, including extras such as comments. Missing nodes are skipped,
+    /// because they don't correspond to any text in the document.
+    #[must_use]
+    pub fn token_before_byte(&self, offset: usize) -> Option<Node> {
+        let mut cursor = self.walk();
+        loop {
+            let node = cursor.node();
+            if node.child_count() > 0 {
+                if cursor.goto_first_child_for_byte(offset).is_none() {
+                    cursor.goto_last_child();
+                }
+                continue;
+            }
+            if node.end_byte() <= offset && !node.is_missing() {
+                return Some(node);
+            }
+            while !cursor.goto_previous_sibling() {
+                if !cursor.goto_parent() {
+                    return None;
+                }
+            }
+        }
+    }
+
+    /// Get every edge between a node and its children in the tree, as a flat list.
+    ///
+    /// The edges are listed in the order that their children are visited in a
+    /// preorder traversal, so there is one fewer edge than there are nodes. This is
+    /// a convenient form for exporting the tree into graph-based tools.
+    #[must_use]
+    pub fn to_edges(&self) -> Vec<TreeEdge> {
+        let mut edges = Vec::new();
+        let mut parents = Vec::<Node>::new();
+        let mut cursor = self.walk();
+        loop {
+            let node = cursor.node();
+            if let Some(parent) = parents.last() {
+                edges.push(TreeEdge {
+                    parent_id: parent.id(),
+                    parent_kind: parent.kind(),
+                    parent_range: parent.range(),
+                    child_id: node.id(),
+                    child_kind: node.kind(),
+                    child_range: node.range(),
+                    field_name: cursor.field_name(),
+                });
+            }
+            if cursor.goto_first_child() {
+                parents.push(node);
+                continue;
+            }
+            while !cursor.goto_next_sibling() {
+                if !cursor.goto_parent() {
+                    return edges;
+                }
+                parents.pop();
+            }
+        }
+    }
+
+    /// Compare this old edited syntax tree to a new syntax tree representing
+    /// the same document, returning a sequence of ranges whose syntactic
+    /// structure has changed.
+    ///
+    /// For this to work correctly, this syntax tree must have been edited such
+    /// that its ranges match up to the new tree. Generally, you'll want to
+    /// call this method right after calling one of the [`Parser::parse`]
+    /// functions. Call it on the old tree that was passed to parse, and
+    /// pass the new tree that was returned from `parse`.
+    ///
+    /// The ranges are positions in the new tree, and they extend to the end of the
+    /// document when the nodes whose structure changed do. Only changes to the structure
+    /// of the tree are reported, so a token whose text changed without changing its kind
+    /// isn't included, and neither is text that was deleted along with all of its nodes,
+    /// because nothing is left of it in the new tree. Editors that need to refresh
+    /// everything that was edited should also include the edited ranges themselves.
+    #[doc(alias = "ts_tree_get_changed_ranges")]
+    #[must_use]
+    pub fn changed_ranges(&self, other: &Self) -> impl ExactSizeIterator<Item = Range> {
+        let mut count = 0u32;
+        unsafe {
+            let ptr = ffi::ts_tree_get_changed_ranges(
+                self.0.as_ptr(),
+                other.0.as_ptr(),
+                core::ptr::addr_of_mut!(count),
+            );
+            util::CBufferIter::new(ptr, count as usize).map(Into::into)
+        }
+    }
+
+    /// Get the included ranges that were used to parse the syntax tree.
+    #[doc(alias = "ts_tree_included_ranges")]
     #[must_use]
     pub fn included_ranges(&self) -> Vec<Range> {
         let mut count = 0u32;
@@ -1541,6 +2296,104 @@ impl Tree {
             unsafe { ffi::ts_tree_print_dot_graph(self.0.as_ptr(), fd) }
         }
     }
+
+    /// Write a graph of the tree to the given writer, formatted in the DOT language.
+    ///
+    /// Unlike [`Tree::print_dot_graph`], which shows the parser's internal view of the
+    /// tree, this graph shows the nodes that you would reach with a [`TreeCursor`]. Edges
+    /// are labeled with the names of the fields that their children occupy, leaf nodes
+    /// are drawn without a border, extras are gray, errors are red, and missing nodes
+    /// are dashed.
+    #[cfg(feature = "std")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+    pub fn write_dot_graph<W: std::io::Write>(&self, writer: &mut W) -> std::io::Result<()> {
+        writeln!(writer, "digraph tree {{")?;
+        writeln!(writer, "edge [arrowhead=none]")?;
+
+        let mut parents = Vec::<usize>::new();
+        let mut cursor = self.walk();
+        let mut index = 0;
+        loop {
+            let node = cursor.node();
+            write!(writer, "node_{index} [label=\"")?;
+            if node.is_missing() {
+                write!(writer, "MISSING ")?;
+            }
+            if node.is_named() {
+                write_dot_escaped(writer, node.kind())?;
+            } else {
+                write!(writer, "\\\"")?;
+                write_dot_escaped(writer, node.kind())?;
+                write!(writer, "\\\"")?;
+            }
+            write!(writer, "\"")?;
+            if node.child_count() == 0 {
+                write!(writer, ", shape=plaintext")?;
+            }
+            if node.is_error() || node.is_missing() {
+                write!(writer, ", color=red, fontcolor=red")?;
+            } else if node.is_extra() {
+                write!(writer, ", fontcolor=gray")?;
+            }
+            if node.is_missing() {
+                write!(writer, ", style=dashed")?;
+            }
+            writeln!(
+                writer,
+                ", tooltip=\"range: {} - {}\"]",
+                node.start_byte(),
+                node.end_byte()
+            )?;
+
+            if let Some(parent) = parents.last() {
+                write!(writer, "node_{parent} -> node_{index}")?;
+                if let Some(field_name) = cursor.field_name() {
+                    write!(writer, " [label=\"")?;
+                    write_dot_escaped(writer, field_name)?;
+                    write!(writer, "\"]")?;
+                }
+                writeln!(writer)?;
+            }
+
+            if cursor.goto_first_child() {
+                parents.push(index);
+            } else {
+                while !cursor.goto_next_sibling() {
+                    if !cursor.goto_parent() {
+                        return writeln!(writer, "}}");
+                    }
+                    parents.pop();
+                }
+            }
+            index += 1;
+        }
+    }
+
+    /// Get a graph of the tree formatted in the DOT language, as described in
+    /// [`Tree::write_dot_graph`].
+    #[cfg(feature = "std")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+    #[must_use]
+    pub fn to_dot_string(&self) -> String {
+        let mut result = Vec::new();
+        self.write_dot_graph(&mut result).unwrap();
+        String::from_utf8(result).unwrap()
+    }
+}
+
+#[cfg(feature = "std")]
+fn write_dot_escaped(writer: &mut impl std::io::Write, text: &str) -> std::io::Result<()> {
+    for c in text.chars() {
+        match c {
+            '"' => write!(writer, "\\\"")?,
+            '\\' => write!(writer, "\\\\")?,
+            '\n' => write!(writer, "\\n")?,
+            '\r' => write!(writer, "\\r")?,
+            '\t' => write!(writer, "\\t")?,
+            _ => write!(writer, "{c}")?,
+        }
+    }
+    Ok(())
 }
 
 impl fmt::Debug for Tree {
@@ -1557,7 +2410,12 @@ impl Drop for Tree {
 
 impl Clone for Tree {
     fn clone(&self) -> Self {
-        unsafe { Self(NonNull::new_unchecked(ffi::ts_tree_copy(self.0.as_ptr()))) }
+        unsafe {
+            Self(
+                NonNull::new_unchecked(ffi::ts_tree_copy(self.0.as_ptr())),
+                self.1.clone(),
+            )
+        }
     }
 }
 
@@ -1581,6 +2439,33 @@ impl<'tree> Node<'tree> {
         self.0.id as usize
     }
 
+    /// Get an id for this node that stays the same across incremental reparses
+    /// in which the node is reused.
+    ///
+    /// Unlike [`Node::id`], which can change when a reused node is given a new
+    /// parent, this identifies the node's underlying data. It can be used to
+    /// keep track of a node, such as by attaching decorations to it, as the
+    /// document is edited:
+    ///
+    /// - If a new tree is created based on an older tree, and a node from the old tree is reused in
+    ///   the process, then that node will have the same stable id in both trees, even if its
+    ///   position has moved.
+    ///
+    /// - Ids are only meaningful among trees that share nodes, such as the trees produced by one
+    ///   parser session of incremental reparses. Once all of the trees that contain a node have been
+    ///   dropped, its id may be given to a different node.
+    ///
+    /// - Identical subtrees within one tree may share an id.
+    ///
+    /// Small leaf nodes, such as most tokens, are stored within their parent rather than on their
+    /// own, so they have no stable id, and this returns `None`.
+    #[doc(alias = "ts_node_stable_id")]
+    #[must_use]
+    pub fn stable_id(&self) -> Option<u64> {
+        let id = unsafe { ffi::ts_node_stable_id(self.0) };
+        (!id.is_null()).then_some(id as usize as u64)
+    }
+
     /// Get this node's type as a numerical id.
     #[doc(alias = "ts_node_symbol")]
     #[must_use]
@@ -1632,10 +2517,31 @@ impl<'tree> Node<'tree> {
         unsafe { ffi::ts_node_is_named(self.0) }
     }
 
+    /// Check if this node has the given kind.
+    ///
+    /// This is equivalent to `node.kind() == kind`, but compares the kind's
+    /// numerical id rather than its name. To check many nodes for the same kinds
+    /// in a hot loop, look the ids up once with [`Language::node_kind_set`]
+    /// instead.
+    #[must_use]
+    pub fn is_kind(&self, kind: &str) -> bool {
+        let kind_id = self.language().id_for_node_kind(kind, self.is_named());
+        kind_id != 0 && kind_id == self.kind_id()
+    }
+
+    /// Check if this node is *named* and has the given kind.
+    ///
+    /// This is equivalent to `node.is_named() && node.kind() == kind`.
+    #[must_use]
+    pub fn is_named_kind(&self, kind: &str) -> bool {
+        self.is_named() && self.is_kind(kind)
+    }
+
     /// Check if this node is *extra*.
     ///
     /// Extra nodes represent things like comments, which are not required by the
-    /// grammar, but can appear anywhere.
+    /// grammar, but can appear anywhere. Nodes whose kinds were passed to
+    /// [`Parser::set_extra_override`] when the tree was parsed are not reported as extra.
     #[doc(alias = "ts_node_is_extra")]
     #[must_use]
     pub fn is_extra(&self) -> bool {
@@ -1667,6 +2573,20 @@ impl<'tree> Node<'tree> {
         unsafe { ffi::ts_node_is_error(self.0) }
     }
 
+    /// Get the cost of recovering from this syntax error, if this node is an `ERROR`
+    /// or `MISSING` node.
+    ///
+    /// The cost grows with the amount of input that the parser had to skip, and with
+    /// the number of nodes that it had to insert. Ranking errors by their costs can
+    /// help to report the most significant errors first, rather than small errors
+    /// that follow from them.
+    #[doc(alias = "ts_node_error_cost")]
+    #[must_use]
+    pub fn error_cost(&self) -> Option<usize> {
+        (self.is_error() || self.is_missing())
+            .then(|| unsafe { ffi::ts_node_error_cost(self.0) } as usize)
+    }
+
     /// Get this node's parse state.
     #[doc(alias = "ts_node_parse_state")]
     #[must_use]
@@ -1868,6 +2788,56 @@ impl<'tree> Node<'tree> {
         })
     }
 
+    /// Iterate over this node's named children, along with the name of the
+    /// field that each child is stored in, if any.
+    ///
+    /// See also [`Node::named_children`].
+    pub fn named_children_with_fields<'cursor>(
+        &self,
+        cursor: &'cursor mut TreeCursor<'tree>,
+    ) -> impl ExactSizeIterator<Item = (Option<&'static str>, Node<'tree>)> + 'cursor {
+        cursor.reset(*self);
+        cursor.goto_first_child();
+        (0..self.named_child_count()).map(move |_| {
+            while !cursor.node().is_named() {
+                if !cursor.goto_next_sibling() {
+                    break;
+                }
+            }
+            let result = (cursor.field_name(), cursor.node());
+            cursor.goto_next_sibling();
+            result
+        })
+    }
+
+    /// Visit this node and all of its descendants in depth-first order, calling
+    /// the given callback with each node and its depth below this node.
+    ///
+    /// This drives a single internal [`TreeCursor`], and doesn't allocate for each
+    /// node. The callback returns [`ControlFlow::Continue`] with a [`Descend`]
+    /// value to either visit or skip the node's subtree, or [`ControlFlow::Break`]
+    /// to stop the traversal, in which case the break value is returned.
+    pub fn for_each_descendant<B>(
+        &self,
+        mut callback: impl FnMut(Self, usize) -> ControlFlow<B, Descend>,
+    ) -> ControlFlow<B> {
+        let mut cursor = self.walk();
+        let mut depth = 0;
+        loop {
+            let descend = callback(cursor.node(), depth)?;
+            if descend == Descend::Children && cursor.goto_first_child() {
+                depth += 1;
+                continue;
+            }
+            while !cursor.goto_next_sibling() {
+                if depth == 0 || !cursor.goto_parent() {
+                    return ControlFlow::Continue(());
+                }
+                depth -= 1;
+            }
+        }
+    }
+
     /// Iterate over this node's children with a given field name.
     ///
     /// See also [`Node::children`].
@@ -1927,6 +2897,44 @@ impl<'tree> Node<'tree> {
         })
     }
 
+    /// Iterate over this node and its descendants that overlap the given byte
+    /// range, in document order.
+    ///
+    /// A node overlaps the range if it contains at least one byte in it, so an
+    /// empty range yields no nodes. Subtrees that lie entirely outside of the
+    /// range are skipped without being visited.
+    pub fn nodes_in_range(&self, range: ops::Range<usize>) -> impl Iterator<Item = Node<'tree>> {
+        let overlaps =
+            move |node: Node| node.start_byte() < range.end && node.end_byte() > range.start;
+        let mut cursor = self.walk();
+        let mut done = !overlaps(*self);
+        iter::from_fn(move || {
+            while !done {
+                let node = cursor.node();
+                if cursor.goto_first_child_for_byte(range.start).is_none() {
+                    loop {
+                        if cursor.goto_next_sibling() {
+                            break;
+                        }
+                        if !cursor.goto_parent() {
+                            done = true;
+                            break;
+                        }
+                    }
+                }
+                // Nodes are visited in order of their start byte, so once a node
+                // starts after the range, no later node can overlap it. An empty
+                // node, like a MISSING node, doesn't overlap the range even if it
+                // starts within it, but the nodes after it still can.
+                done = done || cursor.node().start_byte() >= range.end;
+                if overlaps(node) {
+                    return Some(node);
+                }
+            }
+            None
+        })
+    }
+
     /// Get this node's immediate parent.
     /// Prefer [`child_with_descendant`](Node::child_with_descendant)
     /// for iterating over this node's ancestors.
@@ -1936,6 +2944,49 @@ impl<'tree> Node<'tree> {
         Self::new(unsafe { ffi::ts_node_parent(self.0) })
     }
 
+    /// Get the name of the field in which this node is stored within its
+    /// parent, if any.
+    #[must_use]
+    pub fn field_name(&self) -> Option<&'static str> {
+        let field_id = self.field_id_in_parent(self.parent()?)?;
+        self.language().field_name_for_id(field_id.get())
+    }
+
+    /// Find the closest node, starting with this node and moving up through its
+    /// ancestors, that is stored in the field with the given name within its
+    /// own parent.
+    ///
+    /// For example, from within a function's parameter list, this can find the
+    /// whole list by searching for the `parameters` field.
+    #[must_use]
+    pub fn ancestor_in_field(&self, field_name: &str) -> Option<Self> {
+        let field_id = self.language().field_id_for_name(field_name)?;
+        let mut node = *self;
+        while let Some(parent) = node.parent() {
+            if node.field_id_in_parent(parent) == Some(field_id) {
+                return Some(node);
+            }
+            node = parent;
+        }
+        None
+    }
+
+    /// Get the id of the field in which this node is stored within `parent`.
+    fn field_id_in_parent(&self, parent: Self) -> Option<FieldId> {
+        let mut cursor = parent.walk();
+        if cursor.goto_first_child() {
+            loop {
+                if cursor.node() == *self {
+                    return cursor.field_id();
+                }
+                if !cursor.goto_next_sibling() {
+                    break;
+                }
+            }
+        }
+        None
+    }
+
     /// Get the node that contains `descendant`.
     ///
     /// Note that this can return `descendant` itself.
@@ -1973,6 +3024,47 @@ impl<'tree> Node<'tree> {
         Self::new(unsafe { ffi::ts_node_prev_named_sibling(self.0) })
     }
 
+    /// Get this node's previous sibling that is not an *extra* node, such as a comment.
+    ///
+    /// Unlike [`Node::prev_named_sibling`], this can return anonymous nodes.
+    #[must_use]
+    pub fn prev_non_extra_sibling(&self) -> Option<Self> {
+        let mut sibling = self.prev_sibling();
+        while let Some(node) = sibling {
+            if !node.is_extra() {
+                return Some(node);
+            }
+            sibling = node.prev_sibling();
+        }
+        None
+    }
+
+    /// Check if this node is at the given position among its parent's children, as
+    /// required by the `#nth-child?` query predicate. Named nodes are counted among
+    /// their named siblings, and anonymous nodes among all of their siblings. A
+    /// negative index counts backwards from the last child.
+    fn is_nth_child(&self, index: i64) -> bool {
+        if self.parent().is_none() {
+            return false;
+        }
+        let step = match (self.is_named(), index < 0) {
+            (true, true) => Self::next_named_sibling,
+            (true, false) => Self::prev_named_sibling,
+            (false, true) => Self::next_sibling,
+            (false, false) => Self::prev_sibling,
+        };
+        let (mut count, mut sibling) = (0, *self);
+        while let Some(node) = step(&sibling) {
+            count += 1;
+            sibling = node;
+        }
+        if index < 0 {
+            count == -(index + 1)
+        } else {
+            count == index
+        }
+    }
+
     /// Get this node's first child that contains or starts after the given byte offset.
     #[doc(alias = "ts_node_first_child_for_byte")]
     #[must_use]
@@ -2013,6 +3105,10 @@ impl<'tree> Node<'tree> {
     }
 
     /// Get the smallest node within this node that spans the given point range.
+    ///
+    /// If `start` and `end` are equal, this returns the smallest node that contains that
+    /// point. If the range crosses the boundary between nodes, this returns their lowest
+    /// common ancestor. Returns `None` if `start` is after `end`.
     #[doc(alias = "ts_node_descendant_for_point_range")]
     #[must_use]
     pub fn descendant_for_point_range(&self, start: Point, end: Point) -> Option<Self> {
@@ -2022,6 +3118,9 @@ impl<'tree> Node<'tree> {
     }
 
     /// Get the smallest named node within this node that spans the given point range.
+    ///
+    /// See [`descendant_for_point_range`](Node::descendant_for_point_range) for how empty
+    /// ranges and ranges that cross node boundaries are handled.
     #[doc(alias = "ts_node_named_descendant_for_point_range")]
     #[must_use]
     pub fn named_descendant_for_point_range(&self, start: Point, end: Point) -> Option<Self> {
@@ -2052,6 +3151,36 @@ impl<'tree> Node<'tree> {
         &source[self.start_byte() / 2..self.end_byte() / 2]
     }
 
+    /// Get the range of UTF-16 code units that this node represents, for a tree that was parsed
+    /// from the UTF-8 encoding of `source`.
+    ///
+    /// This is useful for editors that index text in UTF-16 code units, such as LSP clients.
+    /// If the tree was parsed from UTF-16 text instead, its byte offsets are already twice the
+    /// UTF-16 offsets. Each call walks `source` up to the end of the node.
+    #[must_use]
+    pub fn utf16_range(&self, source: &[u16]) -> core::ops::Range<usize> {
+        utf16_offset_for_byte(source, self.start_byte())
+            ..utf16_offset_for_byte(source, self.end_byte())
+    }
+
+    /// Get this node's start position, with the column counted in UTF-16 code units rather than
+    /// bytes, for a tree that was parsed from the UTF-8 encoding of `source`.
+    ///
+    /// See [`Node::utf16_range`].
+    #[must_use]
+    pub fn utf16_start_position(&self, source: &[u16]) -> Point {
+        utf16_position(source, self.start_byte(), self.start_position())
+    }
+
+    /// Get this node's end position, with the column counted in UTF-16 code units rather than
+    /// bytes, for a tree that was parsed from the UTF-8 encoding of `source`.
+    ///
+    /// See [`Node::utf16_range`].
+    #[must_use]
+    pub fn utf16_end_position(&self, source: &[u16]) -> Point {
+        utf16_position(source, self.end_byte(), self.end_position())
+    }
+
     /// Create a new [`TreeCursor`] starting from this node.
     ///
     /// Note that the given node is considered the root of the cursor,
@@ -2069,6 +3198,12 @@ impl<'tree> Node<'tree> {
     /// the tree afterward will already reflect the edit. You only need to
     /// use [`Node::edit`] when you have a specific [`Node`] instance that
     /// you want to keep and continue to use after an edit.
+    ///
+    /// This only updates the position that this handle has cached. The tree
+    /// that the node belongs to, and any other copies of the node, are unchanged.
+    /// The node's size isn't changed either, so its end position shifts along
+    /// with its start position. This means that the edited node is only accurate
+    /// if it doesn't overlap the edited range.
     #[doc(alias = "ts_node_edit")]
     pub fn edit(&mut self, edit: &InputEdit) {
         let edit = edit.into();
@@ -2076,6 +3211,28 @@ impl<'tree> Node<'tree> {
     }
 }
 
+/// Find the offset, in UTF-16 code units, of a byte offset in the UTF-8 encoding of `source`.
+fn utf16_offset_for_byte(source: &[u16], byte: usize) -> usize {
+    let mut utf8_offset = 0;
+    let mut utf16_offset = 0;
+    for c in char::decode_utf16(source.iter().copied()) {
+        if utf8_offset >= byte {
+            break;
+        }
+        let c = c.unwrap_or(char::REPLACEMENT_CHARACTER);
+        utf8_offset += c.len_utf8();
+        utf16_offset += c.len_utf16();
+    }
+    utf16_offset
+}
+
+/// Convert a byte position in the UTF-8 encoding of `source` into one whose column is counted
+/// in UTF-16 code units.
+fn utf16_position(source: &[u16], byte: usize, point: Point) -> Point {
+    let line_start = utf16_offset_for_byte(source, byte - point.column);
+    Point::new(point.row, utf16_offset_for_byte(source, byte) - line_start)
+}
+
 impl PartialEq for Node<'_> {
     fn eq(&self, other: &Self) -> bool {
         core::ptr::eq(self.0.id, other.0.id)
@@ -2250,10 +3407,14 @@ impl<'tree> TreeCursor<'tree> {
     }
 
     /// Move this cursor to the first child of its current node that contains or
-    /// starts after the given byte offset.
+    /// starts after the given point.
+    ///
+    /// Like [`goto_first_child_for_byte`](TreeCursor::goto_first_child_for_byte),
+    /// a child that ends exactly at the given point is skipped. Columns are
+    /// measured in bytes, as in [`Point`].
     ///
     /// This returns the index of the child node if one was found, and returns
-    /// `None` if no such child was found.
+    /// `None` if no such child was found, in which case the cursor is not moved.
     #[doc(alias = "ts_tree_cursor_goto_first_child_for_point")]
     pub fn goto_first_child_for_point(&mut self, point: Point) -> Option<usize> {
         let result = unsafe {
@@ -2294,6 +3455,69 @@ impl Drop for TreeCursor<'_> {
     }
 }
 
+impl<'tree> NodeIndex<'tree> {
+    /// Build an index over all of the named nodes in the given tree.
+    #[must_use]
+    pub fn new(tree: &'tree Tree) -> Self {
+        // Record a boundary wherever the innermost named node changes. Entering a
+        // named node starts a run for that node, and leaving it resumes the run of
+        // its closest named ancestor.
+        let mut boundaries = Vec::new();
+        let mut ancestors = Vec::<Node<'tree>>::new();
+        let mut cursor = tree.walk();
+        loop {
+            let node = cursor.node();
+            if node.is_named() {
+                Self::push_boundary(&mut boundaries, node.start_byte(), Some(node));
+                ancestors.push(node);
+            }
+
+            if cursor.goto_first_child() {
+                continue;
+            }
+
+            loop {
+                let node = cursor.node();
+                if node.is_named() {
+                    ancestors.pop();
+                    Self::push_boundary(
+                        &mut boundaries,
+                        node.end_byte(),
+                        ancestors.last().copied(),
+                    );
+                }
+                if cursor.goto_next_sibling() {
+                    break;
+                }
+                if !cursor.goto_parent() {
+                    return Self { boundaries };
+                }
+            }
+        }
+    }
+
+    fn push_boundary(
+        boundaries: &mut Vec<(usize, Option<Node<'tree>>)>,
+        byte: usize,
+        node: Option<Node<'tree>>,
+    ) {
+        match boundaries.last_mut() {
+            Some(last) if last.0 == byte => last.1 = node,
+            _ => boundaries.push((byte, node)),
+        }
+    }
+
+    /// Get the smallest named node that contains the given byte offset.
+    ///
+    /// A node contains a byte if the byte lies within its start byte (inclusive)
+    /// and end byte (exclusive), so zero-width nodes never contain any byte.
+    #[must_use]
+    pub fn named_node_for_byte(&self, byte: usize) -> Option<Node<'tree>> {
+        let index = self.boundaries.partition_point(|(start, _)| *start <= byte);
+        self.boundaries[..index].last().and_then(|(_, node)| *node)
+    }
+}
+
 impl LookaheadIterator {
     /// Get the current language of the lookahead iterator.
     #[doc(alias = "ts_lookahead_iterator_language")]
@@ -2347,6 +3571,11 @@ impl LookaheadIterator {
     pub fn iter_names(&mut self) -> impl Iterator<Item = &'static str> + '_ {
         LookaheadNamesIterator(self)
     }
+
+    /// Iterate symbol ids together with their names.
+    pub fn iter_symbols(&mut self) -> impl Iterator<Item = (u16, &'static str)> + '_ {
+        LookaheadSymbolsIterator(self)
+    }
 }
 
 impl Iterator for LookaheadNamesIterator<'_> {
@@ -2359,6 +3588,16 @@ impl Iterator for LookaheadNamesIterator<'_> {
     }
 }
 
+impl Iterator for LookaheadSymbolsIterator<'_> {
+    type Item = (u16, &'static str);
+
+    #[doc(alias = "ts_lookahead_iterator_next")]
+    fn next(&mut self) -> Option<Self::Item> {
+        unsafe { ffi::ts_lookahead_iterator_next(self.0.0.as_ptr()) }
+            .then(|| (self.0.current_symbol(), self.0.current_symbol_name()))
+    }
+}
+
 impl Iterator for LookaheadIterator {
     type Item = u16;
 
@@ -2388,6 +3627,36 @@ impl Query {
         unsafe { Self::from_raw_parts(ptr, source) }
     }
 
+    /// Create a new query like [`Query::new`], but reject any predicate that
+    /// isn't one of the built-in predicates handled by the query engine.
+    ///
+    /// By default, unrecognized predicates are made available through
+    /// [`Query::general_predicates`] so that callers can implement their own.
+    /// In strict mode they are instead treated as mistakes, and the returned
+    /// error points at the first offending predicate.
+    pub fn new_strict(language: &Language, source: &str) -> Result<Self, QueryError> {
+        let query = Self::new(language, source)?;
+        for (pattern_index, predicates) in query.general_predicates.iter().enumerate() {
+            let Some(predicate) = predicates.first() else {
+                continue;
+            };
+            let pattern_start = query.start_byte_for_pattern(pattern_index);
+            let pattern_end = query.end_byte_for_pattern(pattern_index);
+            let offset = source[pattern_start..pattern_end]
+                .find(&format!("#{}", predicate.operator))
+                .map_or(pattern_start, |i| pattern_start + i);
+            let line_start = source[..offset].rfind('\n').map_or(0, |i| i + 1);
+            return Err(QueryError {
+                row: source[..offset].matches('\n').count(),
+                column: offset - line_start,
+                offset,
+                message: format!("Unknown predicate #{}", predicate.operator),
+                kind: QueryErrorKind::Predicate,
+            });
+        }
+        Ok(query)
+    }
+
     /// Constructs a raw [`TSQuery`](ffi::TSQuery) pointer without performing extra checks specific to the rust
     /// bindings, such as predicate validation. A [`Query`] object can be constructed from the
     /// returned pointer using [`from_raw_parts`](Query::from_raw_parts). The caller is
@@ -2605,7 +3874,8 @@ impl Query {
                 // Build a predicate for each of the known predicate function names.
                 let operator_name = string_values[p[0].value_id as usize];
                 match operator_name {
-                    "eq?" | "not-eq?" | "any-eq?" | "any-not-eq?" => {
+                    "eq?" | "not-eq?" | "any-eq?" | "any-not-eq?" | "eq-ci?" | "not-eq-ci?"
+                    | "any-eq-ci?" | "any-not-eq-ci?" => {
                         if p.len() != 3 {
                             return Err(predicate_error(
                                 row,
@@ -2625,10 +3895,16 @@ impl Query {
                             ));
                         }
 
-                        let is_positive = operator_name == "eq?" || operator_name == "any-eq?";
-                        let match_all = match operator_name {
-                            "eq?" | "not-eq?" => true,
-                            "any-eq?" | "any-not-eq?" => false,
+                        let (base_operator_name, case_insensitive) =
+                            match operator_name.strip_suffix("-ci?") {
+                                Some(name) => (name, true),
+                                None => (operator_name.trim_end_matches('?'), false),
+                            };
+                        let is_positive =
+                            base_operator_name == "eq" || base_operator_name == "any-eq";
+                        let match_all = match base_operator_name {
+                            "eq" | "not-eq" => true,
+                            "any-eq" | "any-not-eq" => false,
                             _ => unreachable!(),
                         };
                         text_predicates.push(if p[2].type_ == TYPE_CAPTURE {
@@ -2637,6 +3913,7 @@ impl Query {
                                 p[2].value_id,
                                 is_positive,
                                 match_all,
+                                case_insensitive,
                             )
                         } else {
                             TextPredicateCapture::EqString(
@@ -2644,11 +3921,13 @@ impl Query {
                                 string_values[p[2].value_id as usize].to_string().into(),
                                 is_positive,
                                 match_all,
+                                case_insensitive,
                             )
                         });
                     }
 
-                    "match?" | "not-match?" | "any-match?" | "any-not-match?" => {
+                    "match?" | "not-match?" | "any-match?" | "any-not-match?" | "match-ci?"
+                    | "not-match-ci?" | "any-match-ci?" | "any-not-match-ci?" => {
                         if p.len() != 3 {
                             return Err(predicate_error(
                                 row,
@@ -2677,19 +3956,27 @@ impl Query {
                             ));
                         }
 
+                        let (base_operator_name, case_insensitive) =
+                            match operator_name.strip_suffix("-ci?") {
+                                Some(name) => (name, true),
+                                None => (operator_name.trim_end_matches('?'), false),
+                            };
                         let is_positive =
-                            operator_name == "match?" || operator_name == "any-match?";
-                        let match_all = match operator_name {
-                            "match?" | "not-match?" => true,
-                            "any-match?" | "any-not-match?" => false,
+                            base_operator_name == "match" || base_operator_name == "any-match";
+                        let match_all = match base_operator_name {
+                            "match" | "not-match" => true,
+                            "any-match" | "any-not-match" => false,
                             _ => unreachable!(),
                         };
                         let regex = &string_values[p[2].value_id as usize];
                         text_predicates.push(TextPredicateCapture::MatchString(
                             p[1].value_id,
-                            regex::bytes::Regex::new(regex).map_err(|_| {
-                                predicate_error(row, format!("Invalid regex '{regex}'"))
-                            })?,
+                            regex::bytes::RegexBuilder::new(regex)
+                                .case_insensitive(case_insensitive)
+                                .build()
+                                .map_err(|_| {
+                                    predicate_error(row, format!("Invalid regex '{regex}'"))
+                                })?,
                             is_positive,
                             match_all,
                         ));
@@ -2759,6 +4046,49 @@ impl Query {
                         ));
                     }
 
+                    "nth-child?" => {
+                        if p.len() != 3 {
+                            return Err(predicate_error(
+                                row,
+                                format!(
+                                    "Wrong number of arguments to #nth-child? predicate. Expected 2, got {}.",
+                                    p.len() - 1
+                                ),
+                            ));
+                        }
+                        if p[1].type_ != TYPE_CAPTURE {
+                            return Err(predicate_error(
+                                row,
+                                format!(
+                                    "First argument to #nth-child? predicate must be a capture name. Got literal \"{}\".",
+                                    string_values[p[1].value_id as usize],
+                                ),
+                            ));
+                        }
+                        if p[2].type_ == TYPE_CAPTURE {
+                            return Err(predicate_error(
+                                row,
+                                format!(
+                                    "Second argument to #nth-child? predicate must be a literal. Got capture @{}.",
+                                    capture_names[p[2].value_id as usize],
+                                ),
+                            ));
+                        }
+
+                        let index = string_values[p[2].value_id as usize];
+                        text_predicates.push(TextPredicateCapture::NthChild(
+                            p[1].value_id,
+                            index.parse().map_err(|_| {
+                                predicate_error(
+                                    row,
+                                    format!(
+                                        "Second argument to #nth-child? predicate must be an integer. Got \"{index}\"."
+                                    ),
+                                )
+                            })?,
+                        ));
+                    }
+
                     _ => general_predicates.push(QueryPredicate {
                         operator: operator_name.to_string().into(),
                         args: p[1..]
@@ -2841,12 +4171,31 @@ impl Query {
         &self.capture_names
     }
 
-    /// Get the quantifiers of the captures used in the query.
+    /// Get the quantifiers of the captures used in the given pattern, indexed by
+    /// capture index.
     #[must_use]
     pub const fn capture_quantifiers(&self, index: usize) -> &[CaptureQuantifier] {
         &self.capture_quantifiers[index]
     }
 
+    /// Get the quantifier of the given capture within the given pattern, which tells
+    /// how many nodes the capture can have in each of the pattern's matches.
+    ///
+    /// This is [`CaptureQuantifier::Zero`] if the capture doesn't appear in the pattern.
+    ///
+    /// # Panics
+    ///
+    /// Panics if either index is out of bounds.
+    #[doc(alias = "ts_query_capture_quantifier_for_id")]
+    #[must_use]
+    pub fn capture_quantifier(
+        &self,
+        pattern_index: usize,
+        capture_index: u32,
+    ) -> CaptureQuantifier {
+        self.capture_quantifiers[pattern_index][capture_index as usize]
+    }
+
     /// Get the index for a given capture name.
     #[must_use]
     pub fn capture_index_for_name(&self, name: &str) -> Option<u32> {
@@ -2951,6 +4300,87 @@ impl Query {
         }
     }
 
+    /// Check if this query could be affected by anonymous nodes.
+    ///
+    /// This is the case if one of its patterns can match an anonymous node,
+    /// or uses an anchor or quantifier whose behavior depends on anonymous
+    /// siblings. Queries for which this returns `false` can skip anonymous
+    /// nodes entirely; see [`QueryCursor::set_skip_anonymous_nodes`].
+    #[doc(alias = "ts_query_matches_anonymous_nodes")]
+    #[must_use]
+    pub fn matches_anonymous_nodes(&self) -> bool {
+        unsafe { ffi::ts_query_matches_anonymous_nodes(self.ptr.as_ptr()) }
+    }
+
+    /// Describe the structure of a given pattern within a query.
+    ///
+    /// The shape is reconstructed from the compiled pattern, so it doesn't
+    /// reproduce the pattern's source, only the nodes that it matches and
+    /// how they're nested.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the index isn't less than the number of patterns in the query.
+    #[doc(alias = "ts_query_steps_for_pattern")]
+    #[must_use]
+    pub fn describe_pattern(&self, index: usize) -> PatternShape {
+        assert!(
+            index < self.pattern_count(),
+            "Pattern index is {index} but the pattern count is {}",
+            self.pattern_count(),
+        );
+        let ptr = self.ptr.as_ptr();
+        let count =
+            unsafe { ffi::ts_query_steps_for_pattern(ptr, index as u32, ptr::null_mut(), 0) };
+        let mut steps = Vec::with_capacity(count as usize);
+        unsafe {
+            ffi::ts_query_steps_for_pattern(ptr, index as u32, steps.as_mut_ptr(), count);
+            steps.set_len(count as usize);
+        }
+
+        let language = LanguageRef(unsafe { ffi::ts_query_language(ptr) }, PhantomData);
+        let mut steps = steps.iter().peekable();
+        let roots = self.pattern_nodes(&language, &mut steps, 0);
+        PatternShape { roots }
+    }
+
+    fn pattern_nodes(
+        &self,
+        language: &Language,
+        steps: &mut iter::Peekable<slice::Iter<'_, ffi::TSQueryPatternStep>>,
+        depth: u16,
+    ) -> Vec<PatternNode> {
+        let mut nodes = Vec::new();
+        while let Some(step) = steps.next_if(|step| step.depth == depth) {
+            let children = match steps.peek().map(|child| child.depth) {
+                Some(child_depth) if child_depth > depth => {
+                    self.pattern_nodes(language, steps, child_depth)
+                }
+                _ => Vec::new(),
+            };
+            nodes.push(PatternNode {
+                kind: if step.symbol == 0 {
+                    None
+                } else {
+                    language.node_kind_for_id(step.symbol)
+                },
+                is_named: step.is_named,
+                field: if step.field == 0 {
+                    None
+                } else {
+                    language.field_name_for_id(step.field)
+                },
+                captures: step.capture_ids[..step.capture_count as usize]
+                    .iter()
+                    .map(|id| self.capture_names[*id as usize])
+                    .collect(),
+                is_optional: step.is_optional,
+                children,
+            });
+        }
+        nodes
+    }
+
     fn parse_property(
         row: usize,
         function_name: &str,
@@ -3026,6 +4456,7 @@ impl QueryCursor {
     pub fn new() -> Self {
         Self {
             ptr: unsafe { NonNull::new_unchecked(ffi::ts_query_cursor_new()) },
+            byte_ranges: Vec::new(),
         }
     }
 
@@ -3038,6 +4469,12 @@ impl QueryCursor {
 
     /// Set the maximum number of in-progress matches for this cursor.  The
     /// limit must be > 0 and <= 65536.
+    ///
+    /// When a query would need to track more matches than this at once, the
+    /// in-progress matches whose captures start earliest in the document are
+    /// dropped, and
+    /// [`did_exceed_match_limit`](QueryCursor::did_exceed_match_limit) will
+    /// return `true`.
     #[doc(alias = "ts_query_cursor_set_match_limit")]
     pub fn set_match_limit(&mut self, limit: u32) {
         unsafe {
@@ -3047,6 +4484,12 @@ impl QueryCursor {
 
     /// Check if, on its last execution, this cursor exceeded its maximum number
     /// of in-progress matches.
+    ///
+    /// If so, some matches may have been missed. The flag is reset each time
+    /// the cursor is executed with [`matches`](QueryCursor::matches) or
+    /// [`captures`](QueryCursor::captures), so a caller can raise the limit with
+    /// [`set_match_limit`](QueryCursor::set_match_limit) and run the query
+    /// again to get the complete results.
     #[doc(alias = "ts_query_cursor_did_exceed_match_limit")]
     #[must_use]
     pub fn did_exceed_match_limit(&self) -> bool {
@@ -3251,6 +4694,30 @@ impl QueryCursor {
         self
     }
 
+    /// Restrict the query to the given byte range, until the next call to
+    /// [`pop_byte_range`](QueryCursor::pop_byte_range).
+    ///
+    /// This allows one cursor to search several regions of a document in turn,
+    /// reusing its allocations. Like [`set_byte_range`](QueryCursor::set_byte_range),
+    /// the pushed range replaces any range that the cursor already had.
+    pub fn push_byte_range(&mut self, range: ops::Range<usize>) -> &mut Self {
+        self.set_byte_range(range.clone());
+        self.byte_ranges.push(range);
+        self
+    }
+
+    /// Restore the byte range that was pushed before the most recent call to
+    /// [`push_byte_range`](QueryCursor::push_byte_range), or remove the byte range
+    /// restriction entirely if there is none.
+    ///
+    /// Returns the range that was removed.
+    pub fn pop_byte_range(&mut self) -> Option<ops::Range<usize>> {
+        let range = self.byte_ranges.pop()?;
+        let previous_range = self.byte_ranges.last().cloned().unwrap_or(0..usize::MAX);
+        self.set_byte_range(previous_range);
+        Some(range)
+    }
+
     /// Set the range in which the query will be executed, in terms of rows and
     /// columns.
     #[doc(alias = "ts_query_cursor_set_point_range")]
@@ -3327,6 +4794,203 @@ impl QueryCursor {
         }
         self
     }
+
+    /// Set whether this cursor should skip over anonymous leaf nodes.
+    ///
+    /// This is only a hint: it takes effect for queries that can't be affected
+    /// by anonymous nodes (see [`Query::matches_anonymous_nodes`]), so the
+    /// matches are the same either way. For those queries, skipping the
+    /// anonymous tokens can make the traversal much faster on token-dense
+    /// trees.
+    #[doc(alias = "ts_query_cursor_set_skip_anonymous_nodes")]
+    pub fn set_skip_anonymous_nodes(&mut self, skip: bool) -> &mut Self {
+        unsafe {
+            ffi::ts_query_cursor_set_skip_anonymous_nodes(self.ptr.as_ptr(), skip);
+        }
+        self
+    }
+}
+
+impl Default for LayeredQueryCursor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LayeredQueryCursor {
+    /// Create a new cursor for executing queries on several layers.
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            cursor: QueryCursor::new(),
+        }
+    }
+
+    /// Find all of the captures in the given layers, ordered by their position
+    /// in the full document.
+    ///
+    /// Captures are sorted by their start byte. When captures start at the same
+    /// byte, the one that ends later comes first, so that enclosing captures
+    /// precede the captures within them. Captures with identical ranges are
+    /// ordered by layer, so a layer's captures precede those of the layers after
+    /// it, and captures from the same layer keep the order in which that layer's
+    /// query produced them.
+    ///
+    /// The `text` is the full document. Each layer's captures are checked against
+    /// the query's predicates using the text starting at the layer's byte offset.
+    pub fn captures<'tree>(
+        &mut self,
+        layers: &[QueryLayer<'_, 'tree>],
+        text: &[u8],
+    ) -> Vec<LayeredQueryCapture<'tree>> {
+        let mut result = Vec::new();
+        for (layer_index, layer) in layers.iter().enumerate() {
+            let mut captures =
+                self.cursor
+                    .captures(layer.query, layer.node, &text[layer.byte_offset..]);
+            while let Some((query_match, capture_index)) = captures.next() {
+                let capture = query_match.captures[*capture_index];
+                let range = capture.node.byte_range();
+                result.push(LayeredQueryCapture {
+                    layer_index,
+                    pattern_index: query_match.pattern_index,
+                    capture,
+                    byte_range: range.start + layer.byte_offset..range.end + layer.byte_offset,
+                });
+            }
+        }
+        result.sort_by(|a, b| {
+            a.byte_range
+                .start
+                .cmp(&b.byte_range.start)
+                .then_with(|| b.byte_range.end.cmp(&a.byte_range.end))
+                .then_with(|| a.layer_index.cmp(&b.layer_index))
+        });
+        result
+    }
+}
+
+impl CachedQuery {
+    /// Create an empty cache for the given query.
+    #[must_use]
+    pub fn new(query: Query) -> Self {
+        Self {
+            query,
+            cursor: QueryCursor::new(),
+            entries: Vec::new(),
+        }
+    }
+
+    /// Get the query whose matches are cached.
+    #[must_use]
+    pub const fn query(&self) -> &Query {
+        &self.query
+    }
+
+    /// Get the matches of the query within the given byte range of a document.
+    ///
+    /// If the query has already been run on exactly this range, and the cache
+    /// hasn't been invalidated since, the stored matches are returned without
+    /// running the query again. The `node` and `text` must therefore always
+    /// belong to the same document, and the cache must be kept up to date with
+    /// [`CachedQuery::edit`] and [`CachedQuery::invalidate_changed_ranges`]
+    /// when that document changes.
+    pub fn matches(
+        &mut self,
+        node: Node,
+        byte_range: ops::Range<usize>,
+        text: &[u8],
+    ) -> &[CachedQueryMatch] {
+        let index = if let Some(index) = self.entry_index(&byte_range) {
+            index
+        } else {
+            self.cursor.set_byte_range(byte_range.clone());
+            let mut extent = byte_range.clone();
+            let mut matches = Vec::new();
+            let mut query_matches = self.cursor.matches(&self.query, node, text);
+            while let Some(query_match) = query_matches.next() {
+                let captures = query_match
+                    .captures
+                    .iter()
+                    .map(|capture| {
+                        extent.start = extent.start.min(capture.node.start_byte());
+                        extent.end = extent.end.max(capture.node.end_byte());
+                        CachedQueryCapture {
+                            index: capture.index,
+                            range: capture.node.range(),
+                        }
+                    })
+                    .collect();
+                matches.push(CachedQueryMatch {
+                    pattern_index: query_match.pattern_index,
+                    captures,
+                });
+            }
+            self.entries.push(CachedQueryEntry {
+                byte_range,
+                extent,
+                matches,
+            });
+            self.entries.len() - 1
+        };
+        &self.entries[index].matches
+    }
+
+    /// Check if the matches for the given byte range are currently cached.
+    #[must_use]
+    pub fn is_cached(&self, byte_range: &ops::Range<usize>) -> bool {
+        self.entry_index(byte_range).is_some()
+    }
+
+    /// Update the cache to reflect an edit to the document, in the same way as
+    /// [`Tree::edit`].
+    ///
+    /// Cached results that depend on the edited text are discarded, and those
+    /// that come after it are shifted to their new positions.
+    pub fn edit(&mut self, edit: &InputEdit) {
+        self.entries.retain_mut(|entry| {
+            if entry.extent.end < edit.start_byte {
+                return true;
+            }
+            if entry.extent.start <= edit.old_end_byte {
+                return false;
+            }
+            let shift = |byte: usize| (byte - edit.old_end_byte).saturating_add(edit.new_end_byte);
+            entry.byte_range = shift(entry.byte_range.start)..shift(entry.byte_range.end);
+            entry.extent = shift(entry.extent.start)..shift(entry.extent.end);
+            for query_match in &mut entry.matches {
+                for capture in &mut query_match.captures {
+                    edit.edit_range(&mut capture.range);
+                }
+            }
+            true
+        });
+    }
+
+    /// Discard the cached results that are affected by the differences between
+    /// two versions of a document's syntax tree, as reported by
+    /// [`Tree::changed_ranges`].
+    ///
+    /// The `old_tree` is the previous tree, after it was edited, and `new_tree`
+    /// is the result of reparsing it.
+    pub fn invalidate_changed_ranges(&mut self, old_tree: &Tree, new_tree: &Tree) {
+        for range in old_tree.changed_ranges(new_tree) {
+            self.entries.retain(|entry| {
+                entry.extent.end < range.start_byte || entry.extent.start > range.end_byte
+            });
+        }
+    }
+
+    /// Discard all of the cached results.
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    fn entry_index(&self, byte_range: &ops::Range<usize>) -> Option<usize> {
+        self.entries
+            .iter()
+            .position(|entry| entry.byte_range == *byte_range)
+    }
 }
 
 impl<'tree> QueryMatch<'_, 'tree> {
@@ -3340,6 +5004,11 @@ impl<'tree> QueryMatch<'_, 'tree> {
         unsafe { ffi::ts_query_cursor_remove_match(self.cursor, self.id) }
     }
 
+    /// Get the nodes that were captured with the given capture index in this match.
+    ///
+    /// A quantified capture like `(comment)+ @doc` can capture several nodes in one match,
+    /// and they are yielded in the order that they were captured. If the capture isn't part of
+    /// this match, the iterator is empty.
     pub fn nodes_for_capture_index(
         &self,
         capture_ix: u32,
@@ -3411,7 +5080,13 @@ impl<'tree> QueryMatch<'_, 'tree> {
         query.text_predicates[self.pattern_index]
             .iter()
             .all(|predicate| match predicate {
-                TextPredicateCapture::EqCapture(i, j, is_positive, match_all_nodes) => {
+                TextPredicateCapture::EqCapture(
+                    i,
+                    j,
+                    is_positive,
+                    match_all_nodes,
+                    case_insensitive,
+                ) => {
                     let mut nodes_1 = self.nodes_for_capture_index(*i).peekable();
                     let mut nodes_2 = self.nodes_for_capture_index(*j).peekable();
                     while nodes_1.peek().is_some() && nodes_2.peek().is_some() {
@@ -3421,7 +5096,11 @@ impl<'tree> QueryMatch<'_, 'tree> {
                         let mut text2 = text_provider.text(node2);
                         let text1 = node_text1.get_text(&mut text1);
                         let text2 = node_text2.get_text(&mut text2);
-                        let is_positive_match = text1 == text2;
+                        let is_positive_match = if *case_insensitive {
+                            eq_ignore_case(text1, text2)
+                        } else {
+                            text1 == text2
+                        };
                         if is_positive_match != *is_positive && *match_all_nodes {
                             return false;
                         }
@@ -3431,12 +5110,22 @@ impl<'tree> QueryMatch<'_, 'tree> {
                     }
                     nodes_1.next().is_none() && nodes_2.next().is_none()
                 }
-                TextPredicateCapture::EqString(i, s, is_positive, match_all_nodes) => {
+                TextPredicateCapture::EqString(
+                    i,
+                    s,
+                    is_positive,
+                    match_all_nodes,
+                    case_insensitive,
+                ) => {
                     let nodes = self.nodes_for_capture_index(*i);
                     for node in nodes {
                         let mut text = text_provider.text(node);
                         let text = node_text1.get_text(&mut text);
-                        let is_positive_match = text == s.as_bytes();
+                        let is_positive_match = if *case_insensitive {
+                            eq_ignore_case(text, s.as_bytes())
+                        } else {
+                            text == s.as_bytes()
+                        };
                         if is_positive_match != *is_positive && *match_all_nodes {
                             return false;
                         }
@@ -3472,10 +5161,25 @@ impl<'tree> QueryMatch<'_, 'tree> {
                     }
                     true
                 }
+                TextPredicateCapture::NthChild(i, n) => self
+                    .nodes_for_capture_index(*i)
+                    .all(|node| node.is_nth_child(*n)),
             })
     }
 }
 
+/// Compare two texts after lowercasing them. Text that isn't valid UTF-8 is
+/// compared ignoring ASCII case only.
+fn eq_ignore_case(a: &[u8], b: &[u8]) -> bool {
+    match (str::from_utf8(a), str::from_utf8(b)) {
+        (Ok(a), Ok(b)) => a
+            .chars()
+            .flat_map(char::to_lowercase)
+            .eq(b.chars().flat_map(char::to_lowercase)),
+        _ => a.eq_ignore_ascii_case(b),
+    }
+}
+
 impl QueryProperty {
     #[must_use]
     pub fn new(key: &str, value: Option<&str>, capture_id: Option<usize>) -> Self {
@@ -3570,7 +5274,60 @@ impl<T: TextProvider<I>, I: AsRef<[u8]>> StreamingIteratorMut for QueryCaptures<
     }
 }
 
-impl<T: TextProvider<I>, I: AsRef<[u8]>> QueryMatches<'_, '_, T, I> {
+impl<'query, 'tree, T, I, F> StreamingIterator for FilteredQueryMatches<'query, 'tree, T, I, F>
+where
+    T: TextProvider<I>,
+    I: AsRef<[u8]>,
+    F: FnMut(&QueryMatch<'query, 'tree>, &mut T) -> bool,
+{
+    type Item = QueryMatch<'query, 'tree>;
+
+    fn advance(&mut self) {
+        loop {
+            self.matches.advance();
+            match &self.matches.current_match {
+                Some(m) if !(self.predicate)(m, &mut self.matches.text_provider) => {}
+                _ => break,
+            }
+        }
+    }
+
+    fn get(&self) -> Option<&Self::Item> {
+        self.matches.get()
+    }
+}
+
+impl<'query, 'tree, T, I, F> StreamingIteratorMut for FilteredQueryMatches<'query, 'tree, T, I, F>
+where
+    T: TextProvider<I>,
+    I: AsRef<[u8]>,
+    F: FnMut(&QueryMatch<'query, 'tree>, &mut T) -> bool,
+{
+    fn get_mut(&mut self) -> Option<&mut Self::Item> {
+        self.matches.get_mut()
+    }
+}
+
+impl<'query, 'tree, T: TextProvider<I>, I: AsRef<[u8]>> QueryMatches<'query, 'tree, T, I> {
+    /// Only yield the matches for which `predicate` returns `true`.
+    ///
+    /// This is checked after the query's own predicates, so it can be used to add
+    /// constraints that can't be expressed in the query language. The predicate is
+    /// given each match along with the text provider that was passed to
+    /// [`QueryCursor::matches`], so it can inspect the matched nodes' text.
+    pub const fn filter_matches<F>(
+        self,
+        predicate: F,
+    ) -> FilteredQueryMatches<'query, 'tree, T, I, F>
+    where
+        F: FnMut(&QueryMatch<'query, 'tree>, &mut T) -> bool,
+    {
+        FilteredQueryMatches {
+            matches: self,
+            predicate,
+        }
+    }
+
     #[doc(alias = "ts_query_cursor_set_byte_range")]
     pub fn set_byte_range(&mut self, range: ops::Range<usize>) {
         unsafe {
@@ -3777,7 +5534,28 @@ const fn predicate_error(row: usize, message: String) -> QueryError {
 
 impl fmt::Display for IncludedRangesError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "Incorrect range by index: {}", self.0)
+        let index = self.index;
+        match self.kind {
+            IncludedRangesErrorKind::EndBeforeStart => {
+                write!(f, "Included range {index} ends before it starts")
+            }
+            IncludedRangesErrorKind::OutOfOrder => {
+                write!(f, "Included range {index} starts before the previous range")
+            }
+            IncludedRangesErrorKind::Overlap => {
+                write!(f, "Included range {index} overlaps the previous range")
+            }
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Timeout => write!(f, "Parsing timed out"),
+            Self::NoLanguage => write!(f, "The parser has no language assigned"),
+            Self::Aborted => write!(f, "Parsing was aborted"),
+        }
     }
 }
 
@@ -3798,6 +5576,12 @@ impl fmt::Display for LanguageError {
     }
 }
 
+impl fmt::Display for SexpError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} at offset {}", self.message, self.offset)
+    }
+}
+
 impl fmt::Display for QueryError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         let msg = match self.kind {
@@ -3926,6 +5710,221 @@ pub fn format_sexp(sexp: &str, initial_indent_level: usize) -> String {
     formatted
 }
 
+/// A syntax tree that was parsed from an S-expression by [`parse_sexp`].
+///
+/// This is useful for comparing the structure of a [`Tree`] against an
+/// expected S-expression, such as the ones in a grammar's corpus tests,
+/// without comparing the text of the S-expressions directly. Displaying an
+/// `SexpTree` produces the same format as [`Node::to_sexp`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SexpTree {
+    /// The kind of the node. For unexpected characters, which are written as
+    /// `(UNEXPECTED 'c')`, this is `ERROR`.
+    pub kind: String,
+    /// The name of the field that this node is associated with in its parent.
+    pub field_name: Option<String>,
+    pub is_named: bool,
+    /// Whether the node was written as `(MISSING ...)`.
+    pub is_missing: bool,
+    /// For nodes written as `(UNEXPECTED 'c')`, the unexpected character, as
+    /// it was written.
+    pub unexpected: Option<String>,
+    pub children: Vec<Self>,
+}
+
+/// An error that occurred in [`parse_sexp`].
+#[derive(Debug, PartialEq, Eq)]
+pub struct SexpError {
+    /// The byte offset in the S-expression where the error occurred.
+    pub offset: usize,
+    pub message: String,
+}
+
+/// Parse an S-expression in the format produced by [`Node::to_sexp`].
+///
+/// Any amount of whitespace is allowed between nodes, so S-expressions that
+/// have been formatted across multiple lines can be parsed as well.
+pub fn parse_sexp(sexp: &str) -> Result<SexpTree, SexpError> {
+    let mut parser = SexpParser {
+        input: sexp,
+        offset: 0,
+    };
+    parser.skip_whitespace();
+    let tree = parser.parse_node(None)?;
+    parser.skip_whitespace();
+    if parser.offset < sexp.len() {
+        return Err(parser.error("Unexpected text after the end of the tree"));
+    }
+    Ok(tree)
+}
+
+struct SexpParser<'a> {
+    input: &'a str,
+    offset: usize,
+}
+
+impl<'a> SexpParser<'a> {
+    fn parse_node(&mut self, field_name: Option<String>) -> Result<SexpTree, SexpError> {
+        self.expect('(')?;
+        let mut node = SexpTree {
+            field_name,
+            ..Default::default()
+        };
+
+        if self.rest().starts_with('"') {
+            node.kind = self.parse_quoted_kind()?;
+        } else {
+            let word = self.parse_word();
+            match word {
+                "MISSING" => {
+                    node.is_missing = true;
+                    self.skip_whitespace();
+                    if self.rest().starts_with('"') {
+                        node.kind = self.parse_quoted_kind()?;
+                    } else {
+                        node.kind = self.parse_word().to_string();
+                        node.is_named = true;
+                    }
+                }
+                "UNEXPECTED" => {
+                    self.skip_whitespace();
+                    node.kind = "ERROR".to_string();
+                    node.is_named = true;
+                    node.unexpected = Some(self.parse_character()?.to_string());
+                }
+                _ => {
+                    node.kind = word.to_string();
+                    node.is_named = true;
+                }
+            }
+            if node.kind.is_empty() {
+                return Err(self.error("Expected a node kind"));
+            }
+        }
+
+        loop {
+            self.skip_whitespace();
+            let rest = self.rest();
+            if rest.starts_with(')') {
+                self.offset += 1;
+                return Ok(node);
+            }
+            if rest.is_empty() {
+                return Err(self.error("Unexpected end of input"));
+            }
+            let child_field_name = if rest.starts_with('(') {
+                None
+            } else {
+                let Some(name) = self
+                    .parse_word()
+                    .strip_suffix(':')
+                    .filter(|n| !n.is_empty())
+                else {
+                    return Err(self.error("Expected a child node or a field name"));
+                };
+                self.skip_whitespace();
+                Some(name.to_string())
+            };
+            node.children.push(self.parse_node(child_field_name)?);
+        }
+    }
+
+    /// Parse a quoted anonymous node kind, like `"("`. Because the kind itself
+    /// is not escaped, it ends at the last quote before the closing paren.
+    fn parse_quoted_kind(&mut self) -> Result<String, SexpError> {
+        let start = self.offset + 1;
+        let Some(len) = self
+            .input
+            .get(start + 1..)
+            .and_then(|rest| rest.find("\")"))
+        else {
+            return Err(self.error("Unterminated quoted node kind"));
+        };
+        self.offset = start + 1 + len + 1;
+        Ok(self.input[start..=start + len].to_string())
+    }
+
+    /// Parse an unexpected character, like `'c'`, `'\n'`, or a decimal code point.
+    fn parse_character(&mut self) -> Result<&'a str, SexpError> {
+        let rest = self.rest();
+        let len = if rest.starts_with("'\\") {
+            4
+        } else if rest.starts_with('\'') {
+            rest.char_indices().nth(2).map_or(0, |(i, _)| i + 1)
+        } else {
+            return Ok(self.parse_word());
+        };
+        match rest.get(..len) {
+            Some(character) if len > 2 && character.ends_with('\'') => {
+                self.offset += len;
+                Ok(character)
+            }
+            _ => Err(self.error("Invalid unexpected character")),
+        }
+    }
+
+    fn parse_word(&mut self) -> &'a str {
+        let rest = self.rest();
+        let len = rest
+            .find(|c: char| c.is_whitespace() || c == '(' || c == ')')
+            .unwrap_or(rest.len());
+        self.offset += len;
+        &rest[..len]
+    }
+
+    fn expect(&mut self, c: char) -> Result<(), SexpError> {
+        if self.rest().starts_with(c) {
+            self.offset += c.len_utf8();
+            Ok(())
+        } else {
+            Err(self.error(&format!("Expected '{c}'")))
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = self.rest();
+        self.offset += rest.len() - rest.trim_start().len();
+    }
+
+    fn rest(&self) -> &'a str {
+        &self.input[self.offset..]
+    }
+
+    fn error(&self, message: &str) -> SexpError {
+        SexpError {
+            offset: self.offset,
+            message: message.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for SexpTree {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(unexpected) = &self.unexpected {
+            write!(f, "(UNEXPECTED {unexpected}")?;
+        } else {
+            if self.is_missing {
+                write!(f, "(MISSING ")?;
+            } else {
+                write!(f, "(")?;
+            }
+            if self.is_named {
+                write!(f, "{}", self.kind)?;
+            } else {
+                write!(f, "\"{}\"", self.kind)?;
+            }
+        }
+        for child in &self.children {
+            write!(f, " ")?;
+            if let Some(field_name) = &child.field_name {
+                write!(f, "{field_name}: ")?;
+            }
+            write!(f, "{child}")?;
+        }
+        write!(f, ")")
+    }
+}
+
 pub fn wasm_stdlib_symbols() -> impl Iterator<Item = &'static str> {
     const WASM_STDLIB_SYMBOLS: &str = include_str!(concat!(env!("OUT_DIR"), "/stdlib-symbols.txt"));
 
@@ -3959,6 +5958,11 @@ pub struct Allocator {
 ///
 /// Pass `Some` to install an allocator, or `None` to restore libc defaults.
 ///
+/// The allocator is global: every parser, tree, query, and cursor in the process uses it, on
+/// every thread. This makes it suitable for accounting for or limiting the memory that
+/// tree-sitter uses, for example by wrapping the system allocator with functions that
+/// update a counter.
+///
 /// # Safety
 ///
 /// All of the following must hold:
@@ -3995,7 +5999,13 @@ impl error::Error for IncludedRangesError {}
 impl error::Error for LanguageError {}
 #[cfg(feature = "std")]
 #[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+impl error::Error for ParseError {}
+#[cfg(feature = "std")]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
 impl error::Error for QueryError {}
+#[cfg(feature = "std")]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+impl error::Error for SexpError {}
 
 unsafe impl Send for Language {}
 unsafe impl Sync for Language {}
@@ -0,0 +1,137 @@
+#[cfg(not(feature = "std"))]
+use alloc::string::{String, ToString};
+
+use crate::{LogType, Point};
+
+/// A message logged by a [`Parser`](crate::Parser), parsed into its fields.
+///
+/// Only the most common kinds of messages are recognized. The rest are passed through as
+/// [`ParseLogEvent::Raw`], and the exact wording of the recognized ones may change between
+/// versions of the library.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseLogEvent {
+    /// The parser started to look for a token in the given lex state, using either the
+    /// grammar's generated lexer or its external scanner.
+    Lex {
+        state: u16,
+        external: bool,
+        position: Point,
+    },
+    /// The lexer consumed a character as part of a token, or skipped it as whitespace.
+    Advance { character: i32, skip: bool },
+    /// The lexer found a token, which is `size` bytes long including any skipped whitespace.
+    Lookahead { symbol: String, size: u32 },
+    /// The parser shifted the lookahead token, moving to the given parse state.
+    Shift { state: u16 },
+    /// The parser shifted the lookahead token as an extra, without changing its parse state.
+    ShiftExtra,
+    /// The parser combined the given number of nodes into a node with the given symbol.
+    Reduce { symbol: String, child_count: u32 },
+    /// The parser finished the tree.
+    Accept,
+    /// A message that isn't one of the kinds above.
+    Raw(String),
+}
+
+impl ParseLogEvent {
+    /// Parse a message that was passed to a logger set with
+    /// [`Parser::set_logger`](crate::Parser::set_logger).
+    #[must_use]
+    pub fn parse(log_type: LogType, message: &str) -> Self {
+        let event = match log_type {
+            LogType::Parse => Self::parse_parser_message(message),
+            LogType::Lex => Self::parse_lexer_message(message),
+        };
+        event.unwrap_or_else(|| Self::Raw(message.to_string()))
+    }
+
+    fn parse_parser_message(message: &str) -> Option<Self> {
+        match message {
+            "shift_extra" => return Some(Self::ShiftExtra),
+            "accept" => return Some(Self::Accept),
+            _ => {}
+        }
+
+        let (name, fields) = message.split_once(' ')?;
+        match name {
+            "lex_internal" | "lex_external" => {
+                let [state, row, column] = fields_with_keys(fields, ["state", "row", "column"])?;
+                Some(Self::Lex {
+                    state: state.parse().ok()?,
+                    external: name == "lex_external",
+                    position: Point::new(row.parse().ok()?, column.parse().ok()?),
+                })
+            }
+            "shift" => Some(Self::Shift {
+                state: fields.strip_prefix("state:")?.parse().ok()?,
+            }),
+            // Symbol names can contain any characters, so the last field is split off first.
+            "reduce" => {
+                let (symbol, child_count) = fields.rsplit_once(", child_count:")?;
+                Some(Self::Reduce {
+                    symbol: symbol.strip_prefix("sym:")?.to_string(),
+                    child_count: child_count.parse().ok()?,
+                })
+            }
+            "lexed_lookahead" => {
+                let (symbol, size) = fields.rsplit_once(", size:")?;
+                Some(Self::Lookahead {
+                    symbol: unescape_symbol_name(symbol.strip_prefix("sym:")?),
+                    size: size.parse().ok()?,
+                })
+            }
+            _ => None,
+        }
+    }
+
+    fn parse_lexer_message(message: &str) -> Option<Self> {
+        let (name, character) = message.split_once(" character:")?;
+        let skip = match name {
+            "skip" => true,
+            "consume" => false,
+            _ => return None,
+        };
+        // Printable ASCII characters are quoted, and all others are written as numbers.
+        let character = match character
+            .strip_prefix('\'')
+            .and_then(|c| c.strip_suffix('\''))
+        {
+            Some(quoted) if quoted.len() == 1 => i32::from(quoted.as_bytes()[0]),
+            Some(_) => return None,
+            None => character.parse().ok()?,
+        };
+        Some(Self::Advance { character, skip })
+    }
+}
+
+/// Split a list of `key:value` fields, which must have exactly the given keys in order.
+fn fields_with_keys<'a, const N: usize>(fields: &'a str, keys: [&str; N]) -> Option<[&'a str; N]> {
+    let mut fields = fields.split(", ");
+    let mut values = [""; N];
+    for (value, key) in values.iter_mut().zip(keys) {
+        *value = fields.next()?.strip_prefix(key)?.strip_prefix(':')?;
+    }
+    fields.next().is_none().then_some(values)
+}
+
+/// Undo the escaping of whitespace and backslashes in the symbol names of lookahead messages.
+fn unescape_symbol_name(name: &str) -> String {
+    let mut result = String::with_capacity(name.len());
+    let mut chars = name.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            result.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('t') => result.push('\t'),
+            Some('n') => result.push('\n'),
+            Some('v') => result.push('\u{b}'),
+            Some('f') => result.push('\u{c}'),
+            Some('r') => result.push('\r'),
+            Some(c) => result.push(c),
+            None => result.push('\\'),
+        }
+    }
+    result
+}
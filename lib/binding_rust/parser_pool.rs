@@ -0,0 +1,178 @@
+use std::{
+    num::NonZero,
+    ops::{Deref, DerefMut},
+    sync::{Arc, Mutex, MutexGuard, PoisonError},
+    thread,
+    time::{Duration, Instant},
+};
+
+use crate::{Language, LanguageError, LogType, Logger, Parser, Tree};
+
+type SharedLogger = Arc<dyn Fn(LogType, &str) + Send + Sync>;
+
+/// A set of [`Parser`]s that are configured in the same way, which can be shared between
+/// threads to parse many documents without setting up a new parser for each one.
+///
+/// Parsers are created as they are needed, and are returned to the pool when the
+/// [`PooledParser`] that holds one is dropped. Each parser is reset before it is returned, so
+/// that no state from one document carries over to the next.
+pub struct ParserPool {
+    language: Language,
+    timeout: Option<Duration>,
+    logger: Option<SharedLogger>,
+    capacity: usize,
+    parsers: Mutex<Vec<Parser>>,
+}
+
+/// A [`Parser`] that has been taken from a [`ParserPool`], and is returned to it when this is
+/// dropped.
+pub struct PooledParser<'a> {
+    pool: &'a ParserPool,
+    parser: Option<Parser>,
+}
+
+impl ParserPool {
+    /// Create a pool of parsers for the given language.
+    ///
+    /// By default, the pool keeps as many idle parsers as the number of threads that can run in
+    /// parallel.
+    ///
+    /// Returns an error if the language can't be used by a parser, for the same reasons as
+    /// [`Parser::set_language`].
+    pub fn new(language: &Language) -> Result<Self, LanguageError> {
+        let mut parser = Parser::new();
+        parser.set_language(language)?;
+        Ok(Self {
+            language: language.clone(),
+            timeout: None,
+            logger: None,
+            capacity: thread::available_parallelism().map_or(1, NonZero::get),
+            parsers: Mutex::new(vec![parser]),
+        })
+    }
+
+    /// Set the maximum number of idle parsers that the pool keeps. More parsers are created if
+    /// they are all in use, but the extra ones are dropped when they are returned.
+    #[must_use]
+    pub fn capacity(mut self, capacity: usize) -> Self {
+        self.capacity = capacity;
+        self.parsers
+            .get_mut()
+            .unwrap_or_else(PoisonError::into_inner)
+            .truncate(capacity);
+        self
+    }
+
+    /// Set the maximum amount of time that [`ParserPool::parse`] may take for each document.
+    #[must_use]
+    pub const fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Set the logging callback that every parser in the pool should use during parsing.
+    #[must_use]
+    pub fn logger(mut self, logger: impl Fn(LogType, &str) + Send + Sync + 'static) -> Self {
+        let logger: SharedLogger = Arc::new(logger);
+        for parser in self
+            .parsers
+            .get_mut()
+            .unwrap_or_else(PoisonError::into_inner)
+        {
+            parser.set_logger(Some(boxed_logger(&logger)));
+        }
+        self.logger = Some(logger);
+        self
+    }
+
+    /// Get the language that the pool's parsers use.
+    #[must_use]
+    pub const fn language(&self) -> &Language {
+        &self.language
+    }
+
+    /// Take an idle parser from the pool, or create a new one if none are idle.
+    ///
+    /// The parser is returned to the pool when the guard is dropped, unless it was given a
+    /// different language. Its included ranges are cleared, but other settings that are changed
+    /// through the guard, such as the logger, are kept.
+    #[must_use]
+    pub fn get(&self) -> PooledParser<'_> {
+        let parser = self
+            .idle_parsers()
+            .pop()
+            .unwrap_or_else(|| self.new_parser());
+        PooledParser {
+            pool: self,
+            parser: Some(parser),
+        }
+    }
+
+    /// Parse a slice of UTF8 text with one of the pool's parsers, as with [`Parser::parse`].
+    ///
+    /// Returns `None` if the pool has a timeout and parsing takes longer than that.
+    pub fn parse(&self, text: impl AsRef<[u8]>, old_tree: Option<&Tree>) -> Option<Tree> {
+        let mut parser = self.get();
+        match self.timeout {
+            Some(timeout) => parser
+                .parse_with_deadline(text, old_tree, Instant::now() + timeout)
+                .ok(),
+            None => parser.parse(text, old_tree),
+        }
+    }
+
+    fn idle_parsers(&self) -> MutexGuard<'_, Vec<Parser>> {
+        // The list of parsers is never left in an inconsistent state, so it's still usable if
+        // another thread panicked while holding the lock.
+        self.parsers.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn new_parser(&self) -> Parser {
+        let mut parser = Parser::new();
+        // The language was checked when the pool was created.
+        parser.set_language(&self.language).unwrap();
+        parser.set_logger(self.logger.as_ref().map(boxed_logger));
+        parser
+    }
+}
+
+fn boxed_logger(logger: &SharedLogger) -> Logger<'static> {
+    let logger = logger.clone();
+    Box::new(move |log_type, message| logger(log_type, message))
+}
+
+impl Deref for PooledParser<'_> {
+    type Target = Parser;
+
+    fn deref(&self) -> &Parser {
+        self.parser.as_ref().unwrap()
+    }
+}
+
+impl DerefMut for PooledParser<'_> {
+    fn deref_mut(&mut self) -> &mut Parser {
+        self.parser.as_mut().unwrap()
+    }
+}
+
+impl Drop for PooledParser<'_> {
+    fn drop(&mut self) {
+        let Some(mut parser) = self.parser.take() else {
+            return;
+        };
+        if parser
+            .language()
+            .is_none_or(|language| *language != self.pool.language)
+        {
+            return;
+        }
+
+        parser.reset();
+        // Clearing the included ranges can't fail.
+        parser.set_included_ranges(&[]).unwrap();
+        let mut parsers = self.pool.idle_parsers();
+        if parsers.len() < self.pool.capacity {
+            parsers.push(parser);
+        }
+    }
+}
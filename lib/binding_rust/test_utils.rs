@@ -0,0 +1,135 @@
+//! Utilities for testing grammars from Rust.
+
+use core::ops::{self, ControlFlow};
+
+use crate::{Descend, InputEdit, Language, Node, Parser, Point, Tree};
+
+/// An edit to a document, which replaces a byte range with new text.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SourceEdit<'a> {
+    /// The byte range to replace, in the text as it was before this edit.
+    pub range: ops::Range<usize>,
+    /// The text to insert in place of the range.
+    pub text: &'a str,
+}
+
+/// Check that a language's external scanner restores its state correctly when a tree is
+/// reparsed after some edits.
+///
+/// The source is parsed, and then the edits are applied in order. After each edit, the
+/// previous tree is edited to match and the text is reparsed incrementally. When the parser
+/// reuses part of the previous tree, the external scanner resumes from the state that it
+/// serialized after the last reused external token, so the reparsed tree only matches a
+/// fresh parse of the same text if the scanner's `deserialize` function restores exactly what
+/// its `serialize` function wrote. This catches scanners that keep state which they don't
+/// serialize, or that don't reset their state when `deserialize` is called with no data.
+///
+/// The parser deserializes the scanner's state before every external token, even in a fresh
+/// parse, so a scanner that misreads its state in the same way every time affects both trees
+/// alike, and isn't caught. For the best coverage, choose edits that start or end inside of the
+/// constructs that the scanner tracks.
+///
+/// # Panics
+///
+/// Panics if the language can't be used by a [`Parser`], if an edit's range isn't valid for
+/// the current text, or if a reparsed tree differs from a fresh parse. In that case, the
+/// message names the edit and the first node at which the two trees differ.
+pub fn assert_scanner_roundtrip(language: &Language, source: &str, edits: &[SourceEdit]) {
+    let new_parser = || {
+        let mut parser = Parser::new();
+        parser
+            .set_language(language)
+            .expect("Incompatible language version");
+        parser
+    };
+    let mut parser = new_parser();
+
+    let mut text = source.to_string();
+    let mut tree = parser.parse(&text, None).unwrap();
+    for (i, edit) in edits.iter().enumerate() {
+        assert!(
+            edit.range.start <= edit.range.end
+                && text.is_char_boundary(edit.range.start)
+                && text.is_char_boundary(edit.range.end),
+            "Edit {i} has an invalid range {:?} for text of length {}",
+            edit.range,
+            text.len(),
+        );
+        let start_position = point_for_offset(&text, edit.range.start);
+        let old_end_position = point_for_offset(&text, edit.range.end);
+        text.replace_range(edit.range.clone(), edit.text);
+        let new_end_byte = edit.range.start + edit.text.len();
+        tree.edit(&InputEdit {
+            start_byte: edit.range.start,
+            old_end_byte: edit.range.end,
+            new_end_byte,
+            start_position,
+            old_end_position,
+            new_end_position: point_for_offset(&text, new_end_byte),
+        });
+
+        let reparsed_tree = parser.parse(&text, Some(&tree)).unwrap();
+        // The fresh parse uses a new parser, so that its scanner can't start out with any state
+        // left over from earlier parses.
+        let fresh_tree = new_parser().parse(&text, None).unwrap();
+        if let Some((expected, actual)) = first_difference(&fresh_tree, &reparsed_tree) {
+            panic!(
+                "After edit {i}, the reparsed tree differs from a fresh parse of the same text, \
+                 which suggests that the external scanner doesn't deserialize the state that it \
+                 serialized.\n\
+                 Expected: {}\n\
+                 Actual:   {}",
+                describe(expected),
+                describe(actual),
+            );
+        }
+        tree = reparsed_tree;
+    }
+}
+
+fn point_for_offset(text: &str, offset: usize) -> Point {
+    let before = &text[..offset];
+    let row = before.matches('\n').count();
+    let column = before
+        .rfind('\n')
+        .map_or(offset, |newline| offset - newline - 1);
+    Point::new(row, column)
+}
+
+/// Find the first node, in depth-first order, at which the two trees differ. If one tree
+/// has fewer nodes, the missing node is reported as `None`.
+fn first_difference<'a>(
+    expected: &'a Tree,
+    actual: &'a Tree,
+) -> Option<(Option<Node<'a>>, Option<Node<'a>>)> {
+    let nodes = |tree: &'a Tree| {
+        let mut nodes = Vec::new();
+        let _: ControlFlow<()> = tree.root_node().for_each_descendant(|node, depth| {
+            nodes.push((node, depth));
+            ControlFlow::Continue(Descend::Children)
+        });
+        nodes
+    };
+    let expected = nodes(expected);
+    let actual = nodes(actual);
+    let same = |(a, a_depth): &(Node, usize), (b, b_depth): &(Node, usize)| {
+        a_depth == b_depth
+            && a.kind_id() == b.kind_id()
+            && a.is_missing() == b.is_missing()
+            && a.byte_range() == b.byte_range()
+    };
+    (0..expected.len().max(actual.len())).find_map(|i| {
+        let (a, b) = (expected.get(i), actual.get(i));
+        match (a, b) {
+            (Some(a), Some(b)) if same(a, b) => None,
+            _ => Some((a.map(|(node, _)| *node), b.map(|(node, _)| *node))),
+        }
+    })
+}
+
+fn describe(node: Option<Node>) -> String {
+    node.map_or_else(
+        || "no node".to_string(),
+        |node| format!("{} at bytes {:?}", node.to_sexp(), node.byte_range()),
+    )
+}
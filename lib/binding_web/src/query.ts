@@ -194,8 +194,8 @@ const isStringStep = (step: PredicateStep): step is Extract<PredicateStep, { typ
  * @internal
  *
  * A function that checks if a given set of captures matches a particular
- * condition. This is used in the built-in `eq?`, `match?`, and `any-of?`
- * predicates.
+ * condition. This is used in the built-in `eq?`, `match?`, `any-of?`, and
+ * `nth-child?` predicates.
  */
 export type TextPredicate = (captures: QueryCapture[]) => boolean;
 
@@ -307,7 +307,8 @@ function parseAnyPredicate(
 }
 
 /**
- * Parses the `match?` and `not-match?` predicates in a query, and updates the text predicates.
+ * Parses the `match?` and `not-match?` predicates (and their case-insensitive `-ci` variants)
+ * in a query, and updates the text predicates.
  */
 function parseMatchPredicate(
   steps: PredicateStep[],
@@ -333,10 +334,12 @@ function parseMatchPredicate(
     );
   }
 
-  const isPositive = operator === 'match?' || operator === 'any-match?';
+  const caseInsensitive = operator.endsWith('-ci?');
+  const baseOperator = caseInsensitive ? operator.slice(0, -4) + '?' : operator;
+  const isPositive = baseOperator === 'match?' || baseOperator === 'any-match?';
   const matchAll = !operator.startsWith('any-');
   const captureName = steps[1].name;
-  const regex = new RegExp(steps[2].value);
+  const regex = new RegExp(steps[2].value, caseInsensitive ? 'i' : undefined);
   textPredicates[index].push((captures) => {
     const nodes = [];
     for (const c of captures) {
@@ -396,6 +399,47 @@ function parseAnyOfPredicate(
   });
 }
 
+/**
+ * Parses the `nth-child?` predicate in a query, and updates the text predicates.
+ */
+function parseNthChildPredicate(
+  steps: PredicateStep[],
+  index: number,
+  operator: string,
+  textPredicates: TextPredicate[][],
+) {
+  if (steps.length !== 3) {
+    throw new Error(
+      `Wrong number of arguments to \`#${operator}\` predicate. Expected 2, got ${steps.length - 1}.`,
+    );
+  }
+
+  if (steps[1].type !== 'capture') {
+    throw new Error(
+      `First argument of \`#${operator}\` predicate must be a capture. Got "${steps[1].value}".`,
+    );
+  }
+
+  if (steps[2].type !== 'string' || !/^-?\d+$/.test(steps[2].value)) {
+    throw new Error(
+      `Second argument of \`#${operator}\` predicate must be an integer.`,
+    );
+  }
+
+  const captureName = steps[1].name;
+  const position = Number(steps[2].value);
+  textPredicates[index].push((captures) => {
+    return captures.every((c) => {
+      if (c.name !== captureName) return true;
+      const parent = c.node.parent;
+      if (!parent) return false;
+      const siblings = c.node.isNamed ? parent.namedChildren : parent.children;
+      const i = siblings.findIndex((sibling) => sibling.id === c.node.id);
+      return position < 0 ? i === siblings.length + position : i === position;
+    });
+  });
+}
+
 /**
  * Parses the `is?` and `is-not?` predicates in a query, and updates the asserted or refuted properties,
  * depending on if the operator is positive or negative.
@@ -482,6 +526,10 @@ function parsePattern(
       case 'not-match?':
       case 'any-match?':
       case 'match?':
+      case 'any-not-match-ci?':
+      case 'not-match-ci?':
+      case 'any-match-ci?':
+      case 'match-ci?':
         parseMatchPredicate(steps, index, operator, textPredicates);
         break;
 
@@ -490,6 +538,10 @@ function parsePattern(
         parseAnyOfPredicate(steps, index, operator, textPredicates);
         break;
 
+      case 'nth-child?':
+        parseNthChildPredicate(steps, index, operator, textPredicates);
+        break;
+
       case 'is?':
       case 'is-not?':
         parseIsPredicate(steps, index, operator, assertedProperties, refutedProperties);
@@ -531,7 +583,7 @@ export class Query {
    * The other user-defined predicates associated with the given index.
    *
    * This includes predicates with operators other than:
-   * - `match?`
+   * - `match?` and `match-ci?`
    * - `eq?` and `not-eq?`
    * - `any-of?` and `not-any-of?`
    * - `is?` and `is-not?`
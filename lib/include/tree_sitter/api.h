@@ -104,6 +104,17 @@ typedef struct TSParseOptions {
   bool (*progress_callback)(TSParseState *state);
 } TSParseOptions;
 
+typedef struct TSParseStats {
+  uint32_t reused_node_count;
+  uint32_t lexed_token_count;
+  uint32_t error_recovery_count;
+} TSParseStats;
+
+typedef enum TSErrorRecoveryMode {
+  TSErrorRecoveryModeDefault,
+  TSErrorRecoveryModeConservative,
+} TSErrorRecoveryMode;
+
 typedef enum TSLogType {
   TSLogTypeParse,
   TSLogTypeLex,
@@ -173,6 +184,16 @@ typedef struct TSQueryPredicateStep {
   uint32_t value_id;
 } TSQueryPredicateStep;
 
+typedef struct TSQueryPatternStep {
+  TSSymbol symbol;
+  TSFieldId field;
+  uint16_t depth;
+  uint16_t capture_ids[3];
+  uint8_t capture_count;
+  bool is_named;
+  bool is_optional;
+} TSQueryPatternStep;
+
 typedef enum TSQueryError {
   TSQueryErrorNone = 0,
   TSQueryErrorSyntax,
@@ -400,6 +421,73 @@ TSLogger ts_parser_logger(const TSParser *self);
  */
 void ts_parser_print_dot_graphs(TSParser *self, int fd);
 
+/**
+ * Get statistics about the parser's most recent parse.
+ *
+ * The counters are reset whenever a new parse begins, and accumulate across
+ * calls that resume a parse which was halted by the progress callback:
+ * 1. `reused_node_count`: The number of visible nodes reused from the old tree.
+ * 2. `lexed_token_count`: The number of tokens produced by the lexer.
+ * 3. `error_recovery_count`: The number of times the parser had to handle a
+ *    syntax error.
+ */
+TSParseStats ts_parser_parse_stats(const TSParser *self);
+
+/**
+ * Set whether the parser may insert `MISSING` nodes when recovering from
+ * syntax errors.
+ *
+ * 1. `TSErrorRecoveryModeDefault`: The parser may insert zero-width `MISSING`
+ *    nodes for tokens that would make the input valid, in addition to skipping
+ *    invalid text by wrapping it in `ERROR` nodes.
+ * 2. `TSErrorRecoveryModeConservative`: The parser never inserts `MISSING`
+ *    nodes. This yields larger `ERROR` nodes, but no structure that depends on
+ *    tokens that aren't actually present in the input.
+ *
+ * This is the only difference between the modes. The rest of error recovery,
+ * such as skipping tokens and choosing between recovery strategies by their
+ * cost, works the same way in both.
+ *
+ * The mode takes effect for the next call to one of the parsing functions.
+ */
+void ts_parser_set_error_recovery_mode(TSParser *self, TSErrorRecoveryMode mode);
+
+/**
+ * Get the parser's current error recovery mode.
+ */
+TSErrorRecoveryMode ts_parser_error_recovery_mode(const TSParser *self);
+
+/**
+ * Set the symbols of extra nodes, like comments, that the trees produced by
+ * the parser should not report as extra.
+ *
+ * This override only affects reporting. The parser still shifts these nodes as
+ * extras, because extras are compiled into the language's parse table, so the
+ * shape of the tree is unchanged: the nodes can still appear anywhere, and are
+ * never stored in a field. Only the result of [`ts_node_is_extra`] changes for
+ * them, so that code which skips extra nodes treats them like the rest of the
+ * tree.
+ *
+ * The parser does *not* take ownership of the symbols; it copies them. Pass a
+ * `count` of zero to remove the override. The override takes effect for the
+ * next call to one of the parsing functions.
+ */
+void ts_parser_set_extra_override(
+  TSParser *self,
+  const TSSymbol *symbols,
+  uint32_t count
+);
+
+/**
+ * Get the symbols of extra nodes that the parser's trees do not report as
+ * extra, in ascending order.
+ *
+ * The returned pointer is owned by the parser. The caller should not free it
+ * or write to it. The length of the array will be written to the given
+ * `count` pointer.
+ */
+const TSSymbol *ts_parser_extra_override(const TSParser *self, uint32_t *count);
+
 /******************/
 /* Section - Tree */
 /******************/
@@ -571,6 +659,9 @@ bool ts_node_is_missing(TSNode self);
 /**
  * Check if the node is *extra*. Extra nodes represent things like comments,
  * which are not required by the grammar, but can appear anywhere.
+ *
+ * Nodes whose symbols were passed to [`ts_parser_set_extra_override`] when the
+ * tree was parsed are not reported as extra.
  */
 bool ts_node_is_extra(TSNode self);
 
@@ -589,6 +680,16 @@ bool ts_node_has_error(TSNode self);
 */
 bool ts_node_is_error(TSNode self);
 
+/**
+ * Get the cost that the parser assigned to the syntax errors within the node.
+ *
+ * The cost grows with the number of characters, lines, and nodes that were
+ * skipped, and with the number of missing nodes that were inserted, in order
+ * to recover from errors. It is zero if the node doesn't contain any errors.
+ * This can be used to rank errors by how much of the input they affect.
+*/
+uint32_t ts_node_error_cost(TSNode self);
+
 /**
  * Get this node's parse state.
 */
@@ -694,6 +795,16 @@ TSNode ts_node_first_named_child_for_byte(TSNode self, uint32_t byte);
  */
 uint32_t ts_node_descendant_count(TSNode self);
 
+/**
+ * Get an identifier for the subtree that backs the node, which stays the same
+ * when that subtree is reused by an incremental parse, even if the node's
+ * position or parent changes.
+ *
+ * Small leaf nodes are stored inline within their parent, and have no such
+ * identifier. For these, this returns `NULL`.
+ */
+const void *ts_node_stable_id(TSNode self);
+
 /**
  * Get the smallest node within this node that spans the given range of bytes
  * or (row, column) positions.
@@ -928,6 +1039,11 @@ void ts_query_delete(TSQuery *self);
  */
 TSQuery *ts_query_copy(const TSQuery *self);
 
+/**
+ * Get the language that the query was created for.
+ */
+const TSLanguage *ts_query_language(const TSQuery *self);
+
 /**
  * Get the number of patterns, captures, or string literals in the query.
  */
@@ -994,6 +1110,34 @@ bool ts_query_is_pattern_non_local(const TSQuery *self, uint32_t pattern_index);
  */
 bool ts_query_is_pattern_guaranteed_at_step(const TSQuery *self, uint32_t byte_offset);
 
+/**
+ * Check if the query could be affected by anonymous nodes, either because one
+ * of its patterns can match an anonymous node, or because it uses an anchor or
+ * quantifier whose behavior depends on anonymous siblings.
+ *
+ * Queries for which this returns `false` can skip anonymous nodes entirely.
+ * See [`ts_query_cursor_set_skip_anonymous_nodes`].
+ */
+bool ts_query_matches_anonymous_nodes(const TSQuery *self);
+
+/**
+ * Get the node steps of a given pattern in the query.
+ *
+ * Each node in the pattern's S-expression corresponds to one step, in the
+ * order in which the nodes appear. The `symbol` of a wildcard step is zero,
+ * and `is_optional` is set for steps that are part of an alternation or a
+ * quantifier that allows them to be skipped.
+ *
+ * Returns the number of steps in the pattern, and writes up to `length` of
+ * them into the `steps` buffer, which may be `NULL`.
+ */
+uint32_t ts_query_steps_for_pattern(
+  const TSQuery *self,
+  uint32_t pattern_index,
+  TSQueryPatternStep *steps,
+  uint32_t length
+);
+
 /**
  * Get the name and length of one of the query's captures, or one of the
  * query's string literals. Each capture and string is associated with a
@@ -1202,6 +1346,16 @@ bool ts_query_cursor_next_capture(
  */
 void ts_query_cursor_set_max_start_depth(TSQueryCursor *self, uint32_t max_start_depth);
 
+/**
+ * Set whether a query cursor should skip over anonymous leaf nodes.
+ *
+ * This is a hint: it only takes effect for queries that can't be affected by
+ * anonymous nodes, as reported by [`ts_query_matches_anonymous_nodes`], so the
+ * matches are the same either way. For such queries, skipping the anonymous
+ * tokens can make the traversal substantially faster on token-dense trees.
+ */
+void ts_query_cursor_set_skip_anonymous_nodes(TSQueryCursor *self, bool skip);
+
 /**********************/
 /* Section - Language */
 /**********************/
@@ -1257,6 +1411,15 @@ TSFieldId ts_language_field_id_for_name(const TSLanguage *self, const char *name
 */
 const TSSymbol *ts_language_supertypes(const TSLanguage *self, uint32_t *length);
 
+/**
+ * Get a list of the symbols for the language's external tokens.
+ *
+ * The symbols are ordered by their index in the grammar's `externals` array,
+ * which is also the order of the `valid_symbols` array that is passed to the
+ * external scanner.
+*/
+const TSSymbol *ts_language_external_tokens(const TSLanguage *self, uint32_t *length);
+
 /**
  * Get a list of all subtype symbol ids for a given supertype symbol.
  *
@@ -1303,9 +1466,37 @@ const TSLanguageMetadata *ts_language_metadata(const TSLanguage *self);
  * Get the next parse state. Combine this with lookahead iterators to generate
  * completion suggestions or valid symbols in error nodes. Use
  * [`ts_node_grammar_symbol`] for valid symbols.
+ *
+ * Returns 0 if the symbol can't follow the given state, or if either of them
+ * is not valid for the language.
 */
 TSStateId ts_language_next_state(const TSLanguage *self, TSStateId state, TSSymbol symbol);
 
+/**
+ * Run the language's generated lexer on the given UTF8 string, as the parser
+ * would when looking for a token in the given parse state.
+ *
+ * If a token is found, this returns `true` and writes the token's symbol and
+ * byte range to the given pointers. The symbol is the token's grammar symbol,
+ * as returned by [`ts_node_grammar_symbol`], and is `0` at the end of the
+ * string. Any leading whitespace and other skipped characters are excluded
+ * from the range. The language's external scanner,
+ * if it has one, is never called. This always returns `false` for Wasm
+ * languages.
+ *
+ * This is useful for testing a grammar's tokenization independently of
+ * parsing.
+ */
+bool ts_language_lex(
+  const TSLanguage *self,
+  TSStateId state,
+  const char *string,
+  uint32_t length,
+  TSSymbol *symbol,
+  uint32_t *start_byte,
+  uint32_t *end_byte
+);
+
 /**
  * Get the name of this language. This returns `NULL` in older parsers.
  */
@@ -1,4 +1,5 @@
 #include "./language.h"
+#include "./lexer.h"
 #include "./wasm_store.h"
 #include "tree_sitter/api.h"
 #include <string.h>
@@ -34,6 +35,11 @@ const TSSymbol *ts_language_supertypes(const TSLanguage *self, uint32_t *length)
   }
 }
 
+const TSSymbol *ts_language_external_tokens(const TSLanguage *self, uint32_t *length) {
+  *length = self->external_token_count;
+  return self->external_token_count ? self->external_scanner.symbol_map : NULL;
+}
+
 const TSSymbol *ts_language_subtypes(
   const TSLanguage *self,
   TSSymbol supertype,
@@ -146,6 +152,8 @@ TSStateId ts_language_next_state(
 ) {
   if (symbol == ts_builtin_sym_error || symbol == ts_builtin_sym_error_repeat) {
     return 0;
+  } else if (state >= self->state_count || symbol >= self->symbol_count) {
+    return 0;
   } else if (symbol < self->token_count) {
     uint32_t count;
     const TSParseAction *actions = ts_language_actions(self, state, symbol, &count);
@@ -161,6 +169,85 @@ TSStateId ts_language_next_state(
   }
 }
 
+typedef struct {
+  const char *string;
+  uint32_t length;
+} TSLanguageLexInput;
+
+static const char *ts_language__lex_input_read(
+  void *payload,
+  uint32_t byte,
+  TSPoint point,
+  uint32_t *bytes_read
+) {
+  (void)point;
+  TSLanguageLexInput *self = payload;
+  if (byte >= self->length) {
+    *bytes_read = 0;
+    return "";
+  }
+  *bytes_read = self->length - byte;
+  return self->string + byte;
+}
+
+bool ts_language_lex(
+  const TSLanguage *self,
+  TSStateId state,
+  const char *string,
+  uint32_t length,
+  TSSymbol *symbol,
+  uint32_t *start_byte,
+  uint32_t *end_byte
+) {
+  if (ts_language_is_wasm(self) || state >= self->state_count) return false;
+  TSLexerMode lex_mode = ts_language_lex_mode_for_state(self, state);
+  if (lex_mode.lex_state == (uint16_t)-1) return false;
+
+  TSLanguageLexInput input = {string, length};
+  Lexer lexer;
+  ts_lexer_init(&lexer);
+  ts_lexer_set_input(&lexer, (TSInput) {
+    .payload = &input,
+    .read = ts_language__lex_input_read,
+    .encoding = TSInputEncodingUTF8,
+    .decode = NULL,
+  });
+
+  uint32_t lookahead_end_byte = 0;
+  ts_lexer_start(&lexer);
+  bool found_token = self->lex_fn(&lexer.data, lex_mode.lex_state);
+  ts_lexer_finish(&lexer, &lookahead_end_byte);
+
+  if (found_token) {
+    TSSymbol result = lexer.data.result_symbol;
+    Length token_start = lexer.token_start_position;
+    uint32_t token_end_byte = lexer.token_end_position.bytes;
+
+    // Like the parser, check if a word token is really a keyword.
+    if (result == self->keyword_capture_token && result != 0) {
+      ts_lexer_reset(&lexer, token_start);
+      ts_lexer_start(&lexer);
+      if (
+        self->keyword_lex_fn(&lexer.data, 0) &&
+        lexer.token_end_position.bytes == token_end_byte &&
+        (
+          ts_language_has_actions(self, state, lexer.data.result_symbol) ||
+          ts_language_is_reserved_word(self, state, lexer.data.result_symbol)
+        )
+      ) {
+        result = lexer.data.result_symbol;
+      }
+    }
+
+    *symbol = result;
+    *start_byte = token_start.bytes;
+    *end_byte = token_end_byte;
+  }
+
+  ts_lexer_delete(&lexer);
+  return found_token;
+}
+
 const char *ts_language_symbol_name(
   const TSLanguage *self,
   TSSymbol symbol
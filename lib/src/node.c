@@ -499,7 +499,9 @@ bool ts_node_is_null(TSNode self) {
 }
 
 bool ts_node_is_extra(TSNode self) {
-  return ts_subtree_extra(ts_node__subtree(self));
+  return
+    ts_subtree_extra(ts_node__subtree(self)) &&
+    !ts_tree_has_extra_override(self.tree, ts_node_symbol(self));
 }
 
 bool ts_node_is_named(TSNode self) {
@@ -526,10 +528,19 @@ bool ts_node_is_error(TSNode self) {
   return symbol == ts_builtin_sym_error;
 }
 
+uint32_t ts_node_error_cost(TSNode self) {
+  return ts_subtree_error_cost(ts_node__subtree(self));
+}
+
 uint32_t ts_node_descendant_count(TSNode self) {
   return ts_subtree_visible_descendant_count(ts_node__subtree(self)) + 1;
 }
 
+const void *ts_node_stable_id(TSNode self) {
+  Subtree subtree = ts_node__subtree(self);
+  return subtree.data.is_inline ? NULL : subtree.ptr;
+}
+
 TSStateId ts_node_parse_state(TSNode self) {
   return ts_subtree_parse_state(ts_node__subtree(self));
 }
@@ -700,7 +711,7 @@ const char *ts_node_field_name_for_child(TSNode self, uint32_t child_index) {
     while (ts_node_child_iterator_next(&iterator, &child)) {
       if (ts_node__is_relevant(child, true)) {
         if (index == child_index) {
-          if (ts_node_is_extra(child)) {
+          if (ts_subtree_extra(ts_node__subtree(child))) {
             return NULL;
           }
           const char *field_name = ts_node__field_name_from_language(result, iterator.structural_child_index - 1);
@@ -742,7 +753,7 @@ const char *ts_node_field_name_for_named_child(TSNode self, uint32_t named_child
     while (ts_node_child_iterator_next(&iterator, &child)) {
       if (ts_node__is_relevant(child, false)) {
         if (index == named_child_index) {
-          if (ts_node_is_extra(child)) {
+          if (ts_subtree_extra(ts_node__subtree(child))) {
             return NULL;
           }
           const char *field_name = ts_node__field_name_from_language(result, iterator.structural_child_index - 1);
@@ -105,8 +105,11 @@ struct TSParser {
   unsigned operation_count;
   Subtree old_tree;
   TSRangeArray included_range_differences;
+  Array(TSSymbol) extra_override;
   TSParseOptions parse_options;
   TSParseState parse_state;
+  TSParseStats parse_stats;
+  TSErrorRecoveryMode error_recovery_mode;
   unsigned included_range_difference_index;
   bool has_scanner_error;
   bool canceled_balancing;
@@ -697,6 +700,7 @@ static Subtree ts_parser__lex(
     SYM_NAME(ts_subtree_symbol(result)),
     ts_subtree_total_size(result).bytes
   );
+  self->parse_stats.lexed_token_count++;
   return result;
 }
 
@@ -828,6 +832,8 @@ static Subtree ts_parser__reuse_node(
     }
 
     LOG("reuse_node symbol:%s", TREE_NAME(result));
+    self->parse_stats.reused_node_count +=
+      ts_subtree_visible_descendant_count(result) + ts_subtree_visible(result);
     ts_subtree_retain(result);
     return result;
   }
@@ -1473,6 +1479,7 @@ static void ts_parser__handle_error(
   Subtree lookahead
 ) {
   uint32_t previous_version_count = ts_stack_version_count(self->stack);
+  self->parse_stats.error_recovery_count++;
 
   // Perform any reductions that can happen in this state, regardless of the lookahead. After
   // skipping one or more invalid tokens, the parser might find a token that would have allowed
@@ -1483,9 +1490,11 @@ static void ts_parser__handle_error(
 
   // Push a discontinuity onto the stack. Merge all of the stack versions that
   // were created in the previous step.
+  // In the conservative mode, never guess at tokens which aren't present in the input.
+  bool can_insert_missing_token = self->error_recovery_mode != TSErrorRecoveryModeConservative;
   bool did_insert_missing_token = false;
   for (StackVersion v = version; v < version_count;) {
-    if (!did_insert_missing_token) {
+    if (can_insert_missing_token && !did_insert_missing_token) {
       TSStateId state = ts_stack_state(self->stack, v);
       for (
         TSSymbol missing_symbol = 1;
@@ -1982,6 +1991,8 @@ TSParser *ts_parser_new(void) {
   self->old_tree = NULL_SUBTREE;
   self->included_range_differences = (TSRangeArray) array_new();
   self->included_range_difference_index = 0;
+  array_init(&self->extra_override);
+  self->error_recovery_mode = TSErrorRecoveryModeDefault;
   ts_parser__set_cached_token(self, 0, NULL_SUBTREE, NULL_SUBTREE);
   return self;
 }
@@ -2009,6 +2020,7 @@ void ts_parser_delete(TSParser *self) {
   array_delete(&self->trailing_extras);
   array_delete(&self->trailing_extras2);
   array_delete(&self->scratch_trees);
+  array_delete(&self->extra_override);
   ts_free(self);
 }
 
@@ -2075,6 +2087,35 @@ const TSRange *ts_parser_included_ranges(const TSParser *self, uint32_t *count)
   return ts_lexer_included_ranges(&self->lexer, count);
 }
 
+TSParseStats ts_parser_parse_stats(const TSParser *self) {
+  return self->parse_stats;
+}
+
+void ts_parser_set_error_recovery_mode(TSParser *self, TSErrorRecoveryMode mode) {
+  self->error_recovery_mode = mode;
+}
+
+TSErrorRecoveryMode ts_parser_error_recovery_mode(const TSParser *self) {
+  return self->error_recovery_mode;
+}
+
+void ts_parser_set_extra_override(
+  TSParser *self,
+  const TSSymbol *symbols,
+  uint32_t count
+) {
+  // Keep the symbols sorted so that trees can binary search them.
+  array_clear(&self->extra_override);
+  for (uint32_t i = 0; i < count; i++) {
+    array_insert_sorted_by(&self->extra_override, , symbols[i]);
+  }
+}
+
+const TSSymbol *ts_parser_extra_override(const TSParser *self, uint32_t *count) {
+  *count = self->extra_override.size;
+  return self->extra_override.contents;
+}
+
 void ts_parser_reset(TSParser *self) {
   ts_parser__external_scanner_destroy(self);
   if (self->wasm_store) {
@@ -2125,6 +2166,7 @@ TSTree *ts_parser_parse(
     LOG("resume_parsing");
     if (self->canceled_balancing) goto balance;
   } else {
+    self->parse_stats = (TSParseStats) {0};
     ts_parser__external_scanner_create(self);
     if (self->has_scanner_error) goto exit;
 
@@ -2220,7 +2262,9 @@ balance:
     self->finished_tree,
     self->language,
     self->lexer.included_ranges,
-    self->lexer.included_range_count
+    self->lexer.included_range_count,
+    self->extra_override.contents,
+    self->extra_override.size
   );
   self->finished_tree = NULL_SUBTREE;
 
@@ -262,8 +262,8 @@ static inline bool set_contains(const TSCharacterRange *ranges, uint32_t len, in
   {{                                                       \
     .reduce = {                                            \
       .type = TSParseActionTypeReduce,                     \
-      .symbol = symbol_name,                               \
       .child_count = children,                             \
+      .symbol = symbol_name,                               \
       .dynamic_precedence = precedence,                    \
       .production_id = prod_id                             \
     },                                                     \
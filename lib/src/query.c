@@ -321,6 +321,7 @@ struct TSQuery {
   Array(TSSymbol) repeat_symbols_with_rootless_patterns;
   const TSLanguage *language;
   uint16_t wildcard_root_pattern_count;
+  bool matches_anonymous_nodes;
 };
 
 /*
@@ -349,6 +350,7 @@ struct TSQueryCursor {
   bool ascending;
   bool halted;
   bool did_exceed_match_limit;
+  bool skip_anonymous_nodes;
 };
 
 static const TSQueryError PARENT_DONE = -1;
@@ -3017,6 +3019,29 @@ static TSQueryError ts_query__parse_pattern(
   return 0;
 }
 
+// Determine if the given step could be affected by an anonymous node, either
+// because it can match one, or because it requires its match to immediately
+// follow the previous sibling, including any anonymous siblings.
+static bool ts_query__step_matches_anonymous_nodes(
+  const TSQuery *self,
+  const QueryStep *step
+) {
+  if (step->depth == PATTERN_DONE_MARKER || step->is_dead_end) return false;
+  if (step->is_pass_through || step->is_missing) return true;
+  if (step->alternative_is_skip && step->is_immediate) return true;
+  if (step->supertype_symbol) {
+    uint32_t length;
+    const TSSymbol *subtypes = ts_language_subtypes(self->language, step->supertype_symbol, &length);
+    if (length == 0) return true;
+    for (unsigned i = 0; i < length; i++) {
+      if (!ts_language_symbol_metadata(self->language, subtypes[i]).named) return true;
+    }
+    return false;
+  }
+  if (step->symbol == WILDCARD_SYMBOL) return !step->is_named;
+  return !ts_language_symbol_metadata(self->language, step->symbol).named;
+}
+
 TSQuery *ts_query_new(
   const TSLanguage *language,
   const char *source,
@@ -3047,6 +3072,7 @@ TSQuery *ts_query_new(
     .negated_fields = array_new(),
     .repeat_symbols_with_rootless_patterns = array_new(),
     .wildcard_root_pattern_count = 0,
+    .matches_anonymous_nodes = false,
     .language = ts_language_copy(language),
   };
 
@@ -3203,6 +3229,13 @@ TSQuery *ts_query_new(
     ts_query__dump_steps(self, "post-analysis");
   #endif
 
+  for (unsigned i = 0; i < self->steps.size; i++) {
+    if (ts_query__step_matches_anonymous_nodes(self, array_get(&self->steps, i))) {
+      self->matches_anonymous_nodes = true;
+      break;
+    }
+  }
+
   array_delete(&self->string_buffer);
   return self;
 }
@@ -3236,6 +3269,7 @@ TSQuery *ts_query_copy(const TSQuery *self) {
     .predicate_values = symbol_table_new(),
     .language = ts_language_copy(self->language),
     .wildcard_root_pattern_count = self->wildcard_root_pattern_count,
+    .matches_anonymous_nodes = self->matches_anonymous_nodes,
   };
 
   array_assign(&copy->steps, &self->steps);
@@ -3262,6 +3296,10 @@ TSQuery *ts_query_copy(const TSQuery *self) {
   return copy;
 }
 
+const TSLanguage *ts_query_language(const TSQuery *self) {
+  return self->language;
+}
+
 uint32_t ts_query_pattern_count(const TSQuery *self) {
   return self->patterns.size;
 }
@@ -3310,6 +3348,55 @@ const TSQueryPredicateStep *ts_query_predicates_for_pattern(
   return array_get(&self->predicate_steps, slice.offset);
 }
 
+uint32_t ts_query_steps_for_pattern(
+  const TSQuery *self,
+  uint32_t pattern_index,
+  TSQueryPatternStep *steps,
+  uint32_t length
+) {
+  Slice slice = array_get(&self->patterns, pattern_index)->steps;
+  uint32_t end = slice.offset + slice.length;
+  uint32_t count = 0;
+  for (uint32_t i = slice.offset; i < end; i++) {
+    QueryStep *step = array_get(&self->steps, i);
+    if (step->depth == PATTERN_DONE_MARKER) break;
+    if (step->is_pass_through || step->is_dead_end) continue;
+
+    if (steps && count < length) {
+      TSQueryPatternStep *result = &steps[count];
+      *result = (TSQueryPatternStep) {
+        .symbol = step->symbol,
+        .field = step->field,
+        .depth = step->depth,
+        .is_named = step->symbol == WILDCARD_SYMBOL
+          ? step->is_named
+          : ts_language_symbol_metadata(self->language, step->symbol).named,
+      };
+      for (unsigned j = 0; j < MAX_STEP_CAPTURE_COUNT; j++) {
+        if (step->capture_ids[j] == NONE) break;
+        result->capture_ids[result->capture_count++] = step->capture_ids[j];
+      }
+
+      // A step can be skipped if an earlier step at the same or a shallower
+      // depth has an alternative that jumps past it.
+      for (uint32_t j = slice.offset; j <= i; j++) {
+        QueryStep *other = array_get(&self->steps, j);
+        if (
+          other->alternative_index != NONE &&
+          other->alternative_index > i &&
+          other->alternative_index < end &&
+          other->depth <= step->depth
+        ) {
+          result->is_optional = true;
+          break;
+        }
+      }
+    }
+    count++;
+  }
+  return count;
+}
+
 uint32_t ts_query_start_byte_for_pattern(
   const TSQuery *self,
   uint32_t pattern_index
@@ -3337,6 +3424,10 @@ bool ts_query_is_pattern_rooted(
   return true;
 }
 
+bool ts_query_matches_anonymous_nodes(const TSQuery *self) {
+  return self->matches_anonymous_nodes;
+}
+
 bool ts_query_is_pattern_non_local(
   const TSQuery *self,
   uint32_t pattern_index
@@ -3443,6 +3534,7 @@ TSQueryCursor *ts_query_cursor_new(void) {
     },
     .max_start_depth = UINT32_MAX,
     .operation_count = 0,
+    .skip_anonymous_nodes = false,
   };
   array_reserve(&self->states, 8);
   array_reserve(&self->finished_states, 8);
@@ -4144,6 +4236,20 @@ static inline bool ts_query_cursor__advance(
     // Enter a new node.
     else {
       TSNode node = ts_tree_cursor_current_node(&self->cursor);
+
+      // If the query can never be affected by an anonymous node, then anonymous
+      // leaf nodes can be stepped over without updating any of the states.
+      if (
+        self->skip_anonymous_nodes &&
+        self->on_visible_node &&
+        !self->query->matches_anonymous_nodes &&
+        !ts_node_is_named(node) &&
+        ts_node_child_count(node) == 0
+      ) {
+        self->ascending = true;
+        continue;
+      }
+
       TSNode parent_node = ts_tree_cursor_parent_node(&self->cursor);
 
       bool parent_intersects_range =
@@ -4873,4 +4979,8 @@ void ts_query_cursor_set_max_start_depth(
   self->max_start_depth = max_start_depth;
 }
 
+void ts_query_cursor_set_skip_anonymous_nodes(TSQueryCursor *self, bool skip) {
+  self->skip_anonymous_nodes = skip;
+}
+
 #undef LOG
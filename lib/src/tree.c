@@ -8,7 +8,8 @@
 
 TSTree *ts_tree_new(
   Subtree root, const TSLanguage *language,
-  const TSRange *included_ranges, unsigned included_range_count
+  const TSRange *included_ranges, unsigned included_range_count,
+  const TSSymbol *extra_override, unsigned extra_override_count
 ) {
   TSTree *result = ts_malloc(sizeof(TSTree));
   result->root = root;
@@ -16,12 +17,19 @@ TSTree *ts_tree_new(
   result->included_ranges = ts_calloc(included_range_count, sizeof(TSRange));
   memcpy(result->included_ranges, included_ranges, included_range_count * sizeof(TSRange));
   result->included_range_count = included_range_count;
+  result->extra_override = ts_calloc(extra_override_count, sizeof(TSSymbol));
+  memcpy(result->extra_override, extra_override, extra_override_count * sizeof(TSSymbol));
+  result->extra_override_count = extra_override_count;
   return result;
 }
 
 TSTree *ts_tree_copy(const TSTree *self) {
   ts_subtree_retain(self->root);
-  return ts_tree_new(self->root, self->language, self->included_ranges, self->included_range_count);
+  return ts_tree_new(
+    self->root, self->language,
+    self->included_ranges, self->included_range_count,
+    self->extra_override, self->extra_override_count
+  );
 }
 
 void ts_tree_delete(TSTree *self) {
@@ -32,9 +40,27 @@ void ts_tree_delete(TSTree *self) {
   ts_subtree_pool_delete(&pool);
   ts_language_delete(self->language);
   ts_free(self->included_ranges);
+  ts_free(self->extra_override);
   ts_free(self);
 }
 
+bool ts_tree_has_extra_override(const TSTree *self, TSSymbol symbol) {
+  // The parser keeps the symbols sorted.
+  unsigned start = 0;
+  unsigned end = self->extra_override_count;
+  while (start < end) {
+    unsigned mid = start + (end - start) / 2;
+    TSSymbol mid_symbol = self->extra_override[mid];
+    if (mid_symbol == symbol) return true;
+    if (mid_symbol < symbol) {
+      start = mid + 1;
+    } else {
+      end = mid;
+    }
+  }
+  return false;
+}
+
 TSNode ts_tree_root_node(const TSTree *self) {
   return ts_node_new(self, &self->root, ts_subtree_padding(self->root), 0);
 }
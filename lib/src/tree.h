@@ -19,9 +19,16 @@ struct TSTree {
   const TSLanguage *language;
   TSRange *included_ranges;
   unsigned included_range_count;
+  TSSymbol *extra_override;
+  unsigned extra_override_count;
 };
 
-TSTree *ts_tree_new(Subtree root, const TSLanguage *language, const TSRange *included_ranges, unsigned included_range_count);
+TSTree *ts_tree_new(
+  Subtree root, const TSLanguage *language,
+  const TSRange *included_ranges, unsigned included_range_count,
+  const TSSymbol *extra_override, unsigned extra_override_count
+);
+bool ts_tree_has_extra_override(const TSTree *self, TSSymbol symbol);
 TSNode ts_node_new(const TSTree *tree, const Subtree *subtree, Length position, TSSymbol alias);
 
 #ifdef __cplusplus